@@ -0,0 +1,1303 @@
+//! Android Build Tool 命令行版本
+//!
+//! 与 GUI 共用 projects.json / publish_platforms.json / keystores.json / signing_profiles.json，可在 CI 等无界面环境中
+//! 检测环境、管理工程、构建、管理 keystore、签名、生成渠道包与发布。`--json` 输出机器可读结果。
+//! API Key/Token 与密码保存在本地密钥库中，主密码通过环境变量 `ABT_MASTER_PASSWORD` 提供。
+
+use abt_core::{
+  build::{self, ArtifactKind, BuildLogLine, BuildRegistry, BuildRequest, BuildStatus, LogStream},
+  channel::{self, ChannelRequest, ChannelScheme},
+  config::{self, AppDirs, Project, SigningProfile},
+  discovery::DiscoveryMode,
+  env,
+  gradle_options::{self, GradleOptions},
+  history,
+  inspect,
+  keystores::{self, CreateKeystoreRequest, ImportKeystoreRequest, KeyAlgorithm, KeystoreInfo, KeystoreType},
+  project_env,
+  publish::{self, PublishConfig},
+  release_notes, secrets,
+  signature::{self, CertificateInfo},
+  signer::{self, SigningSchemes},
+  version::{self, CodeBump, NameBump, VersionBump},
+  Error, Result,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::{
+  path::{Path, PathBuf},
+  process::ExitCode,
+};
+
+#[derive(Parser)]
+#[command(name = "abt", version, about = "Android Build Tool 命令行版本")]
+struct Cli {
+  /// 配置目录（包含 projects.json 与 publish_platforms.json），默认与 GUI 相同
+  #[arg(long, global = true, value_name = "DIR")]
+  config_dir: Option<PathBuf>,
+
+  /// 以 JSON 输出结果
+  #[arg(long, global = true)]
+  json: bool,
+
+  #[command(subcommand)]
+  command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+  /// 检测构建环境
+  Env {
+    /// 从该工程的 local.properties 读取 SDK 路径，并检查工程的 AGP/Gradle/compileSdk 要求
+    #[arg(long)]
+    project: Option<String>,
+  },
+  /// 工程管理
+  #[command(subcommand)]
+  Projects(ProjectsCommand),
+  /// 构建工程
+  Build(BuildArgs),
+  /// 发布 APK/AAB 到蒲公英或 fir.im
+  Publish(PublishArgs),
+  /// 根据 git 提交生成更新描述（不发布）
+  Notes(NotesArgs),
+  /// 解析 APK/AAB：包名、版本、SDK 版本、权限、ABI 与 AAB 模块
+  Inspect {
+    /// APK 或 AAB 文件
+    file: PathBuf,
+  },
+  /// 校验 APK 签名（v1/v2/v3/v3.1/v4）并显示签名证书
+  Verify {
+    /// APK 文件，同目录下的 `<文件名>.idsig` 作为 v4 签名一并校验
+    file: PathBuf,
+    /// 按 release 构建检查，使用调试证书签名时返回非零
+    #[arg(long)]
+    release: bool,
+  },
+  /// 用签名配置重新签名 APK（zipalign 对齐）
+  Sign(SignArgs),
+  /// 签名配置管理
+  #[command(subcommand)]
+  Signing(SigningCommand),
+  /// 渠道包：在 v2/v3 签名的 APK 中写入或读取渠道（兼容 Walle）
+  #[command(subcommand)]
+  Channel(ChannelCommand),
+  /// keystore 管理：导入、新建与查看
+  #[command(subcommand)]
+  Keystore(KeystoreCommand),
+  /// 本地密钥库：主密码通过环境变量 ABT_MASTER_PASSWORD 提供
+  #[command(subcommand)]
+  Secrets(SecretsCommand),
+  /// 列出发布平台配置
+  Platforms,
+  /// 列出本机安装的 JDK
+  Jdks,
+  /// 查看或修改 versionCode/versionName
+  #[command(subcommand)]
+  Version(VersionCommand),
+}
+
+#[derive(Subcommand)]
+enum VersionCommand {
+  /// 显示当前版本号及其定义位置
+  Show {
+    project: String,
+    #[arg(long)]
+    module: Option<String>,
+  },
+  /// 按策略修改版本号（不构建）
+  Bump {
+    project: String,
+    #[arg(long)]
+    module: Option<String>,
+    #[command(flatten)]
+    bump: VersionBumpArgs,
+  },
+}
+
+#[derive(Args)]
+struct VersionBumpArgs {
+  /// versionCode 策略：increment、timestamp、git-count 或具体数字
+  #[arg(long, value_name = "STRATEGY")]
+  bump_code: Option<CodeBump>,
+  /// versionName 策略：major、minor、patch 或具体版本名
+  #[arg(long, value_name = "STRATEGY")]
+  bump_name: Option<NameBump>,
+  /// 构建失败时保留修改后的版本号（默认回滚）
+  #[arg(long)]
+  keep_version_on_failure: bool,
+}
+
+impl VersionBumpArgs {
+  fn bump(&self) -> Option<VersionBump> {
+    let bump = VersionBump {
+      code: self.bump_code.clone(),
+      name: self.bump_name.clone(),
+      rollback_on_failure: !self.keep_version_on_failure,
+    };
+    (!bump.is_empty()).then_some(bump)
+  }
+}
+
+#[derive(Args)]
+struct SignArgs {
+  /// 未签名或调试签名的 APK
+  file: PathBuf,
+  /// 签名配置名称
+  #[arg(long)]
+  profile: String,
+  /// 输出文件，默认去掉文件名中的 -unsigned，没有时加上 -signed
+  #[arg(long, short)]
+  out: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum ChannelCommand {
+  /// 为渠道文件中的每个渠道生成渠道包，不重新签名
+  Write(ChannelWriteArgs),
+  /// 读取 APK 中的渠道
+  Read {
+    file: PathBuf,
+  },
+}
+
+#[derive(Args)]
+struct ChannelWriteArgs {
+  /// 已签名的 APK
+  file: PathBuf,
+  /// 渠道文件：每行一个渠道，可在渠道名后跟 key=value，# 开头为注释
+  #[arg(long, value_name = "FILE")]
+  channels: Option<PathBuf>,
+  /// 使用该工程的渠道设置（写入方式、渠道文件、文件名模板），命令行参数优先；默认为构建历史中 APK 所属的工程
+  #[arg(long)]
+  project: Option<String>,
+  /// 写入方式，默认 walle；meta-inf 只用于 v1 签名的 APK
+  #[arg(long, value_enum)]
+  scheme: Option<ChannelSchemeArg>,
+  /// 输出目录，默认为 APK 所在目录下的 channels/
+  #[arg(long, short, value_name = "DIR")]
+  out: Option<PathBuf>,
+  /// 输出文件名模板，支持 {{name}}、{{channel}}、{{version}}、{{code}}、{{package}}，默认 {{name}}-{{channel}}.apk
+  #[arg(long)]
+  template: Option<String>,
+  /// 写入每个渠道包的附加信息，可重复
+  #[arg(long = "extra", value_name = "KEY=VALUE", value_parser = channel::parse_extra)]
+  extras: Vec<(String, String)>,
+}
+
+#[derive(Subcommand)]
+enum SigningCommand {
+  /// 列出签名配置
+  List,
+  /// 添加签名配置
+  Add(SigningProfileArgs),
+  /// 更新签名配置，未指定的字段保持不变
+  Update(SigningProfileArgs),
+  /// 删除签名配置
+  Remove { name: String },
+}
+
+#[derive(Args)]
+struct SigningProfileArgs {
+  /// 配置名称
+  name: String,
+  /// 登记的 keystore id（见 `abt keystore list`），也可以是 keystore 文件路径
+  #[arg(long)]
+  keystore: Option<String>,
+  /// keystore 密码，引用登记的 keystore 时可不填
+  #[arg(long)]
+  store_password: Option<String>,
+  /// 密钥别名，keystore 中只有一个私钥时可不填
+  #[arg(long)]
+  key_alias: Option<String>,
+  /// 密钥密码，默认与 keystore 密码相同
+  #[arg(long)]
+  key_password: Option<String>,
+  /// 启用的签名方案，逗号分隔，默认 v1,v2,v3
+  #[arg(long, value_enum, value_delimiter = ',')]
+  schemes: Vec<Scheme>,
+}
+
+/// 可选的签名方案
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Scheme {
+  V1,
+  V2,
+  V3,
+}
+
+#[derive(Subcommand)]
+enum KeystoreCommand {
+  /// 列出登记的 keystore 及其中的别名
+  List,
+  /// 查看 keystore 中的条目、证书指纹与有效期
+  Show { id: String },
+  /// 导入已有的 JKS 或 PKCS12 文件（复制到配置目录）
+  Import {
+    id: String,
+    file: PathBuf,
+    #[arg(long)]
+    store_password: String,
+  },
+  /// 新建 keystore：生成私钥与自签名证书
+  Create(CreateKeystoreArgs),
+  /// 删除 keystore，仍被签名配置引用时拒绝
+  Remove { id: String },
+}
+
+#[derive(Subcommand)]
+enum SecretsCommand {
+  /// 显示密钥库位置与是否可以解锁
+  Status,
+  /// 列出保存的密钥（只显示打码后的预览）
+  List,
+  /// 把配置文件中的明文 API Key/Token 与密码迁移到密钥库
+  Migrate,
+}
+
+#[derive(Args)]
+struct CreateKeystoreArgs {
+  id: String,
+  /// keystore 格式
+  #[arg(long = "type", value_enum, default_value = "pkcs12")]
+  store_type: StoreType,
+  #[arg(long)]
+  store_password: String,
+  /// 密钥别名
+  #[arg(long)]
+  alias: String,
+  /// 密钥密码，仅用于 JKS，默认与 keystore 密码相同
+  #[arg(long)]
+  key_password: Option<String>,
+  /// 密钥算法
+  #[arg(long, value_enum, default_value = "rsa")]
+  key_alg: KeyAlg,
+  /// RSA 为 2048/3072/4096 位，EC 为 256/384，默认 2048 与 256
+  #[arg(long)]
+  key_size: Option<u32>,
+  /// 证书主题，如 "CN=Example, O=Example Inc, C=CN"
+  #[arg(long)]
+  dname: String,
+  /// 有效期（年），默认 25
+  #[arg(long)]
+  validity_years: Option<u32>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StoreType {
+  Jks,
+  Pkcs12,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KeyAlg {
+  Rsa,
+  Ec,
+}
+
+#[derive(Subcommand)]
+enum ProjectsCommand {
+  /// 列出工程
+  List,
+  /// 添加工程（未指定模块时自动发现）
+  Add(ProjectArgs),
+  /// 更新工程，未指定的字段保持不变
+  Update(ProjectArgs),
+  /// 删除工程
+  Remove { name: String },
+  /// 发现工程的模块与变体并写回配置
+  Discover {
+    name: String,
+    /// 调用 Gradle 解析（更准确，但较慢）
+    #[arg(long)]
+    gradle: bool,
+  },
+}
+
+#[derive(Args)]
+struct ProjectArgs {
+  /// 工程名
+  name: String,
+  /// 工程路径（包含 gradlew 的目录）
+  #[arg(long)]
+  path: Option<String>,
+  /// 模块，可重复指定
+  #[arg(long = "module")]
+  modules: Vec<String>,
+  #[arg(long)]
+  default_module: Option<String>,
+  /// flavor 组合，可重复指定
+  #[arg(long = "variant")]
+  variants: Vec<String>,
+  #[arg(long)]
+  default_variant: Option<String>,
+  #[arg(long)]
+  build_type: Option<String>,
+  #[arg(long, value_enum)]
+  kind: Option<Kind>,
+  /// 构建使用的 JDK 主目录（导出为 JAVA_HOME），传空字符串取消指定
+  #[arg(long)]
+  java_home: Option<String>,
+  /// 每次构建附加的 Gradle 参数，与已保存的配置合并
+  #[command(flatten)]
+  gradle: GradleArgs,
+  /// 清空已保存的 Gradle 参数（在合并本次指定的参数之前）
+  #[arg(long)]
+  clear_gradle_options: bool,
+  /// 每次构建前修改版本号的默认策略
+  #[command(flatten)]
+  version: VersionBumpArgs,
+  /// 清除已保存的版本号策略
+  #[arg(long, conflicts_with_all = ["bump_code", "bump_name"])]
+  no_version_bump: bool,
+  /// 构建时以 -PgitBranch、-PgitCommit 等属性传入 git 信息
+  #[arg(long)]
+  inject_git: Option<bool>,
+  /// Release 构建要求工作区没有未提交的修改
+  #[arg(long)]
+  require_clean_release: Option<bool>,
+  /// 允许 Release 构建的分支，支持 * 通配，可重复指定
+  #[arg(long = "release-branch")]
+  release_branches: Vec<String>,
+  /// 清除允许 Release 构建的分支（不再限制分支）
+  #[arg(long, conflicts_with = "release_branches")]
+  clear_release_branches: bool,
+  /// 发布时未填写更新描述则根据提交自动生成
+  #[arg(long)]
+  notes_auto: Option<bool>,
+  /// 按 Conventional Commits 类型分组（默认开启）
+  #[arg(long)]
+  notes_conventional: Option<bool>,
+  /// 更新描述模板，支持 {{notes}}、{{version}}、{{branch}}、{{commit}}、{{count}}，传空字符串恢复默认
+  #[arg(long)]
+  notes_template: Option<String>,
+  /// 构建后重新签名 APK 的签名配置，传空字符串取消
+  #[arg(long)]
+  signing_profile: Option<String>,
+  /// 构建后生成渠道包的写入方式
+  #[arg(long, value_enum)]
+  channel_scheme: Option<ChannelSchemeArg>,
+  /// 构建后生成渠道包的渠道文件（相对工程目录），传空字符串取消
+  #[arg(long)]
+  channel_file: Option<String>,
+  /// 渠道包文件名模板，传空字符串恢复默认
+  #[arg(long)]
+  channel_template: Option<String>,
+}
+
+#[derive(Args)]
+struct BuildArgs {
+  /// 工程名
+  project: String,
+  #[arg(long)]
+  module: Option<String>,
+  /// flavor 组合名，如 freeStaging
+  #[arg(long)]
+  variant: Option<String>,
+  /// 按维度顺序指定 flavor，可重复，优先于 --variant
+  #[arg(long = "flavor")]
+  flavors: Vec<String>,
+  #[arg(long)]
+  build_type: Option<String>,
+  #[arg(long, value_enum)]
+  kind: Option<Kind>,
+  /// 产物输出目录
+  #[arg(long)]
+  output_dir: Option<String>,
+  /// 覆盖工程配置的 Gradle 参数
+  #[command(flatten)]
+  gradle: GradleArgs,
+  /// 构建前修改版本号，未指定时使用工程配置
+  #[command(flatten)]
+  version: VersionBumpArgs,
+  /// 本次构建不修改版本号
+  #[arg(long, conflicts_with_all = ["bump_code", "bump_name"])]
+  no_version_bump: bool,
+  /// 构建后用该签名配置重新签名 APK，覆盖工程配置
+  #[arg(long, value_name = "PROFILE")]
+  sign: Option<String>,
+  /// 本次构建不重新签名
+  #[arg(long, conflicts_with = "sign")]
+  no_sign: bool,
+}
+
+#[derive(Args)]
+struct GradleArgs {
+  /// 额外的 Gradle 参数，如 --stacktrace、--offline，可重复
+  #[arg(long = "gradle-arg", value_name = "ARG", allow_hyphen_values = true)]
+  args: Vec<String>,
+  /// -P 工程属性，可重复
+  #[arg(short = 'P', long = "property", value_name = "KEY=VALUE", value_parser = gradle_options::parse_pair)]
+  properties: Vec<(String, String)>,
+  /// -D 系统属性，可重复
+  #[arg(short = 'D', long = "system-property", value_name = "KEY=VALUE", value_parser = gradle_options::parse_pair)]
+  system_properties: Vec<(String, String)>,
+  /// Gradle 进程的环境变量，可重复
+  #[arg(long = "env", value_name = "KEY=VALUE", value_parser = gradle_options::parse_pair)]
+  env: Vec<(String, String)>,
+  /// 额外视为敏感、需要在日志中隐藏的键名，可重复
+  #[arg(long = "secret", value_name = "KEY")]
+  secrets: Vec<String>,
+}
+
+impl GradleArgs {
+  fn options(&self) -> GradleOptions {
+    GradleOptions {
+      args: self.args.clone(),
+      properties: self.properties.iter().cloned().collect(),
+      system_properties: self.system_properties.iter().cloned().collect(),
+      env: self.env.iter().cloned().collect(),
+      secrets: self.secrets.clone(),
+    }
+  }
+}
+
+#[derive(Args)]
+struct PublishArgs {
+  /// APK 或 AAB 文件
+  file: String,
+  /// 使用已保存的发布平台配置
+  #[arg(long, value_name = "NAME", conflicts_with = "platform")]
+  config: Option<String>,
+  /// 发布平台：pgyer 或 fir
+  #[arg(long, required_unless_present = "config")]
+  platform: Option<String>,
+  #[arg(long)]
+  api_key: Option<String>,
+  #[arg(long)]
+  api_token: Option<String>,
+  #[arg(long)]
+  password: Option<String>,
+  /// 更新描述
+  #[arg(long)]
+  description: Option<String>,
+  /// 产物所属的工程，未指定时从构建历史中查找
+  #[arg(long)]
+  project: Option<String>,
+  /// 根据上次发布以来的提交生成更新描述（覆盖 --description）
+  #[arg(long, conflicts_with = "description")]
+  notes: bool,
+}
+
+#[derive(Args)]
+struct NotesArgs {
+  /// 工程名
+  project: String,
+  /// 使用该发布平台配置的平台类型
+  #[arg(long, value_name = "NAME", conflicts_with = "platform")]
+  config: Option<String>,
+  /// 发布平台：pgyer 或 fir，用于查找上次发布的提交
+  #[arg(long, required_unless_present = "config")]
+  platform: Option<String>,
+  /// 要发布的产物，截止到构建它的提交（默认截止到 HEAD）
+  #[arg(long)]
+  file: Option<PathBuf>,
+  /// 起始提交（不含），覆盖上次发布的记录
+  #[arg(long)]
+  since: Option<String>,
+}
+
+/// 构建产物类型
+#[derive(Clone, Copy, ValueEnum)]
+enum Kind {
+  Apk,
+  Aab,
+  Both,
+}
+
+/// 渠道写入方式
+#[derive(Clone, Copy, ValueEnum)]
+enum ChannelSchemeArg {
+  Walle,
+  #[value(name = "vasdolly")]
+  VasDolly,
+  MetaInf,
+}
+
+impl From<ChannelSchemeArg> for ChannelScheme {
+  fn from(scheme: ChannelSchemeArg) -> Self {
+    match scheme {
+      ChannelSchemeArg::Walle => ChannelScheme::Walle,
+      ChannelSchemeArg::VasDolly => ChannelScheme::VasDolly,
+      ChannelSchemeArg::MetaInf => ChannelScheme::MetaInf,
+    }
+  }
+}
+
+impl From<Kind> for ArtifactKind {
+  fn from(kind: Kind) -> Self {
+    match kind {
+      Kind::Apk => ArtifactKind::Apk,
+      Kind::Aab => ArtifactKind::Aab,
+      Kind::Both => ArtifactKind::Both,
+    }
+  }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let dirs = AppDirs::detect(cli.config_dir.clone());
+  match run(&cli, &dirs).await {
+    Ok(code) => code,
+    Err(e) => {
+      if cli.json {
+        print_json(&serde_json::json!({ "error": e }));
+      } else {
+        eprintln!("错误: {}", e);
+      }
+      ExitCode::FAILURE
+    }
+  }
+}
+
+async fn run(cli: &Cli, dirs: &AppDirs) -> Result<ExitCode> {
+  match &cli.command {
+    Commands::Env { project } => {
+      let project = match project {
+        Some(name) => Some(config::find_project(dirs, name)?),
+        None => None,
+      };
+      let project_path = project.as_ref().map(|p| PathBuf::from(&p.path));
+      let mut checks = env::check_env(project_path.as_deref());
+      if let (Some(project), Some(path)) = (&project, &project_path) {
+        checks.extend(project_env::checks(path, project.java_home.as_deref().map(Path::new)));
+      }
+      if cli.json {
+        print_json(&checks);
+      } else {
+        for c in &checks {
+          let mark = if !c.applicable { "➖" } else if c.ok { "✅" } else { "❌" };
+          println!("{} {}: {}", mark, c.tool, c.message);
+          if !c.items.is_empty() {
+            println!("    {}", c.items.join(", "));
+          }
+        }
+      }
+      // 适用于当前系统的检查项未通过时返回非零，便于 CI 判断
+      Ok(if checks.iter().all(|c| c.ok || !c.applicable) { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+    }
+    Commands::Projects(cmd) => {
+      projects(cli, dirs, cmd).await?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Build(args) => build(cli, dirs, args).await,
+    Commands::Publish(args) => {
+      let config = publish_config(dirs, args)?;
+      let result = publish::publish_artifact(dirs, &args.file, config, args.project.as_deref(), args.notes).await?;
+      if cli.json {
+        print_json(&result);
+      } else {
+        println!("{}", result.message);
+        if let Some(desc) = result.update_description.as_deref().filter(|d| !d.is_empty()) {
+          println!("更新描述:\n{}", desc);
+        }
+        if let Some(url) = &result.download_url {
+          println!("下载地址: {}", url);
+        }
+        if let Some(url) = &result.qr_code_url {
+          println!("二维码: {}", url);
+        }
+      }
+      Ok(if result.success { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+    }
+    Commands::Notes(args) => {
+      let platform = match &args.config {
+        Some(name) => config::find_publish_platform(dirs, name)?.platform,
+        None => args.platform.clone().unwrap_or_default(),
+      };
+      let notes = release_notes::generate_for_project(dirs, &args.project, &platform, args.file.as_deref(), args.since.as_deref())?;
+      if cli.json {
+        print_json(&notes);
+      } else {
+        let since = notes.since.as_deref().map(|s| &s[..s.len().min(7)]).unwrap_or("（无发布记录）");
+        eprintln!("{}..{}，{} 个提交", since, &notes.until[..notes.until.len().min(7)], notes.commits.len());
+        println!("{}", notes.text);
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Inspect { file } => {
+      let info = inspect::inspect(file)?;
+      if cli.json {
+        print_json(&info);
+      } else {
+        print_artifact_info(&info);
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Verify { file, release } => {
+      let report = signature::verify_apk(file, *release)?;
+      if cli.json {
+        print_json(&report);
+      } else {
+        print_signature_report(&report);
+      }
+      // 校验失败或 release 构建使用调试证书时返回非零，便于 CI 判断
+      Ok(if report.verified && !(*release && report.debug_signed) { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+    }
+    Commands::Sign(args) => {
+      let output = args.out.clone().unwrap_or_else(|| signer::default_output(&args.file));
+      let result = signer::sign_apk(dirs, &args.profile, &args.file, &output)?;
+      if cli.json {
+        print_json(&result);
+      } else {
+        println!("已使用签名配置 {}（{}）签名: {}", result.profile, result.alias, result.output);
+        print_signature_report(&result.report);
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Signing(cmd) => {
+      signing(cli, dirs, cmd)?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Channel(cmd) => {
+      channel(cli, dirs, cmd)?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Keystore(cmd) => {
+      keystore(cli, dirs, cmd)?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Secrets(cmd) => {
+      secrets(cli, dirs, cmd)?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Platforms => {
+      let cfg = config::list_publish_platforms(dirs)?;
+      if cli.json {
+        print_json(&cfg);
+      } else {
+        for p in &cfg.platforms {
+          println!("{}\t{}", p.name, p.platform);
+        }
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Version(cmd) => {
+      version(cli, dirs, cmd)?;
+      Ok(ExitCode::SUCCESS)
+    }
+    Commands::Jdks => {
+      let jdks = env::list_jdks();
+      if cli.json {
+        print_json(&jdks);
+      } else {
+        for j in &jdks {
+          println!("{}\t{}", j.label(), j.home.display());
+        }
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+  }
+}
+
+async fn projects(cli: &Cli, dirs: &AppDirs, cmd: &ProjectsCommand) -> Result<()> {
+  match cmd {
+    ProjectsCommand::List => {
+      let cfg = config::list_projects(dirs)?;
+      if cli.json {
+        print_json(&cfg);
+      } else {
+        for p in &cfg.projects {
+          println!("{}\t{}", p.name, p.path);
+          if let Some(modules) = &p.modules {
+            println!("  模块: {}", modules.join(", "));
+          }
+          if let Some(variants) = &p.variants {
+            println!("  变体: {}", variants.join(", "));
+          }
+        }
+      }
+    }
+    ProjectsCommand::Add(args) => {
+      let path = args.path.clone().ok_or_else(|| Error::InvalidInput("添加工程需要指定 --path".into()))?;
+      let mut project = Project {
+        name: args.name.clone(),
+        path,
+        default_module: None,
+        modules: None,
+        default_variant: None,
+        variants: None,
+        build_type: None,
+        artifact_kind: None,
+        java_home: None,
+        gradle_options: None,
+        version_bump: None,
+        git_policy: None,
+        release_notes: None,
+        signing_profile: None,
+        channels: None,
+        discovered: None,
+      };
+      apply_project_args(&mut project, args);
+      config::add_project(dirs, project)?;
+      done(cli, &format!("已添加工程：{}", args.name));
+    }
+    ProjectsCommand::Update(args) => {
+      let mut project = config::find_project(dirs, &args.name)?;
+      apply_project_args(&mut project, args);
+      config::update_project(dirs, &args.name, project)?;
+      done(cli, &format!("已更新工程：{}", args.name));
+    }
+    ProjectsCommand::Remove { name } => {
+      config::delete_project(dirs, name)?;
+      done(cli, &format!("已删除工程：{}", name));
+    }
+    ProjectsCommand::Discover { name, gradle } => {
+      let mode = if *gradle { DiscoveryMode::Gradle } else { DiscoveryMode::Static };
+      let structure = config::discover_project(dirs, name, mode).await?;
+      if cli.json {
+        print_json(&structure);
+      } else {
+        for m in &structure.app_modules {
+          let variants: Vec<&str> = m.variants.iter().map(|v| v.name.as_str()).collect();
+          println!("{}: {}", m.module, variants.join(", "));
+        }
+        for w in &structure.warnings {
+          eprintln!("警告: {}", w);
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+fn apply_project_args(project: &mut Project, args: &ProjectArgs) {
+  if let Some(path) = &args.path {
+    project.path = path.clone();
+  }
+  if !args.modules.is_empty() {
+    project.modules = Some(args.modules.clone());
+  }
+  if !args.variants.is_empty() {
+    project.variants = Some(args.variants.clone());
+  }
+  if args.default_module.is_some() {
+    project.default_module = args.default_module.clone();
+  }
+  if args.default_variant.is_some() {
+    project.default_variant = args.default_variant.clone();
+  }
+  if args.build_type.is_some() {
+    project.build_type = args.build_type.clone();
+  }
+  if let Some(kind) = args.kind {
+    project.artifact_kind = Some(kind.into());
+  }
+  if let Some(home) = &args.java_home {
+    project.java_home = (!home.is_empty()).then(|| home.clone());
+  }
+  if args.no_version_bump {
+    project.version_bump = None;
+  } else if let Some(bump) = args.version.bump() {
+    project.version_bump = Some(bump);
+  }
+  if args.clear_gradle_options {
+    project.gradle_options = None;
+  }
+  let options = args.gradle.options();
+  if !options.is_empty() {
+    let merged = project.gradle_options.clone().unwrap_or_default().merged(&options);
+    project.gradle_options = Some(merged);
+  }
+  if args.inject_git.is_some() || args.require_clean_release.is_some() || !args.release_branches.is_empty() || args.clear_release_branches {
+    let policy = project.git_policy.get_or_insert_with(Default::default);
+    if let Some(inject) = args.inject_git {
+      policy.inject_properties = inject;
+    }
+    if let Some(clean) = args.require_clean_release {
+      policy.require_clean_for_release = clean;
+    }
+    if args.clear_release_branches {
+      policy.release_branches.clear();
+    } else if !args.release_branches.is_empty() {
+      policy.release_branches = args.release_branches.clone();
+    }
+  }
+  if args.notes_auto.is_some() || args.notes_conventional.is_some() || args.notes_template.is_some() {
+    let options = project.release_notes.get_or_insert_with(Default::default);
+    if let Some(auto) = args.notes_auto {
+      options.auto = auto;
+    }
+    if let Some(conventional) = args.notes_conventional {
+      options.conventional = conventional;
+    }
+    if let Some(template) = &args.notes_template {
+      // 命令行中的 \n 视为换行
+      options.template = (!template.is_empty()).then(|| template.replace("\\n", "\n"));
+    }
+  }
+  if let Some(profile) = &args.signing_profile {
+    project.signing_profile = (!profile.is_empty()).then(|| profile.clone());
+  }
+  if args.channel_scheme.is_some() || args.channel_file.is_some() || args.channel_template.is_some() {
+    let options = project.channels.get_or_insert_with(Default::default);
+    if let Some(scheme) = args.channel_scheme {
+      options.scheme = scheme.into();
+    }
+    if let Some(file) = &args.channel_file {
+      options.channel_file = (!file.is_empty()).then(|| file.clone());
+    }
+    if let Some(template) = &args.channel_template {
+      options.name_template = (!template.is_empty()).then(|| template.clone());
+    }
+  }
+}
+
+fn signing(cli: &Cli, dirs: &AppDirs, cmd: &SigningCommand) -> Result<()> {
+  match cmd {
+    SigningCommand::List => {
+      let cfg = config::list_signing_profiles(dirs)?;
+      if cli.json {
+        print_json(&cfg);
+      } else {
+        for p in &cfg.profiles {
+          let alias = p.key_alias.as_deref().unwrap_or("-");
+          println!("{}\t{}\t{}\t{}", p.name, p.keystore, alias, p.schemes.labels().join(","));
+        }
+      }
+    }
+    SigningCommand::Add(args) => {
+      let keystore = args.keystore.clone().ok_or_else(|| Error::InvalidInput("添加签名配置需要指定 --keystore".into()))?;
+      let mut profile = SigningProfile {
+        name: args.name.clone(),
+        keystore,
+        store_password: None,
+        key_alias: None,
+        key_password: None,
+        schemes: SigningSchemes::default(),
+      };
+      apply_signing_args(&mut profile, args);
+      config::add_signing_profile(dirs, profile)?;
+      done(cli, &format!("已添加签名配置：{}", args.name));
+    }
+    SigningCommand::Update(args) => {
+      let mut profile = config::find_signing_profile(dirs, &args.name)?;
+      apply_signing_args(&mut profile, args);
+      config::update_signing_profile(dirs, &args.name, profile)?;
+      done(cli, &format!("已更新签名配置：{}", args.name));
+    }
+    SigningCommand::Remove { name } => {
+      config::delete_signing_profile(dirs, name)?;
+      done(cli, &format!("已删除签名配置：{}", name));
+    }
+  }
+  Ok(())
+}
+
+fn apply_signing_args(profile: &mut SigningProfile, args: &SigningProfileArgs) {
+  if let Some(keystore) = &args.keystore {
+    profile.keystore = keystore.clone();
+  }
+  if let Some(password) = &args.store_password {
+    profile.store_password = (!password.is_empty()).then(|| password.clone());
+  }
+  if let Some(alias) = &args.key_alias {
+    profile.key_alias = (!alias.is_empty()).then(|| alias.clone());
+  }
+  if let Some(password) = &args.key_password {
+    profile.key_password = (!password.is_empty()).then(|| password.clone());
+  }
+  if !args.schemes.is_empty() {
+    profile.schemes = SigningSchemes {
+      v1: args.schemes.contains(&Scheme::V1),
+      v2: args.schemes.contains(&Scheme::V2),
+      v3: args.schemes.contains(&Scheme::V3),
+    };
+  }
+}
+
+fn keystore(cli: &Cli, dirs: &AppDirs, cmd: &KeystoreCommand) -> Result<()> {
+  match cmd {
+    KeystoreCommand::List => {
+      let list = keystores::list(dirs)?;
+      if cli.json {
+        print_json(&list);
+      } else {
+        for info in &list {
+          let aliases: Vec<&str> = info.entries.iter().filter(|e| e.key_entry).map(|e| e.alias.as_str()).collect();
+          println!("{}\t{}\t{}\t{}", info.id, info.store_type.label(), aliases.join(","), info.path);
+          print_keystore_problems(info);
+        }
+      }
+    }
+    KeystoreCommand::Show { id } => {
+      let info = keystores::inspect(dirs, id)?;
+      if cli.json {
+        print_json(&info);
+      } else {
+        print_keystore(&info);
+      }
+    }
+    KeystoreCommand::Import { id, file, store_password } => {
+      let info = keystores::import(
+        dirs,
+        ImportKeystoreRequest {
+          id: id.clone(),
+          path: file.to_string_lossy().to_string(),
+          store_password: store_password.clone(),
+        },
+      )?;
+      if cli.json {
+        print_json(&info);
+      } else {
+        println!("已导入 keystore：{}", info.id);
+        print_keystore(&info);
+      }
+    }
+    KeystoreCommand::Create(args) => {
+      let request = CreateKeystoreRequest {
+        id: args.id.clone(),
+        store_type: match args.store_type {
+          StoreType::Jks => KeystoreType::Jks,
+          StoreType::Pkcs12 => KeystoreType::Pkcs12,
+        },
+        store_password: args.store_password.clone(),
+        alias: args.alias.clone(),
+        key_password: args.key_password.clone(),
+        key_algorithm: match args.key_alg {
+          KeyAlg::Rsa => KeyAlgorithm::Rsa,
+          KeyAlg::Ec => KeyAlgorithm::Ec,
+        },
+        key_size: args.key_size,
+        subject: args.dname.clone(),
+        validity_years: args.validity_years,
+      };
+      let info = keystores::create(dirs, request)?;
+      if cli.json {
+        print_json(&info);
+      } else {
+        println!("已创建 keystore：{}", info.id);
+        print_keystore(&info);
+      }
+    }
+    KeystoreCommand::Remove { id } => {
+      keystores::delete(dirs, id)?;
+      done(cli, &format!("已删除 keystore：{}", id));
+    }
+  }
+  Ok(())
+}
+
+fn channel(cli: &Cli, dirs: &AppDirs, cmd: &ChannelCommand) -> Result<()> {
+  match cmd {
+    ChannelCommand::Write(args) => {
+      // 未指定工程时从构建历史中查找 APK 所属的工程，与构建后自动生成渠道包时的设置一致
+      let project = match &args.project {
+        Some(name) => Some(config::find_project(dirs, name)?),
+        None => history::find_by_artifact(&dirs.history_dir(), &args.file)?.and_then(|b| config::find_project(dirs, &b.project).ok()),
+      };
+      let request = ChannelRequest::resolve(
+        project.as_ref(),
+        args.channels.clone(),
+        args.scheme.map(ChannelScheme::from),
+        args.template.clone(),
+      )?;
+      let mut channels = channel::read_channel_file(&request.channel_file)?;
+      // 渠道文件中的同名键优先
+      for info in &mut channels {
+        for (key, value) in &args.extras {
+          info.extras.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+      }
+      let output = channel::write_channels(&args.file, request.scheme, &channels, args.out.as_deref(), request.name_template.as_deref())?;
+      if cli.json {
+        print_json(&output);
+      } else {
+        for apk in &output.apks {
+          println!("{}\t{}", apk.channel, apk.path);
+        }
+        for warning in &output.warnings {
+          println!("⚠️ {}", warning);
+        }
+        println!("已用 {} 方式生成 {} 个渠道包：{}", output.scheme.label(), output.apks.len(), output.output_dir);
+      }
+    }
+    ChannelCommand::Read { file } => {
+      let found = channel::read_channel(file)?;
+      if cli.json {
+        print_json(&found);
+      } else if found.is_empty() {
+        println!("未写入渠道");
+      } else {
+        for item in &found {
+          println!("渠道（{}）：{}", item.scheme.label(), item.info.channel);
+          for (key, value) in &item.info.extras {
+            println!("  {} = {}", key, value);
+          }
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+fn secrets(cli: &Cli, dirs: &AppDirs, cmd: &SecretsCommand) -> Result<()> {
+  match cmd {
+    SecretsCommand::Status => {
+      let status = secrets::status(dirs)?;
+      if cli.json {
+        print_json(&status);
+      } else {
+        println!("密钥库：{}", status.path);
+        let state = match (status.initialized, status.unlocked) {
+          (false, _) => "未创建（首次保存密钥时用 ABT_MASTER_PASSWORD 创建）",
+          (true, true) => "可解锁",
+          (true, false) => "未解锁（未设置 ABT_MASTER_PASSWORD 或主密码错误）",
+        };
+        println!("状态：{}，共 {} 个密钥", state, status.count);
+      }
+    }
+    SecretsCommand::List => {
+      let list = secrets::list(dirs)?;
+      if cli.json {
+        print_json(&list);
+      } else {
+        for secret in &list {
+          println!("{}\t{}", secret.name, secret.preview);
+        }
+      }
+    }
+    SecretsCommand::Migrate => {
+      let migrated = config::migrate_secrets(dirs)?;
+      if cli.json {
+        print_json(&migrated);
+      } else if migrated.is_empty() {
+        println!("配置文件中没有明文密钥");
+      } else {
+        println!("已迁移 {} 项到密钥库：", migrated.len());
+        for item in &migrated {
+          println!("  {}", item);
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+fn version(cli: &Cli, dirs: &AppDirs, cmd: &VersionCommand) -> Result<()> {
+  let (name, module) = match cmd {
+    VersionCommand::Show { project, module } | VersionCommand::Bump { project, module, .. } => (project, module),
+  };
+  let project = config::find_project(dirs, name)?;
+  let module = module.clone().or_else(|| project.default_build_module());
+  let project_path = Path::new(&project.path);
+  match cmd {
+    VersionCommand::Show { .. } => {
+      let info = version::read(project_path, module.as_deref());
+      if cli.json {
+        print_json(&info);
+      } else {
+        let location = |l: &Option<version::VersionLocation>| l.as_ref().map(|l| l.to_string()).unwrap_or_else(|| "未找到".into());
+        println!("versionCode: {}\t{}", info.version_code.map(|c| c.to_string()).unwrap_or_else(|| "-".into()), location(&info.code_location));
+        println!("versionName: {}\t{}", info.version_name.as_deref().unwrap_or("-"), location(&info.name_location));
+      }
+    }
+    VersionCommand::Bump { bump, .. } => {
+      let bump = bump.bump().ok_or_else(|| Error::InvalidInput("请指定 --bump-code 或 --bump-name".into()))?;
+      let Some(mut change) = version::bump(project_path, module.as_deref(), &bump)? else {
+        return Ok(());
+      };
+      change.keep();
+      if cli.json {
+        print_json(&serde_json::json!({ "previous": change.previous, "applied": change.applied }));
+      } else {
+        println!("{} -> {}", change.previous, change.applied);
+      }
+    }
+  }
+  Ok(())
+}
+
+async fn build(cli: &Cli, dirs: &AppDirs, args: &BuildArgs) -> Result<ExitCode> {
+  let build_id = build::new_build_id();
+  let request = BuildRequest {
+    name: args.project.clone(),
+    module: args.module.clone(),
+    variant: args.variant.clone(),
+    flavors: if args.flavors.is_empty() { None } else { Some(args.flavors.clone()) },
+    build_type: args.build_type.clone(),
+    artifact_kind: args.kind.map(Into::into),
+    output_dir: args.output_dir.clone(),
+    build_id: Some(build_id.clone()),
+    gradle_options: Some(args.gradle.options()).filter(|o| !o.is_empty()),
+    version_bump: if args.no_version_bump { Some(VersionBump::default()) } else { args.version.bump() },
+    signing_profile: if args.no_sign { Some(String::new()) } else { args.sign.clone() },
+  };
+
+  // JSON 模式下 stdout 只输出最终结果，日志写到 stderr
+  let json = cli.json;
+  let sink = Box::new(move |line: &BuildLogLine| match line.stream {
+    LogStream::Stderr => eprintln!("{}", line.line),
+    _ if json => eprintln!("{}", line.line),
+    _ => println!("{}", line.line),
+  });
+
+  let registry = BuildRegistry::default();
+  let running = build::run_build(dirs, &registry, request, sink);
+  tokio::pin!(running);
+  let result = tokio::select! {
+    result = &mut running => result,
+    _ = tokio::signal::ctrl_c() => {
+      // Gradle 运行在独立进程组中，需要主动终止
+      eprintln!("正在取消构建...");
+      match registry.cancel(&build_id) {
+        Ok(pid) => tokio::join!(&mut running, registry.kill_after_grace_period(&build_id, pid)).0,
+        Err(_) => running.await,
+      }
+    }
+  }?;
+
+  if json {
+    print_json(&result);
+  } else {
+    if let Some(version) = &result.version {
+      println!("版本: {}", version);
+    }
+    if let Some(git) = &result.git {
+      println!("源码: {}", git.summary());
+    }
+    for artifact in &result.artifacts {
+      println!("产物: {}", artifact);
+    }
+    for info in &result.artifact_info {
+      println!("包: {}", info.summary());
+    }
+    for report in &result.signatures {
+      println!("签名: {}", report.summary());
+      for warning in &report.warnings {
+        println!("  ⚠️ {}", warning);
+      }
+    }
+  }
+  Ok(match result.status {
+    BuildStatus::Success => ExitCode::SUCCESS,
+    BuildStatus::Cancelled => ExitCode::from(130),
+    BuildStatus::Failed => ExitCode::from(u8::try_from(result.code).ok().filter(|c| *c != 0).unwrap_or(1)),
+  })
+}
+
+/// 命令行参数优先，其次使用已保存的发布平台配置
+fn publish_config(dirs: &AppDirs, args: &PublishArgs) -> Result<PublishConfig> {
+  let saved = match &args.config {
+    Some(name) => Some(config::find_publish_platform(dirs, name)?),
+    None => None,
+  };
+  let platform = args
+    .platform
+    .clone()
+    .or_else(|| saved.as_ref().map(|p| p.platform.clone()))
+    .ok_or_else(|| Error::InvalidInput("请指定 --platform 或 --config".into()))?;
+  Ok(PublishConfig {
+    platform,
+    api_key: args.api_key.clone().or_else(|| saved.as_ref().and_then(|p| p.api_key.clone())),
+    api_token: args.api_token.clone().or_else(|| saved.as_ref().and_then(|p| p.api_token.clone())),
+    password: args.password.clone().or_else(|| saved.as_ref().and_then(|p| p.password.clone())),
+    update_description: args
+      .description
+      .clone()
+      .or_else(|| saved.as_ref().and_then(|p| p.default_description.clone())),
+    config_name: None,
+  })
+}
+
+fn print_signature_report(report: &signature::SignatureReport) {
+  for scheme in &report.schemes {
+    let mark = if scheme.verified { "✅" } else { "❌" };
+    let sdk = match (scheme.min_sdk, scheme.max_sdk) {
+      (Some(min), Some(max)) => format!("（SDK {}-{}）", min, max),
+      _ => String::new(),
+    };
+    println!("{} {}{}", mark, scheme.scheme.label(), sdk);
+    for error in &scheme.errors {
+      println!("    {}", error);
+    }
+  }
+  for (i, cert) in report.signers.iter().enumerate() {
+    println!("证书 #{}{}", i + 1, if cert.debug { "（调试证书）" } else { "" });
+    print_certificate(cert, "  ");
+  }
+  for warning in &report.warnings {
+    println!("⚠️ {}", warning);
+  }
+  println!("{}", if report.verified { "签名校验通过" } else { "签名校验失败" });
+}
+
+fn print_certificate(cert: &CertificateInfo, indent: &str) {
+  println!("{}主题: {}", indent, cert.subject);
+  println!("{}颁发者: {}", indent, cert.issuer);
+  println!("{}密钥: {}", indent, cert.key_algorithm);
+  println!("{}有效期: {} 至 {}", indent, abt_core::format_date(cert.not_before), abt_core::format_date(cert.not_after));
+  println!("{}SHA-1: {}", indent, cert.sha1);
+  println!("{}SHA-256: {}", indent, cert.sha256);
+}
+
+fn print_keystore(info: &KeystoreInfo) {
+  println!("{}（{}）: {}", info.id, info.store_type.label(), info.path);
+  for entry in &info.entries {
+    let kind = if entry.key_entry { "私钥" } else { "受信任证书" };
+    println!("别名 {} [{}]，证书链 {} 个", entry.alias, kind, entry.certificates.len());
+    if let Some(cert) = entry.certificates.first() {
+      print_certificate(cert, "  ");
+    }
+  }
+  print_keystore_problems(info);
+}
+
+fn print_keystore_problems(info: &KeystoreInfo) {
+  if let Some(error) = &info.error {
+    println!("❌ {}", error);
+  }
+  for warning in &info.warnings {
+    println!("⚠️ {}", warning);
+  }
+}
+
+fn print_artifact_info(info: &inspect::ArtifactInfo) {
+  let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".into());
+  println!("包名: {}", info.package);
+  println!("版本: {} ({})", or_dash(info.version_name.clone()), or_dash(info.version_code.map(|c| c.to_string())));
+  println!(
+    "SDK: min {}，target {}，compile {}",
+    or_dash(info.min_sdk.map(|v| v.to_string())),
+    or_dash(info.target_sdk.map(|v| v.to_string())),
+    or_dash(info.compile_sdk.map(|v| v.to_string()))
+  );
+  println!("应用名: {}", or_dash(info.label.clone()));
+  println!("启动 Activity: {}", or_dash(info.launchable_activity.clone()));
+  println!("ABI: {}", if info.abis.is_empty() { "-".into() } else { info.abis.join(", ") });
+  println!("大小: {:.2} MB", info.size as f64 / 1024.0 / 1024.0);
+  println!("权限 ({}):", info.permissions.len());
+  for permission in &info.permissions {
+    println!("  {}", permission);
+  }
+  if !info.modules.is_empty() {
+    println!("模块 ({}):", info.modules.len());
+    for module in &info.modules {
+      let kind = match module.kind {
+        inspect::ModuleKind::Base => "base",
+        inspect::ModuleKind::Feature => "动态功能",
+        inspect::ModuleKind::AssetPack => "资源包",
+      };
+      println!(
+        "  {} [{}] {:.2} MB（解压后 {:.2} MB）",
+        module.name,
+        kind,
+        module.size as f64 / 1024.0 / 1024.0,
+        module.uncompressed_size as f64 / 1024.0 / 1024.0
+      );
+    }
+  }
+}
+
+fn done(cli: &Cli, message: &str) {
+  if cli.json {
+    print_json(&serde_json::json!({ "ok": true, "message": message }));
+  } else {
+    println!("{}", message);
+  }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+  println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+}
@@ -0,0 +1,131 @@
+//! AAB 中 aapt2 生成的 proto 格式清单（`XmlNode`）与资源表（`ResourceTable`）
+//!
+//! 字段号见 aapt2 的 `Resources.proto`，转换为与 APK 相同的 [`XmlElement`] 与 [`ResourceTable`]，
+//! 以便共用清单信息的读取。
+
+use crate::{
+  arsc::ResourceTable,
+  axml::{ResValue, XmlAttribute, XmlElement, XmlValue, ANDROID_ATTRS},
+  pb::Message,
+  Result,
+};
+
+/// 解析 proto 格式的 `AndroidManifest.xml`，返回根元素
+pub(crate) fn parse_manifest(data: &[u8], what: &'static str) -> Result<XmlElement> {
+  // XmlNode { XmlElement element = 1; string text = 2; }
+  let node = Message::new(data, what);
+  match node.message(1)? {
+    Some(element) => parse_element(&element),
+    None => Err(crate::axml::invalid(what, "没有根元素")),
+  }
+}
+
+fn parse_element(element: &Message) -> Result<XmlElement> {
+  // XmlElement { string name = 3; repeated XmlAttribute attribute = 4; repeated XmlNode child = 5; }
+  let name = element.string(3)?.unwrap_or_default();
+  let attributes = element
+    .messages(4)?
+    .iter()
+    .map(parse_attribute)
+    .collect::<Result<Vec<_>>>()?;
+  let mut children = Vec::new();
+  for child in element.messages(5)? {
+    if let Some(child) = child.message(1)? {
+      children.push(parse_element(&child)?);
+    }
+  }
+  Ok(XmlElement { name, attributes, children })
+}
+
+fn parse_attribute(attribute: &Message) -> Result<XmlAttribute> {
+  // XmlAttribute { string name = 2; string value = 3; uint32 resource_id = 5; Item compiled_item = 6; }
+  let resource_id = attribute.uint(5)?.map(|id| id as u32);
+  let known = resource_id.and_then(|id| ANDROID_ATTRS.iter().find(|(attr_id, _)| *attr_id == id));
+  let name = match known {
+    Some((_, name)) => name.to_string(),
+    None => attribute.string(2)?.unwrap_or_default(),
+  };
+  let compiled = match attribute.message(6)? {
+    Some(item) => item_value(&item, None)?,
+    None => None,
+  };
+  let value = match compiled {
+    Some(value) if value.data_type != ResValue::TYPE_STRING => XmlValue::Value(value),
+    _ => XmlValue::String(attribute.string(3)?.unwrap_or_default()),
+  };
+  Ok(XmlAttribute { name, value })
+}
+
+/// `Item` 转为 `Res_value`；字符串追加到 `strings` 中（不需要时为 None，只返回类型）
+fn item_value(item: &Message, strings: Option<&mut ResourceTable>) -> Result<Option<ResValue>> {
+  // Item { Reference ref = 1; String str = 2; RawString raw_str = 3; Primitive prim = 7; }
+  if let Some(reference) = item.message(1)? {
+    let id = reference.uint(2)?.unwrap_or(0) as u32;
+    return Ok(Some(ResValue { data_type: ResValue::TYPE_REFERENCE, data: id }));
+  }
+  for number in [2, 3] {
+    if let Some(s) = item.message(number)? {
+      let data = match strings {
+        Some(table) => table.strings.push(s.string(1)?.unwrap_or_default()),
+        None => 0,
+      };
+      return Ok(Some(ResValue { data_type: ResValue::TYPE_STRING, data }));
+    }
+  }
+  if let Some(prim) = item.message(7)? {
+    // Primitive { int32 int_decimal_value = 6; int32 int_hexadecimal_value = 7; bool boolean_value = 8; }
+    let kinds = [(6, ResValue::TYPE_INT_DEC), (7, ResValue::TYPE_INT_HEX), (8, ResValue::TYPE_INT_BOOLEAN)];
+    for (number, data_type) in kinds {
+      if prim.has(number)? {
+        let data = prim.uint(number)?.unwrap_or(0) as u32;
+        return Ok(Some(ResValue { data_type, data }));
+      }
+    }
+  }
+  Ok(None)
+}
+
+/// 解析 proto 格式的 `resources.pb`
+pub(crate) fn parse_resources(data: &[u8], what: &'static str) -> Result<ResourceTable> {
+  let mut table = ResourceTable::default();
+  let root = Message::new(data, what);
+  // ResourceTable { repeated Package package = 2; }
+  for package in root.messages(2)? {
+    // Package { PackageId package_id = 1; repeated Type type = 3; }
+    let package_id = id_of(&package)?;
+    for kind in package.messages(3)? {
+      // Type { TypeId type_id = 1; repeated Entry entry = 3; }
+      let type_id = id_of(&kind)?;
+      for entry in kind.messages(3)? {
+        // Entry { EntryId entry_id = 1; repeated ConfigValue config_value = 6; }
+        let id = (package_id << 24) | (type_id << 16) | id_of(&entry)?;
+        for config_value in entry.messages(6)? {
+          // ConfigValue { Configuration config = 1; Value value = 2; }，Configuration 的 locale 为 3
+          let default_config = match config_value.message(1)? {
+            Some(config) => config.string(3)?.unwrap_or_default().is_empty(),
+            None => true,
+          };
+          // Value { Item item = 4; }
+          let item = match config_value.message(2)? {
+            Some(value) => value.message(4)?,
+            None => None,
+          };
+          if let Some(item) = item {
+            if let Some(value) = item_value(&item, Some(&mut table))? {
+              table.insert(id, value, default_config);
+            }
+          }
+        }
+      }
+    }
+  }
+  Ok(table)
+}
+
+/// `PackageId`、`TypeId`、`EntryId` 都是 `{ uint32 id = 1; }`
+fn id_of(message: &Message) -> Result<u32> {
+  Ok(match message.message(1)? {
+    Some(id) => id.uint(1)?.unwrap_or(0) as u32,
+    None => 0,
+  })
+}
@@ -0,0 +1,141 @@
+//! 资源表 `resources.arsc`：按资源 ID 查找默认配置下的值，用于解析清单中的 `@string/...` 引用
+
+use std::collections::HashMap;
+
+use crate::{
+  axml::{invalid, Bytes, Chunk, ResValue, StringPool, RES_STRING_POOL_TYPE, RES_TABLE_TYPE},
+  Result,
+};
+
+const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+const RES_TABLE_TYPE_TYPE: u16 = 0x0201;
+
+const FLAG_COMPLEX: u16 = 0x0001;
+const FLAG_COMPACT: u16 = 0x0008;
+const TYPE_FLAG_SPARSE: u8 = 0x01;
+const TYPE_FLAG_OFFSET16: u8 = 0x02;
+const NO_ENTRY: u32 = u32::MAX;
+
+/// 引用链的最大深度，避免循环引用
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+struct Entry {
+  value: ResValue,
+  /// 语言与地区为空（默认配置）
+  default_config: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct ResourceTable {
+  pub strings: StringPool,
+  entries: HashMap<u32, Vec<Entry>>,
+}
+
+impl ResourceTable {
+  /// 添加资源 `id` 在某个配置下的值
+  pub fn insert(&mut self, id: u32, value: ResValue, default_config: bool) {
+    self.entries.entry(id).or_default().push(Entry { value, default_config });
+  }
+
+  pub fn parse(data: &[u8]) -> Result<ResourceTable> {
+    let what = "resources.arsc";
+    let bytes = Bytes::new(data, what);
+    let root = Chunk::read(&bytes, 0)?;
+    if root.kind != RES_TABLE_TYPE {
+      return Err(invalid(what, "不是资源表"));
+    }
+    let mut table = ResourceTable::default();
+    for chunk in Chunk::children(&bytes, root.header_size, root.end())? {
+      match chunk.kind {
+        RES_STRING_POOL_TYPE => table.strings = StringPool::read(&bytes, &chunk)?,
+        RES_TABLE_PACKAGE_TYPE => table.read_package(&bytes, &chunk)?,
+        _ => {}
+      }
+    }
+    Ok(table)
+  }
+
+  fn read_package(&mut self, bytes: &Bytes, package: &Chunk) -> Result<()> {
+    let package_id = bytes.u32(package.offset + 8)?;
+    for chunk in Chunk::children(bytes, package.offset + package.header_size, package.end())? {
+      if chunk.kind == RES_TABLE_TYPE_TYPE {
+        self.read_type(bytes, &chunk, package_id)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn read_type(&mut self, bytes: &Bytes, chunk: &Chunk, package_id: u32) -> Result<()> {
+    let base = chunk.offset;
+    let type_id = bytes.u8(base + 8)? as u32;
+    let flags = bytes.u8(base + 9)?;
+    let entry_count = bytes.u32(base + 12)? as usize;
+    let entries_start = base + bytes.u32(base + 16)? as usize;
+    // ResTable_config：size(4) mcc(2) mnc(2) language(2) country(2)
+    let config = base + 20;
+    let default_config = bytes.u32(config + 8)? == 0;
+    let offsets = base + chunk.header_size;
+
+    let mut entries: Vec<(u32, u32)> = Vec::new();
+    for i in 0..entry_count {
+      if flags & TYPE_FLAG_SPARSE != 0 {
+        // 稀疏表：(entry 序号, 偏移 / 4)
+        let index = bytes.u16(offsets + i * 4)? as u32;
+        let offset = bytes.u16(offsets + i * 4 + 2)? as u32 * 4;
+        entries.push((index, offset));
+      } else if flags & TYPE_FLAG_OFFSET16 != 0 {
+        let offset = bytes.u16(offsets + i * 2)?;
+        if offset != u16::MAX {
+          entries.push((i as u32, offset as u32 * 4));
+        }
+      } else {
+        let offset = bytes.u32(offsets + i * 4)?;
+        if offset != NO_ENTRY {
+          entries.push((i as u32, offset));
+        }
+      }
+    }
+
+    for (index, offset) in entries {
+      let at = entries_start + offset as usize;
+      let entry_flags = bytes.u16(at + 2)?;
+      let value = if entry_flags & FLAG_COMPACT != 0 {
+        // 紧凑格式：key(2) flags(2，高 8 位为数据类型) data(4)
+        ResValue { data_type: (entry_flags >> 8) as u8, data: bytes.u32(at + 4)? }
+      } else if entry_flags & FLAG_COMPLEX != 0 {
+        // style、plurals 等复合资源不需要
+        continue;
+      } else {
+        let size = bytes.u16(at)? as usize;
+        ResValue::read(bytes, at + size)?
+      };
+      self.insert((package_id << 24) | (type_id << 16) | index, value, default_config);
+    }
+    Ok(())
+  }
+
+  /// 资源的值，优先默认配置；引用会继续解析
+  fn value(&self, id: u32) -> Option<ResValue> {
+    let mut id = id;
+    for _ in 0..MAX_REFERENCE_DEPTH {
+      let entries = self.entries.get(&id)?;
+      let entry = entries.iter().find(|e| e.default_config).or_else(|| entries.first())?;
+      if entry.value.data_type != ResValue::TYPE_REFERENCE {
+        return Some(entry.value);
+      }
+      id = entry.value.data;
+    }
+    None
+  }
+
+  pub fn string(&self, id: u32) -> Option<String> {
+    let value = self.value(id)?;
+    (value.data_type == ResValue::TYPE_STRING)
+      .then(|| self.strings.get(value.data).map(str::to_string))
+      .flatten()
+  }
+
+  pub fn int(&self, id: u32) -> Option<u32> {
+    self.value(id).filter(ResValue::is_int).map(|v| v.data)
+  }
+}
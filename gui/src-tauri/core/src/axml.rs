@@ -0,0 +1,325 @@
+//! Android 二进制资源格式：二进制 XML（AXML）、字符串池与资源值
+//!
+//! 只实现读取清单与资源表所需的部分，格式见 AOSP `frameworks/base/libs/androidfw/include/androidfw/ResourceTypes.h`。
+
+use crate::{Error, Result};
+
+pub(crate) const RES_STRING_POOL_TYPE: u16 = 0x0001;
+pub(crate) const RES_TABLE_TYPE: u16 = 0x0002;
+const RES_XML_TYPE: u16 = 0x0003;
+const RES_XML_START_ELEMENT_TYPE: u16 = 0x0102;
+const RES_XML_END_ELEMENT_TYPE: u16 = 0x0103;
+const RES_XML_RESOURCE_MAP_TYPE: u16 = 0x0180;
+
+const UTF8_FLAG: u32 = 1 << 8;
+
+/// 常用的 `android:` 属性资源 ID，属性名被混淆（如 AndResGuard）时按 ID 识别
+pub(crate) const ANDROID_ATTRS: &[(u32, &str)] = &[
+  (0x0101_0001, "label"),
+  (0x0101_0003, "name"),
+  (0x0101_020c, "minSdkVersion"),
+  (0x0101_021b, "versionCode"),
+  (0x0101_021c, "versionName"),
+  (0x0101_0270, "targetSdkVersion"),
+  (0x0101_0572, "compileSdkVersion"),
+  (0x0101_0573, "compileSdkVersionCodename"),
+  (0x0101_0576, "versionCodeMajor"),
+];
+
+pub(crate) fn invalid(what: &str, detail: impl std::fmt::Display) -> Error {
+  Error::Artifact(format!("{} 格式错误：{}", what, detail))
+}
+
+/// 带边界检查的小端读取
+#[derive(Clone, Copy)]
+pub(crate) struct Bytes<'a> {
+  pub data: &'a [u8],
+  /// 出错时提示的文件名
+  pub what: &'static str,
+}
+
+impl<'a> Bytes<'a> {
+  pub fn new(data: &'a [u8], what: &'static str) -> Self {
+    Self { data, what }
+  }
+
+  pub fn slice(&self, offset: usize, len: usize) -> Result<&'a [u8]> {
+    offset
+      .checked_add(len)
+      .and_then(|end| self.data.get(offset..end))
+      .ok_or_else(|| invalid(self.what, format!("偏移 {} 超出范围", offset)))
+  }
+
+  pub fn u8(&self, offset: usize) -> Result<u8> {
+    Ok(self.slice(offset, 1)?[0])
+  }
+
+  pub fn u16(&self, offset: usize) -> Result<u16> {
+    let b = self.slice(offset, 2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+  }
+
+  pub fn u32(&self, offset: usize) -> Result<u32> {
+    let b = self.slice(offset, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+}
+
+/// `ResChunk_header`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Chunk {
+  pub offset: usize,
+  pub kind: u16,
+  pub header_size: usize,
+  pub size: usize,
+}
+
+impl Chunk {
+  pub fn read(bytes: &Bytes, offset: usize) -> Result<Chunk> {
+    let chunk = Chunk {
+      offset,
+      kind: bytes.u16(offset)?,
+      header_size: bytes.u16(offset + 2)? as usize,
+      size: bytes.u32(offset + 4)? as usize,
+    };
+    if chunk.header_size < 8 || chunk.size < chunk.header_size || offset + chunk.size > bytes.data.len() {
+      return Err(invalid(bytes.what, format!("偏移 {} 处的数据块大小无效", offset)));
+    }
+    Ok(chunk)
+  }
+
+  pub fn end(&self) -> usize {
+    self.offset + self.size
+  }
+
+  /// 依次读取 `[start, end)` 范围内的数据块
+  pub fn children(bytes: &Bytes, mut start: usize, end: usize) -> Result<Vec<Chunk>> {
+    let mut chunks = Vec::new();
+    while start + 8 <= end {
+      let chunk = Chunk::read(bytes, start)?;
+      start = chunk.end();
+      chunks.push(chunk);
+    }
+    Ok(chunks)
+  }
+}
+
+/// `ResStringPool`
+#[derive(Default)]
+pub(crate) struct StringPool {
+  strings: Vec<String>,
+}
+
+impl StringPool {
+  pub fn read(bytes: &Bytes, chunk: &Chunk) -> Result<StringPool> {
+    let base = chunk.offset;
+    let count = bytes.u32(base + 8)? as usize;
+    let flags = bytes.u32(base + 16)?;
+    let strings_start = base + bytes.u32(base + 20)? as usize;
+    let utf8 = flags & UTF8_FLAG != 0;
+    let mut strings = Vec::with_capacity(count.min(1 << 16));
+    for i in 0..count {
+      let offset = strings_start + bytes.u32(base + chunk.header_size + i * 4)? as usize;
+      let s = if utf8 { read_utf8(bytes, offset)? } else { read_utf16(bytes, offset)? };
+      strings.push(s);
+    }
+    Ok(StringPool { strings })
+  }
+
+  pub fn get(&self, index: u32) -> Option<&str> {
+    self.strings.get(index as usize).map(String::as_str)
+  }
+
+  /// 追加字符串，返回其序号（proto 格式的资源表中字符串直接内联）
+  pub fn push(&mut self, s: String) -> u32 {
+    self.strings.push(s);
+    (self.strings.len() - 1) as u32
+  }
+}
+
+fn read_utf8(bytes: &Bytes, mut offset: usize) -> Result<String> {
+  // 先是 UTF-16 长度，再是 UTF-8 字节数，各占 1 或 2 字节
+  let len = |offset: &mut usize| -> Result<usize> {
+    let b0 = bytes.u8(*offset)? as usize;
+    *offset += 1;
+    if b0 & 0x80 == 0 {
+      return Ok(b0);
+    }
+    let b1 = bytes.u8(*offset)? as usize;
+    *offset += 1;
+    Ok(((b0 & 0x7f) << 8) | b1)
+  };
+  len(&mut offset)?;
+  let byte_len = len(&mut offset)?;
+  Ok(String::from_utf8_lossy(bytes.slice(offset, byte_len)?).into_owned())
+}
+
+fn read_utf16(bytes: &Bytes, mut offset: usize) -> Result<String> {
+  let mut len = bytes.u16(offset)? as usize;
+  offset += 2;
+  if len & 0x8000 != 0 {
+    len = ((len & 0x7fff) << 16) | bytes.u16(offset)? as usize;
+    offset += 2;
+  }
+  let units: Vec<u16> = bytes
+    .slice(offset, len * 2)?
+    .chunks_exact(2)
+    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+    .collect();
+  Ok(String::from_utf16_lossy(&units))
+}
+
+/// `Res_value`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ResValue {
+  pub data_type: u8,
+  pub data: u32,
+}
+
+impl ResValue {
+  pub const TYPE_NULL: u8 = 0x00;
+  pub const TYPE_REFERENCE: u8 = 0x01;
+  pub const TYPE_STRING: u8 = 0x03;
+  pub const TYPE_INT_DEC: u8 = 0x10;
+  pub const TYPE_INT_HEX: u8 = 0x11;
+  pub const TYPE_INT_BOOLEAN: u8 = 0x12;
+
+  pub fn read(bytes: &Bytes, offset: usize) -> Result<ResValue> {
+    Ok(ResValue { data_type: bytes.u8(offset + 3)?, data: bytes.u32(offset + 4)? })
+  }
+
+  pub fn is_int(&self) -> bool {
+    matches!(self.data_type, Self::TYPE_INT_DEC | Self::TYPE_INT_HEX | Self::TYPE_INT_BOOLEAN)
+  }
+}
+
+/// 属性值：字符串直接给出，其余保留原始的 `Res_value`
+#[derive(Clone, Debug)]
+pub(crate) enum XmlValue {
+  String(String),
+  Value(ResValue),
+}
+
+impl XmlValue {
+  pub fn as_str(&self) -> Option<&str> {
+    match self {
+      XmlValue::String(s) => Some(s),
+      XmlValue::Value(_) => None,
+    }
+  }
+
+  pub fn as_int(&self) -> Option<u32> {
+    match self {
+      XmlValue::Value(v) if v.is_int() => Some(v.data),
+      XmlValue::String(s) => s.trim().parse().ok(),
+      _ => None,
+    }
+  }
+
+  /// 资源引用（`@string/app_name`）的资源 ID
+  pub fn as_reference(&self) -> Option<u32> {
+    match self {
+      XmlValue::Value(v) if v.data_type == ResValue::TYPE_REFERENCE && v.data != 0 => Some(v.data),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct XmlAttribute {
+  pub name: String,
+  pub value: XmlValue,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct XmlElement {
+  pub name: String,
+  pub attributes: Vec<XmlAttribute>,
+  pub children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+  pub fn attr(&self, name: &str) -> Option<&XmlValue> {
+    self.attributes.iter().find(|a| a.name == name).map(|a| &a.value)
+  }
+
+  pub fn attr_str(&self, name: &str) -> Option<&str> {
+    self.attr(name).and_then(XmlValue::as_str)
+  }
+
+  pub fn children_named<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s XmlElement> + 's {
+    self.children.iter().filter(move |c| c.name == name)
+  }
+
+  pub fn child(&self, name: &str) -> Option<&XmlElement> {
+    self.children.iter().find(|c| c.name == name)
+  }
+}
+
+/// 解析二进制 XML，返回根元素
+pub(crate) fn parse(data: &[u8], what: &'static str) -> Result<XmlElement> {
+  let bytes = Bytes::new(data, what);
+  let root = Chunk::read(&bytes, 0)?;
+  if root.kind != RES_XML_TYPE {
+    return Err(invalid(what, "不是二进制 XML"));
+  }
+  let mut strings = StringPool::default();
+  let mut resource_ids: Vec<u32> = Vec::new();
+  // 栈底是虚拟的文档节点
+  let mut stack = vec![XmlElement::default()];
+  for chunk in Chunk::children(&bytes, root.header_size, root.end())? {
+    match chunk.kind {
+      RES_STRING_POOL_TYPE => strings = StringPool::read(&bytes, &chunk)?,
+      RES_XML_RESOURCE_MAP_TYPE => {
+        let count = (chunk.size - chunk.header_size) / 4;
+        resource_ids = (0..count)
+          .map(|i| bytes.u32(chunk.offset + chunk.header_size + i * 4))
+          .collect::<Result<_>>()?;
+      }
+      RES_XML_START_ELEMENT_TYPE => {
+        let ext = chunk.offset + chunk.header_size;
+        let name = strings.get(bytes.u32(ext + 4)?).unwrap_or_default().to_string();
+        let attr_start = bytes.u16(ext + 8)? as usize;
+        let attr_size = bytes.u16(ext + 10)? as usize;
+        let attr_count = bytes.u16(ext + 12)? as usize;
+        let mut attributes = Vec::with_capacity(attr_count);
+        for i in 0..attr_count {
+          let at = ext + attr_start + i * attr_size;
+          let name_index = bytes.u32(at + 4)?;
+          let raw = bytes.u32(at + 8)?;
+          let typed = ResValue::read(&bytes, at + 12)?;
+          let known = resource_ids
+            .get(name_index as usize)
+            .and_then(|id| ANDROID_ATTRS.iter().find(|(attr_id, _)| attr_id == id))
+            .map(|(_, n)| n.to_string());
+          let name = known.unwrap_or_else(|| strings.get(name_index).unwrap_or_default().to_string());
+          let value = match typed.data_type {
+            ResValue::TYPE_STRING => XmlValue::String(strings.get(typed.data).unwrap_or_default().to_string()),
+            ResValue::TYPE_NULL if strings.get(raw).is_some() => XmlValue::String(strings.get(raw).unwrap_or_default().to_string()),
+            _ => XmlValue::Value(typed),
+          };
+          attributes.push(XmlAttribute { name, value });
+        }
+        stack.push(XmlElement { name, attributes, children: Vec::new() });
+      }
+      RES_XML_END_ELEMENT_TYPE if stack.len() > 1 => {
+        let element = stack.pop().unwrap_or_default();
+        if let Some(parent) = stack.last_mut() {
+          parent.children.push(element);
+        }
+      }
+      _ => {}
+    }
+  }
+  // 缺少结束标签时把未闭合的元素依次挂到父元素上
+  while stack.len() > 1 {
+    let element = stack.pop().unwrap_or_default();
+    if let Some(parent) = stack.last_mut() {
+      parent.children.push(element);
+    }
+  }
+  stack
+    .pop()
+    .and_then(|doc| doc.children.into_iter().next())
+    .ok_or_else(|| invalid(what, "没有根元素"))
+}
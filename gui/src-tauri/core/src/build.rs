@@ -0,0 +1,752 @@
+//! 执行 Gradle 构建：日志实时输出、取消、产物收集与构建历史
+
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  process::Stdio,
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+  time::Duration,
+};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncRead, BufReader},
+  process::Command,
+  sync::mpsc,
+};
+
+use crate::{
+  channel,
+  config::{self, gradlew_path, AppDirs},
+  discovery, env,
+  git::{self, GitInfo},
+  inspect::{self, ArtifactInfo},
+  gradle_options::{GradleOptions, Redactor},
+  history::{self, BuildRecord},
+  now_millis, run_command,
+  signature::{self, SignatureReport},
+  signer,
+  variant::BuildVariant,
+  version::{self, AppVersion, VersionBump},
+  Error, Result,
+};
+
+/// 构建产物类型：APK 使用 assemble 任务，AAB 使用 bundle 任务
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+  #[default]
+  Apk,
+  Aab,
+  Both,
+}
+
+impl ArtifactKind {
+  /// 对应的 Gradle 任务前缀
+  fn task_prefixes(self) -> &'static [&'static str] {
+    match self {
+      ArtifactKind::Apk => &["assemble"],
+      ArtifactKind::Aab => &["bundle"],
+      ArtifactKind::Both => &["assemble", "bundle"],
+    }
+  }
+
+  fn includes_apk(self) -> bool {
+    matches!(self, ArtifactKind::Apk | ArtifactKind::Both)
+  }
+
+  fn includes_aab(self) -> bool {
+    matches!(self, ArtifactKind::Aab | ArtifactKind::Both)
+  }
+}
+
+/// 构建结果状态，取消的构建单独标记，不再只看退出码
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildStatus {
+  Success,
+  Failed,
+  Cancelled,
+}
+
+#[derive(Serialize)]
+pub struct BuildResult {
+  pub build_id: String,
+  pub status: BuildStatus,
+  pub variant: BuildVariant,
+  pub code: i32,
+  pub output: String,
+  /// 复制到输出目录的构建产物
+  pub artifacts: Vec<String>,
+  /// 构建使用的版本号
+  pub version: Option<AppVersion>,
+  /// 构建时的 git 状态，非 git 工程为 None
+  pub git: Option<GitInfo>,
+  /// 解析产物得到的包名、版本、SDK 版本等
+  pub artifact_info: Vec<ArtifactInfo>,
+  /// APK 的签名校验结果
+  pub signatures: Vec<SignatureReport>,
+}
+
+/// 构建参数，未传入的字段使用工程配置的默认值
+#[derive(Deserialize, Default)]
+pub struct BuildRequest {
+  /// 工程名
+  pub name: String,
+  pub module: Option<String>,
+  /// flavor 组合名，如 `freeStaging`
+  pub variant: Option<String>,
+  /// 按维度排列的 flavor，优先于 `variant`
+  pub flavors: Option<Vec<String>>,
+  pub build_type: Option<String>,
+  pub artifact_kind: Option<ArtifactKind>,
+  pub output_dir: Option<String>,
+  pub build_id: Option<String>,
+  /// 覆盖工程配置的 Gradle 参数、属性与环境变量
+  pub gradle_options: Option<GradleOptions>,
+  /// 构建前修改版本号，未传入时使用工程配置，传入空策略表示本次不修改
+  pub version_bump: Option<VersionBump>,
+  /// 构建后重新签名 APK 的签名配置，未传入时使用工程配置，传入空字符串表示本次不签名
+  pub signing_profile: Option<String>,
+}
+
+/// 取消构建时先发送终止信号，超过该时间仍未退出则强制结束
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+struct RunningBuild {
+  pid: u32,
+  cancelled: Arc<AtomicBool>,
+}
+
+/// 正在运行的构建登记表，以 build id 为键
+#[derive(Default)]
+pub struct BuildRegistry {
+  builds: Mutex<HashMap<String, RunningBuild>>,
+}
+
+impl BuildRegistry {
+  fn register(&self, build_id: &str, pid: u32) -> Result<Arc<AtomicBool>> {
+    let mut builds = self.builds.lock().unwrap();
+    if builds.contains_key(build_id) {
+      return Err(Error::BuildExists(build_id.to_string()));
+    }
+    let cancelled = Arc::new(AtomicBool::new(false));
+    builds.insert(build_id.to_string(), RunningBuild { pid, cancelled: cancelled.clone() });
+    Ok(cancelled)
+  }
+
+  fn unregister(&self, build_id: &str) {
+    self.builds.lock().unwrap().remove(build_id);
+  }
+
+  /// 标记为已取消，返回构建进程的 pid
+  fn mark_cancelled(&self, build_id: &str) -> Option<u32> {
+    let builds = self.builds.lock().unwrap();
+    let build = builds.get(build_id)?;
+    build.cancelled.store(true, Ordering::SeqCst);
+    Some(build.pid)
+  }
+
+  fn is_running(&self, build_id: &str, pid: u32) -> bool {
+    self.builds.lock().unwrap().get(build_id).is_some_and(|b| b.pid == pid)
+  }
+
+  /// 正在运行的构建 ID
+  pub fn running(&self) -> Vec<String> {
+    self.builds.lock().unwrap().keys().cloned().collect()
+  }
+
+  /// 取消构建：向进程树发送终止信号，返回构建进程的 pid。
+  /// 之后应调用 `kill_after_grace_period` 确保进程最终退出
+  pub fn cancel(&self, build_id: &str) -> Result<u32> {
+    let pid = self
+      .mark_cancelled(build_id)
+      .ok_or_else(|| Error::BuildNotFound(build_id.to_string()))?;
+
+    log::info!("取消构建 {}（pid: {}）", build_id, pid);
+    let (ok, out) = terminate_process_tree(pid, false);
+    if !ok {
+      // 无法正常终止时直接强制结束
+      log::warn!("终止构建进程失败，强制结束: {}", out);
+      terminate_process_tree(pid, true);
+    }
+    Ok(pid)
+  }
+
+  /// 等待构建在宽限期内退出，超时则强制结束进程树
+  pub async fn kill_after_grace_period(&self, build_id: &str, pid: u32) {
+    let deadline = tokio::time::Instant::now() + CANCEL_GRACE_PERIOD;
+    while self.is_running(build_id, pid) {
+      if tokio::time::Instant::now() >= deadline {
+        log::warn!("构建 {} 在 {} 秒内未退出，强制结束", build_id, CANCEL_GRACE_PERIOD.as_secs());
+        let (ok, out) = terminate_process_tree(pid, true);
+        if !ok {
+          log::error!("强制结束构建进程失败: {}", out);
+        }
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+  }
+}
+
+/// 构建结束（包括出错提前返回）时从登记表中移除
+struct RegisteredBuild<'a> {
+  registry: &'a BuildRegistry,
+  build_id: String,
+}
+
+impl Drop for RegisteredBuild<'_> {
+  fn drop(&mut self) {
+    self.registry.unregister(&self.build_id);
+  }
+}
+
+/// 结束构建进程树。gradlew 以独立进程组启动，信号发送给整个进程组
+#[cfg(unix)]
+fn terminate_process_tree(pid: u32, force: bool) -> (bool, String) {
+  let signal = if force { "-KILL" } else { "-TERM" };
+  run_command("kill", &[signal, "--", &format!("-{}", pid)])
+}
+
+/// 结束构建进程树（taskkill /T 会连同子进程一起结束）
+#[cfg(windows)]
+fn terminate_process_tree(pid: u32, force: bool) -> (bool, String) {
+  let pid = pid.to_string();
+  let mut args = vec!["/PID", pid.as_str(), "/T"];
+  if force {
+    args.push("/F");
+  }
+  run_command("taskkill", &args)
+}
+
+/// 日志来源：Gradle 的 stdout/stderr，或本工具自身输出的提示信息
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+  Stdout,
+  Stderr,
+  System,
+}
+
+/// 单行构建日志（GUI 以事件推送给前端，命令行直接输出）
+#[derive(Serialize, Clone)]
+pub struct BuildLogLine {
+  pub build_id: String,
+  pub stream: LogStream,
+  pub line: String,
+  /// Unix 时间戳（毫秒）
+  pub timestamp: u64,
+}
+
+/// 构建日志的实时输出目标
+pub type LogSink = Box<dyn Fn(&BuildLogLine) + Send + Sync>;
+
+/// 生成构建 ID（调用方未传入时使用）
+pub fn new_build_id() -> String {
+  static COUNTER: AtomicU64 = AtomicU64::new(0);
+  format!("build-{}-{}", now_millis(), COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// 构建日志：每一行既实时输出，也累积到最终的 `BuildResult.output`，
+/// 保证两者内容一致
+struct BuildLog {
+  sink: LogSink,
+  build_id: String,
+  text: String,
+  /// 推送与保存前隐藏 Gradle 参数中的敏感值
+  redactor: Redactor,
+}
+
+impl BuildLog {
+  fn new(sink: LogSink, build_id: String, redactor: Redactor) -> Self {
+    Self { sink, build_id, text: String::new(), redactor }
+  }
+
+  fn push(&mut self, stream: LogStream, line: impl Into<String>) {
+    let line = self.redactor.redact(&line.into());
+    self.text.push_str(&line);
+    self.text.push('\n');
+    let event = BuildLogLine {
+      build_id: self.build_id.clone(),
+      stream,
+      line,
+      timestamp: now_millis(),
+    };
+    (self.sink)(&event);
+  }
+
+  fn info(&mut self, line: impl Into<String>) {
+    self.push(LogStream::System, line);
+  }
+}
+
+/// 在阻塞线程中执行签名、解析等文件与加密操作，避免占用异步运行时的工作线程
+async fn blocking<T: Send + 'static>(task: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+  tokio::task::spawn_blocking(task)
+    .await
+    .map_err(|e| Error::Artifact(format!("后台任务异常退出：{}", e)))?
+}
+
+/// 按行读取子进程输出并转发到通道（按字节读取，避免非 UTF-8 输出中断读取）
+async fn forward_lines<R: AsyncRead + Unpin>(
+  reader: R,
+  stream: LogStream,
+  tx: mpsc::UnboundedSender<(LogStream, String)>,
+) {
+  let mut reader = BufReader::new(reader);
+  let mut buf = Vec::new();
+  loop {
+    buf.clear();
+    match reader.read_until(b'\n', &mut buf).await {
+      Ok(0) => break,
+      Ok(_) => {
+        while matches!(buf.last(), Some(b'\n' | b'\r')) {
+          buf.pop();
+        }
+        if tx.send((stream, String::from_utf8_lossy(&buf).to_string())).is_err() {
+          break;
+        }
+      }
+      Err(e) => {
+        log::warn!("读取构建输出失败: {}", e);
+        break;
+      }
+    }
+  }
+}
+
+/// 执行构建：运行 Gradle 任务、实时输出日志、复制产物并记录构建历史
+pub async fn run_build(
+  dirs: &AppDirs,
+  registry: &BuildRegistry,
+  request: BuildRequest,
+  sink: LogSink,
+) -> Result<BuildResult> {
+  let BuildRequest {
+    name,
+    module,
+    variant,
+    flavors,
+    build_type,
+    artifact_kind,
+    output_dir,
+    build_id,
+    gradle_options,
+    version_bump,
+    signing_profile,
+  } = request;
+  let project = config::find_project(dirs, &name)?;
+  
+  // 获取构建参数，优先使用传入的值，否则使用工程默认值
+  let variant = variant.or_else(|| {
+    // 如果有 variants 数组，使用第一个
+    project.variants.as_ref()
+      .and_then(|v| v.first())
+      .cloned()
+      .or_else(|| project.default_variant.clone())
+  });
+  let build_type = build_type.or_else(|| project.build_type.clone()).unwrap_or_else(|| "Debug".into());
+  let artifact_kind = artifact_kind.or(project.artifact_kind).unwrap_or_default();
+  let module = module.or_else(|| project.default_build_module());
+  
+  // 解析变体：有发现结果时校验 flavor/buildType 组合是否存在
+  let module_variants = module
+    .as_deref()
+    .and_then(|m| project.discovered.as_ref().and_then(|d| d.module(m)));
+  let variant = BuildVariant::resolve(variant.as_deref(), flavors, &build_type, module_variants)?;
+  
+  // 输出目录：优先使用传入的 output_dir，否则使用 path+module+flavor/buildType
+  let output_dir = if let Some(ref custom_dir) = output_dir {
+    Path::new(custom_dir).to_path_buf()
+  } else {
+    let mut dir = Path::new(&project.path).to_path_buf();
+    if let Some(m) = &module {
+      dir = discovery::module_dir(&dir, m);
+    }
+    dir.join(variant.output_subdir())
+  };
+  
+  // 构建gradle任务名称（APK 为 assemble，AAB 为 bundle）
+  let tasks: Vec<String> = artifact_kind
+    .task_prefixes()
+    .iter()
+    .map(|prefix| variant.task(prefix, module.as_deref()))
+    .collect();
+  
+  let gradle_path = gradlew_path(&project.path);
+  if !gradle_path.exists() {
+    return Err(Error::GradlewNotFound(gradle_path));
+  }
+  // 工程保存的敏感值先解密，单次构建的覆盖项按原值使用
+  let mut gradle_options = project
+    .gradle_options
+    .clone()
+    .unwrap_or_default()
+    .revealed(dirs)?
+    .merged(&gradle_options.unwrap_or_default());
+  gradle_options.validate()?;
+  let java_home = match project.java_home.as_deref().filter(|h| !h.is_empty()) {
+    Some(home) => {
+      let home = PathBuf::from(home);
+      if !env::java_executable(&home).is_file() {
+        return Err(Error::JdkNotFound(home));
+      }
+      Some(home)
+    }
+    None => None,
+  };
+
+  // 在修改版本号之前读取 git 状态，版本号的修改不算作未提交的修改
+  let project_path = Path::new(&project.path);
+  let git_info = git::read(project_path);
+  let git_policy = project.git_policy.clone().unwrap_or_default();
+  if variant.build_type.eq_ignore_ascii_case("release") {
+    git::check_release(&git_policy, git_info.as_ref())?;
+  }
+  if let (true, Some(info)) = (git_policy.inject_properties, &git_info) {
+    // 用户配置的同名属性优先
+    for (key, value) in info.gradle_properties() {
+      gradle_options.properties.entry(key).or_insert(value);
+    }
+  }
+  
+  // 构建前读取签名配置，配置不存在时不必等构建结束才报错
+  let signing = match signing_profile.or_else(|| project.signing_profile.clone()).filter(|p| !p.is_empty()) {
+    Some(name) => Some(config::find_signing_profile(dirs, &name)?),
+    None => None,
+  };
+
+  let build_id = build_id.unwrap_or_else(new_build_id);
+  let started_at = now_millis();
+  let mut copied = Vec::new();
+  let mut artifact_info = Vec::new();
+  let mut signatures = Vec::new();
+  let mut log = BuildLog::new(sink, build_id.clone(), gradle_options.redactor());
+  let output_dir_str = output_dir.to_string_lossy().to_string();
+  let extra_args = gradle_options.command_args();
+  log.info(format!("执行任务: {}", tasks.join(" ")));
+  if let Some(info) = &git_info {
+    log.info(format!("源码: {}", info.summary()));
+  }
+  if !extra_args.is_empty() {
+    log.info(format!("Gradle 参数: {}", extra_args.join(" ")));
+  }
+  if !gradle_options.env.is_empty() {
+    let names: Vec<&str> = gradle_options.env.keys().map(String::as_str).collect();
+    log.info(format!("环境变量: {}", names.join(", ")));
+  }
+
+  // 构建前修改版本号；未修改时记录当前的版本号
+  let version_bump = version_bump.or_else(|| project.version_bump.clone()).unwrap_or_default();
+  let mut version_change = version::bump(project_path, module.as_deref(), &version_bump)?;
+  let used_version = match &version_change {
+    Some(change) => {
+      log.info(format!("版本号: {} -> {}", change.previous, change.applied));
+      for file in change.files() {
+        log.info(format!("  已修改 {}", file.display()));
+      }
+      change.applied.clone()
+    }
+    None => version::read(project_path, module.as_deref()).app_version(),
+  };
+  
+  // 使用异步 Command 执行构建命令，逐行读取 stdout/stderr 并实时推送给前端
+  let mut cmd = Command::new(&gradle_path);
+  cmd.args(&tasks).args(&extra_args).envs(&gradle_options.env);
+  // 工程指定的 JDK：导出 JAVA_HOME，并覆盖 gradle.properties 中的 org.gradle.java.home
+  if let Some(home) = &java_home {
+    log.info(format!("使用工程指定的 JDK: {}", home.display()));
+    cmd.env("JAVA_HOME", home)
+      .arg(format!("-Dorg.gradle.java.home={}", home.display()));
+  }
+  cmd.current_dir(&project.path)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true);
+  // 独立进程组，取消时可以结束整个进程树
+  #[cfg(unix)]
+  cmd.process_group(0);
+  let mut child = cmd.spawn().map_err(|e| Error::io("执行构建命令失败", e))?;
+
+  let pid = child.id().ok_or_else(|| Error::Build("无法获取构建进程 ID".into()))?;
+  let cancelled = registry.register(&build_id, pid)?;
+  let _registered = RegisteredBuild { registry, build_id: build_id.clone() };
+
+  let (tx, mut rx) = mpsc::unbounded_channel();
+  if let Some(stdout) = child.stdout.take() {
+    tokio::spawn(forward_lines(stdout, LogStream::Stdout, tx.clone()));
+  }
+  if let Some(stderr) = child.stderr.take() {
+    tokio::spawn(forward_lines(stderr, LogStream::Stderr, tx.clone()));
+  }
+  drop(tx);
+
+  // 两个读取任务都结束（输出管道关闭）后通道才会关闭
+  while let Some((stream, line)) = rx.recv().await {
+    log.push(stream, line);
+  }
+
+  let status = child
+    .wait()
+    .await
+    .map_err(|e| Error::io("等待构建进程失败", e))?;
+  let code = status.code().unwrap_or(-1);
+  let mut status = if cancelled.load(Ordering::SeqCst) {
+    BuildStatus::Cancelled
+  } else if code == 0 {
+    BuildStatus::Success
+  } else {
+    BuildStatus::Failed
+  };
+  
+  // 如果构建成功，复制构建产物到输出目录
+  if status == BuildStatus::Cancelled {
+    log.info("");
+    log.info("构建已取消");
+  } else if code == 0 {
+    log.info("");
+    log.info(format!("输出目录: {}", output_dir_str));
+    
+    // 查找并复制构建产物
+    let module_path = match &module {
+      Some(m) => discovery::module_dir(Path::new(&project.path), m),
+      None => Path::new(&project.path).to_path_buf(),
+    };
+    
+    log.info(format!("查找路径: {}", module_path.to_string_lossy()));
+    let artifacts = find_build_artifacts(&module_path, artifact_kind, &variant);
+    log.info(format!("找到 {} 个构建产物", artifacts.len()));
+    
+    if !artifacts.is_empty() {
+      for artifact in &artifacts {
+        log.info(format!("  - {}", artifact.to_string_lossy()));
+      }
+      
+      // 如果输出目录已存在，先清理目录（确保只保留最新的构建产物）
+      if output_dir.exists() {
+        log.info(format!("清理输出目录: {}", output_dir.to_string_lossy()));
+        clean_directory(&output_dir, &mut log);
+      }
+      
+      // 确保输出目录存在
+      if let Err(e) = fs::create_dir_all(&output_dir) {
+        log.info(format!("创建输出目录失败: {}", e));
+      } else {
+        for artifact in &artifacts {
+          if let Some(file_name) = artifact.file_name() {
+            let dest = output_dir.join(file_name);
+            if !artifact.exists() {
+              log.info(format!("❌ 源文件不存在: {}", artifact.to_string_lossy()));
+              continue;
+            }
+            if let Err(e) = fs::copy(artifact, &dest) {
+              log.info(format!("❌ 复制失败 {}: {}", file_name.to_string_lossy(), e));
+            } else {
+              log.info(format!("✅ 已复制: {} -> {}", file_name.to_string_lossy(), dest.to_string_lossy()));
+              copied.push(dest);
+            }
+          }
+        }
+      }
+    } else {
+      log.info("未找到构建产物，请检查构建是否成功");
+    }
+
+    // 用签名配置重新签名 APK，去掉文件名中的 -unsigned
+    if let Some(profile) = &signing {
+      for path in copied.iter_mut().filter(|p| p.extension().is_some_and(|e| e == "apk")) {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().replace("-unsigned", "");
+        let dest = path.with_file_name(&file_name);
+        let (task_dirs, task_profile, input, output) = (dirs.clone(), profile.clone(), path.clone(), dest.clone());
+        match blocking(move || signer::sign_with_profile(&task_dirs, &task_profile, &input, &output)).await {
+          Ok(result) => {
+            log.info(format!("🔏 已使用签名配置 {}（{}）签名: {}", profile.name, result.alias, file_name));
+            if dest != *path {
+              let _ = fs::remove_file(&*path);
+              *path = dest;
+            }
+          }
+          Err(e) => {
+            log.info(format!("❌ 签名失败 {}: {}", path.to_string_lossy(), e));
+            status = BuildStatus::Failed;
+          }
+        }
+      }
+    }
+
+    for path in copied.iter().filter(|p| p.extension().is_some_and(|e| e == "apk" || e == "aab")) {
+      let input = path.clone();
+      match blocking(move || inspect::inspect(&input)).await {
+        Ok(info) => {
+          log.info(format!("📦 {}", info.summary()));
+          artifact_info.push(info);
+        }
+        Err(e) => log.info(format!("⚠️ 解析 {} 失败: {}", path.to_string_lossy(), e)),
+      }
+    }
+
+    let release = variant.build_type.eq_ignore_ascii_case("release");
+    for path in copied.iter().filter(|p| p.extension().is_some_and(|e| e == "apk")) {
+      let input = path.clone();
+      match blocking(move || signature::verify_apk(&input, release)).await {
+        Ok(report) => {
+          log.info(format!("🔏 {}", report.summary()));
+          for warning in &report.warnings {
+            log.info(format!("⚠️ {}", warning));
+          }
+          signatures.push(report);
+        }
+        Err(e) => log.info(format!("⚠️ 校验 {} 的签名失败: {}", path.to_string_lossy(), e)),
+      }
+    }
+
+    // 按工程的渠道设置生成渠道包
+    if let Some(options) = project.channels.as_ref().filter(|_| status == BuildStatus::Success) {
+      for path in copied.iter().filter(|p| p.extension().is_some_and(|e| e == "apk")) {
+        let (task_project, task_options, input) = (project_path.to_path_buf(), options.clone(), path.clone());
+        match blocking(move || channel::write_project_channels(&task_project, &task_options, &input, None)).await {
+          Ok(Some(output)) => {
+            log.info(format!(
+              "🏷️ 已用 {} 方式生成 {} 个渠道包: {}",
+              output.scheme.label(),
+              output.apks.len(),
+              output.output_dir
+            ));
+            for warning in &output.warnings {
+              log.info(format!("⚠️ {}", warning));
+            }
+          }
+          Ok(None) => {}
+          Err(e) => {
+            log.info(format!("❌ 生成渠道包失败 {}: {}", path.to_string_lossy(), e));
+            status = BuildStatus::Failed;
+          }
+        }
+      }
+    }
+  } else {
+    // 构建失败时也显示输出目录信息
+    log.info("");
+    log.info(format!("输出目录: {}", output_dir_str));
+  }
+
+  if let Some(change) = version_change.as_mut() {
+    if status != BuildStatus::Success && change.rollback_on_failure {
+      match change.rollback() {
+        Ok(()) => log.info(format!("构建未成功，版本号已恢复为 {}", change.previous)),
+        Err(e) => log.info(format!("❌ {}", e)),
+      }
+    } else {
+      change.keep();
+    }
+  }
+  let version = (used_version != AppVersion::default()).then_some(used_version);
+
+  // 保存构建历史，失败只记录警告，不影响构建结果
+  let history_dir = dirs.history_dir();
+  let log_path = match history::write_log(&history_dir, &build_id, &log.text) {
+    Ok(p) => Some(p.to_string_lossy().to_string()),
+    Err(e) => {
+      log::warn!("{}", e);
+      None
+    }
+  };
+  let artifacts = copied
+    .iter()
+    .filter_map(|p| history::artifact_record(p).map_err(|e| log::warn!("{}", e)).ok())
+    .collect();
+  let record = BuildRecord {
+    build_id: build_id.clone(),
+    project: project.name.clone(),
+    module: module.clone(),
+    variant: variant.clone(),
+    tasks,
+    started_at,
+    finished_at: now_millis(),
+    status,
+    code,
+    artifacts,
+    log_path,
+    git_commit: git_info.as_ref().map(|g| g.commit.clone()),
+    git: git_info.clone(),
+    version: version.clone(),
+  };
+  if let Err(e) = history::append(&history_dir, record) {
+    log::warn!("保存构建历史失败: {}", e);
+  }
+
+  Ok(BuildResult {
+    build_id,
+    status,
+    variant,
+    code,
+    output: log.text,
+    artifacts: copied.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+    version,
+    git: git_info,
+    artifact_info,
+    signatures,
+  })
+}
+
+/// 清理目录中的所有文件和子目录
+fn clean_directory(dir: &Path, log: &mut BuildLog) {
+  if !dir.exists() {
+    return;
+  }
+  
+  if let Ok(entries) = fs::read_dir(dir) {
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let result = if path.is_dir() {
+        fs::remove_dir_all(&path)
+      } else {
+        fs::remove_file(&path)
+      };
+      
+      if let Err(e) = result {
+        log.info(format!("⚠️ 清理文件/目录失败: {}: {}", path.to_string_lossy(), e));
+      }
+    }
+  }
+}
+
+/// 查找构建产物（APK/AAB），只返回本次构建类型与变体对应的产物
+fn find_build_artifacts(module_path: &Path, kind: ArtifactKind, variant: &BuildVariant) -> Vec<PathBuf> {
+  let mut artifacts = Vec::new();
+  
+  // APK 文件路径
+  let apk_path = variant.apk_output_dir(module_path);
+  // AAB 文件路径
+  let bundle_path = variant.bundle_output_dir(module_path);
+  
+  // 查找所有 APK 文件
+  if kind.includes_apk() && apk_path.exists() {
+    find_artifacts_recursive(&apk_path, "apk", &mut artifacts);
+  }
+  
+  // 查找所有 AAB 文件
+  if kind.includes_aab() && bundle_path.exists() {
+    find_artifacts_recursive(&bundle_path, "aab", &mut artifacts);
+  }
+  
+  artifacts
+}
+
+/// 递归查找构建产物
+fn find_artifacts_recursive(dir: &Path, extension: &str, artifacts: &mut Vec<PathBuf>) {
+  if let Ok(entries) = fs::read_dir(dir) {
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if path.is_dir() {
+        find_artifacts_recursive(&path, extension, artifacts);
+      } else if path.extension().and_then(|s| s.to_str()) == Some(extension) {
+        artifacts.push(path);
+      }
+    }
+  }
+}
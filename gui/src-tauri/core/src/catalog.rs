@@ -0,0 +1,105 @@
+//! Gradle 版本目录（`gradle/libs.versions.toml`）读取
+
+use std::{fs, path::Path};
+use toml_edit::{DocumentMut, Item};
+
+/// 工程的版本目录
+pub struct VersionCatalog {
+  doc: DocumentMut,
+}
+
+impl VersionCatalog {
+  pub const RELATIVE_PATH: &'static str = "gradle/libs.versions.toml";
+
+  /// 读取工程的版本目录，不存在或格式错误时返回 None
+  pub fn load(project_path: &Path) -> Option<Self> {
+    let content = fs::read_to_string(project_path.join(Self::RELATIVE_PATH)).ok()?;
+    match content.parse::<DocumentMut>() {
+      Ok(doc) => Some(Self { doc }),
+      Err(e) => {
+        log::warn!("解析版本目录失败: {}", e);
+        None
+      }
+    }
+  }
+
+  /// `[versions]` 中的版本，名称比较忽略 `-`/`_`/`.` 与大小写（与 Gradle 生成的访问器一致）
+  pub fn version(&self, name: &str) -> Option<String> {
+    let versions = self.doc.get("versions")?.as_table_like()?;
+    let wanted = accessor_key(name);
+    versions
+      .iter()
+      .find(|(key, _)| accessor_key(key) == wanted)
+      .and_then(|(_, item)| version_value(item))
+  }
+
+  /// `[plugins]` 中指定插件 id 的版本
+  pub fn plugin_version(&self, plugin_id: &str) -> Option<String> {
+    let plugins = self.doc.get("plugins")?.as_table_like()?;
+    plugins.iter().find_map(|(_, item)| {
+      if let Some(s) = item.as_str() {
+        // 简写形式 "com.android.application:8.3.0"
+        let (id, version) = s.split_once(':')?;
+        return (id == plugin_id).then(|| version.to_string());
+      }
+      let table = item.as_table_like()?;
+      if table.get("id")?.as_str()? != plugin_id {
+        return None;
+      }
+      self.resolve_version(table.get("version")?)
+    })
+  }
+
+  /// `[libraries]` 中指定 `group:name` 的版本
+  pub fn library_version(&self, module: &str) -> Option<String> {
+    let libraries = self.doc.get("libraries")?.as_table_like()?;
+    libraries.iter().find_map(|(_, item)| {
+      if let Some(s) = item.as_str() {
+        // 简写形式 "group:name:version"
+        let (coordinate, version) = s.rsplit_once(':')?;
+        return (coordinate == module).then(|| version.to_string());
+      }
+      let table = item.as_table_like()?;
+      let coordinate = match table.get("module").and_then(Item::as_str) {
+        Some(m) => m.to_string(),
+        None => format!("{}:{}", table.get("group")?.as_str()?, table.get("name")?.as_str()?),
+      };
+      if coordinate != module {
+        return None;
+      }
+      self.resolve_version(table.get("version")?)
+    })
+  }
+
+  /// `version = "1.0"`、`version.ref = "agp"` 或 `version = { ref = "agp" }`
+  fn resolve_version(&self, item: &Item) -> Option<String> {
+    if let Some(s) = item.as_str() {
+      return Some(s.to_string());
+    }
+    let table = item.as_table_like()?;
+    if let Some(reference) = table.get("ref").and_then(Item::as_str) {
+      return self.version(reference);
+    }
+    version_value(item)
+  }
+}
+
+/// 版本可以是字符串，也可以是 `{ strictly = "..." }` / `{ require = "..." }` / `{ prefer = "..." }`
+fn version_value(item: &Item) -> Option<String> {
+  if let Some(s) = item.as_str() {
+    return Some(s.to_string());
+  }
+  let table = item.as_table_like()?;
+  ["strictly", "require", "prefer"]
+    .iter()
+    .find_map(|k| table.get(k).and_then(Item::as_str))
+    .map(str::to_string)
+}
+
+pub(crate) fn accessor_key(name: &str) -> String {
+  name
+    .chars()
+    .filter(|c| !matches!(c, '-' | '_' | '.'))
+    .flat_map(char::to_lowercase)
+    .collect()
+}
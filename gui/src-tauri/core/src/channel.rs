@@ -0,0 +1,577 @@
+//! 渠道包：把渠道写入已签名的 APK，支持三种读取方式
+//!
+//! - Walle：`{"channel": "...", 其他键值}` 形式的 JSON 保存在签名块中 ID 为 0x71777777 的 ID-值对；
+//! - VasDolly：渠道名（UTF-8）保存在签名块中 ID 为 0x881155ff 的 ID-值对；
+//! - META-INF：新增空文件 `META-INF/channel_<渠道>`，用于只有 v1 签名的老应用。
+//!
+//! 签名块不在 v2/v3 签名的摘要范围内，v1 签名不校验 META-INF 下的顶层文件，所以写入后都不需要重新签名。
+//! 每个渠道包写出前都会读回渠道并重新校验签名。
+
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  config::Project,
+  inspect,
+  signature::{self, SignatureReport, SignatureScheme},
+  signing_block::{Apk, V2_BLOCK_ID, V31_BLOCK_ID, V3_BLOCK_ID},
+  zipalign::{self, ZipEntry},
+  Error, Result,
+};
+
+/// Walle 的 ID-值对
+pub(crate) const WALLE_BLOCK_ID: u32 = 0x7177_7777;
+/// VasDolly 的 ID-值对
+pub(crate) const VASDOLLY_BLOCK_ID: u32 = 0x8811_55ff;
+/// 签名块中保存渠道的 ID-值对，写入时只保留一种，避免同一个 APK 带有互相矛盾的渠道
+const CHANNEL_BLOCK_IDS: [u32; 2] = [WALLE_BLOCK_ID, VASDOLLY_BLOCK_ID];
+/// META-INF 方案的渠道文件前缀
+const META_INF_PREFIX: &str = "META-INF/channel_";
+/// apksigner 为 v4（fs-verity）补齐签名块大小的 ID-值对
+const VERITY_PADDING_BLOCK_ID: u32 = 0x4272_6577;
+/// 带填充的签名块按 4096 字节对齐
+const BLOCK_ALIGNMENT: usize = 4096;
+/// Walle JSON 中的渠道字段
+const CHANNEL_KEY: &str = "channel";
+/// 默认的输出文件名，支持 `{{name}}`（原文件名）、`{{channel}}`、`{{version}}`、`{{code}}`、`{{package}}`
+pub const DEFAULT_NAME_TEMPLATE: &str = "{{name}}-{{channel}}.apk";
+
+/// 渠道的写入方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum ChannelScheme {
+  /// Walle：签名块中的 JSON，支持附加信息
+  #[default]
+  #[serde(rename = "walle")]
+  Walle,
+  /// VasDolly：签名块中的渠道名
+  #[serde(rename = "vasdolly")]
+  VasDolly,
+  /// `META-INF/channel_<渠道>` 空文件，只用于 v1 签名的 APK
+  #[serde(rename = "meta-inf")]
+  MetaInf,
+}
+
+impl ChannelScheme {
+  pub const ALL: [ChannelScheme; 3] = [ChannelScheme::Walle, ChannelScheme::VasDolly, ChannelScheme::MetaInf];
+
+  pub fn label(self) -> &'static str {
+    match self {
+      ChannelScheme::Walle => "Walle",
+      ChannelScheme::VasDolly => "VasDolly",
+      ChannelScheme::MetaInf => "META-INF",
+    }
+  }
+
+  fn write(self, apk: &Apk, info: &ChannelInfo) -> Result<Vec<u8>> {
+    match self {
+      ChannelScheme::Walle => write_walle(apk, info),
+      ChannelScheme::VasDolly => Ok(replace_pair(apk, VASDOLLY_BLOCK_ID, info.channel.as_bytes())),
+      ChannelScheme::MetaInf => write_meta_inf(apk, info),
+    }
+  }
+
+  fn read(self, apk: &Apk) -> Result<Option<ChannelInfo>> {
+    match self {
+      ChannelScheme::Walle => read_walle(apk),
+      ChannelScheme::VasDolly => read_vasdolly(apk),
+      ChannelScheme::MetaInf => read_meta_inf(apk),
+    }
+  }
+}
+
+/// 工程的渠道包设置，配置后构建成功的 APK 会自动生成渠道包
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelOptions {
+  #[serde(default)]
+  pub scheme: ChannelScheme,
+  /// 渠道文件，相对路径相对于工程目录
+  #[serde(default)]
+  pub channel_file: Option<String>,
+  /// 输出文件名模板，为空时使用 [`DEFAULT_NAME_TEMPLATE`]
+  #[serde(default)]
+  pub name_template: Option<String>,
+}
+
+/// 单次生成渠道包的参数：未指定的项取工程的渠道设置，与构建后自动生成时一致
+pub struct ChannelRequest {
+  pub channel_file: PathBuf,
+  pub scheme: ChannelScheme,
+  pub name_template: Option<String>,
+}
+
+impl ChannelRequest {
+  pub fn resolve(
+    project: Option<&Project>,
+    channel_file: Option<PathBuf>,
+    scheme: Option<ChannelScheme>,
+    name_template: Option<String>,
+  ) -> Result<Self> {
+    let options = project.and_then(|p| p.channels.clone()).unwrap_or_default();
+    let project_file = project.zip(options.channel_file.filter(|f| !f.trim().is_empty()));
+    let channel_file = match (channel_file, project_file) {
+      (Some(file), _) => file,
+      (None, Some((project, file))) => Path::new(&project.path).join(file),
+      _ => return Err(Error::InvalidInput("请指定渠道文件，或在工程中配置渠道文件".into())),
+    };
+    Ok(Self {
+      channel_file,
+      scheme: scheme.unwrap_or(options.scheme),
+      name_template: name_template.filter(|t| !t.trim().is_empty()).or(options.name_template),
+    })
+  }
+}
+
+/// 渠道与附加的键值
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChannelInfo {
+  pub channel: String,
+  pub extras: BTreeMap<String, String>,
+}
+
+/// APK 中按某种方式写入的渠道
+#[derive(Serialize)]
+pub struct FoundChannel {
+  pub scheme: ChannelScheme,
+  #[serde(flatten)]
+  pub info: ChannelInfo,
+}
+
+/// 生成的渠道包
+#[derive(Serialize)]
+pub struct ChannelApk {
+  pub channel: String,
+  pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct ChannelOutput {
+  pub input: String,
+  pub scheme: ChannelScheme,
+  pub output_dir: String,
+  pub apks: Vec<ChannelApk>,
+  pub warnings: Vec<String>,
+}
+
+/// 读取渠道文件：每行一个渠道，渠道名后可以跟 `key=value` 形式的附加信息，`#` 开头为注释
+pub fn read_channel_file(path: &Path) -> Result<Vec<ChannelInfo>> {
+  let content = fs::read_to_string(path).map_err(|e| Error::io(format!("读取渠道文件失败 {}", path.display()), e))?;
+  parse_channel_list(&content)
+}
+
+pub fn parse_channel_list(content: &str) -> Result<Vec<ChannelInfo>> {
+  let mut channels: Vec<ChannelInfo> = Vec::new();
+  for (index, line) in content.lines().enumerate() {
+    let line = line.trim().trim_start_matches('\u{feff}');
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut parts = line.split_whitespace();
+    let channel = parts.next().unwrap_or_default().to_string();
+    check_channel(&channel).map_err(|e| Error::InvalidInput(format!("渠道文件第 {} 行：{}", index + 1, e)))?;
+    if channels.iter().any(|c| c.channel == channel) {
+      return Err(Error::InvalidInput(format!("渠道文件第 {} 行：渠道重复 {}", index + 1, channel)));
+    }
+    let mut extras = BTreeMap::new();
+    for pair in parts {
+      let (key, value) = parse_extra(pair).map_err(|e| Error::InvalidInput(format!("渠道文件第 {} 行：{}", index + 1, e)))?;
+      extras.insert(key, value);
+    }
+    channels.push(ChannelInfo { channel, extras });
+  }
+  if channels.is_empty() {
+    return Err(Error::InvalidInput("渠道文件中没有渠道".into()));
+  }
+  Ok(channels)
+}
+
+/// 解析 `key=value`，键不能为 `channel`
+pub fn parse_extra(pair: &str) -> std::result::Result<(String, String), String> {
+  let (key, value) = pair.split_once('=').ok_or_else(|| format!("附加信息应为 key=value：{}", pair))?;
+  let key = key.trim();
+  if key.is_empty() || key == CHANNEL_KEY {
+    return Err(format!("附加信息的键无效：{}", pair));
+  }
+  Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// 渠道名用于文件名，不能包含路径分隔符等字符
+fn check_channel(channel: &str) -> std::result::Result<(), String> {
+  if channel.is_empty() || channel.chars().any(|c| c.is_control() || r#"/\:*?"<>|"#.contains(c)) {
+    return Err(format!("渠道名无效：{}", channel));
+  }
+  Ok(())
+}
+
+/// 为每个渠道写出一个渠道包，`output_dir` 默认为 APK 所在目录下的 `channels/`
+pub fn write_channels(
+  input: &Path,
+  scheme: ChannelScheme,
+  channels: &[ChannelInfo],
+  output_dir: Option<&Path>,
+  name_template: Option<&str>,
+) -> Result<ChannelOutput> {
+  let data = fs::read(input).map_err(|e| Error::io(format!("读取 {} 失败", input.display()), e))?;
+  let apk = Apk::parse(&data)?;
+  let block_signed = [V2_BLOCK_ID, V3_BLOCK_ID, V31_BLOCK_ID].iter().any(|id| apk.get(*id).is_some());
+  match scheme {
+    ChannelScheme::Walle | ChannelScheme::VasDolly if !block_signed => {
+      return Err(Error::InvalidInput(format!(
+        "{} 没有 v2/v3 签名，无法用 {} 方式写入渠道，只有 v1 签名时请使用 META-INF 方式",
+        input.display(),
+        scheme.label()
+      )));
+    }
+    ChannelScheme::MetaInf if block_signed => {
+      return Err(Error::InvalidInput(format!(
+        "{} 带有 v2/v3 签名，新增 META-INF 文件会使签名失效，请使用 Walle 或 VasDolly 方式",
+        input.display()
+      )));
+    }
+    _ => {}
+  }
+  if scheme != ChannelScheme::Walle {
+    if let Some(info) = channels.iter().find(|c| !c.extras.is_empty()) {
+      return Err(Error::InvalidInput(format!("{} 方式不支持附加信息（渠道 {}）", scheme.label(), info.channel)));
+    }
+  }
+  // 原 APK 的签名必须有效，写入后逐个对比
+  let original = signature::verify_data(input, &data, false)?;
+  let expected = verified_schemes(&original);
+  if expected.is_empty() {
+    return Err(Error::Signing(format!("{} 的签名校验未通过，无法生成渠道包", input.display())));
+  }
+  let mut warnings = Vec::new();
+  if original.schemes.iter().any(|s| s.scheme == SignatureScheme::V4) {
+    warnings.push("原 APK 带有 v4 签名（.idsig），渠道包的 v4 签名需要重新生成".to_string());
+  }
+
+  let output_dir = match output_dir {
+    Some(dir) => dir.to_path_buf(),
+    None => input.parent().map(|p| p.join("channels")).unwrap_or_else(|| PathBuf::from("channels")),
+  };
+  fs::create_dir_all(&output_dir).map_err(|e| Error::io(format!("创建目录失败 {}", output_dir.display()), e))?;
+
+  let template = name_template.filter(|t| !t.trim().is_empty()).unwrap_or(DEFAULT_NAME_TEMPLATE);
+  let vars = template_vars(input);
+  // 先写到输出目录中的临时文件，全部渠道成功后再改名，失败时不留下部分渠道包
+  let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+  let result = channels.iter().try_for_each(|info| {
+    check_channel(&info.channel).map_err(Error::InvalidInput)?;
+    let file_name = render_name(template, &vars, &info.channel)?;
+    let path = output_dir.join(&file_name);
+    if path == input {
+      return Err(Error::InvalidInput(format!("输出文件与原 APK 相同：{}", path.display())));
+    }
+    if staged.iter().any(|(_, p)| *p == path) {
+      return Err(Error::InvalidInput(format!("多个渠道的输出文件相同：{}，请在文件名模板中使用 {{{{channel}}}}", file_name)));
+    }
+    let out = scheme.write(&apk, info)?;
+    // 写出前读回确认
+    if scheme.read(&Apk::parse(&out)?)?.as_ref() != Some(info) {
+      return Err(Error::Artifact(format!("渠道 {} 写入后读取的内容不一致", info.channel)));
+    }
+    let report = signature::verify_data(&path, &out, false)?;
+    if verified_schemes(&report) != expected {
+      let errors: Vec<String> = report.schemes.iter().flat_map(|s| s.errors.iter().cloned()).collect();
+      return Err(Error::Signing(format!("渠道 {} 写入后签名校验未通过：{}", info.channel, errors.join("；"))));
+    }
+    let temp = output_dir.join(format!(".{}.tmp", file_name));
+    fs::write(&temp, &out).map_err(|e| Error::io(format!("写入渠道包失败 {}", temp.display()), e))?;
+    staged.push((temp, path));
+    Ok(())
+  });
+  if let Err(e) = result {
+    for (temp, _) in &staged {
+      let _ = fs::remove_file(temp);
+    }
+    return Err(e);
+  }
+  let mut apks = Vec::new();
+  for (index, (temp, path)) in staged.iter().enumerate() {
+    if let Err(e) = fs::rename(temp, path) {
+      // 已改名的渠道包与剩余的临时文件都删除，不留下部分渠道包
+      for (_, renamed) in &staged[..index] {
+        let _ = fs::remove_file(renamed);
+      }
+      for (temp, _) in &staged[index..] {
+        let _ = fs::remove_file(temp);
+      }
+      return Err(Error::io(format!("写入渠道包失败 {}", path.display()), e));
+    }
+    let channel = &channels[index].channel;
+    log::info!("已生成渠道包 {}（{}）：{}", channel, scheme.label(), path.display());
+    apks.push(ChannelApk { channel: channel.clone(), path: path.to_string_lossy().to_string() });
+  }
+  Ok(ChannelOutput {
+    input: input.to_string_lossy().to_string(),
+    scheme,
+    output_dir: output_dir.to_string_lossy().to_string(),
+    apks,
+    warnings,
+  })
+}
+
+/// 按工程的渠道设置生成渠道包，未配置渠道文件时返回 None
+pub fn write_project_channels(
+  project_dir: &Path,
+  options: &ChannelOptions,
+  input: &Path,
+  output_dir: Option<&Path>,
+) -> Result<Option<ChannelOutput>> {
+  let Some(file) = options.channel_file.as_deref().filter(|f| !f.trim().is_empty()) else {
+    return Ok(None);
+  };
+  let channels = read_channel_file(&project_dir.join(file))?;
+  write_channels(input, options.scheme, &channels, output_dir, options.name_template.as_deref()).map(Some)
+}
+
+/// 读取 APK 中各种方式写入的渠道
+pub fn read_channel(path: &Path) -> Result<Vec<FoundChannel>> {
+  let data = fs::read(path).map_err(|e| Error::io(format!("读取 {} 失败", path.display()), e))?;
+  let apk = Apk::parse(&data)?;
+  let mut found = Vec::new();
+  for scheme in ChannelScheme::ALL {
+    if let Some(info) = scheme.read(&apk)? {
+      found.push(FoundChannel { scheme, info });
+    }
+  }
+  Ok(found)
+}
+
+/// 校验通过的签名方案，v4 签名在 `.idsig` 文件中，不参与对比
+fn verified_schemes(report: &SignatureReport) -> Vec<SignatureScheme> {
+  report.schemes.iter().filter(|s| s.verified && s.scheme != SignatureScheme::V4).map(|s| s.scheme).collect()
+}
+
+fn write_walle(apk: &Apk, info: &ChannelInfo) -> Result<Vec<u8>> {
+  let mut json = serde_json::Map::new();
+  json.insert(CHANNEL_KEY.into(), info.channel.clone().into());
+  for (key, value) in &info.extras {
+    json.insert(key.clone(), value.clone().into());
+  }
+  let payload = serde_json::Value::Object(json).to_string().into_bytes();
+  Ok(replace_pair(apk, WALLE_BLOCK_ID, &payload))
+}
+
+/// 替换签名块中的渠道 ID-值对（同时去掉另一种方式写入的渠道）；原签名块带 v4 填充时重新计算填充，保持 4096 字节对齐
+fn replace_pair(apk: &Apk, id: u32, payload: &[u8]) -> Vec<u8> {
+  let padded = apk.get(VERITY_PADDING_BLOCK_ID).is_some();
+  let mut pairs: Vec<(u32, &[u8])> = apk
+    .pairs
+    .iter()
+    .copied()
+    .filter(|(i, _)| !CHANNEL_BLOCK_IDS.contains(i) && *i != VERITY_PADDING_BLOCK_ID)
+    .collect();
+  pairs.push((id, payload));
+  let padding;
+  if padded {
+    // 签名块总大小：首尾的 u64 大小 + 魔数 + 每个 ID-值对的 u64 长度与 u32 ID
+    let size = 32 + pairs.iter().map(|(_, v)| 12 + v.len()).sum::<usize>() + 12;
+    padding = vec![0u8; (BLOCK_ALIGNMENT - size % BLOCK_ALIGNMENT) % BLOCK_ALIGNMENT];
+    pairs.push((VERITY_PADDING_BLOCK_ID, &padding));
+  }
+  apk.with_pairs(&pairs)
+}
+
+/// 去掉已有的渠道文件后追加 `META-INF/channel_<渠道>`
+fn write_meta_inf(apk: &Apk, info: &ChannelInfo) -> Result<Vec<u8>> {
+  let mut entries: Vec<ZipEntry> =
+    zipalign::read_entries(apk)?.into_iter().filter(|e| !e.name.starts_with(META_INF_PREFIX)).collect();
+  entries.push(ZipEntry::stored(format!("{}{}", META_INF_PREFIX, info.channel), &[]));
+  zipalign::write_aligned(&entries)
+}
+
+fn read_vasdolly(apk: &Apk) -> Result<Option<ChannelInfo>> {
+  let Some(value) = apk.get(VASDOLLY_BLOCK_ID) else {
+    return Ok(None);
+  };
+  let channel = String::from_utf8(value.to_vec()).map_err(|_| Error::Artifact("VasDolly 渠道不是有效的 UTF-8".into()))?;
+  Ok(Some(ChannelInfo { channel, extras: BTreeMap::new() }))
+}
+
+fn read_meta_inf(apk: &Apk) -> Result<Option<ChannelInfo>> {
+  let channel = zipalign::read_entries(apk)?
+    .into_iter()
+    .find_map(|e| e.name.strip_prefix(META_INF_PREFIX).filter(|c| !c.is_empty() && !c.contains('/')).map(str::to_string));
+  Ok(channel.map(|channel| ChannelInfo { channel, extras: BTreeMap::new() }))
+}
+
+fn read_walle(apk: &Apk) -> Result<Option<ChannelInfo>> {
+  let Some(value) = apk.get(WALLE_BLOCK_ID) else {
+    return Ok(None);
+  };
+  let json: serde_json::Map<String, serde_json::Value> =
+    serde_json::from_slice(value).map_err(|e| Error::json("Walle 渠道信息格式错误", e))?;
+  let mut channel = None;
+  let mut extras = BTreeMap::new();
+  for (key, value) in json {
+    let value = match value {
+      serde_json::Value::String(s) => s,
+      other => other.to_string(),
+    };
+    if key == CHANNEL_KEY {
+      channel = Some(value);
+    } else {
+      extras.insert(key, value);
+    }
+  }
+  Ok(channel.map(|channel| ChannelInfo { channel, extras }))
+}
+
+/// 文件名模板中与渠道无关的变量
+fn template_vars(input: &Path) -> Vec<(&'static str, String)> {
+  let name = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+  let info = inspect::inspect(input).ok();
+  vec![
+    ("name", name),
+    ("version", info.as_ref().and_then(|i| i.version_name.clone()).unwrap_or_default()),
+    ("code", info.as_ref().and_then(|i| i.version_code).map(|c| c.to_string()).unwrap_or_default()),
+    ("package", info.map(|i| i.package).unwrap_or_default()),
+  ]
+}
+
+fn render_name(template: &str, vars: &[(&str, String)], channel: &str) -> Result<String> {
+  let name = vars
+    .iter()
+    .map(|(k, v)| (*k, v.as_str()))
+    .chain([("channel", channel)])
+    .fold(template.to_string(), |acc, (key, value)| acc.replace(&format!("{{{{{}}}}}", key), value));
+  if name.is_empty() || name.contains(['/', '\\']) {
+    return Err(Error::InvalidInput(format!("输出文件名无效：{}", name)));
+  }
+  Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    keystores::{tests::signing_key, KeyAlgorithm},
+    signer::{sign, SigningSchemes},
+    signing_block::tests::sample_apk,
+  };
+
+  fn signed(schemes: SigningSchemes) -> Vec<u8> {
+    sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), schemes, None).unwrap()
+  }
+
+  fn channel(name: &str) -> ChannelInfo {
+    ChannelInfo { channel: name.into(), extras: BTreeMap::new() }
+  }
+
+  fn verified(data: &[u8]) -> Vec<SignatureScheme> {
+    verified_schemes(&signature::verify_data(Path::new("test.apk"), data, false).unwrap())
+  }
+
+  #[test]
+  fn channel_list() {
+    let channels = parse_channel_list("\u{feff}# 注释\nhuawei\n\nxiaomi  store=mi  region=cn\n").unwrap();
+    assert_eq!(channels.len(), 2);
+    assert_eq!(channels[0], channel("huawei"));
+    assert_eq!(channels[1].extras.get("store").map(String::as_str), Some("mi"));
+    assert_eq!(channels[1].extras.len(), 2);
+
+    assert!(parse_channel_list("# 只有注释\n").is_err());
+    assert!(parse_channel_list("a\na\n").is_err());
+    assert!(parse_channel_list("a/b\n").is_err());
+    assert!(parse_channel_list("a channel=b\n").is_err());
+    assert!(parse_channel_list("a store\n").is_err());
+  }
+
+  #[test]
+  fn signing_block_channels() {
+    let data = signed(SigningSchemes::default());
+    let expected = verified(&data);
+    let mut info = channel("huawei");
+    info.extras.insert("store".into(), "appgallery".into());
+
+    let walle = ChannelScheme::Walle.write(&Apk::parse(&data).unwrap(), &info).unwrap();
+    let apk = Apk::parse(&walle).unwrap();
+    assert_eq!(ChannelScheme::Walle.read(&apk).unwrap(), Some(info.clone()));
+    assert_eq!(verified(&walle), expected);
+
+    // 重复写入时替换原来的渠道
+    let again = ChannelScheme::Walle.write(&apk, &channel("xiaomi")).unwrap();
+    assert_eq!(ChannelScheme::Walle.read(&Apk::parse(&again).unwrap()).unwrap(), Some(channel("xiaomi")));
+    assert_eq!(verified(&again), expected);
+
+    // 改用 VasDolly 时去掉 Walle 的渠道
+    let vasdolly = ChannelScheme::VasDolly.write(&apk, &channel("xiaomi")).unwrap();
+    let apk = Apk::parse(&vasdolly).unwrap();
+    assert_eq!(ChannelScheme::VasDolly.read(&apk).unwrap(), Some(channel("xiaomi")));
+    assert_eq!(ChannelScheme::Walle.read(&apk).unwrap(), None);
+    assert_eq!(verified(&vasdolly), expected);
+
+    // 再改回 Walle 时去掉 VasDolly 的渠道
+    let walle = ChannelScheme::Walle.write(&apk, &channel("oppo")).unwrap();
+    let apk = Apk::parse(&walle).unwrap();
+    assert_eq!(ChannelScheme::Walle.read(&apk).unwrap(), Some(channel("oppo")));
+    assert_eq!(ChannelScheme::VasDolly.read(&apk).unwrap(), None);
+  }
+
+  #[test]
+  fn meta_inf_channel() {
+    let data = signed(SigningSchemes { v1: true, v2: false, v3: false });
+    let first = ChannelScheme::MetaInf.write(&Apk::parse(&data).unwrap(), &channel("huawei")).unwrap();
+    let second = ChannelScheme::MetaInf.write(&Apk::parse(&first).unwrap(), &channel("xiaomi")).unwrap();
+    let apk = Apk::parse(&second).unwrap();
+    assert_eq!(ChannelScheme::MetaInf.read(&apk).unwrap(), Some(channel("xiaomi")));
+    let channel_files = zipalign::read_entries(&apk).unwrap().iter().filter(|e| e.name.starts_with(META_INF_PREFIX)).count();
+    assert_eq!(channel_files, 1);
+    assert_eq!(verified(&second), [SignatureScheme::V1]);
+  }
+
+  #[test]
+  fn write_channel_files() {
+    let dir = std::env::temp_dir().join(format!("abt-test-channel-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("app-release.apk");
+    fs::write(&input, signed(SigningSchemes::default())).unwrap();
+    let output_dir = dir.join("out");
+    let channels = [channel("huawei"), channel("xiaomi")];
+
+    let output = write_channels(&input, ChannelScheme::Walle, &channels, Some(&output_dir), None).unwrap();
+    let paths: Vec<&str> = output.apks.iter().map(|a| a.path.as_str()).collect();
+    assert_eq!(
+      paths,
+      [output_dir.join("app-release-huawei.apk").to_str().unwrap(), output_dir.join("app-release-xiaomi.apk").to_str().unwrap()]
+    );
+    let found = read_channel(&output_dir.join("app-release-xiaomi.apk")).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].scheme, ChannelScheme::Walle);
+    assert_eq!(found[0].info, channel("xiaomi"));
+
+    // 有 v2/v3 签名时不能用 META-INF 方式，VasDolly 不支持附加信息
+    assert!(write_channels(&input, ChannelScheme::MetaInf, &channels, Some(&output_dir), None).is_err());
+    let mut extra = channel("vivo");
+    extra.extras.insert("store".into(), "vivo".into());
+    assert!(write_channels(&input, ChannelScheme::VasDolly, &[extra], Some(&output_dir), None).is_err());
+    // 输出文件名冲突时不留下任何文件
+    let conflict = dir.join("conflict");
+    assert!(write_channels(&input, ChannelScheme::Walle, &channels, Some(&conflict), Some("fixed.apk")).is_err());
+    assert_eq!(fs::read_dir(&conflict).unwrap().count(), 0);
+    // 后面的渠道包改名失败时，删除已改名的渠道包
+    let blocked = dir.join("blocked");
+    fs::create_dir_all(blocked.join("app-release-xiaomi.apk/keep")).unwrap();
+    assert!(write_channels(&input, ChannelScheme::Walle, &channels, Some(&blocked), None).is_err());
+    let left: Vec<_> = fs::read_dir(&blocked).unwrap().map(|e| e.unwrap().file_name()).collect();
+    assert_eq!(left, ["app-release-xiaomi.apk"]);
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn output_names() {
+    let vars = [("name", "app".to_string()), ("version", "1.2.0".to_string())];
+    assert_eq!(render_name(DEFAULT_NAME_TEMPLATE, &vars, "huawei").unwrap(), "app-huawei.apk");
+    assert_eq!(render_name("{{name}}_{{version}}_{{channel}}.apk", &vars, "mi").unwrap(), "app_1.2.0_mi.apk");
+    assert!(render_name("{{channel}}/app.apk", &vars, "mi").is_err());
+  }
+}
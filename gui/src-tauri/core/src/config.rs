@@ -0,0 +1,729 @@
+//! 工程配置（projects.json）、发布平台配置（publish_platforms.json）、keystore（keystores.json）与签名配置（signing_profiles.json）
+//!
+//! GUI 与命令行共用同一套配置文件，查找规则：
+//! 1. 显式指定的配置目录（命令行 `--config-dir`）
+//! 2. 当前目录或上级目录的 `config/`
+//! 3. 应用配置目录（与 Tauri 的 `app_config_dir` 一致）
+//!
+//! API Key/Token 与各类密码保存在本地密钥库（见 [`crate::secrets`]），配置文件中只有 `secret:<名称>` 引用，
+//! `list_*` 返回打码后的预览。
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  build::ArtifactKind,
+  channel::ChannelOptions,
+  discovery::{self, DiscoveryMode, ProjectStructure},
+  env,
+  git::GitPolicy,
+  gradle_options::GradleOptions,
+  keystores::{self, KeystoreType},
+  now_millis,
+  release_notes::ReleaseNotesOptions,
+  secrets,
+  signer::SigningSchemes,
+  version::VersionBump,
+  Error, Result,
+};
+
+/// 应用标识，与 tauri.conf.json 中的 identifier 一致
+pub const APP_IDENTIFIER: &str = "com.itoys.android-build-tool";
+
+/// 配置与数据目录
+#[derive(Clone, Default)]
+pub struct AppDirs {
+  /// 显式指定的配置目录，优先级最高
+  pub config_override: Option<PathBuf>,
+  /// 应用配置目录
+  pub config_dir: Option<PathBuf>,
+  /// 应用数据目录（构建历史等）
+  pub data_dir: Option<PathBuf>,
+}
+
+impl AppDirs {
+  /// 不依赖 Tauri 计算应用目录（与 Tauri 的 `app_config_dir`/`app_data_dir` 规则相同）
+  pub fn detect(config_override: Option<PathBuf>) -> Self {
+    Self {
+      config_override,
+      config_dir: dirs::config_dir().map(|d| d.join(APP_IDENTIFIER)),
+      data_dir: dirs::data_dir().map(|d| d.join(APP_IDENTIFIER)),
+    }
+  }
+
+  fn config_file(&self, file_name: &str) -> PathBuf {
+    if let Some(dir) = &self.config_override {
+      return dir.join(file_name);
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let candidates = [
+      cwd.join("config").join(file_name),
+      cwd.join("../config").join(file_name),
+    ];
+    for c in candidates {
+      if c.exists() {
+        return c;
+      }
+    }
+
+    if let Some(dir) = &self.config_dir {
+      return dir.join(file_name);
+    }
+
+    std::env::temp_dir().join(file_name)
+  }
+
+  pub fn history_dir(&self) -> PathBuf {
+    match &self.data_dir {
+      Some(dir) => dir.join("history"),
+      None => std::env::temp_dir().join("android-build-tool-history"),
+    }
+  }
+
+  /// 本地密钥库文件，放在数据目录而不是配置目录，配置目录可能位于工程仓库中
+  pub fn secrets_file(&self) -> PathBuf {
+    match &self.data_dir {
+      Some(dir) => dir.join("secrets.json"),
+      None => std::env::temp_dir().join("android-build-tool-secrets.json"),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Project {
+  pub name: String,
+  pub path: String,
+  #[serde(rename = "defaultModule")]
+  pub default_module: Option<String>,
+  #[serde(rename = "modules")]
+  pub modules: Option<Vec<String>>,
+  #[serde(rename = "defaultVariant")]
+  pub default_variant: Option<String>,
+  #[serde(rename = "variants")]
+  pub variants: Option<Vec<String>>,
+  #[serde(rename = "buildType")]
+  pub build_type: Option<String>,
+  /// 默认构建产物类型（未配置时为 APK）
+  #[serde(rename = "artifactKind")]
+  pub artifact_kind: Option<ArtifactKind>,
+  /// 构建使用的 JDK 主目录，构建时导出为 JAVA_HOME（未配置时沿用系统环境）
+  #[serde(rename = "javaHome")]
+  pub java_home: Option<String>,
+  /// 每次构建附加的 Gradle 参数、属性与环境变量
+  #[serde(rename = "gradleOptions")]
+  pub gradle_options: Option<GradleOptions>,
+  /// 每次构建前修改版本号的默认策略
+  #[serde(rename = "versionBump")]
+  pub version_bump: Option<VersionBump>,
+  /// git 信息注入与 Release 构建检查
+  #[serde(rename = "gitPolicy")]
+  pub git_policy: Option<GitPolicy>,
+  /// 发布时根据提交生成更新描述
+  #[serde(rename = "releaseNotes")]
+  pub release_notes: Option<ReleaseNotesOptions>,
+  /// 构建后重新签名 APK 使用的签名配置名称
+  #[serde(rename = "signingProfile")]
+  pub signing_profile: Option<String>,
+  /// 渠道包设置
+  #[serde(rename = "channels")]
+  pub channels: Option<ChannelOptions>,
+  /// 自动发现的模块与变体信息
+  #[serde(rename = "discovered")]
+  pub discovered: Option<ProjectStructure>,
+}
+
+impl Project {
+  /// 未指定模块时构建的模块：modules 中的第一个，其次 defaultModule
+  pub fn default_build_module(&self) -> Option<String> {
+    self
+      .modules
+      .as_ref()
+      .and_then(|m| m.first())
+      .cloned()
+      .or_else(|| self.default_module.clone())
+  }
+
+  /// 用发现结果填充 modules/variants，原默认值失效时清空
+  pub fn apply_discovery(&mut self, structure: ProjectStructure) {
+    let app_modules: Vec<String> = structure.app_modules.iter().map(|m| m.module.clone()).collect();
+    let modules = if app_modules.is_empty() { structure.modules.clone() } else { app_modules };
+    if !modules.is_empty() {
+      if self.default_module.as_ref().is_some_and(|m| !modules.contains(m)) {
+        self.default_module = None;
+      }
+      self.modules = Some(modules);
+    }
+
+    let module = self
+      .default_module
+      .clone()
+      .or_else(|| self.modules.as_ref().and_then(|m| m.first()).cloned());
+    if let Some(module_variants) = module.and_then(|m| structure.module(&m)) {
+      // variants 保存 flavor 组合（不含 buildType），构建时再与 buildType 拼接
+      let mut variants: Vec<String> = Vec::new();
+      for v in &module_variants.variants {
+        let name = discovery::flavor_combination_name(&v.flavors);
+        if !name.is_empty() && !variants.contains(&name) {
+          variants.push(name);
+        }
+      }
+      if self.default_variant.as_ref().is_some_and(|v| !variants.contains(v)) {
+        self.default_variant = None;
+      }
+      self.variants = if variants.is_empty() { None } else { Some(variants) };
+    }
+    self.discovered = Some(structure);
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectsConfig {
+  pub projects: Vec<Project>,
+}
+
+fn ensure_config(dirs: &AppDirs) -> PathBuf {
+  let path = dirs.config_file("projects.json");
+  if let Some(parent) = path.parent() {
+    let _ = fs::create_dir_all(parent);
+  }
+  if !path.exists() {
+    let default = ProjectsConfig { projects: vec![] };
+    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
+  }
+  path
+}
+
+fn read_config<T: DeserializeOwned>(path: &Path) -> Result<T> {
+  let content = fs::read_to_string(path).map_err(|e| Error::io(format!("读取配置失败 {}", path.display()), e))?;
+  serde_json::from_str(&content).map_err(|e| Error::json(format!("解析配置失败 {}", path.display()), e))
+}
+
+fn write_config<T: Serialize>(path: &Path, cfg: &T) -> Result<()> {
+  let content = serde_json::to_string_pretty(cfg).map_err(|e| Error::json("序列化配置失败", e))?;
+  fs::write(path, content).map_err(|e| Error::io(format!("写入配置失败 {}", path.display()), e))
+}
+
+pub fn gradlew_path(project_path: &str) -> PathBuf {
+  let gradle_name = if cfg!(windows) { "gradlew.bat" } else { "gradlew" };
+  Path::new(project_path).join(gradle_name)
+}
+
+fn read_projects(dirs: &AppDirs) -> Result<ProjectsConfig> {
+  let path = ensure_config(dirs);
+  read_config(&path)
+}
+
+/// 工程列表，Gradle 参数中的敏感值只返回打码后的预览
+pub fn list_projects(dirs: &AppDirs) -> Result<ProjectsConfig> {
+  let mut cfg = read_projects(dirs)?;
+  for p in &mut cfg.projects {
+    p.gradle_options = p.gradle_options.as_ref().map(|o| o.masked(dirs));
+  }
+  Ok(cfg)
+}
+
+/// 按名称查找工程，Gradle 参数中的敏感值为密钥库引用，使用前需要解密
+pub fn find_project(dirs: &AppDirs, name: &str) -> Result<Project> {
+  read_projects(dirs)?
+    .projects
+    .into_iter()
+    .find(|p| p.name == name)
+    .ok_or_else(|| Error::ProjectNotFound(name.to_string()))
+}
+
+/// 工程指定的 JDK 必须包含 java 可执行文件
+fn check_java_home(project: &Project) -> Result<()> {
+  match project.java_home.as_deref().filter(|h| !h.is_empty()) {
+    Some(home) if !env::java_executable(Path::new(home)).is_file() => Err(Error::JdkNotFound(PathBuf::from(home))),
+    _ => Ok(()),
+  }
+}
+
+/// 工程 Gradle 参数中的敏感值存入密钥库，`current` 为修改前的工程
+fn store_project_secrets(dirs: &AppDirs, project: &mut Project, current: Option<&Project>) -> Result<()> {
+  let current = current.and_then(|c| c.gradle_options.as_ref());
+  match project.gradle_options.as_mut() {
+    Some(options) => options.store_secrets(dirs, &project.name, current),
+    None => current.map_or(Ok(()), |c| c.remove_secrets(dirs)),
+  }
+}
+
+pub fn add_project(dirs: &AppDirs, project: Project) -> Result<()> {
+  let path = ensure_config(dirs);
+  let mut cfg = read_projects(dirs)?;
+  if cfg.projects.iter().any(|p| p.name == project.name) {
+    return Err(Error::ProjectExists(project.name));
+  }
+  let gradle_path = gradlew_path(&project.path);
+  if !gradle_path.exists() {
+    return Err(Error::GradlewNotFound(gradle_path));
+  }
+  check_java_home(&project)?;
+  if let Some(options) = &project.gradle_options {
+    options.validate()?;
+  }
+  // 未填写模块时自动从工程中发现（静态解析，失败不影响添加）
+  let mut project = project;
+  if project.modules.as_ref().map_or(true, |m| m.is_empty()) {
+    match discovery::discover_static(Path::new(&project.path), now_millis()) {
+      Ok(structure) => project.apply_discovery(structure),
+      Err(e) => log::warn!("自动发现模块失败: {}", e),
+    }
+  }
+  store_project_secrets(dirs, &mut project, None)?;
+  cfg.projects.push(project);
+  write_config(&path, &cfg)
+}
+
+/// 修改工程：Gradle 参数中的敏感值传入列表返回的预览表示不修改
+pub fn update_project(dirs: &AppDirs, name: &str, mut project: Project) -> Result<()> {
+  let path = ensure_config(dirs);
+  let mut cfg = read_projects(dirs)?;
+  let index = cfg.projects.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      let existing_project = &cfg.projects[idx];
+
+      // 如果更新了路径，需要验证 gradlew 是否存在
+      let gradle_path = gradlew_path(&project.path);
+      if project.path != existing_project.path && !gradle_path.exists() {
+        return Err(Error::GradlewNotFound(gradle_path));
+      }
+      check_java_home(&project)?;
+      if let Some(options) = &project.gradle_options {
+        options.validate()?;
+      }
+
+      // 更新工程信息（保留原有字段，用新值覆盖）
+      store_project_secrets(dirs, &mut project, Some(&cfg.projects[idx]))?;
+      cfg.projects[idx] = project;
+      write_config(&path, &cfg)
+    }
+    None => Err(Error::ProjectNotFound(name.to_string())),
+  }
+}
+
+pub fn delete_project(dirs: &AppDirs, name: &str) -> Result<()> {
+  let path = ensure_config(dirs);
+  let mut cfg = read_projects(dirs)?;
+  let index = cfg.projects.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      let project = cfg.projects.remove(idx);
+      write_config(&path, &cfg)?;
+      project.gradle_options.map_or(Ok(()), |o| o.remove_secrets(dirs))
+    }
+    None => Err(Error::ProjectNotFound(name.to_string())),
+  }
+}
+
+/// 从工程的 settings.gradle 与构建脚本中发现模块和变体，并写回 projects.json
+pub async fn discover_project(dirs: &AppDirs, name: &str, mode: DiscoveryMode) -> Result<ProjectStructure> {
+  let project = find_project(dirs, name)?;
+  let project_path = PathBuf::from(&project.path);
+
+  let structure = match mode {
+    DiscoveryMode::Static => discovery::discover_static(&project_path, now_millis())?,
+    DiscoveryMode::Gradle => {
+      let gradle_path = gradlew_path(&project.path);
+      if !gradle_path.exists() {
+        return Err(Error::GradlewNotFound(gradle_path));
+      }
+      discovery::discover_with_gradle(&project_path, &gradle_path, now_millis()).await?
+    }
+  };
+
+  // Gradle 解析耗时较长，写回前重新读取配置，避免覆盖期间的其他修改
+  let path = ensure_config(dirs);
+  let mut cfg = read_projects(dirs)?;
+  let project = cfg
+    .projects
+    .iter_mut()
+    .find(|p| p.name == name)
+    .ok_or_else(|| Error::ProjectNotFound(name.to_string()))?;
+  project.apply_discovery(structure.clone());
+  write_config(&path, &cfg)?;
+  Ok(structure)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishPlatformConfig {
+  pub name: String, // 配置名称
+  pub platform: String, // "pgyer" 或 "fir"
+  pub api_key: Option<String>, // pgyer 使用
+  pub api_token: Option<String>, // fir 使用
+  pub password: Option<String>, // pgyer 可选密码
+  pub default_description: Option<String>, // 默认更新描述
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PublishPlatformsConfig {
+  pub platforms: Vec<PublishPlatformConfig>,
+}
+
+fn ensure_publish_config(dirs: &AppDirs) -> PathBuf {
+  let path = dirs.config_file("publish_platforms.json");
+  if let Some(parent) = path.parent() {
+    let _ = fs::create_dir_all(parent);
+  }
+  if !path.exists() {
+    let default = PublishPlatformsConfig { platforms: vec![] };
+    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
+  }
+  path
+}
+
+fn read_publish_platforms(dirs: &AppDirs) -> Result<PublishPlatformsConfig> {
+  let path = ensure_publish_config(dirs);
+  read_config(&path)
+}
+
+/// 发布配置列表，API Key/Token 与密码只返回打码后的预览
+pub fn list_publish_platforms(dirs: &AppDirs) -> Result<PublishPlatformsConfig> {
+  let mut cfg = read_publish_platforms(dirs)?;
+  for p in &mut cfg.platforms {
+    p.api_key = secrets::mask(dirs, p.api_key.as_deref());
+    p.api_token = secrets::mask(dirs, p.api_token.as_deref());
+    p.password = secrets::mask(dirs, p.password.as_deref());
+  }
+  Ok(cfg)
+}
+
+/// 按名称查找发布配置，密钥字段为密钥库引用，使用前需要解密
+pub fn find_publish_platform(dirs: &AppDirs, name: &str) -> Result<PublishPlatformConfig> {
+  read_publish_platforms(dirs)?
+    .platforms
+    .into_iter()
+    .find(|p| p.name == name)
+    .ok_or_else(|| Error::PlatformNotFound(name.to_string()))
+}
+
+/// 发布配置的密钥字段存入密钥库，`current` 为修改前的配置
+fn store_platform_secrets(
+  dirs: &AppDirs,
+  platform: &mut PublishPlatformConfig,
+  current: Option<&PublishPlatformConfig>,
+) -> Result<()> {
+  let hint = |field: &str| format!("publish/{}/{}", platform.name, field);
+  let (key_hint, token_hint, password_hint) = (hint("api_key"), hint("api_token"), hint("password"));
+  platform.api_key = secrets::store(dirs, &key_hint, current.and_then(|c| c.api_key.as_deref()), platform.api_key.take())?;
+  platform.api_token =
+    secrets::store(dirs, &token_hint, current.and_then(|c| c.api_token.as_deref()), platform.api_token.take())?;
+  platform.password =
+    secrets::store(dirs, &password_hint, current.and_then(|c| c.password.as_deref()), platform.password.take())?;
+  Ok(())
+}
+
+pub fn add_publish_platform(dirs: &AppDirs, mut platform: PublishPlatformConfig) -> Result<()> {
+  let path = ensure_publish_config(dirs);
+  let mut cfg = read_publish_platforms(dirs)?;
+  if cfg.platforms.iter().any(|p| p.name == platform.name) {
+    return Err(Error::PlatformExists(platform.name));
+  }
+  store_platform_secrets(dirs, &mut platform, None)?;
+  cfg.platforms.push(platform);
+  write_config(&path, &cfg)
+}
+
+/// 修改发布配置：密钥字段传入列表返回的预览表示不修改，传空表示清除
+pub fn update_publish_platform(dirs: &AppDirs, name: &str, mut platform: PublishPlatformConfig) -> Result<()> {
+  let path = ensure_publish_config(dirs);
+  let mut cfg = read_publish_platforms(dirs)?;
+  let index = cfg.platforms.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      store_platform_secrets(dirs, &mut platform, Some(&cfg.platforms[idx]))?;
+      cfg.platforms[idx] = platform;
+      write_config(&path, &cfg)
+    }
+    None => Err(Error::PlatformNotFound(name.to_string())),
+  }
+}
+
+pub fn delete_publish_platform(dirs: &AppDirs, name: &str) -> Result<()> {
+  let path = ensure_publish_config(dirs);
+  let mut cfg = read_publish_platforms(dirs)?;
+  let index = cfg.platforms.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      let platform = cfg.platforms.remove(idx);
+      write_config(&path, &cfg)?;
+      for value in [&platform.api_key, &platform.api_token, &platform.password] {
+        secrets::remove(dirs, value.as_deref())?;
+      }
+      Ok(())
+    }
+    None => Err(Error::PlatformNotFound(name.to_string())),
+  }
+}
+
+/// 登记的 keystore：导入或创建的文件保存在配置目录的 keystores/ 下，签名配置通过 id 引用
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreRecord {
+  pub id: String,
+  pub path: String,
+  pub store_type: KeystoreType,
+  /// keystore 密码的密钥库引用
+  pub store_password: String,
+  /// 导入时的原文件路径，新建的 keystore 没有
+  pub source: Option<String>,
+  pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeystoresConfig {
+  pub keystores: Vec<KeystoreRecord>,
+}
+
+fn ensure_keystores_config(dirs: &AppDirs) -> PathBuf {
+  let path = dirs.config_file("keystores.json");
+  if let Some(parent) = path.parent() {
+    let _ = fs::create_dir_all(parent);
+  }
+  if !path.exists() {
+    let default = KeystoresConfig { keystores: vec![] };
+    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
+  }
+  path
+}
+
+/// 保存 keystore 文件的目录，与 keystores.json 同级
+pub(crate) fn keystore_dir(dirs: &AppDirs) -> PathBuf {
+  let path = ensure_keystores_config(dirs);
+  path.parent().map(|p| p.join("keystores")).unwrap_or_else(|| PathBuf::from("keystores"))
+}
+
+pub fn list_keystores(dirs: &AppDirs) -> Result<KeystoresConfig> {
+  let path = ensure_keystores_config(dirs);
+  read_config(&path)
+}
+
+pub fn find_keystore(dirs: &AppDirs, id: &str) -> Result<KeystoreRecord> {
+  list_keystores(dirs)?
+    .keystores
+    .into_iter()
+    .find(|k| k.id == id)
+    .ok_or_else(|| Error::KeystoreNotFound(id.to_string()))
+}
+
+pub(crate) fn add_keystore(dirs: &AppDirs, record: KeystoreRecord) -> Result<()> {
+  let path = ensure_keystores_config(dirs);
+  let mut cfg = list_keystores(dirs)?;
+  if cfg.keystores.iter().any(|k| k.id == record.id) {
+    return Err(Error::KeystoreExists(record.id));
+  }
+  cfg.keystores.push(record);
+  write_config(&path, &cfg)
+}
+
+pub(crate) fn delete_keystore(dirs: &AppDirs, id: &str) -> Result<KeystoreRecord> {
+  let path = ensure_keystores_config(dirs);
+  let mut cfg = list_keystores(dirs)?;
+  let index = cfg.keystores.iter().position(|k| k.id == id);
+  match index {
+    Some(idx) => {
+      let record = cfg.keystores.remove(idx);
+      write_config(&path, &cfg)?;
+      Ok(record)
+    }
+    None => Err(Error::KeystoreNotFound(id.to_string())),
+  }
+}
+
+/// 签名配置：构建后用其中的 keystore 重新签名 APK，密钥不必写进工程的 build.gradle
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningProfile {
+  pub name: String,
+  /// 登记的 keystore id，也可以直接填写 keystore 文件路径（JKS 或 PKCS12）
+  pub keystore: String,
+  /// keystore 密码，引用登记的 keystore 时可不填
+  pub store_password: Option<String>,
+  /// 密钥别名，keystore 中只有一个私钥时可不填
+  pub key_alias: Option<String>,
+  /// 密钥密码，不填时与 keystore 密码相同
+  pub key_password: Option<String>,
+  #[serde(default)]
+  pub schemes: SigningSchemes,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SigningProfilesConfig {
+  pub profiles: Vec<SigningProfile>,
+}
+
+fn ensure_signing_config(dirs: &AppDirs) -> PathBuf {
+  let path = dirs.config_file("signing_profiles.json");
+  if let Some(parent) = path.parent() {
+    let _ = fs::create_dir_all(parent);
+  }
+  if !path.exists() {
+    let default = SigningProfilesConfig { profiles: vec![] };
+    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
+  }
+  path
+}
+
+fn read_signing_profiles(dirs: &AppDirs) -> Result<SigningProfilesConfig> {
+  let path = ensure_signing_config(dirs);
+  read_config(&path)
+}
+
+/// 签名配置列表，密码只返回打码后的预览
+pub fn list_signing_profiles(dirs: &AppDirs) -> Result<SigningProfilesConfig> {
+  let mut cfg = read_signing_profiles(dirs)?;
+  for p in &mut cfg.profiles {
+    p.store_password = secrets::mask(dirs, p.store_password.as_deref());
+    p.key_password = secrets::mask(dirs, p.key_password.as_deref());
+  }
+  Ok(cfg)
+}
+
+/// 按名称查找签名配置，密码为密钥库引用，使用前需要解密
+pub fn find_signing_profile(dirs: &AppDirs, name: &str) -> Result<SigningProfile> {
+  read_signing_profiles(dirs)?
+    .profiles
+    .into_iter()
+    .find(|p| p.name == name)
+    .ok_or_else(|| Error::SigningProfileNotFound(name.to_string()))
+}
+
+/// keystore 必须已登记或文件存在，且至少启用一种签名方案
+fn check_signing_profile(dirs: &AppDirs, profile: &SigningProfile) -> Result<()> {
+  keystores::locate(dirs, profile)?;
+  if profile.schemes.is_empty() {
+    return Err(Error::InvalidInput("至少需要启用一种签名方案".into()));
+  }
+  Ok(())
+}
+
+/// 签名配置的密码存入密钥库，`current` 为修改前的配置
+fn store_profile_secrets(dirs: &AppDirs, profile: &mut SigningProfile, current: Option<&SigningProfile>) -> Result<()> {
+  let store_hint = format!("signing/{}/store_password", profile.name);
+  let key_hint = format!("signing/{}/key_password", profile.name);
+  profile.store_password =
+    secrets::store(dirs, &store_hint, current.and_then(|c| c.store_password.as_deref()), profile.store_password.take())?;
+  profile.key_password =
+    secrets::store(dirs, &key_hint, current.and_then(|c| c.key_password.as_deref()), profile.key_password.take())?;
+  Ok(())
+}
+
+pub fn add_signing_profile(dirs: &AppDirs, mut profile: SigningProfile) -> Result<()> {
+  let path = ensure_signing_config(dirs);
+  let mut cfg = read_signing_profiles(dirs)?;
+  if cfg.profiles.iter().any(|p| p.name == profile.name) {
+    return Err(Error::SigningProfileExists(profile.name));
+  }
+  check_signing_profile(dirs, &profile)?;
+  store_profile_secrets(dirs, &mut profile, None)?;
+  cfg.profiles.push(profile);
+  write_config(&path, &cfg)
+}
+
+/// 修改签名配置：密码传入列表返回的预览表示不修改，传空表示清除
+pub fn update_signing_profile(dirs: &AppDirs, name: &str, mut profile: SigningProfile) -> Result<()> {
+  let path = ensure_signing_config(dirs);
+  let mut cfg = read_signing_profiles(dirs)?;
+  let index = cfg.profiles.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      check_signing_profile(dirs, &profile)?;
+      store_profile_secrets(dirs, &mut profile, Some(&cfg.profiles[idx]))?;
+      cfg.profiles[idx] = profile;
+      write_config(&path, &cfg)
+    }
+    None => Err(Error::SigningProfileNotFound(name.to_string())),
+  }
+}
+
+pub fn delete_signing_profile(dirs: &AppDirs, name: &str) -> Result<()> {
+  let path = ensure_signing_config(dirs);
+  let mut cfg = read_signing_profiles(dirs)?;
+  let index = cfg.profiles.iter().position(|p| p.name == name);
+  match index {
+    Some(idx) => {
+      let profile = cfg.profiles.remove(idx);
+      write_config(&path, &cfg)?;
+      secrets::remove(dirs, profile.store_password.as_deref())?;
+      secrets::remove(dirs, profile.key_password.as_deref())
+    }
+    None => Err(Error::SigningProfileNotFound(name.to_string())),
+  }
+}
+
+/// 把配置文件中的明文密钥迁移到密钥库，返回迁移的字段
+pub fn migrate_secrets(dirs: &AppDirs) -> Result<Vec<String>> {
+  let mut migrated = Vec::new();
+  let plain = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.is_empty() && !secrets::is_reference(v));
+
+  let path = ensure_publish_config(dirs);
+  let mut cfg = read_publish_platforms(dirs)?;
+  let mut changed = false;
+  for p in &mut cfg.platforms {
+    for (field, value) in [("api_key", &mut p.api_key), ("api_token", &mut p.api_token), ("password", &mut p.password)] {
+      if plain(value) {
+        *value = secrets::store(dirs, &format!("publish/{}/{}", p.name, field), None, value.take())?;
+        migrated.push(format!("发布配置 {} 的 {}", p.name, field));
+        changed = true;
+      }
+    }
+  }
+  if changed {
+    write_config(&path, &cfg)?;
+  }
+
+  let path = ensure_keystores_config(dirs);
+  let mut cfg = read_config::<KeystoresConfig>(&path)?;
+  let mut changed = false;
+  for k in &mut cfg.keystores {
+    if !k.store_password.is_empty() && !secrets::is_reference(&k.store_password) {
+      let hint = format!("keystore/{}/store_password", k.id);
+      k.store_password = secrets::store(dirs, &hint, None, Some(std::mem::take(&mut k.store_password)))?.unwrap_or_default();
+      migrated.push(format!("keystore {} 的密码", k.id));
+      changed = true;
+    }
+  }
+  if changed {
+    write_config(&path, &cfg)?;
+  }
+
+  let path = ensure_signing_config(dirs);
+  let mut cfg = read_signing_profiles(dirs)?;
+  let mut changed = false;
+  for p in &mut cfg.profiles {
+    for (field, value) in [("store_password", &mut p.store_password), ("key_password", &mut p.key_password)] {
+      if plain(value) {
+        *value = secrets::store(dirs, &format!("signing/{}/{}", p.name, field), None, value.take())?;
+        migrated.push(format!("签名配置 {} 的 {}", p.name, field));
+        changed = true;
+      }
+    }
+  }
+  if changed {
+    write_config(&path, &cfg)?;
+  }
+
+  let path = ensure_config(dirs);
+  let mut cfg = read_projects(dirs)?;
+  let mut changed = false;
+  for p in &mut cfg.projects {
+    let Some(options) = p.gradle_options.as_mut() else {
+      continue;
+    };
+    let current = options.clone();
+    options.store_secrets(dirs, &p.name, Some(&current))?;
+    if *options != current {
+      migrated.push(format!("工程 {} 的 Gradle 参数", p.name));
+      changed = true;
+    }
+  }
+  if changed {
+    write_config(&path, &cfg)?;
+  }
+  Ok(migrated)
+}
@@ -0,0 +1,633 @@
+//! 从 Gradle 工程中自动发现模块与构建变体
+//!
+//! 支持两种方式：
+//! - 静态解析：读取 `settings.gradle(.kts)` 与模块的构建脚本，速度快，但无法识别动态生成的配置
+//! - Gradle 解析：注入 init script 运行 Gradle，由 AGP 输出真实的变体列表（需要可用的 JDK）
+
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  sync::atomic::{AtomicU64, Ordering},
+};
+use tokio::process::Command;
+
+use crate::{Error, Result};
+
+/// 发现方式
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryMode {
+  #[default]
+  Static,
+  Gradle,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductFlavor {
+  pub name: String,
+  pub dimension: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantInfo {
+  /// Gradle 中的变体名，如 `freeStagingRelease`
+  pub name: String,
+  /// 按 flavor dimension 顺序排列的 flavor 名称
+  pub flavors: Vec<String>,
+  pub build_type: String,
+}
+
+/// 单个 application 模块的变体信息
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleVariants {
+  pub module: String,
+  pub flavor_dimensions: Vec<String>,
+  pub product_flavors: Vec<ProductFlavor>,
+  pub build_types: Vec<String>,
+  pub variants: Vec<VariantInfo>,
+}
+
+/// 工程结构发现结果，保存在 `projects.json` 对应工程的 `discovered` 字段中
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStructure {
+  /// `settings.gradle` 中 include 的全部模块（不带前导冒号，如 `app`、`feature:login`）
+  pub modules: Vec<String>,
+  /// application 模块及其变体
+  pub app_modules: Vec<ModuleVariants>,
+  pub mode: DiscoveryMode,
+  /// 发现时间（Unix 时间戳，毫秒）
+  pub discovered_at: u64,
+  /// 发现过程中的提示信息（如 Gradle 解析失败后回退到静态解析）
+  pub warnings: Vec<String>,
+}
+
+impl ProjectStructure {
+  pub fn module(&self, name: &str) -> Option<&ModuleVariants> {
+    self.app_modules.iter().find(|m| m.module == name)
+  }
+}
+
+/// 模块名对应的目录（`feature:login` -> `feature/login`）
+pub fn module_dir(project_path: &Path, module: &str) -> PathBuf {
+  module
+    .trim_start_matches(':')
+    .split(':')
+    .filter(|s| !s.is_empty())
+    .fold(project_path.to_path_buf(), |dir, part| dir.join(part))
+}
+
+/// 静态解析工程结构
+pub fn discover_static(project_path: &Path, discovered_at: u64) -> Result<ProjectStructure> {
+  let modules = read_settings_modules(project_path)?;
+  let mut app_modules = Vec::new();
+  for module in &modules {
+    let Some(script) = read_build_script(&module_dir(project_path, module)) else {
+      continue;
+    };
+    if is_application_script(&script) {
+      app_modules.push(parse_module_variants(module, &script));
+    }
+  }
+  Ok(ProjectStructure {
+    modules,
+    app_modules,
+    mode: DiscoveryMode::Static,
+    discovered_at,
+    warnings: vec![],
+  })
+}
+
+/// 通过 Gradle init script 获取真实的变体信息，失败时回退到静态解析
+pub async fn discover_with_gradle(
+  project_path: &Path,
+  gradle_path: &Path,
+  discovered_at: u64,
+) -> Result<ProjectStructure> {
+  let mut structure = discover_static(project_path, discovered_at)?;
+  match run_gradle_dump(project_path, gradle_path).await {
+    Ok(app_modules) => {
+      structure.app_modules = app_modules;
+      structure.mode = DiscoveryMode::Gradle;
+    }
+    Err(e) => {
+      log::warn!("Gradle 解析变体失败，使用静态解析结果: {}", e);
+      structure.warnings.push(format!("Gradle 解析失败，已使用静态解析结果：{}", e));
+    }
+  }
+  Ok(structure)
+}
+
+const DUMP_MARKER: &str = "ABT_VARIANTS_JSON:";
+
+/// 在配置阶段结束后输出所有 application 模块的变体信息
+const INIT_SCRIPT: &str = r#"
+gradle.projectsEvaluated {
+  def result = [:]
+  gradle.rootProject.allprojects.each { p ->
+    if (!p.plugins.hasPlugin("com.android.application")) return
+    def android = p.extensions.findByName("android")
+    if (android == null) return
+    def dimensions = []
+    try { dimensions = android.flavorDimensionList.collect { it.toString() } } catch (ignored) {
+      try { dimensions = android.flavorDimensions.collect { it.toString() } } catch (ignored2) {}
+    }
+    def variants = []
+    android.applicationVariants.all { v ->
+      variants << [name: v.name, buildType: v.buildType.name, flavors: v.productFlavors.collect { it.name }]
+    }
+    result[p.path] = [
+      flavorDimensions: dimensions,
+      productFlavors: android.productFlavors.collect { [name: it.name, dimension: it.dimension] },
+      buildTypes: android.buildTypes.collect { it.name },
+      variants: variants,
+    ]
+  }
+  println "ABT_VARIANTS_JSON:" + groovy.json.JsonOutput.toJson(result)
+}
+"#;
+
+async fn run_gradle_dump(project_path: &Path, gradle_path: &Path) -> Result<Vec<ModuleVariants>> {
+  // 同一进程中可能同时发现多个工程，文件名加上序号避免互相覆盖
+  static COUNTER: AtomicU64 = AtomicU64::new(0);
+  let script_path = std::env::temp_dir().join(format!(
+    "abt-discover-{}-{}.gradle",
+    std::process::id(),
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  fs::write(&script_path, INIT_SCRIPT).map_err(|e| Error::io("写入 init script 失败", e))?;
+
+  let output = Command::new(gradle_path)
+    .arg("-q")
+    .arg("--init-script")
+    .arg(&script_path)
+    .arg("help")
+    .current_dir(project_path)
+    .output()
+    .await;
+  let _ = fs::remove_file(&script_path);
+  let output = output.map_err(|e| Error::io("执行 Gradle 失败", e))?;
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let json = stdout
+    .lines()
+    .find_map(|line| line.trim().strip_prefix(DUMP_MARKER))
+    .ok_or_else(|| {
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      Error::Discovery(format!(
+        "Gradle 未输出变体信息（退出码 {}）: {}",
+        output.status.code().unwrap_or(-1),
+        stderr.trim()
+      ))
+    })?;
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  struct RawModule {
+    flavor_dimensions: Vec<String>,
+    product_flavors: Vec<ProductFlavor>,
+    build_types: Vec<String>,
+    variants: Vec<VariantInfo>,
+  }
+
+  let raw: serde_json::Map<String, serde_json::Value> =
+    serde_json::from_str(json).map_err(|e| Error::json("解析 Gradle 输出失败", e))?;
+  let mut modules = Vec::new();
+  for (path, value) in raw {
+    let m: RawModule = serde_json::from_value(value).map_err(|e| Error::json(format!("解析模块 {} 失败", path), e))?;
+    modules.push(ModuleVariants {
+      module: path.trim_start_matches(':').to_string(),
+      flavor_dimensions: m.flavor_dimensions,
+      product_flavors: m.product_flavors,
+      build_types: m.build_types,
+      variants: m.variants,
+    });
+  }
+  Ok(modules)
+}
+
+fn read_settings_modules(project_path: &Path) -> Result<Vec<String>> {
+  let settings = ["settings.gradle.kts", "settings.gradle"]
+    .iter()
+    .map(|name| project_path.join(name))
+    .find(|p| p.exists())
+    .ok_or_else(|| Error::Discovery("未找到 settings.gradle(.kts)".into()))?;
+  let content = fs::read_to_string(&settings).map_err(|e| Error::io(format!("读取 {} 失败", settings.display()), e))?;
+  Ok(parse_settings_includes(&content))
+}
+
+pub(crate) fn read_build_script(module_dir: &Path) -> Option<String> {
+  ["build.gradle.kts", "build.gradle"]
+    .iter()
+    .map(|name| module_dir.join(name))
+    .find(|p| p.exists())
+    .and_then(|p| fs::read_to_string(p).ok())
+    .map(|s| strip_comments(&s))
+}
+
+/// 插件 id（`com.android.application`）或版本目录别名（`libs.plugins.android.application`）
+fn is_application_script(script: &str) -> bool {
+  script.contains("android.application")
+}
+
+/// 解析 `include ':app', ':lib'` / `include(":app", ":feature:login")`
+fn parse_settings_includes(content: &str) -> Vec<String> {
+  let content = strip_comments(content);
+  let mut modules: Vec<String> = Vec::new();
+  let mut rest = content.as_str();
+  while let Some(idx) = find_word(rest, "include") {
+    let after = &rest[idx + "include".len()..];
+    let trimmed = after.trim_start_matches([' ', '\t']);
+    let statement = if let Some(inner) = trimmed.strip_prefix('(') {
+      let end = find_matching(inner, '(', ')').unwrap_or(inner.len());
+      &inner[..end]
+    } else {
+      // 无括号写法：逗号结尾时继续读取下一行
+      let mut end = 0;
+      for line in trimmed.split_inclusive('\n') {
+        end += line.len();
+        if !line.trim_end().ends_with(',') {
+          break;
+        }
+      }
+      &trimmed[..end]
+    };
+    for name in string_literals(statement) {
+      let name = name.trim_start_matches(':').to_string();
+      if !name.is_empty() && !modules.contains(&name) {
+        modules.push(name);
+      }
+    }
+    rest = after;
+  }
+  modules
+}
+
+fn parse_module_variants(module: &str, script: &str) -> ModuleVariants {
+  // buildTypes 始终包含 debug 和 release
+  let mut build_types = vec!["debug".to_string(), "release".to_string()];
+  if let Some(block) = find_block(script, "buildTypes") {
+    for (name, _) in named_entries(block) {
+      if !build_types.contains(&name) {
+        build_types.push(name);
+      }
+    }
+  }
+
+  let mut flavor_dimensions = parse_flavor_dimensions(script);
+  let mut product_flavors = Vec::new();
+  if let Some(block) = find_block(script, "productFlavors") {
+    for (name, body) in named_entries(block) {
+      let dimension = parse_dimension(body);
+      product_flavors.push(ProductFlavor { name, dimension });
+    }
+  }
+  // 只声明了一个维度时，未指定维度的 flavor 归入该维度
+  if flavor_dimensions.len() == 1 {
+    for f in product_flavors.iter_mut().filter(|f| f.dimension.is_none()) {
+      f.dimension = Some(flavor_dimensions[0].clone());
+    }
+  }
+  // 未声明 flavorDimensions 时按 flavor 中出现的顺序补齐
+  for f in &product_flavors {
+    if let Some(d) = &f.dimension {
+      if !flavor_dimensions.contains(d) {
+        flavor_dimensions.push(d.clone());
+      }
+    }
+  }
+
+  let variants = combine_variants(&flavor_dimensions, &product_flavors, &build_types);
+  ModuleVariants {
+    module: module.to_string(),
+    flavor_dimensions,
+    product_flavors,
+    build_types,
+    variants,
+  }
+}
+
+/// 按维度顺序做笛卡尔积，生成所有变体
+fn combine_variants(dimensions: &[String], flavors: &[ProductFlavor], build_types: &[String]) -> Vec<VariantInfo> {
+  let mut groups: Vec<Vec<String>> = dimensions
+    .iter()
+    .map(|d| {
+      flavors
+        .iter()
+        .filter(|f| f.dimension.as_deref() == Some(d.as_str()))
+        .map(|f| f.name.clone())
+        .collect()
+    })
+    .filter(|g: &Vec<String>| !g.is_empty())
+    .collect();
+  let undimensioned: Vec<String> = flavors.iter().filter(|f| f.dimension.is_none()).map(|f| f.name.clone()).collect();
+  if !undimensioned.is_empty() {
+    groups.push(undimensioned);
+  }
+
+  let mut combos: Vec<Vec<String>> = vec![vec![]];
+  for group in &groups {
+    combos = combos
+      .iter()
+      .flat_map(|prefix| {
+        group.iter().map(move |f| {
+          let mut c = prefix.clone();
+          c.push(f.clone());
+          c
+        })
+      })
+      .collect();
+  }
+
+  let mut variants = Vec::new();
+  for flavors in &combos {
+    for build_type in build_types {
+      let mut parts = flavors.clone();
+      parts.push(build_type.clone());
+      variants.push(VariantInfo {
+        name: flavor_combination_name(&parts),
+        flavors: flavors.clone(),
+        build_type: build_type.clone(),
+      });
+    }
+  }
+  variants
+}
+
+/// 按 Gradle 规则拼接名称：第一个保持原样，其余首字母大写（`free` + `staging` -> `freeStaging`）
+pub fn flavor_combination_name(parts: &[String]) -> String {
+  parts
+    .iter()
+    .enumerate()
+    .map(|(i, p)| if i == 0 { p.clone() } else { capitalize(p) })
+    .collect()
+}
+
+pub fn capitalize(s: &str) -> String {
+  let mut chars = s.chars();
+  match chars.next() {
+    Some(c) => c.to_uppercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+/// `flavorDimensions "a", "b"` / `flavorDimensions += listOf("a", "b")` / `flavorDimensions.add("a")`
+fn parse_flavor_dimensions(script: &str) -> Vec<String> {
+  let mut dimensions: Vec<String> = Vec::new();
+  let mut rest = script;
+  while let Some(idx) = find_word(rest, "flavorDimensions") {
+    let after = &rest[idx + "flavorDimensions".len()..];
+    let line_end = after.find('\n').unwrap_or(after.len());
+    let mut statement = &after[..line_end];
+    // 括号跨行时读取到匹配的右括号
+    if let Some(open) = statement.find('(') {
+      if let Some(end) = find_matching(&after[open + 1..], '(', ')') {
+        statement = &after[..open + 1 + end];
+      }
+    }
+    for d in string_literals(statement) {
+      if !dimensions.contains(&d) {
+        dimensions.push(d);
+      }
+    }
+    rest = after;
+  }
+  dimensions
+}
+
+/// flavor 内的 `dimension "x"` / `dimension = "x"` / `dimension("x")`
+fn parse_dimension(body: &str) -> Option<String> {
+  let idx = find_word(body, "dimension")?;
+  let after = &body[idx + "dimension".len()..];
+  let line = after.lines().next().unwrap_or("");
+  string_literals(line).into_iter().next()
+}
+
+/// 查找 `name {` 代码块，返回花括号内的内容
+fn find_block<'a>(src: &'a str, name: &str) -> Option<&'a str> {
+  let mut offset = 0;
+  while let Some(idx) = find_word(&src[offset..], name) {
+    let start = offset + idx + name.len();
+    let after = src[start..].trim_start();
+    if let Some(inner) = after.strip_prefix('{') {
+      let end = find_matching(inner, '{', '}')?;
+      return Some(&inner[..end]);
+    }
+    offset = start;
+  }
+  None
+}
+
+/// 列出代码块中第一层的具名条目：
+/// Groovy 的 `staging { ... }`，Kotlin 的 `create("staging") { ... }`、`getByName("release") { ... }`
+fn named_entries(block: &str) -> Vec<(String, &str)> {
+  const IGNORED: [&str; 5] = ["all", "configureEach", "whenObjectAdded", "forEach", "each"];
+  let mut entries = Vec::new();
+  let mut segment_start = 0;
+  let bytes = block.as_bytes();
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'"' | b'\'' => i = skip_string(block, i),
+      b'{' => {
+        let inner = &block[i + 1..];
+        let end = find_matching(inner, '{', '}').unwrap_or(inner.len());
+        let header = block[segment_start..i].trim();
+        let header = header.rsplit(['\n', ';']).next().unwrap_or("").trim();
+        let name = string_literals(header)
+          .into_iter()
+          .next()
+          .or_else(|| {
+            let ident = header.trim_end_matches(|c: char| c == '(' || c == ')' || c.is_whitespace());
+            (!ident.is_empty() && ident.chars().all(|c| c.is_alphanumeric() || c == '_')).then(|| ident.to_string())
+          });
+        if let Some(name) = name {
+          if !IGNORED.contains(&name.as_str()) {
+            entries.push((name, &inner[..end]));
+          }
+        }
+        i += 1 + end + 1;
+        segment_start = i.min(block.len());
+        continue;
+      }
+      _ => i += 1,
+    }
+  }
+  entries
+}
+
+/// 找到与已打开括号匹配的右括号位置（跳过字符串内容）
+fn find_matching(s: &str, open: char, close: char) -> Option<usize> {
+  let bytes = s.as_bytes();
+  let mut depth = 0usize;
+  let mut i = 0;
+  while i < bytes.len() {
+    let c = bytes[i] as char;
+    if c == '"' || c == '\'' {
+      i = skip_string(s, i);
+      continue;
+    }
+    if c == open {
+      depth += 1;
+    } else if c == close {
+      if depth == 0 {
+        return Some(i);
+      }
+      depth -= 1;
+    }
+    i += 1;
+  }
+  None
+}
+
+/// 跳过从 `start` 开始的字符串字面量，返回结束引号之后的位置
+fn skip_string(s: &str, start: usize) -> usize {
+  let bytes = s.as_bytes();
+  let quote = bytes[start];
+  let mut i = start + 1;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => i += 2,
+      c if c == quote => return i + 1,
+      b'\n' => return i,
+      _ => i += 1,
+    }
+  }
+  bytes.len()
+}
+
+/// 提取文本中的所有字符串字面量
+fn string_literals(s: &str) -> Vec<String> {
+  let bytes = s.as_bytes();
+  let mut out = Vec::new();
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'"' || bytes[i] == b'\'' {
+      let end = skip_string(s, i);
+      let close = if end > i + 1 && bytes.get(end - 1) == Some(&bytes[i]) { end - 1 } else { end };
+      out.push(s[i + 1..close].to_string());
+      i = end;
+    } else {
+      i += 1;
+    }
+  }
+  out
+}
+
+/// 查找完整单词（前后不是标识符字符）
+fn find_word(s: &str, word: &str) -> Option<usize> {
+  let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+  let mut offset = 0;
+  while let Some(idx) = s[offset..].find(word) {
+    let start = offset + idx;
+    let end = start + word.len();
+    let before_ok = s[..start].chars().next_back().map_or(true, |c| !is_ident(c) && c != '.');
+    let after_ok = s[end..].chars().next().map_or(true, |c| !is_ident(c));
+    if before_ok && after_ok {
+      return Some(start);
+    }
+    offset = end;
+  }
+  None
+}
+
+/// 去除 `//` 与 `/* */` 注释（保留字符串中的内容，如 URL）
+pub(crate) fn strip_comments(s: &str) -> String {
+  remove_comments(s, false)
+}
+
+/// 把注释替换为等长的空白（保留换行），字节偏移与原文一致，用于定位后原地修改脚本
+pub(crate) fn blank_comments(s: &str) -> String {
+  remove_comments(s, true)
+}
+
+fn remove_comments(s: &str, keep_layout: bool) -> String {
+  let bytes = s.as_bytes();
+  let mut out = String::with_capacity(s.len());
+  let blank = |out: &mut String, comment: &str| {
+    if keep_layout {
+      out.extend(comment.bytes().map(|b| if b == b'\n' { '\n' } else { ' ' }));
+    }
+  };
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'"' | b'\'' => {
+        let end = skip_string(s, i);
+        out.push_str(&s[i..end]);
+        i = end;
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        let start = i;
+        while i < bytes.len() && bytes[i] != b'\n' {
+          i += 1;
+        }
+        blank(&mut out, &s[start..i]);
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        let start = i;
+        i = s[i + 2..].find("*/").map(|e| i + 2 + e + 2).unwrap_or(bytes.len());
+        blank(&mut out, &s[start..i]);
+      }
+      _ => {
+        let ch = s[i..].chars().next().unwrap();
+        out.push(ch);
+        i += ch.len_utf8();
+      }
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn settings_includes() {
+    let groovy = "rootProject.name = 'demo'\ninclude ':app', ':lib'\n// include ':old'\ninclude ':feature:login',\n  ':feature:pay'\n";
+    assert_eq!(parse_settings_includes(groovy), ["app", "lib", "feature:login", "feature:pay"]);
+    let kotlin = "include(\n  \":app\",\n  \":app\", /* \":ignored\" */\n)\nincludeBuild(\"build-logic\")\n";
+    assert_eq!(parse_settings_includes(kotlin), ["app"]);
+  }
+
+  #[test]
+  fn named_entries_groovy_and_kotlin() {
+    let groovy = "release { minifyEnabled true }\n  staging { initWith debug }\n  all { }\n";
+    let names: Vec<String> = named_entries(groovy).into_iter().map(|(n, _)| n).collect();
+    assert_eq!(names, ["release", "staging"]);
+
+    let kotlin = "create(\"free\") { dimension = \"tier\" }\ngetByName(\"paid\") { dimension(\"tier\") }\nconfigureEach { }\n";
+    let entries = named_entries(kotlin);
+    assert_eq!(entries.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), ["free", "paid"]);
+    assert_eq!(parse_dimension(entries[1].1).as_deref(), Some("tier"));
+  }
+
+  #[test]
+  fn module_variants_cartesian_product() {
+    let script = r#"
+      android {
+        flavorDimensions "tier", "env"
+        productFlavors {
+          free { dimension "tier" }
+          paid { dimension "tier" }
+          staging { dimension "env" }
+        }
+        buildTypes {
+          release { }
+          benchmark { }
+        }
+      }
+    "#;
+    let module = parse_module_variants("app", script);
+    assert_eq!(module.build_types, ["debug", "release", "benchmark"]);
+    let names: Vec<&str> = module.variants.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names.len(), 6);
+    assert!(names.contains(&"freeStagingDebug"));
+    assert!(names.contains(&"paidStagingBenchmark"));
+  }
+}
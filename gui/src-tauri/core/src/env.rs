@@ -0,0 +1,433 @@
+//! 构建环境检测（仅检查状态，不做安装）
+//!
+//! JDK 依次从 PATH、`JAVA_HOME` 与各系统常见的安装目录查找，并解析 `java -version`
+//! 得到发行商、版本号与位数；
+//! Xcode Command Line Tools 仅在 macOS 上检查，其他系统标记为不适用；
+//! Android SDK 的检查见 [`crate::sdk`]。
+
+use serde::Serialize;
+use std::{
+  env, fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{properties, run_command, sdk};
+
+#[derive(Serialize)]
+pub struct EnvCheck {
+  pub tool: String,
+  pub ok: bool,
+  pub message: String,
+  pub detail: Option<String>,
+  /// 是否适用于当前系统，不适用的检查项不参与结果判断
+  pub applicable: bool,
+  /// 已安装的版本等列表信息
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub items: Vec<String>,
+}
+
+impl EnvCheck {
+  fn skipped(tool: &str, message: &str) -> Self {
+    EnvCheck {
+      tool: tool.into(),
+      ok: true,
+      message: message.into(),
+      detail: None,
+      applicable: false,
+      items: vec![],
+    }
+  }
+}
+
+const JAVA_EXE: &str = if cfg!(windows) { "java.exe" } else { "java" };
+
+/// JDK 主目录下的 java 可执行文件
+pub fn java_executable(java_home: &Path) -> PathBuf {
+  java_home.join("bin").join(JAVA_EXE)
+}
+
+/// 在 PATH 中查找可执行文件
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+  let paths = env::var_os("PATH")?;
+  env::split_paths(&paths)
+    .map(|dir| dir.join(name))
+    .find(|p| p.is_file())
+}
+
+/// 环境变量 `JAVA_HOME`（仅当其中存在 java 可执行文件时返回）
+pub fn java_home_from_env() -> Option<PathBuf> {
+  let home = PathBuf::from(env::var_os("JAVA_HOME")?);
+  java_executable(&home).is_file().then_some(home)
+}
+
+/// 当前系统常见的 JDK 安装目录下找到的 JDK 主目录
+pub fn well_known_jdk_homes() -> Vec<PathBuf> {
+  let home = dirs::home_dir();
+  let mut roots: Vec<PathBuf> = Vec::new();
+  if cfg!(target_os = "macos") {
+    roots.push("/Library/Java/JavaVirtualMachines".into());
+    if let Some(h) = &home {
+      roots.push(h.join("Library/Java/JavaVirtualMachines"));
+    }
+  } else if cfg!(windows) {
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+      if let Some(dir) = env::var_os(var).map(PathBuf::from) {
+        for vendor in ["Java", "Eclipse Adoptium", "Microsoft", "Zulu", "Amazon Corretto", "BellSoft", "Android\\Android Studio"] {
+          roots.push(dir.join(vendor));
+        }
+      }
+    }
+  } else {
+    roots.extend(["/usr/lib/jvm", "/usr/java", "/usr/local/java", "/opt/java", "/opt"].map(PathBuf::from));
+  }
+  if let Some(h) = &home {
+    roots.push(h.join(".jdks"));
+    roots.push(h.join(".sdkman/candidates/java"));
+    roots.push(h.join(".gradle/jdks"));
+  }
+
+  let mut homes: Vec<PathBuf> = Vec::new();
+  for root in roots {
+    let Ok(entries) = fs::read_dir(&root) else {
+      continue;
+    };
+    for entry in entries.flatten() {
+      let dir = entry.path();
+      // macOS 的 JDK 位于 *.jdk/Contents/Home，Android Studio 自带的 JDK 位于 jbr/
+      let candidates = [dir.join("Contents/Home"), dir.join("jbr"), dir.clone()];
+      let Some(jdk) = candidates.into_iter().find(|c| java_executable(c).is_file()) else {
+        continue;
+      };
+      // 跳过符号链接指向的重复目录（如 /usr/lib/jvm/default-java）
+      let jdk = fs::canonicalize(&jdk).unwrap_or(jdk);
+      if !homes.contains(&jdk) {
+        homes.push(jdk);
+      }
+    }
+  }
+  homes.sort();
+  homes
+}
+
+fn java_version(java: &Path) -> (bool, String) {
+  run_command(&java.to_string_lossy(), &["-version"])
+}
+
+/// `java -version` 的解析结果
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaVersion {
+  /// 原始版本号，如 `17.0.9`、`1.8.0_392`
+  pub version: String,
+  pub major: u32,
+  pub minor: u32,
+  pub patch: u32,
+  /// 从输出识别的发行商，无法识别时为 None
+  pub vendor: Option<String>,
+  /// 32 或 64 位，无法识别时为 None
+  pub bits: Option<u32>,
+}
+
+/// 已安装的 JDK
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Jdk {
+  pub home: PathBuf,
+  pub vendor: String,
+  pub version: String,
+  pub major: u32,
+  pub minor: u32,
+  pub patch: u32,
+  pub bits: Option<u32>,
+}
+
+impl Jdk {
+  /// 用于列表展示，如 `17.0.9 Eclipse Adoptium 64 位`
+  pub fn label(&self) -> String {
+    match self.bits {
+      Some(bits) => format!("{} {} {} 位", self.version, self.vendor, bits),
+      None => format!("{} {}", self.version, self.vendor),
+    }
+  }
+}
+
+/// 运行时名称中的关键字 -> 发行商
+const JDK_VENDORS: &[(&str, &str)] = &[
+  ("Temurin", "Eclipse Adoptium"),
+  ("AdoptOpenJDK", "AdoptOpenJDK"),
+  ("Zulu", "Azul Zulu"),
+  ("Corretto", "Amazon Corretto"),
+  ("Microsoft", "Microsoft"),
+  ("JBR", "JetBrains Runtime"),
+  ("GraalVM", "GraalVM"),
+  ("Semeru", "IBM Semeru"),
+  ("BellSoft", "BellSoft Liberica"),
+  ("Liberica", "BellSoft Liberica"),
+  ("SapMachine", "SAP SapMachine"),
+  ("Red_Hat", "Red Hat"),
+  ("Homebrew", "Homebrew"),
+  ("Ubuntu", "Ubuntu"),
+  ("Debian", "Debian"),
+  ("Java(TM)", "Oracle"),
+];
+
+/// 解析 `java -version` 的输出（java 将版本信息输出到 stderr）：
+///
+/// ```text
+/// openjdk version "17.0.9" 2023-10-17
+/// OpenJDK Runtime Environment Temurin-17.0.9+9 (build 17.0.9+9)
+/// OpenJDK 64-Bit Server VM Temurin-17.0.9+9 (build 17.0.9+9, mixed mode)
+/// ```
+pub fn parse_java_version(output: &str) -> Option<JavaVersion> {
+  let start = output.find("version \"")? + "version \"".len();
+  let version = output[start..].split('"').next()?.to_string();
+
+  // `1.8.0_392` 为旧版命名，主版本号是第二段
+  let numbers: Vec<u32> = version
+    .split(|c: char| !c.is_ascii_digit())
+    .filter(|s| !s.is_empty())
+    .filter_map(|s| s.parse().ok())
+    .collect();
+  let numbers = match numbers.first()? {
+    1 => &numbers[1..],
+    _ => &numbers[..],
+  };
+  let part = |i: usize| numbers.get(i).copied().unwrap_or(0);
+  let major = *numbers.first()?;
+
+  let runtime = output.lines().find(|l| l.contains("Runtime Environment")).unwrap_or_default();
+  let vendor = JDK_VENDORS
+    .iter()
+    .find(|(keyword, _)| runtime.contains(keyword))
+    .map(|(_, vendor)| vendor.to_string())
+    .or_else(|| output.trim_start().starts_with("openjdk").then(|| "OpenJDK".to_string()));
+
+  let vm = output.lines().find(|l| l.contains(" VM")).unwrap_or_default();
+  let bits = if vm.contains("64-Bit") {
+    Some(64)
+  } else if vm.contains("Server VM") || vm.contains("Client VM") {
+    Some(32)
+  } else {
+    None
+  };
+
+  Some(JavaVersion { version, major, minor: part(1), patch: part(2), vendor, bits })
+}
+
+/// 从 `java -version` 输出中解析主版本号（`1.8.0_392` -> 8，`17.0.9` -> 17）
+pub fn parse_java_major(output: &str) -> Option<u32> {
+  parse_java_version(output).map(|v| v.major)
+}
+
+/// java 可执行文件的主版本号
+pub fn java_major_version(java: &Path) -> Option<u32> {
+  let (ok, out) = java_version(java);
+  if ok {
+    parse_java_major(&out)
+  } else {
+    None
+  }
+}
+
+/// 读取 JDK 主目录的版本信息，发行商优先取 `release` 文件中的 `IMPLEMENTOR`
+pub fn describe_jdk(home: &Path) -> Option<Jdk> {
+  let (ok, out) = java_version(&java_executable(home));
+  if !ok {
+    return None;
+  }
+  let parsed = parse_java_version(&out)?;
+  let implementor = properties::read(&home.join("release"))
+    .and_then(|mut p| p.remove("IMPLEMENTOR"))
+    .map(|v| v.trim_matches('"').to_string())
+    .filter(|v| !v.is_empty());
+  Some(Jdk {
+    home: home.to_path_buf(),
+    vendor: implementor.or(parsed.vendor).unwrap_or_else(|| "未知".into()),
+    version: parsed.version,
+    major: parsed.major,
+    minor: parsed.minor,
+    patch: parsed.patch,
+    bits: parsed.bits,
+  })
+}
+
+/// PATH 中 java 对应的 JDK 主目录（解析符号链接，JDK 8 的 `jre/bin/java` 取上一级）
+fn java_home_from_path() -> Option<PathBuf> {
+  let java = fs::canonicalize(find_in_path(JAVA_EXE)?).ok()?;
+  let home = java.parent()?.parent()?;
+  match home.file_name() {
+    Some(name) if name == "jre" && java_executable(home.parent()?).is_file() => home.parent().map(Path::to_path_buf),
+    _ => Some(home.to_path_buf()),
+  }
+}
+
+/// 本机安装的全部 JDK（`JAVA_HOME`、PATH 与常见安装目录），按版本从高到低排列
+pub fn list_jdks() -> Vec<Jdk> {
+  let mut homes: Vec<PathBuf> = Vec::new();
+  let candidates = java_home_from_env()
+    .into_iter()
+    .chain(java_home_from_path())
+    .chain(well_known_jdk_homes());
+  for home in candidates {
+    let home = fs::canonicalize(&home).unwrap_or(home);
+    if !homes.contains(&home) {
+      homes.push(home);
+    }
+  }
+  let mut jdks: Vec<Jdk> = homes.iter().filter_map(|h| describe_jdk(h)).collect();
+  jdks.sort_by(|a, b| {
+    (b.major, b.minor, b.patch)
+      .cmp(&(a.major, a.minor, a.patch))
+      .then_with(|| a.home.cmp(&b.home))
+  });
+  jdks
+}
+
+/// `17.0.9（Eclipse Adoptium，64 位）`
+fn describe_version(out: &str) -> String {
+  let Some(v) = parse_java_version(out) else {
+    return "版本未知".into();
+  };
+  let mut extra: Vec<String> = v.vendor.into_iter().collect();
+  extra.extend(v.bits.map(|b| format!("{} 位", b)));
+  if extra.is_empty() {
+    v.version
+  } else {
+    format!("{}（{}）", v.version, extra.join("，"))
+  }
+}
+
+fn check_java() -> EnvCheck {
+  let tool = "Java (JDK)";
+  // 本机全部 JDK，便于为工程指定构建使用的 JDK
+  let installed: Vec<String> = list_jdks()
+    .iter()
+    .map(|j| format!("{} ({})", j.label(), j.home.display()))
+    .collect();
+
+  if let Some(java) = find_in_path(JAVA_EXE) {
+    let (ok, out) = java_version(&java);
+    if ok {
+      return EnvCheck {
+        tool: tool.into(),
+        ok,
+        message: format!("已检测到 JDK {}: {}", describe_version(&out), java.display()),
+        detail: Some(out),
+        applicable: true,
+        items: installed,
+      };
+    }
+  }
+
+  if let Some(home) = java_home_from_env() {
+    let (ok, out) = java_version(&java_executable(&home));
+    if ok {
+      return EnvCheck {
+        tool: tool.into(),
+        ok,
+        message: format!("已检测到 JDK {}（JAVA_HOME）: {}", describe_version(&out), home.display()),
+        detail: Some(out),
+        applicable: true,
+        items: installed,
+      };
+    }
+  }
+
+  // 已安装但未配置到 PATH/JAVA_HOME 时，Gradle 仍然找不到 JDK
+  if !installed.is_empty() {
+    return EnvCheck {
+      tool: tool.into(),
+      ok: false,
+      message: "已找到 JDK，但未加入 PATH 也未设置 JAVA_HOME".into(),
+      detail: None,
+      applicable: true,
+      items: installed,
+    };
+  }
+
+  EnvCheck {
+    tool: tool.into(),
+    ok: false,
+    message: "未检测到 JDK，请安装 JDK 17+".into(),
+    detail: None,
+    applicable: true,
+    items: vec![],
+  }
+}
+
+fn check_java_home() -> EnvCheck {
+  if let Some(value) = env::var_os("JAVA_HOME") {
+    let home = PathBuf::from(&value);
+    let ok = java_executable(&home).is_file();
+    return EnvCheck {
+      tool: "JAVA_HOME".into(),
+      ok,
+      message: if ok {
+        format!("JAVA_HOME: {}", home.display())
+      } else {
+        format!("JAVA_HOME 无效，未找到 {}", java_executable(&home).display())
+      },
+      detail: Some(home.display().to_string()),
+      applicable: true,
+      items: vec![],
+    };
+  }
+
+  if cfg!(target_os = "macos") {
+    let (ok, out) = run_command("/usr/libexec/java_home", &[]);
+    return EnvCheck {
+      tool: "JAVA_HOME".into(),
+      ok,
+      message: if ok { format!("JAVA_HOME: {out}") } else { "未检测到 JAVA_HOME".into() },
+      detail: Some(out),
+      applicable: true,
+      items: vec![],
+    };
+  }
+
+  // Linux/Windows 没有 java_home 工具，给出可用的候选目录
+  let found = well_known_jdk_homes();
+  EnvCheck {
+    tool: "JAVA_HOME".into(),
+    ok: false,
+    message: if found.is_empty() {
+      "未设置 JAVA_HOME".into()
+    } else {
+      format!("未设置 JAVA_HOME，可设置为: {}", found[found.len() - 1].display())
+    },
+    detail: (!found.is_empty())
+      .then(|| found.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n")),
+    applicable: true,
+    items: vec![],
+  }
+}
+
+fn check_xcode() -> EnvCheck {
+  if !cfg!(target_os = "macos") {
+    return EnvCheck::skipped("Xcode CLT", "仅 macOS 需要，已跳过");
+  }
+  let (ok, out) = run_command("xcode-select", &["-p"]);
+  EnvCheck {
+    tool: "Xcode CLT".into(),
+    ok,
+    message: if ok {
+      format!("已检测到 Xcode Command Line Tools: {out}")
+    } else {
+      "未检测到 Xcode CLT".into()
+    },
+    detail: Some(out),
+    applicable: true,
+    items: vec![],
+  }
+}
+
+/// 检查构建环境，`project_path` 用于读取工程 `local.properties` 中的 SDK 路径
+pub fn check_env(project_path: Option<&Path>) -> Vec<EnvCheck> {
+  let mut checks = vec![
+    check_java(),
+    check_java_home(),
+    check_xcode(),
+  ];
+  checks.extend(sdk::checks(project_path));
+  checks
+}
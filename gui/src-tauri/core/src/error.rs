@@ -0,0 +1,265 @@
+//! 核心库的错误类型
+//!
+//! 每个错误都有稳定的机器码（`code`）与分类（`category`），序列化为
+//! `{ code, category, message, detail }`，前端与脚本可按 code 分支处理，
+//! 不必解析中文提示。
+
+use serde::{Serialize, Serializer};
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// 核心库统一的错误类型，`Display` 输出面向用户的中文提示
+#[derive(Debug, Error)]
+pub enum Error {
+  /// 文件读写或外部命令执行失败
+  #[error("{context}: {source}")]
+  Io {
+    context: String,
+    #[source]
+    source: io::Error,
+  },
+
+  /// 配置、历史记录或 Gradle 输出的 JSON 解析失败
+  #[error("{context}: {source}")]
+  Json {
+    context: String,
+    #[source]
+    source: serde_json::Error,
+  },
+
+  /// 网络请求失败
+  #[error("{context}: {source}")]
+  Http {
+    context: String,
+    #[source]
+    source: reqwest::Error,
+  },
+
+  #[error("工程不存在：{0}")]
+  ProjectNotFound(String),
+
+  #[error("工程名已存在：{0}")]
+  ProjectExists(String),
+
+  #[error("未找到 gradlew，请确认工程路径正确")]
+  GradlewNotFound(PathBuf),
+
+  #[error("工程指定的 JDK 无效，未找到 java 可执行文件")]
+  JdkNotFound(PathBuf),
+
+  #[error("配置不存在：{0}")]
+  PlatformNotFound(String),
+
+  #[error("配置名称已存在：{0}")]
+  PlatformExists(String),
+
+  /// 模块、flavor 或 buildType 组合无效
+  #[error("{0}")]
+  InvalidVariant(String),
+
+  /// 模块与变体发现失败
+  #[error("{0}")]
+  Discovery(String),
+
+  #[error("{0}")]
+  Version(String),
+
+  /// 构建进程异常
+  #[error("{0}")]
+  Build(String),
+
+  #[error("{0}")]
+  GitGuard(String),
+
+  #[error("构建 ID 已存在：{0}")]
+  BuildExists(String),
+
+  #[error("构建不存在或已结束：{0}")]
+  BuildNotFound(String),
+
+  #[error("构建记录不存在：{0}")]
+  RecordNotFound(String),
+
+  #[error("该构建没有保存日志：{0}")]
+  LogNotFound(String),
+
+  /// 参数或文件不符合要求（文件不存在、类型不支持、缺少凭证等）
+  #[error("{0}")]
+  InvalidInput(String),
+
+  /// 发布平台返回失败
+  #[error("{0}")]
+  Publish(String),
+
+  /// APK/AAB 文件损坏或格式不支持
+  #[error("{0}")]
+  Artifact(String),
+
+  #[error("签名配置不存在：{0}")]
+  SigningProfileNotFound(String),
+
+  #[error("签名配置名称已存在：{0}")]
+  SigningProfileExists(String),
+
+  #[error("keystore 不存在：{0}")]
+  KeystoreNotFound(String),
+
+  #[error("keystore id 已存在：{0}")]
+  KeystoreExists(String),
+
+  /// keystore 无法读取、密码错误或没有所需的密钥
+  #[error("{0}")]
+  Keystore(String),
+
+  /// APK 签名失败
+  #[error("{0}")]
+  Signing(String),
+
+  #[error("本地密钥库未解锁：请先输入主密码，命令行可设置环境变量 ABT_MASTER_PASSWORD")]
+  SecretsLocked,
+
+  #[error("密钥不存在：{0}")]
+  SecretNotFound(String),
+
+  /// 主密码错误或密钥库文件损坏
+  #[error("{0}")]
+  Secrets(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// 错误分类
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+  /// 本地文件或外部命令
+  Io,
+  /// 配置文件
+  Config,
+  /// 工程登记与工程结构
+  Project,
+  /// 构建与构建进程
+  Build,
+  /// 构建历史
+  History,
+  /// 发布平台
+  Publish,
+  /// 网络请求
+  Network,
+  /// 调用参数
+  Input,
+  /// 构建产物的解析
+  Artifact,
+  /// keystore 与 APK 签名
+  Signing,
+  /// 本地密钥库
+  Secrets,
+}
+
+/// 错误的序列化形式
+#[derive(Serialize, Clone, Debug)]
+pub struct ErrorInfo {
+  /// 稳定的机器码，如 `project_not_found`
+  pub code: &'static str,
+  pub category: ErrorCategory,
+  /// 面向用户的提示
+  pub message: String,
+  /// 底层错误或相关路径等补充信息
+  pub detail: Option<String>,
+}
+
+impl Error {
+  /// 稳定的机器码，发布后不应修改
+  pub fn code(&self) -> &'static str {
+    match self {
+      Error::Io { .. } => "io_error",
+      Error::Json { .. } => "invalid_json",
+      Error::Http { .. } => "network_error",
+      Error::ProjectNotFound(_) => "project_not_found",
+      Error::ProjectExists(_) => "project_exists",
+      Error::GradlewNotFound(_) => "gradlew_not_found",
+      Error::JdkNotFound(_) => "jdk_not_found",
+      Error::PlatformNotFound(_) => "platform_not_found",
+      Error::PlatformExists(_) => "platform_exists",
+      Error::InvalidVariant(_) => "invalid_variant",
+      Error::Discovery(_) => "discovery_failed",
+      Error::Version(_) => "version_error",
+      Error::Build(_) => "build_failed",
+      Error::GitGuard(_) => "git_guard_rejected",
+      Error::BuildExists(_) => "build_exists",
+      Error::BuildNotFound(_) => "build_not_found",
+      Error::RecordNotFound(_) => "record_not_found",
+      Error::LogNotFound(_) => "log_not_found",
+      Error::InvalidInput(_) => "invalid_input",
+      Error::Publish(_) => "publish_failed",
+      Error::Artifact(_) => "invalid_artifact",
+      Error::SigningProfileNotFound(_) => "signing_profile_not_found",
+      Error::SigningProfileExists(_) => "signing_profile_exists",
+      Error::KeystoreNotFound(_) => "keystore_not_found",
+      Error::KeystoreExists(_) => "keystore_exists",
+      Error::Keystore(_) => "keystore_error",
+      Error::Signing(_) => "signing_failed",
+      Error::SecretsLocked => "secrets_locked",
+      Error::SecretNotFound(_) => "secret_not_found",
+      Error::Secrets(_) => "secrets_error",
+    }
+  }
+
+  pub fn category(&self) -> ErrorCategory {
+    match self {
+      Error::Io { .. } => ErrorCategory::Io,
+      Error::Json { .. } => ErrorCategory::Config,
+      Error::Http { .. } => ErrorCategory::Network,
+      Error::ProjectNotFound(_)
+      | Error::ProjectExists(_)
+      | Error::GradlewNotFound(_)
+      | Error::JdkNotFound(_)
+      | Error::InvalidVariant(_)
+      | Error::Discovery(_)
+      | Error::Version(_) => ErrorCategory::Project,
+      Error::PlatformNotFound(_) | Error::PlatformExists(_) | Error::Publish(_) => ErrorCategory::Publish,
+      Error::Build(_) | Error::GitGuard(_) | Error::BuildExists(_) | Error::BuildNotFound(_) => ErrorCategory::Build,
+      Error::RecordNotFound(_) | Error::LogNotFound(_) => ErrorCategory::History,
+      Error::InvalidInput(_) => ErrorCategory::Input,
+      Error::Artifact(_) => ErrorCategory::Artifact,
+      Error::SigningProfileNotFound(_)
+      | Error::SigningProfileExists(_)
+      | Error::KeystoreNotFound(_)
+      | Error::KeystoreExists(_)
+      | Error::Keystore(_)
+      | Error::Signing(_) => ErrorCategory::Signing,
+      Error::SecretsLocked | Error::SecretNotFound(_) | Error::Secrets(_) => ErrorCategory::Secrets,
+    }
+  }
+
+  /// 序列化形式：带上下文的错误只把上下文作为 message，底层错误放入 detail
+  pub fn info(&self) -> ErrorInfo {
+    let (message, detail) = match self {
+      Error::Io { context, source } => (context.clone(), Some(source.to_string())),
+      Error::Json { context, source } => (context.clone(), Some(source.to_string())),
+      Error::Http { context, source } => (context.clone(), Some(source.to_string())),
+      Error::GradlewNotFound(path) | Error::JdkNotFound(path) => (self.to_string(), Some(path.display().to_string())),
+      _ => (self.to_string(), None),
+    };
+    ErrorInfo { code: self.code(), category: self.category(), message, detail }
+  }
+
+  pub(crate) fn io(context: impl Into<String>, source: io::Error) -> Self {
+    Error::Io { context: context.into(), source }
+  }
+
+  pub(crate) fn json(context: impl Into<String>, source: serde_json::Error) -> Self {
+    Error::Json { context: context.into(), source }
+  }
+
+  pub(crate) fn http(context: impl Into<String>, source: reqwest::Error) -> Self {
+    Error::Http { context: context.into(), source }
+  }
+}
+
+impl Serialize for Error {
+  fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    self.info().serialize(serializer)
+  }
+}
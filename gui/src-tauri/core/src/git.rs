@@ -0,0 +1,133 @@
+//! 构建源码的 git 信息：分支、提交、是否有未提交的修改与最近的 tag，以及 Release 构建的检查
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{run_command, Error, Result};
+
+/// 构建时工作区的 git 状态
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GitInfo {
+  /// 当前分支，detached HEAD 时为 None
+  pub branch: Option<String>,
+  pub commit: String,
+  pub short_commit: String,
+  /// 已跟踪文件有未提交的修改（不含未跟踪文件）
+  pub dirty: bool,
+  /// 最近的 tag（`git describe --tags --abbrev=0`）
+  pub tag: Option<String>,
+}
+
+/// 工程的 git 策略
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPolicy {
+  /// 以 `-PgitBranch`、`-PgitCommit`、`-PgitCommitShort`、`-PgitDirty`、`-PgitTag` 传给 Gradle
+  #[serde(default)]
+  pub inject_properties: bool,
+  /// Release 构建要求工作区没有未提交的修改
+  #[serde(default)]
+  pub require_clean_for_release: bool,
+  /// 允许 Release 构建的分支，支持 `*` 通配（如 `release/*`），为空时不限制
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub release_branches: Vec<String>,
+}
+
+impl GitPolicy {
+  fn guards_release(&self) -> bool {
+    self.require_clean_for_release || !self.release_branches.is_empty()
+  }
+}
+
+pub(crate) fn git(project_path: &Path, args: &[&str]) -> Option<String> {
+  let path = project_path.to_string_lossy();
+  let mut full = vec!["-C", path.as_ref()];
+  full.extend_from_slice(args);
+  let (ok, out) = run_command("git", &full);
+  ok.then(|| out.trim().to_string())
+}
+
+/// 读取工程的 git 状态，不是 git 仓库或没有提交时返回 None
+pub fn read(project_path: &Path) -> Option<GitInfo> {
+  let commit = git(project_path, &["rev-parse", "HEAD"])?;
+  let short_commit = git(project_path, &["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| commit.chars().take(7).collect());
+  let branch = git(project_path, &["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+  let dirty = git(project_path, &["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+  let tag = git(project_path, &["describe", "--tags", "--abbrev=0"]).filter(|t| !t.is_empty());
+  Some(GitInfo { branch, commit, short_commit, dirty, tag })
+}
+
+impl GitInfo {
+  /// 注入 Gradle 的 `-P` 属性
+  pub fn gradle_properties(&self) -> Vec<(String, String)> {
+    let mut props = vec![
+      ("gitCommit".to_string(), self.commit.clone()),
+      ("gitCommitShort".to_string(), self.short_commit.clone()),
+      ("gitDirty".to_string(), self.dirty.to_string()),
+    ];
+    if let Some(branch) = &self.branch {
+      props.push(("gitBranch".into(), branch.clone()));
+    }
+    if let Some(tag) = &self.tag {
+      props.push(("gitTag".into(), tag.clone()));
+    }
+    props
+  }
+
+  /// `main@1a2b3c4（有未提交的修改）`
+  pub fn summary(&self) -> String {
+    let mut s = format!("{}@{}", self.branch.as_deref().unwrap_or("detached"), self.short_commit);
+    if let Some(tag) = &self.tag {
+      s.push_str(&format!("，最近 tag {}", tag));
+    }
+    if self.dirty {
+      s.push_str("（有未提交的修改）");
+    }
+    s
+  }
+}
+
+/// `*` 匹配任意字符（含 `/`）
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+  let parts: Vec<&str> = pattern.split('*').collect();
+  if parts.len() == 1 {
+    return pattern == text;
+  }
+  let (first, last) = (parts[0], parts[parts.len() - 1]);
+  if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+    return false;
+  }
+  let mut rest = &text[first.len()..text.len() - last.len()];
+  for part in &parts[1..parts.len() - 1] {
+    match rest.find(part) {
+      Some(i) => rest = &rest[i + part.len()..],
+      None => return false,
+    }
+  }
+  true
+}
+
+/// Release 构建前检查工作区与分支，不满足策略时拒绝构建
+pub fn check_release(policy: &GitPolicy, info: Option<&GitInfo>) -> Result<()> {
+  if !policy.guards_release() {
+    return Ok(());
+  }
+  let info = info.ok_or_else(|| Error::GitGuard("无法读取 git 状态，工程配置了 Release 构建检查".into()))?;
+  if policy.require_clean_for_release && info.dirty {
+    return Err(Error::GitGuard("工作区有未提交的修改，不允许构建 Release".into()));
+  }
+  if !policy.release_branches.is_empty() {
+    let allowed = info
+      .branch
+      .as_deref()
+      .is_some_and(|b| policy.release_branches.iter().any(|p| wildcard_match(p, b)));
+    if !allowed {
+      return Err(Error::GitGuard(format!(
+        "分支 {} 不允许构建 Release（允许：{}）",
+        info.branch.as_deref().unwrap_or("detached HEAD"),
+        policy.release_branches.join(", ")
+      )));
+    }
+  }
+  Ok(())
+}
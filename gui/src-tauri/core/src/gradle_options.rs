@@ -0,0 +1,271 @@
+//! 传给 Gradle 的额外参数：命令行参数、`-P` 工程属性、`-D` 系统属性与环境变量
+//!
+//! 工程配置中保存默认值，单次构建可以覆盖。其中的敏感值（签名密码、API 密钥等）保存在本地密钥库中，
+//! 配置只记录引用，工程列表只返回打码后的预览；构建日志与返回的输出中替换为 [`REDACTED`]。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{config::AppDirs, secrets, Error, Result};
+
+/// 日志中替换敏感值的占位符
+pub const REDACTED: &str = "******";
+
+/// 键名包含这些片段（忽略大小写）时视为敏感值
+const SECRET_KEY_HINTS: &[&str] = &["password", "passwd", "pwd", "secret", "token", "apikey", "api_key", "credential", "private"];
+
+/// 过短的值替换后会误伤正常输出，不做替换
+const MIN_SECRET_LEN: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GradleOptions {
+  /// 额外的命令行参数，如 `--stacktrace`、`--offline`、`--no-daemon`、`--parallel`
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub args: Vec<String>,
+  /// `-P` 工程属性
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub properties: BTreeMap<String, String>,
+  /// `-D` 系统属性
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub system_properties: BTreeMap<String, String>,
+  /// Gradle 进程的环境变量
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub env: BTreeMap<String, String>,
+  /// 额外视为敏感的键名（按名称可识别的密码、token 等无需填写）
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub secrets: Vec<String>,
+}
+
+impl GradleOptions {
+  pub fn is_empty(&self) -> bool {
+    self.args.is_empty()
+      && self.properties.is_empty()
+      && self.system_properties.is_empty()
+      && self.env.is_empty()
+      && self.secrets.is_empty()
+  }
+
+  /// 以 `self` 为默认值合并单次构建的覆盖项：参数追加（去重），同名属性与环境变量以覆盖项为准
+  pub fn merged(&self, overrides: &GradleOptions) -> GradleOptions {
+    let mut merged = self.clone();
+    for arg in &overrides.args {
+      if !merged.args.contains(arg) {
+        merged.args.push(arg.clone());
+      }
+    }
+    merged.properties.extend(overrides.properties.clone());
+    merged.system_properties.extend(overrides.system_properties.clone());
+    merged.env.extend(overrides.env.clone());
+    for key in &overrides.secrets {
+      if !merged.secrets.contains(key) {
+        merged.secrets.push(key.clone());
+      }
+    }
+    merged
+  }
+
+  /// 校验键名：不能为空，属性键不能包含 `=`，任务名请通过构建参数指定
+  pub fn validate(&self) -> Result<()> {
+    for arg in &self.args {
+      if !arg.starts_with('-') {
+        return Err(Error::InvalidInput(format!("Gradle 参数必须以 - 开头：{}", arg)));
+      }
+    }
+    let maps = [("-P 属性", &self.properties), ("-D 属性", &self.system_properties), ("环境变量", &self.env)];
+    for (label, map) in maps {
+      if let Some(key) = map.keys().find(|k| k.trim().is_empty() || k.contains('=')) {
+        return Err(Error::InvalidInput(format!("无效的{}名：{:?}", label, key)));
+      }
+    }
+    Ok(())
+  }
+
+  /// 追加到 gradlew 任务名之后的参数
+  pub fn command_args(&self) -> Vec<String> {
+    let mut args = self.args.clone();
+    args.extend(self.properties.iter().map(|(k, v)| format!("-P{}={}", k, v)));
+    args.extend(self.system_properties.iter().map(|(k, v)| format!("-D{}={}", k, v)));
+    args
+  }
+
+  fn is_secret_key(&self, key: &str) -> bool {
+    is_secret_key(&self.secrets, key)
+  }
+
+  /// 属性表与环境变量，附带表名（用于命名密钥库中的密钥）
+  fn tables(&self) -> [(&'static str, &BTreeMap<String, String>); 3] {
+    [("properties", &self.properties), ("system_properties", &self.system_properties), ("env", &self.env)]
+  }
+
+  /// 敏感键的值：(表名, 键, 值)
+  fn secret_values(&self) -> Vec<(&'static str, &str, &str)> {
+    self
+      .tables()
+      .into_iter()
+      .flat_map(|(table, map)| map.iter().map(move |(k, v)| (table, k.as_str(), v.as_str())))
+      .filter(|(_, k, _)| self.is_secret_key(k))
+      .collect()
+  }
+
+  fn secret_values_mut(&mut self) -> Vec<(&'static str, &str, &mut String)> {
+    let extra = &self.secrets;
+    [("properties", &mut self.properties), ("system_properties", &mut self.system_properties), ("env", &mut self.env)]
+      .into_iter()
+      .flat_map(|(table, map)| map.iter_mut().map(move |(k, v)| (table, k.as_str(), v)))
+      .filter(|(_, k, _)| is_secret_key(extra, k))
+      .collect()
+  }
+
+  fn secret_value(&self, table: &str, key: &str) -> Option<&str> {
+    self.secret_values().into_iter().find(|(t, k, _)| *t == table && *k == key).map(|(_, _, v)| v)
+  }
+
+  /// 用于展示的参数：敏感值只返回打码后的预览
+  pub(crate) fn masked(&self, dirs: &AppDirs) -> GradleOptions {
+    let mut masked = self.clone();
+    for (_, _, value) in masked.secret_values_mut() {
+      *value = secrets::mask(dirs, Some(value)).unwrap_or_default();
+    }
+    for arg in &mut masked.args {
+      if let Some((flag, key, value)) = property_arg(arg).filter(|(_, key, _)| self.is_secret_key(key)) {
+        *arg = format!("{}{}={}", flag, key, secrets::mask(dirs, Some(value)).unwrap_or_default());
+      }
+    }
+    masked
+  }
+
+  /// 敏感值存入密钥库，配置中只保留引用，`current` 为修改前的参数。
+  /// 直接写在 `args` 中的敏感 `-P`/`-D` 属性先移到对应的属性表，与属性表中的同名键以属性表为准。
+  pub(crate) fn store_secrets(&mut self, dirs: &AppDirs, project: &str, current: Option<&GradleOptions>) -> Result<()> {
+    for arg in std::mem::take(&mut self.args) {
+      match property_arg(&arg).filter(|(_, key, _)| self.is_secret_key(key)) {
+        Some((flag, key, value)) => {
+          let table = if flag == "-P" { &mut self.properties } else { &mut self.system_properties };
+          table.entry(key.to_string()).or_insert_with(|| value.to_string());
+        }
+        None => self.args.push(arg),
+      }
+    }
+    for (table, key, value) in self.secret_values_mut() {
+      let hint = format!("project/{}/{}/{}", project, table, key);
+      let previous = current.and_then(|c| c.secret_value(table, key));
+      *value = match secrets::store(dirs, &hint, previous, Some(std::mem::take(value)))? {
+        // 与修改前相同的明文（密钥库之前的配置）同样存入密钥库
+        Some(kept) if !secrets::is_reference(&kept) => secrets::store(dirs, &hint, None, Some(kept))?,
+        stored => stored,
+      }
+      .unwrap_or_default();
+    }
+    // 删除的键与不再视为敏感的键，清除原来保存的密钥
+    for (table, key, value) in current.map(GradleOptions::secret_values).unwrap_or_default() {
+      if self.secret_value(table, key) != Some(value) {
+        secrets::remove(dirs, Some(value))?;
+      }
+    }
+    Ok(())
+  }
+
+  /// 构建时使用的参数：敏感值解密为原值
+  pub(crate) fn revealed(&self, dirs: &AppDirs) -> Result<GradleOptions> {
+    let mut revealed = self.clone();
+    for (_, _, value) in revealed.secret_values_mut() {
+      *value = secrets::reveal(dirs, value)?;
+    }
+    Ok(revealed)
+  }
+
+  /// 删除保存在密钥库中的敏感值
+  pub(crate) fn remove_secrets(&self, dirs: &AppDirs) -> Result<()> {
+    self.secret_values().into_iter().try_for_each(|(_, _, value)| secrets::remove(dirs, Some(value)))
+  }
+
+  /// 需要在日志中隐藏的值，包括直接写在 `args` 中的 `-Pkey=value`、`-Dkey=value`
+  pub fn redactor(&self) -> Redactor {
+    let arg_pairs = self.args.iter().filter_map(|arg| property_arg(arg).map(|(_, key, value)| (key, value)));
+    let mut values: Vec<String> = [&self.properties, &self.system_properties, &self.env]
+      .into_iter()
+      .flat_map(|map| map.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+      .chain(arg_pairs)
+      .filter(|(k, v)| self.is_secret_key(k) && v.len() >= MIN_SECRET_LEN)
+      .map(|(_, v)| v.to_string())
+      .collect();
+    // 先替换较长的值，避免其中包含的较短值被先替换后无法匹配
+    values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    values.dedup();
+    Redactor { values }
+  }
+}
+
+fn is_secret_key(extra: &[String], key: &str) -> bool {
+  let lower = key.to_ascii_lowercase();
+  extra.iter().any(|s| s.eq_ignore_ascii_case(key)) || SECRET_KEY_HINTS.iter().any(|h| lower.contains(h))
+}
+
+/// 拆分直接写在参数中的 `-Pkey=value`、`-Dkey=value`，返回 (`-P`/`-D`, 键, 值)
+fn property_arg(arg: &str) -> Option<(&str, &str, &str)> {
+  let flag = arg.get(..2).filter(|f| *f == "-P" || *f == "-D")?;
+  let (key, value) = arg[2..].split_once('=')?;
+  Some((flag, key, value))
+}
+
+/// 把文本中的敏感值替换为 [`REDACTED`]
+#[derive(Default, Clone)]
+pub struct Redactor {
+  values: Vec<String>,
+}
+
+impl Redactor {
+  pub fn redact(&self, text: &str) -> String {
+    self
+      .values
+      .iter()
+      .fold(text.to_string(), |acc, secret| acc.replace(secret.as_str(), REDACTED))
+  }
+}
+
+/// 解析 `KEY=VALUE`，用于命令行参数
+pub fn parse_pair(s: &str) -> std::result::Result<(String, String), String> {
+  match s.split_once('=') {
+    Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_string(), v.to_string())),
+    _ => Err(format!("格式应为 KEY=VALUE：{}", s)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn options() -> GradleOptions {
+    GradleOptions {
+      args: vec!["--offline".into(), "-PuploadToken=abcd-1234".into(), "-Dfile.encoding=UTF-8".into()],
+      properties: BTreeMap::from([("storePassword".into(), "hunter2".into()), ("flavor".into(), "cn".into())]),
+      env: BTreeMap::from([("DEPLOY_KEY".into(), "deploy-key-5678".into())]),
+      secrets: vec!["deploy_key".into()],
+      ..GradleOptions::default()
+    }
+  }
+
+  #[test]
+  fn redacts_secret_values() {
+    let redactor = options().redactor();
+    let line = "-PstorePassword=hunter2 -PuploadToken=abcd-1234 key=deploy-key-5678 flavor=cn UTF-8";
+    assert_eq!(redactor.redact(line), "-PstorePassword=****** -PuploadToken=****** key=****** flavor=cn UTF-8");
+  }
+
+  #[test]
+  fn masks_secret_values() {
+    let masked = options().masked(&AppDirs::default());
+    assert_eq!(masked.properties["storePassword"], "********");
+    assert_eq!(masked.properties["flavor"], "cn");
+    assert_eq!(masked.env["DEPLOY_KEY"], "********5678");
+    assert_eq!(masked.args, ["--offline", "-PuploadToken=********", "-Dfile.encoding=UTF-8"]);
+  }
+
+  #[test]
+  fn property_args() {
+    assert_eq!(property_arg("-Pa=b=c"), Some(("-P", "a", "b=c")));
+    assert_eq!(property_arg("-Dkey"), None);
+    assert_eq!(property_arg("--parallel"), None);
+  }
+}
@@ -0,0 +1,264 @@
+//! 构建历史：每次构建的结果、产物哈希与完整日志保存在本地
+//!
+//! 目录结构：
+//! - `history.json`：全部构建记录
+//! - `logs/<build_id>.log`：每次构建的完整日志
+//! - `published.json`：每个工程在各发布平台上最后一次发布的提交
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+  fs,
+  io::Read,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use crate::{build::BuildStatus, git::GitInfo, variant::BuildVariant, version::AppVersion, Error, Result};
+
+/// 同一进程内串行化 history.json 的读写，避免并发构建互相覆盖
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ArtifactRecord {
+  pub path: String,
+  pub size: u64,
+  pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BuildRecord {
+  pub build_id: String,
+  pub project: String,
+  pub module: Option<String>,
+  pub variant: BuildVariant,
+  pub tasks: Vec<String>,
+  /// Unix 时间戳（毫秒）
+  pub started_at: u64,
+  pub finished_at: u64,
+  pub status: BuildStatus,
+  pub code: i32,
+  pub artifacts: Vec<ArtifactRecord>,
+  pub log_path: Option<String>,
+  pub git_commit: Option<String>,
+  /// 构建时的分支、tag 与工作区状态
+  #[serde(default)]
+  pub git: Option<GitInfo>,
+  /// 构建使用的版本号
+  #[serde(default)]
+  pub version: Option<AppVersion>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct HistoryFile {
+  builds: Vec<BuildRecord>,
+}
+
+/// 工程在某个发布平台上最后一次发布的内容
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishedRecord {
+  pub project: String,
+  /// 发布平台（pgyer、fir）
+  pub platform: String,
+  /// 发布的提交，产物找不到对应的构建记录时为空（无法确定）
+  #[serde(default)]
+  pub commit: Option<String>,
+  pub build_id: Option<String>,
+  pub published_at: u64,
+  pub download_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PublishedFile {
+  published: Vec<PublishedRecord>,
+}
+
+/// 历史记录查询条件，所有条件均为可选
+#[derive(Deserialize, Default)]
+pub struct HistoryFilter {
+  pub project: Option<String>,
+  pub module: Option<String>,
+  pub status: Option<BuildStatus>,
+  /// 开始时间下限（毫秒，含）
+  pub since: Option<u64>,
+  /// 开始时间上限（毫秒，不含）
+  pub until: Option<u64>,
+  /// 最多返回条数（按时间倒序）
+  pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+  fn matches(&self, record: &BuildRecord) -> bool {
+    self.project.as_ref().map_or(true, |p| &record.project == p)
+      && self.module.as_ref().map_or(true, |m| record.module.as_ref() == Some(m))
+      && self.status.map_or(true, |s| record.status == s)
+      && self.since.map_or(true, |t| record.started_at >= t)
+      && self.until.map_or(true, |t| record.started_at < t)
+  }
+}
+
+fn history_file(dir: &Path) -> PathBuf {
+  dir.join("history.json")
+}
+
+fn read_history(dir: &Path) -> Result<HistoryFile> {
+  let path = history_file(dir);
+  if !path.exists() {
+    return Ok(HistoryFile::default());
+  }
+  let content = fs::read_to_string(&path).map_err(|e| Error::io("读取构建历史失败", e))?;
+  serde_json::from_str(&content).map_err(|e| Error::json("解析构建历史失败", e))
+}
+
+fn write_history(dir: &Path, history: &HistoryFile) -> Result<()> {
+  fs::create_dir_all(dir).map_err(|e| Error::io("创建历史目录失败", e))?;
+  let content = serde_json::to_string_pretty(history).map_err(|e| Error::json("序列化构建历史失败", e))?;
+  fs::write(history_file(dir), content).map_err(|e| Error::io("写入构建历史失败", e))
+}
+
+/// 保存构建日志，返回日志文件路径
+pub fn write_log(dir: &Path, build_id: &str, text: &str) -> Result<PathBuf> {
+  let logs = dir.join("logs");
+  fs::create_dir_all(&logs).map_err(|e| Error::io("创建日志目录失败", e))?;
+  let path = logs.join(format!("{}.log", sanitize_file_name(build_id)));
+  fs::write(&path, text).map_err(|e| Error::io("写入构建日志失败", e))?;
+  Ok(path)
+}
+
+pub fn append(dir: &Path, record: BuildRecord) -> Result<()> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  let mut history = read_history(dir)?;
+  history.builds.retain(|b| b.build_id != record.build_id);
+  history.builds.push(record);
+  write_history(dir, &history)
+}
+
+/// 按条件查询，按开始时间倒序
+pub fn list(dir: &Path, filter: &HistoryFilter) -> Result<Vec<BuildRecord>> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  let mut builds: Vec<BuildRecord> = read_history(dir)?
+    .builds
+    .into_iter()
+    .filter(|b| filter.matches(b))
+    .collect();
+  builds.sort_by_key(|b| std::cmp::Reverse(b.started_at));
+  if let Some(limit) = filter.limit {
+    builds.truncate(limit);
+  }
+  Ok(builds)
+}
+
+pub fn get(dir: &Path, build_id: &str) -> Result<BuildRecord> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  read_history(dir)?
+    .builds
+    .into_iter()
+    .find(|b| b.build_id == build_id)
+    .ok_or_else(|| Error::RecordNotFound(build_id.to_string()))
+}
+
+/// 删除记录及其日志文件，返回删除的条数
+pub fn delete(dir: &Path, build_ids: &[String]) -> Result<usize> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  let mut history = read_history(dir)?;
+  let (removed, kept): (Vec<_>, Vec<_>) = history
+    .builds
+    .into_iter()
+    .partition(|b| build_ids.contains(&b.build_id));
+  history.builds = kept;
+  write_history(dir, &history)?;
+  for record in &removed {
+    if let Some(log_path) = &record.log_path {
+      if let Err(e) = fs::remove_file(log_path) {
+        log::warn!("删除构建日志失败 {}: {}", log_path, e);
+      }
+    }
+  }
+  Ok(removed.len())
+}
+
+/// 产物所属的构建：先按路径匹配，其次按 SHA-256 匹配（产物被复制或移动过）
+pub fn find_by_artifact(dir: &Path, path: &Path) -> Result<Option<BuildRecord>> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  let mut builds = read_history(dir)?.builds;
+  builds.sort_by_key(|b| std::cmp::Reverse(b.started_at));
+  let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  let same_path = |a: &ArtifactRecord| fs::canonicalize(&a.path).unwrap_or_else(|_| PathBuf::from(&a.path)) == canonical;
+  if let Some(record) = builds.iter().find(|b| b.artifacts.iter().any(same_path)) {
+    return Ok(Some(record.clone()));
+  }
+  let Ok(artifact) = artifact_record(path) else {
+    return Ok(None);
+  };
+  Ok(builds
+    .into_iter()
+    .find(|b| b.artifacts.iter().any(|a| a.sha256 == artifact.sha256)))
+}
+
+fn published_file(dir: &Path) -> PathBuf {
+  dir.join("published.json")
+}
+
+fn read_published(dir: &Path) -> Result<PublishedFile> {
+  let path = published_file(dir);
+  if !path.exists() {
+    return Ok(PublishedFile::default());
+  }
+  let content = fs::read_to_string(&path).map_err(|e| Error::io("读取发布记录失败", e))?;
+  serde_json::from_str(&content).map_err(|e| Error::json("解析发布记录失败", e))
+}
+
+/// 工程在该平台上最后一次发布的记录
+pub fn last_published(dir: &Path, project: &str, platform: &str) -> Result<Option<PublishedRecord>> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  Ok(read_published(dir)?
+    .published
+    .into_iter()
+    .find(|r| r.project == project && r.platform == platform))
+}
+
+/// 记录一次发布，覆盖同一工程与平台的上一条记录
+pub fn record_published(dir: &Path, record: PublishedRecord) -> Result<()> {
+  let _guard = HISTORY_LOCK.lock().unwrap();
+  let mut file = read_published(dir)?;
+  file.published.retain(|r| !(r.project == record.project && r.platform == record.platform));
+  file.published.push(record);
+  fs::create_dir_all(dir).map_err(|e| Error::io("创建历史目录失败", e))?;
+  let content = serde_json::to_string_pretty(&file).map_err(|e| Error::json("序列化发布记录失败", e))?;
+  fs::write(published_file(dir), content).map_err(|e| Error::io("写入发布记录失败", e))
+}
+
+/// 读取历史构建的日志
+pub fn read_log(dir: &Path, build_id: &str) -> Result<String> {
+  let record = get(dir, build_id)?;
+  let log_path = record.log_path.ok_or_else(|| Error::LogNotFound(build_id.to_string()))?;
+  fs::read_to_string(&log_path).map_err(|e| Error::io(format!("读取构建日志失败 {}", log_path), e))
+}
+
+/// 计算产物的大小与 SHA-256
+pub fn artifact_record(path: &Path) -> Result<ArtifactRecord> {
+  let mut file = fs::File::open(path).map_err(|e| Error::io(format!("打开产物失败 {}", path.display()), e))?;
+  let mut hasher = Sha256::new();
+  let mut buf = [0u8; 64 * 1024];
+  let mut size = 0u64;
+  loop {
+    let n = file.read(&mut buf).map_err(|e| Error::io(format!("读取产物失败 {}", path.display()), e))?;
+    if n == 0 {
+      break;
+    }
+    size += n as u64;
+    hasher.update(&buf[..n]);
+  }
+  Ok(ArtifactRecord {
+    path: path.to_string_lossy().to_string(),
+    size,
+    sha256: hex::encode(hasher.finalize()),
+  })
+}
+
+fn sanitize_file_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect()
+}
@@ -0,0 +1,296 @@
+//! 解析构建产物：包名、版本、SDK 版本、应用名、启动 Activity、权限与 ABI
+//!
+//! 直接读取 APK 中的二进制 `AndroidManifest.xml` 与 `resources.arsc`，不依赖 aapt；
+//! AAB 读取 `base/manifest/AndroidManifest.xml` 与 `base/resources.pb` 的 proto 格式，并列出各模块。
+
+use serde::Serialize;
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  fs::File,
+  io::Read,
+  path::Path,
+};
+use zip::ZipArchive;
+
+use crate::{
+  aab,
+  arsc::ResourceTable,
+  axml::{self, XmlElement, XmlValue},
+  Error, Result,
+};
+
+const MANIFEST: &str = "AndroidManifest.xml";
+const RESOURCES: &str = "resources.arsc";
+const BASE_MODULE: &str = "base";
+/// AAB 中不属于模块的顶层目录
+const BUNDLE_METADATA_DIRS: &[&str] = &["BUNDLE-METADATA", "META-INF"];
+const ACTION_MAIN: &str = "android.intent.action.MAIN";
+const CATEGORY_LAUNCHER: &str = "android.intent.category.LAUNCHER";
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ArtifactInfo {
+  pub path: String,
+  /// 文件大小（字节）
+  pub size: u64,
+  pub package: String,
+  pub version_code: Option<u64>,
+  pub version_name: Option<String>,
+  pub min_sdk: Option<u32>,
+  pub target_sdk: Option<u32>,
+  pub compile_sdk: Option<u32>,
+  /// 应用名（默认语言）
+  pub label: Option<String>,
+  /// 桌面启动的 Activity（完整类名）
+  pub launchable_activity: Option<String>,
+  pub permissions: Vec<String>,
+  /// 包含原生库的 ABI，如 `arm64-v8a`
+  pub abis: Vec<String>,
+  /// AAB 的模块（base、动态功能模块与资源包），APK 为空
+  pub modules: Vec<ModuleInfo>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleKind {
+  Base,
+  /// 动态功能模块
+  Feature,
+  /// Play Asset Delivery 资源包
+  AssetPack,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ModuleInfo {
+  pub name: String,
+  pub kind: ModuleKind,
+  /// 模块在 AAB 中的压缩后大小（字节）
+  pub size: u64,
+  /// 解压后的大小（字节）
+  pub uncompressed_size: u64,
+}
+
+pub(crate) fn open_zip(path: &Path) -> Result<ZipArchive<File>> {
+  let file = File::open(path).map_err(|e| Error::io(format!("打开文件失败 {}", path.display()), e))?;
+  ZipArchive::new(file).map_err(|e| Error::Artifact(format!("{} 不是有效的 zip 文件：{}", path.display(), e)))
+}
+
+/// 读取 zip 中的文件，不存在时返回 None
+pub(crate) fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Option<Vec<u8>>> {
+  let mut entry = match zip.by_name(name) {
+    Ok(entry) => entry,
+    Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+    Err(e) => return Err(Error::Artifact(format!("读取 {} 失败：{}", name, e))),
+  };
+  let mut data = Vec::with_capacity(entry.size() as usize);
+  entry
+    .read_to_end(&mut data)
+    .map_err(|e| Error::io(format!("读取 {} 失败", name), e))?;
+  Ok(Some(data))
+}
+
+/// 解析 APK
+pub fn inspect_apk(path: &Path) -> Result<ArtifactInfo> {
+  let mut zip = open_zip(path)?;
+  let manifest = read_entry(&mut zip, MANIFEST)?
+    .ok_or_else(|| Error::Artifact(format!("{} 中没有 {}", path.display(), MANIFEST)))?;
+  let manifest = axml::parse(&manifest, MANIFEST)?;
+  // 资源表解析失败时仍返回清单中的信息，只是无法解析资源引用
+  let resources = match read_entry(&mut zip, RESOURCES)? {
+    Some(data) => ResourceTable::parse(&data).map_err(|e| log::warn!("{}", e)).ok(),
+    None => None,
+  };
+  let abis = zip
+    .file_names()
+    .filter_map(|name| name.strip_prefix("lib/")?.split_once('/').map(|(abi, _)| abi.to_string()))
+    .filter(|abi| !abi.is_empty())
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .collect();
+  let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+  let mut info = manifest_info(&manifest, resources.as_ref());
+  info.path = path.to_string_lossy().to_string();
+  info.size = size;
+  info.abis = abis;
+  Ok(info)
+}
+
+/// 按扩展名解析 APK 或 AAB
+pub fn inspect(path: &Path) -> Result<ArtifactInfo> {
+  match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+    Some("apk") => inspect_apk(path),
+    Some("aab") => inspect_aab(path),
+    _ => Err(Error::InvalidInput(format!("不支持的文件类型，仅支持 .apk 或 .aab 文件: {}", path.display()))),
+  }
+}
+
+/// 解析 AAB：base 模块的清单与资源，以及各模块的类型与大小
+pub fn inspect_aab(path: &Path) -> Result<ArtifactInfo> {
+  let mut zip = open_zip(path)?;
+  let manifest_path = format!("{}/manifest/{}", BASE_MODULE, MANIFEST);
+  let manifest = read_entry(&mut zip, &manifest_path)?
+    .ok_or_else(|| Error::Artifact(format!("{} 中没有 {}", path.display(), manifest_path)))?;
+  let manifest = aab::parse_manifest(&manifest, "base/manifest/AndroidManifest.xml")?;
+  let resources = match read_entry(&mut zip, "base/resources.pb")? {
+    Some(data) => aab::parse_resources(&data, "base/resources.pb").map_err(|e| log::warn!("{}", e)).ok(),
+    None => None,
+  };
+
+  // 按顶层目录统计各模块的大小与 ABI
+  let mut sizes: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+  let mut abis = BTreeSet::new();
+  for i in 0..zip.len() {
+    let entry = zip
+      .by_index_raw(i)
+      .map_err(|e| Error::Artifact(format!("读取 {} 失败：{}", path.display(), e)))?;
+    let Some((module, rest)) = entry.name().split_once('/') else {
+      continue;
+    };
+    if BUNDLE_METADATA_DIRS.contains(&module) {
+      continue;
+    }
+    if let Some((abi, _)) = rest.strip_prefix("lib/").and_then(|r| r.split_once('/')) {
+      abis.insert(abi.to_string());
+    }
+    let size = sizes.entry(module.to_string()).or_default();
+    size.0 += entry.compressed_size();
+    size.1 += entry.size();
+  }
+
+  let mut modules = Vec::new();
+  for (name, (size, uncompressed_size)) in sizes {
+    let kind = if name == BASE_MODULE {
+      ModuleKind::Base
+    } else {
+      let manifest_path = format!("{}/manifest/{}", name, MANIFEST);
+      match read_entry(&mut zip, &manifest_path)? {
+        Some(data) => module_kind(&aab::parse_manifest(&data, "AndroidManifest.xml")?),
+        // 没有清单的目录不是模块
+        None => continue,
+      }
+    };
+    modules.push(ModuleInfo { name, kind, size, uncompressed_size });
+  }
+  // base 排在最前
+  modules.sort_by_key(|m| (m.kind != ModuleKind::Base, m.name.clone()));
+
+  let mut info = manifest_info(&manifest, resources.as_ref());
+  info.path = path.to_string_lossy().to_string();
+  info.size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+  info.abis = abis.into_iter().collect();
+  info.modules = modules;
+  Ok(info)
+}
+
+/// `<dist:module dist:type="asset-pack">` 为资源包，其余为动态功能模块
+fn module_kind(manifest: &XmlElement) -> ModuleKind {
+  match manifest.child("module").and_then(|m| m.attr_str("type")) {
+    Some("asset-pack") => ModuleKind::AssetPack,
+    _ => ModuleKind::Feature,
+  }
+}
+
+fn resolve_string(value: &XmlValue, resources: Option<&ResourceTable>) -> Option<String> {
+  match value.as_reference() {
+    Some(id) => resources?.string(id),
+    None => value.as_str().map(str::to_string),
+  }
+}
+
+fn resolve_int(value: &XmlValue, resources: Option<&ResourceTable>) -> Option<u32> {
+  match value.as_reference() {
+    Some(id) => resources?.int(id),
+    None => value.as_int(),
+  }
+}
+
+/// `.MainActivity` 与 `MainActivity` 补全为完整类名
+fn full_class_name(package: &str, name: &str) -> String {
+  if name.starts_with('.') {
+    format!("{}{}", package, name)
+  } else if !name.contains('.') {
+    format!("{}.{}", package, name)
+  } else {
+    name.to_string()
+  }
+}
+
+fn is_launchable(component: &XmlElement) -> bool {
+  component.children_named("intent-filter").any(|filter| {
+    let has = |tag: &str, value: &str| filter.children_named(tag).any(|e| e.attr_str("name") == Some(value));
+    has("action", ACTION_MAIN) && has("category", CATEGORY_LAUNCHER)
+  })
+}
+
+/// 从清单中读取包信息，APK 与 AAB 共用
+pub(crate) fn manifest_info(manifest: &XmlElement, resources: Option<&ResourceTable>) -> ArtifactInfo {
+  let package = manifest.attr_str("package").unwrap_or_default().to_string();
+  let int = |element: &XmlElement, name: &str| element.attr(name).and_then(|v| resolve_int(v, resources));
+
+  let version_code = int(manifest, "versionCode").map(|code| {
+    let major = int(manifest, "versionCodeMajor").unwrap_or(0) as u64;
+    (major << 32) | code as u64
+  });
+  let version_name = manifest.attr("versionName").and_then(|v| resolve_string(v, resources));
+  let uses_sdk = manifest.child("uses-sdk");
+  // 预览版 SDK 的值为代号（如 "Tiramisu"），不是数字；未声明 targetSdkVersion 时与 minSdkVersion 相同
+  let min_sdk = uses_sdk.and_then(|e| int(e, "minSdkVersion"));
+  let target_sdk = uses_sdk.and_then(|e| int(e, "targetSdkVersion")).or(min_sdk);
+  let compile_sdk = int(manifest, "compileSdkVersion").or_else(|| int(manifest, "platformBuildVersionCode"));
+
+  let application = manifest.child("application");
+  let components = application
+    .into_iter()
+    .flat_map(|app| app.children.iter())
+    .filter(|c| c.name == "activity" || c.name == "activity-alias");
+  let launchable = components.clone().find(|c| is_launchable(c));
+  let launchable_activity = launchable
+    .and_then(|c| c.attr_str("name"))
+    .map(|name| full_class_name(&package, name));
+  let label = application
+    .and_then(|app| app.attr("label"))
+    .or_else(|| launchable.and_then(|c| c.attr("label")))
+    .and_then(|v| resolve_string(v, resources));
+
+  let permissions = manifest
+    .children
+    .iter()
+    .filter(|c| c.name.starts_with("uses-permission"))
+    .filter_map(|c| c.attr_str("name").map(str::to_string))
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .collect();
+
+  ArtifactInfo {
+    package,
+    version_code,
+    version_name,
+    min_sdk,
+    target_sdk,
+    compile_sdk,
+    label,
+    launchable_activity,
+    permissions,
+    ..Default::default()
+  }
+}
+
+impl ArtifactInfo {
+  /// `com.example.app 1.2.0 (120)，minSdk 21，targetSdk 34`
+  pub fn summary(&self) -> String {
+    let mut s = self.package.clone();
+    if let Some(name) = &self.version_name {
+      s.push_str(&format!(" {}", name));
+    }
+    if let Some(code) = self.version_code {
+      s.push_str(&format!(" ({})", code));
+    }
+    if let Some(min) = self.min_sdk {
+      s.push_str(&format!("，minSdk {}", min));
+    }
+    if let Some(target) = self.target_sdk {
+      s.push_str(&format!("，targetSdk {}", target));
+    }
+    s
+  }
+}
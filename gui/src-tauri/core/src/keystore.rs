@@ -0,0 +1,695 @@
+//! 读写 Java keystore（JKS 与 PKCS12）：读取签名密钥与证书链，写出新建的 keystore
+//!
+//! 与 keytool 生成的文件兼容：JKS 使用 Sun 专有的密钥保护算法，PKCS12 支持 PBES2（JDK 8u301+ / OpenSSL 3 默认）
+//! 与旧版的 3DES、RC2 加密。写出的 PKCS12 与 JDK 12+ 的 keytool 默认设置相同。
+
+use std::{fs, path::Path};
+
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, InnerIvInit, KeyIvInit};
+use cms::{content_info::ContentInfo, encrypted_data::EncryptedData};
+use hmac::{Mac, SimpleHmac};
+use pkcs12::{
+  cert_type::CertBag,
+  digest_info::DigestInfo,
+  kdf::{derive_key_utf8, Pkcs12KeyType},
+  mac_data::MacData,
+  pbe_params::{EncryptedPrivateKeyInfo, Pkcs12PbeParams},
+  pfx::{Pfx, Version},
+  safe_bag::{SafeBag, SafeContents},
+  PKCS_12_CERT_BAG_OID, PKCS_12_KEY_BAG_OID, PKCS_12_PBEWITH_SHAAND40_BIT_RC2_CBC,
+  PKCS_12_PBE_WITH_SHAAND128_BIT_RC2_CBC, PKCS_12_PBE_WITH_SHAAND3_KEY_TRIPLE_DES_CBC, PKCS_12_PKCS8_KEY_BAG_OID,
+  PKCS_12_X509_CERT_OID,
+};
+use pkcs8::{EncodePrivateKey, EncodePublicKey};
+use rand::{rngs::OsRng, RngCore};
+use rsa::{pkcs8::DecodePrivateKey, traits::PublicKeyParts, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{
+  digest::{core_api::BlockSizeUser, FixedOutputReset},
+  Digest, Sha256, Sha384, Sha512,
+};
+use x509_cert::{
+  attr::{Attribute, Attributes},
+  der::{
+    asn1::{BmpString, ObjectIdentifier, OctetString, SetOfVec},
+    Any, AnyRef, Decode, Encode,
+  },
+  spki::AlgorithmIdentifierOwned,
+  Certificate,
+};
+
+use crate::{keystores::KeyAlgorithm, signature::Hash, Error, Result};
+
+const JKS_MAGIC: u32 = 0xfeed_feed;
+const JCEKS_MAGIC: u32 = 0xcece_cece;
+/// JKS 完整性校验摘要中固定的盐
+const JKS_WHITENER: &[u8] = b"Mighty Aphrodite";
+/// JKS 密钥保护算法（Sun 专有）
+const OID_JKS_KEY_PROTECTOR: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.42.2.17.1.1");
+const OID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const OID_ENCRYPTED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.6");
+const OID_FRIENDLY_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.20");
+const OID_LOCAL_KEY_ID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.21");
+const OID_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const OID_EC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const OID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+/// 写出时 PBKDF2 与 PKCS12 MAC 的迭代次数，与 keytool 相同
+const WRITE_ITERATIONS: u32 = 10_000;
+
+/// 加密保存的私钥
+enum ProtectedKey {
+  /// JKS 专有算法加密的 PKCS#8 私钥
+  Jks(Vec<u8>),
+  /// PKCS#8 EncryptedPrivateKeyInfo
+  Encrypted(Vec<u8>),
+  /// 未加密的 PKCS#8 PrivateKeyInfo
+  Plain(Vec<u8>),
+}
+
+/// keystore 中的条目：私钥条目或受信任的证书
+pub(crate) struct Entry {
+  pub alias: String,
+  /// 证书链（DER），第一个为条目自身的证书
+  pub certificates: Vec<Vec<u8>>,
+  key: Option<ProtectedKey>,
+}
+
+impl Entry {
+  pub fn has_key(&self) -> bool {
+    self.key.is_some()
+  }
+}
+
+pub(crate) struct Keystore {
+  pub entries: Vec<Entry>,
+}
+
+impl Keystore {
+  pub fn open(path: &Path, password: &str) -> Result<Self> {
+    let data = fs::read(path).map_err(|e| Error::io(format!("读取 keystore 失败 {}", path.display()), e))?;
+    Self::parse(&data, password)
+  }
+
+  /// 按文件头区分 JKS 与 PKCS12，并用 keystore 密码校验完整性
+  pub fn parse(data: &[u8], password: &str) -> Result<Self> {
+    let magic = data.get(..4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    match magic {
+      Some(JKS_MAGIC) => parse_jks(data, password),
+      Some(JCEKS_MAGIC) => Err(Error::Keystore("不支持 JCEKS 格式，请先用 keytool 转换为 PKCS12".into())),
+      _ if data.first() == Some(&0x30) => parse_pkcs12(data, password),
+      _ => Err(Error::Keystore("无法识别的 keystore 格式，仅支持 JKS 与 PKCS12".into())),
+    }
+  }
+
+  /// 取出签名密钥；只有一个私钥条目时可不指定别名，未指定密钥密码时使用 keystore 密码
+  pub fn signing_key(&self, alias: Option<&str>, password: &str) -> Result<SigningKey> {
+    let keys: Vec<&Entry> = self.entries.iter().filter(|e| e.has_key()).collect();
+    let entry = match alias.filter(|a| !a.is_empty()) {
+      // JKS 的别名不区分大小写
+      Some(alias) => keys
+        .iter()
+        .find(|e| e.alias.eq_ignore_ascii_case(alias))
+        .ok_or_else(|| Error::Keystore(format!("keystore 中没有私钥条目：{}", alias)))?,
+      None => match keys.as_slice() {
+        [entry] => entry,
+        [] => return Err(Error::Keystore("keystore 中没有私钥条目".into())),
+        _ => {
+          let aliases: Vec<&str> = keys.iter().map(|e| e.alias.as_str()).collect();
+          return Err(Error::Keystore(format!("keystore 中有多个私钥，请指定别名：{}", aliases.join(", "))));
+        }
+      },
+    };
+    if entry.certificates.is_empty() {
+      return Err(Error::Keystore(format!("私钥条目 {} 没有证书", entry.alias)));
+    }
+    let wrong_password = || Error::Keystore(format!("无法解密私钥 {}，请检查密钥密码", entry.alias));
+    let der = match &entry.key {
+      Some(ProtectedKey::Jks(data)) => jks_decrypt_key(data, password).ok_or_else(wrong_password)?,
+      Some(ProtectedKey::Encrypted(data)) => {
+        let info = EncryptedPrivateKeyInfo::from_der(data).map_err(|e| Error::Keystore(format!("私钥格式错误：{}", e)))?;
+        decrypt(&info.encryption_algorithm, password, info.encrypted_data.as_bytes()).map_err(|_| wrong_password())?
+      }
+      Some(ProtectedKey::Plain(data)) => data.clone(),
+      None => return Err(Error::Keystore(format!("{} 不是私钥条目", entry.alias))),
+    };
+    let key = PrivateKey::from_pkcs8(&der).map_err(|e| Error::Keystore(format!("私钥 {}：{}", entry.alias, e)))?;
+    Ok(SigningKey { alias: entry.alias.clone(), key, certificates: entry.certificates.clone() })
+  }
+}
+
+/// 签名用的私钥与证书链
+pub(crate) struct SigningKey {
+  pub alias: String,
+  pub key: PrivateKey,
+  pub certificates: Vec<Vec<u8>>,
+}
+
+pub(crate) enum PrivateKey {
+  Rsa(Box<RsaPrivateKey>),
+  P256(p256::SecretKey),
+  P384(p384::SecretKey),
+}
+
+impl PrivateKey {
+  /// 生成新的私钥：RSA 按指定位数，EC 为 P-256（256）或 P-384（384）
+  pub fn generate(algorithm: KeyAlgorithm, size: u32) -> Result<Self> {
+    match (algorithm, size) {
+      (KeyAlgorithm::Rsa, 2048 | 3072 | 4096) => RsaPrivateKey::new(&mut OsRng, size as usize)
+        .map(|key| Self::Rsa(Box::new(key)))
+        .map_err(|e| Error::Keystore(format!("生成 RSA 私钥失败：{}", e))),
+      (KeyAlgorithm::Rsa, _) => Err(Error::InvalidInput("RSA 密钥长度只支持 2048、3072 与 4096".into())),
+      (KeyAlgorithm::Ec, 256) => Ok(Self::P256(p256::SecretKey::random(&mut OsRng))),
+      (KeyAlgorithm::Ec, 384) => Ok(Self::P384(p384::SecretKey::random(&mut OsRng))),
+      (KeyAlgorithm::Ec, _) => Err(Error::InvalidInput("EC 密钥长度只支持 256（P-256）与 384（P-384）".into())),
+    }
+  }
+
+  /// PKCS#8 PrivateKeyInfo（DER）
+  fn to_pkcs8(&self) -> Result<Vec<u8>> {
+    let der = match self {
+      Self::Rsa(key) => key.to_pkcs8_der(),
+      Self::P256(key) => key.to_pkcs8_der(),
+      Self::P384(key) => key.to_pkcs8_der(),
+    };
+    der.map(|doc| doc.as_bytes().to_vec()).map_err(|e| Error::Keystore(format!("编码私钥失败：{}", e)))
+  }
+
+  /// 公钥的 SubjectPublicKeyInfo（DER）
+  pub fn public_key(&self) -> Result<Vec<u8>> {
+    let der = match self {
+      Self::Rsa(key) => RsaPublicKey::from(key.as_ref()).to_public_key_der(),
+      Self::P256(key) => key.public_key().to_public_key_der(),
+      Self::P384(key) => key.public_key().to_public_key_der(),
+    };
+    der.map(|doc| doc.into_vec()).map_err(|e| Error::Keystore(format!("编码公钥失败：{}", e)))
+  }
+
+  /// 自签名证书使用的摘要与签名算法：RSA 与 P-256 用 SHA-256，P-384 用 SHA-384
+  pub fn certificate_algorithm(&self) -> (Hash, AlgorithmIdentifierOwned) {
+    let (hash, oid, parameters) = match self {
+      Self::Rsa(_) => (Hash::Sha256, "1.2.840.113549.1.1.11", Some(Any::null())),
+      Self::P256(_) => (Hash::Sha256, "1.2.840.10045.4.3.2", None),
+      Self::P384(_) => (Hash::Sha384, "1.2.840.10045.4.3.3", None),
+    };
+    (hash, AlgorithmIdentifierOwned { oid: ObjectIdentifier::new_unwrap(oid), parameters })
+  }
+
+  fn from_pkcs8(der: &[u8]) -> std::result::Result<Self, String> {
+    let info = pkcs8::PrivateKeyInfo::from_der(der).map_err(|e| format!("无法解析私钥：{}", e))?;
+    let oid = info.algorithm.oid;
+    if oid == OID_RSA {
+      let key = RsaPrivateKey::from_pkcs8_der(der).map_err(|e| format!("无法解析 RSA 私钥：{}", e))?;
+      return Ok(Self::Rsa(Box::new(key)));
+    }
+    if oid == OID_EC {
+      if let Ok(key) = p256::SecretKey::from_pkcs8_der(der) {
+        return Ok(Self::P256(key));
+      }
+      if let Ok(key) = p384::SecretKey::from_pkcs8_der(der) {
+        return Ok(Self::P384(key));
+      }
+      return Err("仅支持 P-256 与 P-384 曲线的 EC 私钥".into());
+    }
+    Err(format!("不支持的私钥算法 {}", oid))
+  }
+
+  /// v2/v3 签名使用的算法 ID，与 apksigner 的选择一致
+  pub fn algorithm_id(&self) -> u32 {
+    match self {
+      Self::Rsa(key) if key.size() * 8 > 3072 => 0x0104,
+      Self::Rsa(_) => 0x0103,
+      Self::P256(_) => 0x0201,
+      Self::P384(_) => 0x0202,
+    }
+  }
+
+  /// JAR 签名块中签名算法的 OID
+  pub fn jar_signature_oid(&self, hash: Hash) -> ObjectIdentifier {
+    let oid = match (self, hash) {
+      (Self::Rsa(_), _) => "1.2.840.113549.1.1.1",
+      (_, Hash::Sha1) => "1.2.840.10045.4.1",
+      (_, Hash::Sha256) => "1.2.840.10045.4.3.2",
+      (_, Hash::Sha384) => "1.2.840.10045.4.3.3",
+      (_, Hash::Sha512) => "1.2.840.10045.4.3.4",
+    };
+    ObjectIdentifier::new_unwrap(oid)
+  }
+
+  /// JAR 签名块的文件扩展名
+  pub fn jar_extension(&self) -> &'static str {
+    match self {
+      Self::Rsa(_) => "RSA",
+      _ => "EC",
+    }
+  }
+
+  /// RSA 使用 PKCS#1 v1.5，EC 使用 DER 编码的 ECDSA 签名
+  pub fn sign(&self, hash: Hash, message: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    let digest = hash.digest(message);
+    let failed = |e: &dyn std::fmt::Display| format!("签名失败：{}", e);
+    match self {
+      Self::Rsa(key) => {
+        let padding = match hash {
+          Hash::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+          Hash::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+          Hash::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+          Hash::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        };
+        key.sign(padding, &digest).map_err(|e| failed(&e))
+      }
+      Self::P256(key) => {
+        let signature: p256::ecdsa::Signature =
+          p256::ecdsa::SigningKey::from(key).sign_prehash(&digest).map_err(|e| failed(&e))?;
+        Ok(signature.to_der().as_bytes().to_vec())
+      }
+      Self::P384(key) => {
+        let signature: p384::ecdsa::Signature =
+          p384::ecdsa::SigningKey::from(key).sign_prehash(&digest).map_err(|e| failed(&e))?;
+        Ok(signature.to_der().as_bytes().to_vec())
+      }
+    }
+  }
+}
+
+/// 按大端读取 JKS 文件
+struct JksReader<'a> {
+  data: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> JksReader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+    let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+    let end = end.ok_or_else(|| Error::Keystore("JKS 文件已损坏".into()))?;
+    let bytes = &self.data[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+
+  fn u16(&mut self) -> Result<u16> {
+    let b = self.take(2)?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+  }
+
+  fn u32(&mut self) -> Result<u32> {
+    let b = self.take(4)?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+  }
+
+  /// Java `DataOutput.writeUTF` 写入的字符串
+  fn utf(&mut self) -> Result<String> {
+    let len = self.u16()? as usize;
+    Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+  }
+
+  fn bytes(&mut self) -> Result<Vec<u8>> {
+    let len = self.u32()? as usize;
+    Ok(self.take(len)?.to_vec())
+  }
+
+  fn certificate(&mut self, version: u32) -> Result<Vec<u8>> {
+    if version == 2 {
+      let kind = self.utf()?;
+      if kind != "X.509" {
+        return Err(Error::Keystore(format!("不支持的证书类型 {}", kind)));
+      }
+    }
+    self.bytes()
+  }
+}
+
+pub(crate) fn is_jks(data: &[u8]) -> bool {
+  data.starts_with(&JKS_MAGIC.to_be_bytes())
+}
+
+/// Java 的 char[] 密码按 UTF-16BE 编码
+fn utf16_be(password: &str) -> Vec<u8> {
+  password.encode_utf16().flat_map(u16::to_be_bytes).collect()
+}
+
+fn parse_jks(data: &[u8], password: &str) -> Result<Keystore> {
+  if data.len() < 20 {
+    return Err(Error::Keystore("JKS 文件已损坏".into()));
+  }
+  let (body, checksum) = data.split_at(data.len() - 20);
+  let expected = Sha1::new()
+    .chain_update(utf16_be(password))
+    .chain_update(JKS_WHITENER)
+    .chain_update(body)
+    .finalize();
+  if expected.as_slice() != checksum {
+    return Err(Error::Keystore("keystore 密码错误或文件已损坏".into()));
+  }
+
+  let mut reader = JksReader { data: body, pos: 4 };
+  let version = reader.u32()?;
+  if version != 1 && version != 2 {
+    return Err(Error::Keystore(format!("不支持的 JKS 版本 {}", version)));
+  }
+  let count = reader.u32()?;
+  let mut entries = Vec::new();
+  for _ in 0..count {
+    let tag = reader.u32()?;
+    let alias = reader.utf()?;
+    // 创建时间
+    reader.take(8)?;
+    match tag {
+      1 => {
+        let key = reader.bytes()?;
+        let chain = reader.u32()?;
+        let certificates = (0..chain).map(|_| reader.certificate(version)).collect::<Result<_>>()?;
+        entries.push(Entry { alias, certificates, key: Some(ProtectedKey::Jks(key)) });
+      }
+      2 => {
+        let certificate = reader.certificate(version)?;
+        entries.push(Entry { alias, certificates: vec![certificate], key: None });
+      }
+      _ => return Err(Error::Keystore(format!("不支持的 JKS 条目类型 {}", tag))),
+    }
+  }
+  Ok(Keystore { entries })
+}
+
+/// 解密 JKS 专有算法保护的私钥：SHA-1 生成密钥流异或，末尾 20 字节为明文校验和
+fn jks_decrypt_key(data: &[u8], password: &str) -> Option<Vec<u8>> {
+  let info = EncryptedPrivateKeyInfo::from_der(data).ok()?;
+  if info.encryption_algorithm.oid != OID_JKS_KEY_PROTECTOR {
+    return None;
+  }
+  let protected = info.encrypted_data.as_bytes();
+  if protected.len() < 40 {
+    return None;
+  }
+  let (salt, rest) = protected.split_at(20);
+  let (encrypted, check) = rest.split_at(rest.len() - 20);
+  let password = utf16_be(password);
+  let mut digest = salt.to_vec();
+  let mut key = Vec::with_capacity(encrypted.len());
+  for chunk in encrypted.chunks(20) {
+    digest = Sha1::new().chain_update(&password).chain_update(&digest).finalize().to_vec();
+    key.extend(chunk.iter().zip(&digest).map(|(a, b)| a ^ b));
+  }
+  let actual = Sha1::new().chain_update(&password).chain_update(&key).finalize();
+  (actual.as_slice() == check).then_some(key)
+}
+
+/// PKCS12 中带属性的证书或私钥
+struct Bag {
+  name: Option<String>,
+  key_id: Option<Vec<u8>>,
+}
+
+fn bag_attributes(attributes: Option<&Attributes>) -> Bag {
+  let value = |oid: ObjectIdentifier| {
+    attributes
+      .into_iter()
+      .flat_map(|set| set.iter())
+      .find(|a| a.oid == oid)
+      .and_then(|a| a.values.iter().next())
+  };
+  Bag {
+    name: value(OID_FRIENDLY_NAME).and_then(|v| v.decode_as::<BmpString>().ok()).map(|s| s.to_string()),
+    key_id: value(OID_LOCAL_KEY_ID).and_then(|v| v.decode_as::<OctetString>().ok()).map(OctetString::into_bytes),
+  }
+}
+
+fn parse_pkcs12(data: &[u8], password: &str) -> Result<Keystore> {
+  let invalid = |e: x509_cert::der::Error| Error::Keystore(format!("PKCS12 文件已损坏：{}", e));
+  let pfx = Pfx::from_der(data).map_err(invalid)?;
+  if pfx.auth_safe.content_type != OID_DATA {
+    return Err(Error::Keystore("不支持公钥完整性模式的 PKCS12 文件".into()));
+  }
+  let auth_safe = pfx.auth_safe.content.decode_as::<OctetString>().map_err(invalid)?;
+  if let Some(mac) = &pfx.mac_data {
+    if !verify_mac(mac, password, auth_safe.as_bytes()) {
+      return Err(Error::Keystore("keystore 密码错误或文件已损坏".into()));
+    }
+  }
+
+  let mut keys = Vec::new();
+  let mut certificates = Vec::new();
+  for info in Vec::<ContentInfo>::from_der(auth_safe.as_bytes()).map_err(invalid)? {
+    let contents = if info.content_type == OID_DATA {
+      info.content.decode_as::<OctetString>().map_err(invalid)?.into_bytes()
+    } else if info.content_type == OID_ENCRYPTED_DATA {
+      let encrypted: EncryptedData = info.content.decode_as().map_err(invalid)?;
+      let content = &encrypted.enc_content_info;
+      let ciphertext = content.encrypted_content.as_ref().map(OctetString::as_bytes).unwrap_or_default();
+      decrypt(&content.content_enc_alg, password, ciphertext)
+        .map_err(|e| Error::Keystore(format!("无法解密 PKCS12 内容：{}", e)))?
+    } else {
+      continue;
+    };
+    for bag in SafeContents::from_der(&contents).map_err(invalid)? {
+      // bag_value 是 [0] EXPLICIT 标签的完整编码
+      let wrapped = AnyRef::from_der(&bag.bag_value).map_err(invalid)?;
+      let value = wrapped.value();
+      let attributes = bag_attributes(bag.bag_attributes.as_ref());
+      if bag.bag_id == PKCS_12_PKCS8_KEY_BAG_OID {
+        keys.push((attributes, ProtectedKey::Encrypted(value.to_vec())));
+      } else if bag.bag_id == PKCS_12_KEY_BAG_OID {
+        keys.push((attributes, ProtectedKey::Plain(value.to_vec())));
+      } else if bag.bag_id == PKCS_12_CERT_BAG_OID {
+        let cert = CertBag::from_der(value).map_err(invalid)?;
+        certificates.push((attributes, cert.cert_value.into_bytes()));
+      }
+    }
+  }
+
+  let parsed: Vec<Option<Certificate>> = certificates.iter().map(|(_, der)| Certificate::from_der(der).ok()).collect();
+  let mut entries = Vec::new();
+  let mut used = vec![false; certificates.len()];
+  for (index, (attributes, key)) in keys.into_iter().enumerate() {
+    let leaf = certificates
+      .iter()
+      .position(|(a, _)| a.key_id.is_some() && a.key_id == attributes.key_id)
+      .or_else(|| certificates.iter().position(|(a, _)| a.name.is_some() && a.name == attributes.name))
+      .or_else(|| (index == 0 && !certificates.is_empty()).then_some(0));
+    let mut chain = Vec::new();
+    if let Some(mut current) = leaf {
+      // 按颁发者依次补全证书链，自签名证书为链的终点
+      loop {
+        chain.push(certificates[current].1.clone());
+        used[current] = true;
+        let Some(cert) = &parsed[current] else { break };
+        let tbs = &cert.tbs_certificate;
+        if tbs.issuer == tbs.subject || chain.len() > certificates.len() {
+          break;
+        }
+        let issuer = parsed
+          .iter()
+          .position(|c| c.as_ref().is_some_and(|c| c.tbs_certificate.subject == tbs.issuer));
+        match issuer {
+          Some(next) if next != current => current = next,
+          _ => break,
+        }
+      }
+    }
+    let alias = attributes
+      .name
+      .or_else(|| leaf.and_then(|i| certificates[i].0.name.clone()))
+      .unwrap_or_else(|| (index + 1).to_string());
+    entries.push(Entry { alias, certificates: chain, key: Some(key) });
+  }
+  // 其余带别名的证书为受信任证书条目
+  for (index, (attributes, der)) in certificates.into_iter().enumerate() {
+    if let (false, Some(alias)) = (used[index], attributes.name) {
+      entries.push(Entry { alias, certificates: vec![der], key: None });
+    }
+  }
+  Ok(Keystore { entries })
+}
+
+fn verify_mac(mac: &pkcs12::mac_data::MacData, password: &str, data: &[u8]) -> bool {
+  fn compute<D>(password: &str, salt: &[u8], iterations: i32, data: &[u8], expected: &[u8]) -> bool
+  where
+    D: Digest + FixedOutputReset + BlockSizeUser + Clone,
+  {
+    let Ok(key) = derive_key_utf8::<D>(password, salt, Pkcs12KeyType::Mac, iterations, <D as Digest>::output_size())
+    else {
+      return false;
+    };
+    let Ok(mut hmac) = SimpleHmac::<D>::new_from_slice(&key) else { return false };
+    hmac.update(data);
+    hmac.verify_slice(expected).is_ok()
+  }
+  let salt = mac.mac_salt.as_bytes();
+  let expected = mac.mac.digest.as_bytes();
+  match mac.mac.algorithm.oid.to_string().as_str() {
+    "1.3.14.3.2.26" => compute::<Sha1>(password, salt, mac.iterations, data, expected),
+    "2.16.840.1.101.3.4.2.1" => compute::<Sha256>(password, salt, mac.iterations, data, expected),
+    "2.16.840.1.101.3.4.2.2" => compute::<Sha384>(password, salt, mac.iterations, data, expected),
+    "2.16.840.1.101.3.4.2.3" => compute::<Sha512>(password, salt, mac.iterations, data, expected),
+    _ => false,
+  }
+}
+
+/// 按加密算法解密 PKCS12 中的内容或私钥：PBES2 或 PKCS12 专有的 PBE 算法
+fn decrypt(algorithm: &AlgorithmIdentifierOwned, password: &str, data: &[u8]) -> std::result::Result<Vec<u8>, String> {
+  let oid = algorithm.oid;
+  let params = || -> std::result::Result<Pkcs12PbeParams, String> {
+    algorithm
+      .parameters
+      .as_ref()
+      .and_then(|p| p.decode_as::<Pkcs12PbeParams>().ok())
+      .ok_or_else(|| "加密参数无效".to_string())
+  };
+  let derive = |params: &Pkcs12PbeParams, kind: Pkcs12KeyType, len: usize| {
+    derive_key_utf8::<Sha1>(password, params.salt.as_bytes(), kind, params.iterations, len).map_err(|e| e.to_string())
+  };
+  let failed = |_| "密码错误".to_string();
+  if oid == PKCS_12_PBE_WITH_SHAAND3_KEY_TRIPLE_DES_CBC {
+    let params = params()?;
+    let key = derive(&params, Pkcs12KeyType::EncryptionKey, 24)?;
+    let iv = derive(&params, Pkcs12KeyType::Iv, 8)?;
+    let cipher = cbc::Decryptor::<des::TdesEde3>::new_from_slices(&key, &iv).map_err(|e| e.to_string())?;
+    return cipher.decrypt_padded_vec_mut::<Pkcs7>(data).map_err(failed);
+  }
+  if oid == PKCS_12_PBEWITH_SHAAND40_BIT_RC2_CBC || oid == PKCS_12_PBE_WITH_SHAAND128_BIT_RC2_CBC {
+    let params = params()?;
+    let len = if oid == PKCS_12_PBEWITH_SHAAND40_BIT_RC2_CBC { 5 } else { 16 };
+    let key = derive(&params, Pkcs12KeyType::EncryptionKey, len)?;
+    let iv = derive(&params, Pkcs12KeyType::Iv, 8)?;
+    let rc2 = rc2::Rc2::new_with_eff_key_len(&key, len * 8);
+    let cipher = cbc::Decryptor::<rc2::Rc2>::inner_iv_slice_init(rc2, &iv).map_err(|e| e.to_string())?;
+    return cipher.decrypt_padded_vec_mut::<Pkcs7>(data).map_err(failed);
+  }
+  let der = algorithm.to_der().map_err(|e| e.to_string())?;
+  let scheme = pkcs5::EncryptionScheme::try_from(der.as_slice()).map_err(|_| format!("不支持的加密算法 {}", oid))?;
+  scheme.decrypt(password, data).map_err(|_| "密码错误".to_string())
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+  let mut bytes = [0; N];
+  OsRng.fill_bytes(&mut bytes);
+  bytes
+}
+
+/// 写出只有一个私钥条目的 JKS；keytool 会把 JKS 的别名转为小写
+pub(crate) fn write_jks(
+  alias: &str,
+  key: &PrivateKey,
+  certificates: &[Vec<u8>],
+  store_password: &str,
+  key_password: &str,
+  created: u64,
+) -> Result<Vec<u8>> {
+  let plain = key.to_pkcs8()?;
+  let password = utf16_be(key_password);
+  let salt: [u8; 20] = random_bytes();
+  let mut protected = salt.to_vec();
+  let mut digest = salt.to_vec();
+  for chunk in plain.chunks(20) {
+    digest = Sha1::new().chain_update(&password).chain_update(&digest).finalize().to_vec();
+    protected.extend(chunk.iter().zip(&digest).map(|(a, b)| a ^ b));
+  }
+  protected.extend(Sha1::new().chain_update(&password).chain_update(&plain).finalize());
+  let invalid = |e: x509_cert::der::Error| Error::Keystore(format!("编码私钥失败：{}", e));
+  let protected_key = EncryptedPrivateKeyInfo {
+    encryption_algorithm: AlgorithmIdentifierOwned { oid: OID_JKS_KEY_PROTECTOR, parameters: Some(Any::null()) },
+    encrypted_data: OctetString::new(protected).map_err(invalid)?,
+  }
+  .to_der()
+  .map_err(invalid)?;
+
+  let mut out = Vec::new();
+  let write_utf = |out: &mut Vec<u8>, value: &str| {
+    out.extend((value.len() as u16).to_be_bytes());
+    out.extend(value.as_bytes());
+  };
+  out.extend(JKS_MAGIC.to_be_bytes());
+  out.extend(2u32.to_be_bytes());
+  out.extend(1u32.to_be_bytes());
+  out.extend(1u32.to_be_bytes());
+  write_utf(&mut out, &alias.to_lowercase());
+  out.extend(created.to_be_bytes());
+  out.extend((protected_key.len() as u32).to_be_bytes());
+  out.extend(protected_key);
+  out.extend((certificates.len() as u32).to_be_bytes());
+  for certificate in certificates {
+    write_utf(&mut out, "X.509");
+    out.extend((certificate.len() as u32).to_be_bytes());
+    out.extend(certificate);
+  }
+  let checksum = Sha1::new()
+    .chain_update(utf16_be(store_password))
+    .chain_update(JKS_WHITENER)
+    .chain_update(&out)
+    .finalize();
+  out.extend(checksum);
+  Ok(out)
+}
+
+/// 写出只有一个私钥条目的 PKCS12：私钥用 PBES2（PBKDF2-SHA256 + AES-256-CBC）加密，证书不加密，
+/// 完整性校验使用 HMAC-SHA256
+pub(crate) fn write_pkcs12(alias: &str, key: &PrivateKey, certificates: &[Vec<u8>], password: &str) -> Result<Vec<u8>> {
+  let invalid = |e: x509_cert::der::Error| Error::Keystore(format!("生成 PKCS12 失败：{}", e));
+  let Some(leaf) = certificates.first() else {
+    return Err(Error::Keystore("私钥条目没有证书".into()));
+  };
+  // 私钥与证书通过相同的 localKeyId 与 friendlyName 关联
+  let attributes = {
+    let name = BmpString::from_utf8(alias).map_err(invalid)?;
+    let key_id = OctetString::new(Hash::Sha1.digest(leaf)).map_err(invalid)?;
+    let attribute = |oid, value: Any| -> Result<Attribute> {
+      Ok(Attribute { oid, values: SetOfVec::try_from(vec![value]).map_err(invalid)? })
+    };
+    Attributes::try_from(vec![
+      attribute(OID_FRIENDLY_NAME, Any::encode_from(&name).map_err(invalid)?)?,
+      attribute(OID_LOCAL_KEY_ID, Any::encode_from(&key_id).map_err(invalid)?)?,
+    ])
+    .map_err(invalid)?
+  };
+
+  let salt: [u8; 16] = random_bytes();
+  let iv: [u8; 16] = random_bytes();
+  let params = pkcs5::pbes2::Parameters::pbkdf2_sha256_aes256cbc(WRITE_ITERATIONS, &salt, &iv)
+    .map_err(|e| Error::Keystore(format!("生成 PKCS12 失败：{}", e)))?;
+  let plain = key.to_pkcs8()?;
+  let encrypted = pkcs8::PrivateKeyInfo::from_der(&plain)
+    .map_err(|e| Error::Keystore(format!("编码私钥失败：{}", e)))?
+    .encrypt_with_params(params, password)
+    .map_err(|e| Error::Keystore(format!("加密私钥失败：{}", e)))?;
+  let key_bags = vec![SafeBag {
+    bag_id: PKCS_12_PKCS8_KEY_BAG_OID,
+    bag_value: encrypted.as_bytes().to_vec(),
+    bag_attributes: Some(attributes.clone()),
+  }];
+  let mut cert_bags = Vec::new();
+  for (index, certificate) in certificates.iter().enumerate() {
+    let bag = CertBag { cert_id: PKCS_12_X509_CERT_OID, cert_value: OctetString::new(certificate.clone()).map_err(invalid)? };
+    cert_bags.push(SafeBag {
+      bag_id: PKCS_12_CERT_BAG_OID,
+      bag_value: bag.to_der().map_err(invalid)?,
+      bag_attributes: (index == 0).then(|| attributes.clone()),
+    });
+  }
+  let data = |bags: Vec<SafeBag>| -> Result<ContentInfo> {
+    let contents = OctetString::new(bags.to_der().map_err(invalid)?).map_err(invalid)?;
+    Ok(ContentInfo { content_type: OID_DATA, content: Any::encode_from(&contents).map_err(invalid)? })
+  };
+  let auth_safe = vec![data(key_bags)?, data(cert_bags)?].to_der().map_err(invalid)?;
+
+  let mac_salt: [u8; 20] = random_bytes();
+  let mac_key = derive_key_utf8::<Sha256>(password, &mac_salt, Pkcs12KeyType::Mac, WRITE_ITERATIONS as i32, 32)
+    .map_err(|e| Error::Keystore(format!("生成 PKCS12 失败：{}", e)))?;
+  let mut hmac = SimpleHmac::<Sha256>::new_from_slice(&mac_key).map_err(|e| Error::Keystore(e.to_string()))?;
+  hmac.update(&auth_safe);
+  let pfx = Pfx {
+    version: Version::V3,
+    auth_safe: ContentInfo {
+      content_type: OID_DATA,
+      content: Any::encode_from(&OctetString::new(auth_safe).map_err(invalid)?).map_err(invalid)?,
+    },
+    mac_data: Some(MacData {
+      mac: DigestInfo {
+        algorithm: AlgorithmIdentifierOwned { oid: OID_SHA256, parameters: Some(Any::null()) },
+        digest: OctetString::new(hmac.finalize().into_bytes().to_vec()).map_err(invalid)?,
+      },
+      mac_salt: OctetString::new(mac_salt.to_vec()).map_err(invalid)?,
+      iterations: WRITE_ITERATIONS as i32,
+    }),
+  };
+  pfx.to_der().map_err(invalid)
+}
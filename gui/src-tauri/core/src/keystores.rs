@@ -0,0 +1,439 @@
+//! keystore 管理：导入已有的 JKS/PKCS12，创建带自签名证书的新 keystore，查看别名、证书指纹与有效期
+//!
+//! 导入与新建的 keystore 复制到配置目录的 `keystores/` 下并登记在 keystores.json 中，签名配置通过 id 引用，
+//! 不依赖原文件的位置。keystore 密码保存在本地密钥库中，keystores.json 只记录引用。
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  str::FromStr,
+  time::Duration,
+};
+
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use x509_cert::{
+  certificate::{TbsCertificate, Version},
+  der::{
+    asn1::{BitString, GeneralizedTime, OctetString, UtcTime},
+    Decode, Encode,
+  },
+  ext::Extension,
+  name::Name,
+  serial_number::SerialNumber,
+  spki::SubjectPublicKeyInfoOwned,
+  time::{Time, Validity},
+  Certificate,
+};
+
+use crate::{
+  config::{self, AppDirs, KeystoreRecord, SigningProfile},
+  keystore::{self, Keystore, PrivateKey},
+  now_millis, secrets,
+  signature::{self, CertificateInfo, Hash},
+  Error, Result,
+};
+
+/// 新建证书默认的有效期，Google Play 要求上传密钥的有效期到 2033 年以后
+const DEFAULT_VALIDITY_YEARS: u32 = 25;
+/// 2050-01-01 00:00 UTC，此前的时间按 RFC 5280 使用 UTCTime
+const UTC_TIME_LIMIT: u64 = 2_524_608_000;
+const OID_SUBJECT_KEY_IDENTIFIER: &str = "2.5.29.14";
+
+/// keystore 文件格式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeystoreType {
+  Jks,
+  #[default]
+  Pkcs12,
+}
+
+impl KeystoreType {
+  fn detect(data: &[u8]) -> Self {
+    if keystore::is_jks(data) {
+      Self::Jks
+    } else {
+      Self::Pkcs12
+    }
+  }
+
+  fn extension(self) -> &'static str {
+    match self {
+      Self::Jks => "jks",
+      Self::Pkcs12 => "p12",
+    }
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Jks => "JKS",
+      Self::Pkcs12 => "PKCS12",
+    }
+  }
+}
+
+/// 新建密钥的算法
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyAlgorithm {
+  Rsa,
+  Ec,
+}
+
+impl KeyAlgorithm {
+  /// RSA 默认 2048 位，EC 默认 P-256，与 keytool 相同
+  fn default_size(self) -> u32 {
+    match self {
+      Self::Rsa => 2048,
+      Self::Ec => 256,
+    }
+  }
+}
+
+/// 导入已有的 keystore
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportKeystoreRequest {
+  pub id: String,
+  pub path: String,
+  pub store_password: String,
+}
+
+/// 新建只有一个私钥条目的 keystore
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateKeystoreRequest {
+  pub id: String,
+  #[serde(default)]
+  pub store_type: KeystoreType,
+  pub store_password: String,
+  pub alias: String,
+  /// 仅用于 JKS，不填时与 keystore 密码相同；PKCS12 的密钥密码总是与 keystore 密码相同
+  pub key_password: Option<String>,
+  pub key_algorithm: KeyAlgorithm,
+  /// RSA 为位数（2048/3072/4096），EC 为曲线长度（256/384）
+  pub key_size: Option<u32>,
+  /// 证书主题，如 `CN=Example, O=Example Inc, C=CN`
+  pub subject: String,
+  /// 有效期（年），默认 25 年
+  pub validity_years: Option<u32>,
+}
+
+/// keystore 中的条目
+#[derive(Serialize, Clone, Debug)]
+pub struct KeystoreEntryInfo {
+  pub alias: String,
+  /// 私钥条目；否则为受信任的证书
+  pub key_entry: bool,
+  /// 证书链，第一个为条目自身的证书
+  pub certificates: Vec<CertificateInfo>,
+}
+
+/// 登记的 keystore 及其内容，不含密码
+#[derive(Serialize, Clone, Debug)]
+pub struct KeystoreInfo {
+  pub id: String,
+  pub path: String,
+  pub store_type: KeystoreType,
+  pub source: Option<String>,
+  pub created_at: u64,
+  pub entries: Vec<KeystoreEntryInfo>,
+  /// 证书已过期或即将过期等提示
+  pub warnings: Vec<String>,
+  /// 无法打开时的原因，如文件不存在或密码错误
+  pub error: Option<String>,
+}
+
+/// 列出登记的 keystore；无法打开的 keystore 在 `error` 中给出原因
+pub fn list(dirs: &AppDirs) -> Result<Vec<KeystoreInfo>> {
+  Ok(config::list_keystores(dirs)?.keystores.into_iter().map(|r| describe(dirs, r)).collect())
+}
+
+pub fn inspect(dirs: &AppDirs, id: &str) -> Result<KeystoreInfo> {
+  config::find_keystore(dirs, id).map(|r| describe(dirs, r))
+}
+
+/// 校验密码后把 keystore 复制到配置目录并登记
+pub fn import(dirs: &AppDirs, request: ImportKeystoreRequest) -> Result<KeystoreInfo> {
+  check_id(dirs, &request.id)?;
+  let source = Path::new(&request.path);
+  let data = fs::read(source).map_err(|e| Error::io(format!("读取 keystore 失败 {}", source.display()), e))?;
+  let parsed = Keystore::parse(&data, &request.store_password)?;
+  if !parsed.entries.iter().any(|e| e.has_key()) {
+    return Err(Error::Keystore("keystore 中没有私钥条目，无法用于签名".into()));
+  }
+  let store_type = KeystoreType::detect(&data);
+  let store_password = store_password(dirs, &request.id, request.store_password)?;
+  let path = save(dirs, &request.id, store_type, &data)?;
+  let record = KeystoreRecord {
+    id: request.id,
+    path: path.to_string_lossy().to_string(),
+    store_type,
+    store_password,
+    source: Some(source.to_string_lossy().to_string()),
+    created_at: now_millis(),
+  };
+  config::add_keystore(dirs, record.clone())?;
+  Ok(describe(dirs, record))
+}
+
+/// 生成私钥与自签名证书，写出新的 keystore 并登记
+pub fn create(dirs: &AppDirs, request: CreateKeystoreRequest) -> Result<KeystoreInfo> {
+  check_id(dirs, &request.id)?;
+  if request.alias.trim().is_empty() {
+    return Err(Error::InvalidInput("别名不能为空".into()));
+  }
+  if request.store_password.chars().count() < 6 {
+    return Err(Error::InvalidInput("keystore 密码至少需要 6 个字符".into()));
+  }
+  let size = request.key_size.unwrap_or_else(|| request.key_algorithm.default_size());
+  let key = PrivateKey::generate(request.key_algorithm, size)?;
+  let years = request.validity_years.unwrap_or(DEFAULT_VALIDITY_YEARS).max(1);
+  let certificate = self_signed_certificate(&key, &request.subject, years)?;
+  let created_at = now_millis();
+  let data = match request.store_type {
+    KeystoreType::Jks => {
+      let key_password = request.key_password.as_deref().filter(|p| !p.is_empty()).unwrap_or(&request.store_password);
+      keystore::write_jks(&request.alias, &key, &[certificate], &request.store_password, key_password, created_at)?
+    }
+    KeystoreType::Pkcs12 => keystore::write_pkcs12(&request.alias, &key, &[certificate], &request.store_password)?,
+  };
+  let store_password = store_password(dirs, &request.id, request.store_password)?;
+  let path = save(dirs, &request.id, request.store_type, &data)?;
+  let record = KeystoreRecord {
+    id: request.id,
+    path: path.to_string_lossy().to_string(),
+    store_type: request.store_type,
+    store_password,
+    source: None,
+    created_at,
+  };
+  config::add_keystore(dirs, record.clone())?;
+  Ok(describe(dirs, record))
+}
+
+/// 取消登记并删除配置目录中的 keystore 文件；仍被签名配置引用时拒绝删除
+pub fn delete(dirs: &AppDirs, id: &str) -> Result<()> {
+  let users: Vec<String> = config::list_signing_profiles(dirs)?
+    .profiles
+    .into_iter()
+    .filter(|p| p.keystore == id)
+    .map(|p| p.name)
+    .collect();
+  if !users.is_empty() {
+    return Err(Error::InvalidInput(format!("keystore {} 正在被签名配置使用：{}", id, users.join(", "))));
+  }
+  let record = config::delete_keystore(dirs, id)?;
+  let path = Path::new(&record.path);
+  if path.starts_with(config::keystore_dir(dirs)) {
+    let _ = fs::remove_file(path);
+  }
+  secrets::remove(dirs, Some(&record.store_password))
+}
+
+/// 签名配置使用的 keystore 文件与解密后的密码
+pub(crate) fn resolve(dirs: &AppDirs, profile: &SigningProfile) -> Result<(PathBuf, String)> {
+  let (path, password) = locate(dirs, profile)?;
+  Ok((path, secrets::reveal(dirs, &password)?))
+}
+
+/// 签名配置使用的 keystore 文件与密码（可能是密钥库引用）：`keystore` 为登记的 id，或者直接是文件路径
+pub(crate) fn locate(dirs: &AppDirs, profile: &SigningProfile) -> Result<(PathBuf, String)> {
+  let password = profile.store_password.clone().filter(|p| !p.is_empty());
+  match config::find_keystore(dirs, &profile.keystore) {
+    Ok(record) => Ok((PathBuf::from(record.path), password.unwrap_or(record.store_password))),
+    Err(Error::KeystoreNotFound(_)) if Path::new(&profile.keystore).is_file() => {
+      let password =
+        password.ok_or_else(|| Error::InvalidInput(format!("签名配置 {} 未填写 keystore 密码", profile.name)))?;
+      Ok((PathBuf::from(&profile.keystore), password))
+    }
+    Err(Error::KeystoreNotFound(_)) => {
+      Err(Error::InvalidInput(format!("keystore 未登记且文件不存在：{}", profile.keystore)))
+    }
+    Err(e) => Err(e),
+  }
+}
+
+/// keystore 密码存入密钥库，返回引用
+fn store_password(dirs: &AppDirs, id: &str, password: String) -> Result<String> {
+  let hint = format!("keystore/{}/store_password", id);
+  Ok(secrets::store(dirs, &hint, None, Some(password))?.unwrap_or_default())
+}
+
+/// id 用作文件名，只允许字母、数字、`-`、`_` 与 `.`
+fn check_id(dirs: &AppDirs, id: &str) -> Result<()> {
+  let valid = !id.is_empty()
+    && !id.starts_with('.')
+    && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+  if !valid {
+    return Err(Error::InvalidInput(format!("keystore id 只能包含字母、数字、-、_ 与 .：{}", id)));
+  }
+  if config::find_keystore(dirs, id).is_ok() {
+    return Err(Error::KeystoreExists(id.to_string()));
+  }
+  Ok(())
+}
+
+/// 写入配置目录的 keystores/，类 Unix 系统上只允许当前用户读写
+fn save(dirs: &AppDirs, id: &str, store_type: KeystoreType, data: &[u8]) -> Result<PathBuf> {
+  let dir = config::keystore_dir(dirs);
+  fs::create_dir_all(&dir).map_err(|e| Error::io(format!("创建目录失败 {}", dir.display()), e))?;
+  let path = dir.join(format!("{}.{}", id, store_type.extension()));
+  if path.exists() {
+    return Err(Error::InvalidInput(format!("文件已存在：{}", path.display())));
+  }
+  fs::write(&path, data).map_err(|e| Error::io(format!("写入 keystore 失败 {}", path.display()), e))?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+  }
+  Ok(path)
+}
+
+fn describe(dirs: &AppDirs, record: KeystoreRecord) -> KeystoreInfo {
+  let mut info = KeystoreInfo {
+    id: record.id,
+    path: record.path,
+    store_type: record.store_type,
+    source: record.source,
+    created_at: record.created_at,
+    entries: Vec::new(),
+    warnings: Vec::new(),
+    error: None,
+  };
+  let keystore = match secrets::reveal(dirs, &record.store_password).and_then(|p| Keystore::open(Path::new(&info.path), &p)) {
+    Ok(keystore) => keystore,
+    Err(e) => {
+      info.error = Some(e.to_string());
+      return info;
+    }
+  };
+  let now = now_millis();
+  for entry in keystore.entries {
+    let certificates: Vec<CertificateInfo> =
+      entry.certificates.iter().filter_map(|der| signature::certificate_info(der).ok()).collect();
+    // 只提示用于签名的证书
+    if entry.has_key() {
+      if let Some(warning) = certificates.first().and_then(|c| signature::validity_warning(c, now)) {
+        info.warnings.push(format!("{}：{}", entry.alias, warning));
+      }
+    }
+    info.entries.push(KeystoreEntryInfo { alias: entry.alias.clone(), key_entry: entry.has_key(), certificates });
+  }
+  info
+}
+
+/// 生成自签名证书（X.509 v3），带主题密钥标识符扩展
+fn self_signed_certificate(key: &PrivateKey, subject: &str, years: u32) -> Result<Vec<u8>> {
+  let invalid = |e: x509_cert::der::Error| Error::Keystore(format!("生成证书失败：{}", e));
+  // 允许逗号后带空格的写法，如 keytool 的 `CN=Name, O=Org`
+  let normalized = subject.split(',').map(str::trim).filter(|s| !s.is_empty()).collect::<Vec<_>>().join(",");
+  let name = Name::from_str(&normalized)
+    .ok()
+    .filter(|name| !name.0.is_empty())
+    .ok_or_else(|| Error::InvalidInput(format!("证书主题格式错误：{}，应为 CN=名称, O=组织, C=CN 的形式", subject)))?;
+
+  let public_key = key.public_key()?;
+  let spki = SubjectPublicKeyInfoOwned::from_der(&public_key).map_err(invalid)?;
+  let key_id = OctetString::new(Hash::Sha1.digest(spki.subject_public_key.raw_bytes())).map_err(invalid)?;
+  let extension = Extension {
+    extn_id: x509_cert::der::asn1::ObjectIdentifier::new_unwrap(OID_SUBJECT_KEY_IDENTIFIER),
+    critical: false,
+    extn_value: OctetString::new(key_id.to_der().map_err(invalid)?).map_err(invalid)?,
+  };
+  // 序列号为 8 字节正整数
+  let mut serial = [0u8; 8];
+  OsRng.fill_bytes(&mut serial);
+  serial[0] = (serial[0] & 0x7f) | 0x01;
+
+  let now = now_millis() / 1000;
+  let not_after = now + u64::from(years) * 365 * 86_400;
+  let (hash, algorithm) = key.certificate_algorithm();
+  let tbs = TbsCertificate {
+    version: Version::V3,
+    serial_number: SerialNumber::new(&serial).map_err(invalid)?,
+    signature: algorithm.clone(),
+    issuer: name.clone(),
+    validity: Validity { not_before: time(now).map_err(invalid)?, not_after: time(not_after).map_err(invalid)? },
+    subject: name,
+    subject_public_key_info: spki,
+    issuer_unique_id: None,
+    subject_unique_id: None,
+    extensions: Some(vec![extension]),
+  };
+  let signature = key.sign(hash, &tbs.to_der().map_err(invalid)?).map_err(Error::Keystore)?;
+  let certificate = Certificate {
+    tbs_certificate: tbs,
+    signature_algorithm: algorithm,
+    signature: BitString::from_bytes(&signature).map_err(invalid)?,
+  };
+  certificate.to_der().map_err(invalid)
+}
+
+fn time(seconds: u64) -> x509_cert::der::Result<Time> {
+  let duration = Duration::from_secs(seconds);
+  if seconds < UTC_TIME_LIMIT {
+    Ok(Time::UtcTime(UtcTime::from_unix_duration(duration)?))
+  } else {
+    Ok(Time::GeneralTime(GeneralizedTime::from_unix_duration(duration)?))
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::keystore::SigningKey;
+
+  /// 测试用的签名密钥，证书为自签名
+  pub(crate) fn signing_key(algorithm: KeyAlgorithm) -> SigningKey {
+    let key = PrivateKey::generate(algorithm, algorithm.default_size()).unwrap();
+    let certificate = self_signed_certificate(&key, "CN=Test, O=ABT, C=CN", 1).unwrap();
+    SigningKey { alias: "test".into(), key, certificates: vec![certificate] }
+  }
+
+  fn assert_same_key(expected: &SigningKey, actual: &SigningKey) {
+    assert_eq!(actual.certificates, expected.certificates);
+    assert_eq!(actual.key.public_key().unwrap(), expected.key.public_key().unwrap());
+  }
+
+  #[test]
+  fn jks_round_trip() {
+    let signing = signing_key(KeyAlgorithm::Ec);
+    let data = keystore::write_jks("Upload", &signing.key, &signing.certificates, "store-pass", "key-pass", 0).unwrap();
+    assert!(keystore::is_jks(&data));
+
+    let store = Keystore::parse(&data, "store-pass").unwrap();
+    let key = store.signing_key(Some("UPLOAD"), "key-pass").unwrap();
+    assert_eq!(key.alias, "upload");
+    assert_same_key(&signing, &key);
+
+    assert!(Keystore::parse(&data, "wrong").is_err());
+    assert!(store.signing_key(None, "wrong").is_err());
+  }
+
+  #[test]
+  fn pkcs12_round_trip() {
+    let signing = signing_key(KeyAlgorithm::Ec);
+    let data = keystore::write_pkcs12("upload", &signing.key, &signing.certificates, "store-pass").unwrap();
+    assert!(!keystore::is_jks(&data));
+
+    let store = Keystore::parse(&data, "store-pass").unwrap();
+    let key = store.signing_key(None, "store-pass").unwrap();
+    assert_eq!(key.alias, "upload");
+    assert_same_key(&signing, &key);
+
+    assert!(Keystore::parse(&data, "wrong").is_err());
+  }
+
+  #[test]
+  fn self_signed_certificate_subject() {
+    let key = PrivateKey::generate(KeyAlgorithm::Ec, 256).unwrap();
+    let der = self_signed_certificate(&key, "CN=Demo, O=ABT", 25).unwrap();
+    let certificate = Certificate::from_der(&der).unwrap();
+    assert_eq!(certificate.tbs_certificate.subject.to_string(), "CN=Demo,O=ABT");
+    assert_eq!(certificate.tbs_certificate.subject, certificate.tbs_certificate.issuer);
+    assert!(self_signed_certificate(&key, " , ", 1).is_err());
+  }
+}
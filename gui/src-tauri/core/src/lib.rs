@@ -0,0 +1,81 @@
+//! Android Build Tool 核心库
+//!
+//! 环境检测（含工程级的 AGP/Gradle/JDK 兼容性检查）、工程配置、模块发现、构建（版本号、git 信息）、构建历史、产物解析、keystore 管理、本地加密密钥库、签名、渠道包与发布（含根据提交生成更新描述），不依赖 Tauri。
+//! GUI 与命令行 `abt` 都基于本库实现。
+
+mod aab;
+mod arsc;
+mod axml;
+pub mod build;
+pub mod catalog;
+pub mod channel;
+pub mod config;
+pub mod discovery;
+pub mod env;
+pub mod git;
+mod error;
+pub mod gradle_options;
+pub mod history;
+pub mod inspect;
+mod keystore;
+pub mod keystores;
+mod pb;
+pub mod project_env;
+pub mod properties;
+pub mod publish;
+pub mod release_notes;
+pub mod sdk;
+pub mod secrets;
+pub mod signature;
+pub mod signer;
+mod signing_block;
+pub mod variant;
+pub mod version;
+mod zipalign;
+
+pub use error::{Error, ErrorCategory, ErrorInfo, Result};
+
+use std::{
+  process::Command as StdCommand,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+pub(crate) fn run_command(cmd: &str, args: &[&str]) -> (bool, String) {
+  match StdCommand::new(cmd).args(args).output() {
+    Ok(out) => {
+      let mut data = Vec::new();
+      data.extend(out.stdout);
+      data.extend(out.stderr);
+      (out.status.success(), String::from_utf8_lossy(&data).trim().to_string())
+    }
+    Err(err) => (false, err.to_string()),
+  }
+}
+
+/// 当前 Unix 时间戳（毫秒）
+pub(crate) fn now_millis() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+/// Unix 纪元以来的天数转为公历 (年, 月, 日)
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  // 见 http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z - era * 146_097;
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  (yoe + era * 400 + i64::from(month <= 2), month as u32, day as u32)
+}
+
+/// 毫秒时间戳格式化为 UTC 日期 `yyyy-MM-dd`
+pub fn format_date(millis: u64) -> String {
+  let (year, month, day) = civil_from_days((millis / 86_400_000) as i64);
+  format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,108 @@
+//! 最小的 protobuf 解码，用于读取 AAB 中 aapt2 生成的 proto 格式清单与资源表
+
+use crate::{axml::invalid, Result};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Field<'a> {
+  Varint(u64),
+  Fixed64(u64),
+  Bytes(&'a [u8]),
+  Fixed32(u32),
+}
+
+/// 一条 protobuf 消息，按字段号读取
+#[derive(Clone, Copy)]
+pub(crate) struct Message<'a> {
+  data: &'a [u8],
+  what: &'static str,
+}
+
+impl<'a> Message<'a> {
+  pub fn new(data: &'a [u8], what: &'static str) -> Self {
+    Self { data, what }
+  }
+
+  fn varint(&self, pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+      let byte = *self.data.get(*pos).ok_or_else(|| invalid(self.what, "varint 不完整"))?;
+      *pos += 1;
+      value |= u64::from(byte & 0x7f) << shift;
+      if byte & 0x80 == 0 {
+        return Ok(value);
+      }
+    }
+    Err(invalid(self.what, "varint 过长"))
+  }
+
+  fn take(&self, pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = pos.checked_add(len).filter(|end| *end <= self.data.len());
+    let end = end.ok_or_else(|| invalid(self.what, format!("偏移 {} 处的字段超出范围", pos)))?;
+    let bytes = &self.data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+  }
+
+  /// 全部字段，按出现顺序
+  pub fn fields(&self) -> Result<Vec<(u32, Field<'a>)>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < self.data.len() {
+      let key = self.varint(&mut pos)?;
+      let number = (key >> 3) as u32;
+      let field = match key & 0x7 {
+        0 => Field::Varint(self.varint(&mut pos)?),
+        1 => {
+          let b = self.take(&mut pos, 8)?;
+          Field::Fixed64(u64::from_le_bytes(b.try_into().unwrap_or_default()))
+        }
+        2 => {
+          let len = self.varint(&mut pos)? as usize;
+          Field::Bytes(self.take(&mut pos, len)?)
+        }
+        5 => {
+          let b = self.take(&mut pos, 4)?;
+          Field::Fixed32(u32::from_le_bytes(b.try_into().unwrap_or_default()))
+        }
+        wire => return Err(invalid(self.what, format!("不支持的 wire type {}", wire))),
+      };
+      fields.push((number, field));
+    }
+    Ok(fields)
+  }
+
+  /// 所有字段号为 `number` 的子消息
+  pub fn messages(&self, number: u32) -> Result<Vec<Message<'a>>> {
+    Ok(
+      self
+        .fields()?
+        .into_iter()
+        .filter_map(|(n, f)| match (n == number, f) {
+          (true, Field::Bytes(data)) => Some(Message::new(data, self.what)),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  pub fn message(&self, number: u32) -> Result<Option<Message<'a>>> {
+    Ok(self.messages(number)?.into_iter().next())
+  }
+
+  pub fn string(&self, number: u32) -> Result<Option<String>> {
+    Ok(self.message(number)?.map(|m| String::from_utf8_lossy(m.data).into_owned()))
+  }
+
+  pub fn uint(&self, number: u32) -> Result<Option<u64>> {
+    Ok(self.fields()?.into_iter().find_map(|(n, f)| match (n == number, f) {
+      (true, Field::Varint(v)) => Some(v),
+      (true, Field::Fixed32(v)) => Some(u64::from(v)),
+      (true, Field::Fixed64(v)) => Some(v),
+      _ => None,
+    }))
+  }
+
+  pub fn has(&self, number: u32) -> Result<bool> {
+    Ok(self.fields()?.iter().any(|(n, _)| *n == number))
+  }
+}
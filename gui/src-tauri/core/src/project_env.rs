@@ -0,0 +1,407 @@
+//! 工程级环境检查：对照工程使用的 Gradle、AGP 与 compileSdk，检查本机 JDK 与 SDK 是否满足
+//!
+//! - Gradle 版本：`gradle/wrapper/gradle-wrapper.properties` 的 `distributionUrl`
+//! - AGP 版本：版本目录、`settings.gradle(.kts)` 的 pluginManagement、根 `build.gradle(.kts)`
+//! - compileSdk：各 application 模块的构建脚本（支持版本目录与 ext 变量引用）
+
+use regex::Regex;
+use serde::Serialize;
+use std::{
+  cmp::Ordering,
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::OnceLock,
+};
+
+use crate::{
+  catalog::VersionCatalog,
+  discovery::{self, read_build_script},
+  env::{self, EnvCheck},
+  properties,
+  sdk::{self, compare_versions},
+};
+
+/// AGP 版本 -> 所需的最低 Gradle 版本
+/// 参考 https://developer.android.com/build/releases/gradle-plugin#updating-gradle
+const AGP_MIN_GRADLE: &[(&str, &str)] = &[
+  ("8.13", "8.13"),
+  ("8.12", "8.13"),
+  ("8.11", "8.13"),
+  ("8.10", "8.11.1"),
+  ("8.9", "8.11.1"),
+  ("8.8", "8.10.2"),
+  ("8.7", "8.9"),
+  ("8.6", "8.7"),
+  ("8.5", "8.7"),
+  ("8.4", "8.6"),
+  ("8.3", "8.4"),
+  ("8.2", "8.2"),
+  ("8.1", "8.0"),
+  ("8.0", "8.0"),
+  ("7.4", "7.5"),
+  ("7.3", "7.4"),
+  ("7.2", "7.3.3"),
+  ("7.1", "7.2"),
+  ("7.0", "7.0"),
+  ("4.2", "6.7.1"),
+  ("4.1", "6.5"),
+  ("4.0", "6.1.1"),
+  ("3.6", "5.6.4"),
+  ("3.5", "5.4.1"),
+  ("3.4", "5.1.1"),
+  ("3.3", "4.10.1"),
+];
+
+/// Gradle 版本 -> 可运行的最高 JDK 版本
+/// 参考 https://docs.gradle.org/current/userguide/compatibility.html
+const GRADLE_MAX_JDK: &[(&str, u32)] = &[
+  ("8.14", 24),
+  ("8.10", 23),
+  ("8.8", 22),
+  ("8.5", 21),
+  ("8.3", 20),
+  ("7.6", 19),
+  ("7.5", 18),
+  ("7.3", 17),
+  ("7.0", 16),
+  ("6.7", 15),
+  ("6.3", 14),
+  ("6.0", 13),
+  ("5.4", 12),
+  ("5.0", 11),
+  ("4.7", 10),
+  ("4.3", 9),
+  ("2.0", 8),
+];
+
+/// AGP 所需的最低 JDK 版本
+fn agp_min_jdk(agp: &str) -> u32 {
+  if compare_versions(agp, "8.0") != Ordering::Less {
+    17
+  } else if compare_versions(agp, "7.0") != Ordering::Less {
+    11
+  } else {
+    8
+  }
+}
+
+fn agp_min_gradle(agp: &str) -> Option<&'static str> {
+  AGP_MIN_GRADLE
+    .iter()
+    .find(|(v, _)| compare_versions(&major_minor(agp), v) != Ordering::Less)
+    .map(|(_, g)| *g)
+}
+
+fn gradle_max_jdk(gradle: &str) -> Option<u32> {
+  GRADLE_MAX_JDK
+    .iter()
+    .find(|(v, _)| compare_versions(gradle, v) != Ordering::Less)
+    .map(|(_, jdk)| *jdk)
+}
+
+/// `8.3.2` -> `8.3`
+fn major_minor(version: &str) -> String {
+  version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// 模块的 compileSdk
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleCompileSdk {
+  pub module: String,
+  /// 解析出的 API Level，无法解析时为 None
+  pub api_level: Option<u32>,
+  /// 构建脚本中的原始写法
+  pub declared: Option<String>,
+}
+
+/// Gradle 使用的 JDK
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GradleJdk {
+  /// JDK 主目录或 java 可执行文件
+  pub path: PathBuf,
+  /// 来源：工程指定、`org.gradle.java.home`、`JAVA_HOME` 或 `PATH`
+  pub source: String,
+  pub major: Option<u32>,
+}
+
+/// 工程对构建环境的要求
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRequirements {
+  pub gradle_version: Option<String>,
+  pub agp_version: Option<String>,
+  pub compile_sdks: Vec<ModuleCompileSdk>,
+}
+
+/// 从 wrapper 配置读取 Gradle 版本
+pub fn gradle_wrapper_version(project_path: &Path) -> Option<String> {
+  let props = properties::read(&project_path.join("gradle/wrapper/gradle-wrapper.properties"))?;
+  let url = props.get("distributionUrl")?;
+  // .../gradle-8.4-bin.zip、gradle-8.4-all.zip、gradle-8.5-rc-1-bin.zip
+  let file = url.rsplit('/').next()?;
+  let version = file.strip_prefix("gradle-")?;
+  let version = version
+    .strip_suffix("-bin.zip")
+    .or_else(|| version.strip_suffix("-all.zip"))
+    .or_else(|| version.strip_suffix(".zip"))?;
+  Some(version.to_string())
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+  cell.get_or_init(|| Regex::new(pattern).expect("无效的正则表达式"))
+}
+
+/// 读取 AGP 版本
+pub fn agp_version(project_path: &Path) -> Option<String> {
+  if let Some(catalog) = VersionCatalog::load(project_path) {
+    let found = ["com.android.application", "com.android.library"]
+      .iter()
+      .find_map(|id| catalog.plugin_version(id))
+      .or_else(|| catalog.library_version("com.android.tools.build:gradle"));
+    if found.is_some() {
+      return found;
+    }
+  }
+
+  static PLUGIN: OnceLock<Regex> = OnceLock::new();
+  static CLASSPATH: OnceLock<Regex> = OnceLock::new();
+  let plugin = regex(
+    &PLUGIN,
+    r#"id\s*\(?\s*["']com\.android\.(?:application|library)["']\s*\)?\s*version\s*\(?\s*["']([^"']+)["']"#,
+  );
+  let classpath = regex(&CLASSPATH, r#"com\.android\.tools\.build:gradle:([^"'\s)]+)"#);
+
+  let gradle_props = properties::read(&project_path.join("gradle.properties")).unwrap_or_default();
+  for script in [read_settings_script(project_path), read_build_script(project_path)].into_iter().flatten() {
+    if let Some(c) = plugin.captures(&script).or_else(|| classpath.captures(&script)) {
+      let value = c[1].to_string();
+      return Some(resolve_reference(&value, &script, &gradle_props).unwrap_or(value));
+    }
+  }
+  None
+}
+
+fn read_settings_script(project_path: &Path) -> Option<String> {
+  ["settings.gradle.kts", "settings.gradle"]
+    .iter()
+    .map(|name| project_path.join(name))
+    .find(|p| p.exists())
+    .and_then(|p| std::fs::read_to_string(p).ok())
+    .map(|s| discovery::strip_comments(&s))
+}
+
+/// 解析 `$agp_version` / `${agpVersion}` / `rootProject.ext.compileSdkVersion` 等变量引用：
+/// 在脚本的 ext 赋值与 gradle.properties 中查找同名变量
+fn resolve_reference(value: &str, script: &str, gradle_props: &HashMap<String, String>) -> Option<String> {
+  let name = value
+    .trim_start_matches('$')
+    .trim_start_matches('{')
+    .trim_end_matches('}')
+    .rsplit('.')
+    .next()?
+    .trim_end_matches("()");
+  if name.is_empty() || value.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+    return None;
+  }
+  if let Some(v) = gradle_props.get(name) {
+    return Some(v.clone());
+  }
+  let assignment = Regex::new(&format!(r#"\b{}\s*[=:]\s*["']?([\w.\-]+)["']?"#, regex::escape(name))).ok()?;
+  assignment.captures(script).map(|c| c[1].to_string())
+}
+
+/// 读取模块的 compileSdk
+pub fn compile_sdk(project_path: &Path, module: &str, catalog: Option<&VersionCatalog>) -> ModuleCompileSdk {
+  static COMPILE_SDK: OnceLock<Regex> = OnceLock::new();
+  static CATALOG_REF: OnceLock<Regex> = OnceLock::new();
+  let pattern = regex(&COMPILE_SDK, r#"\bcompileSdk(?:Version)?\b[\s=(]*([^\n;]+)"#);
+  let catalog_ref = regex(&CATALOG_REF, r#"libs\.versions\.([\w.]+?)\.get\(\)"#);
+
+  let mut result = ModuleCompileSdk { module: module.to_string(), api_level: None, declared: None };
+  let Some(script) = read_build_script(&discovery::module_dir(project_path, module)) else {
+    return result;
+  };
+  let Some(c) = pattern.captures(&script) else {
+    return result;
+  };
+  // `compileSdkVersion(34)` 去掉多余的右括号，`.toInt()` 等转换不影响取值
+  let first = c[1].split_whitespace().next().unwrap_or_default();
+  let mut declared = first.trim_end_matches(".toInt()").to_string();
+  while declared.ends_with(')') && declared.matches(')').count() > declared.matches('(').count() {
+    declared.pop();
+  }
+  let declared = declared.trim().trim_matches(|ch| ch == '"' || ch == '\'').to_string();
+  result.declared = Some(declared.clone());
+
+  let api_level = if let Some(level) = parse_api_level(&declared) {
+    Some(level)
+  } else if let Some(name) = catalog_ref.captures(&declared).map(|c| c[1].to_string()) {
+    catalog.and_then(|c| c.version(&name)).and_then(|v| parse_api_level(&v))
+  } else {
+    // ext 变量：先找模块脚本，再找根脚本
+    let gradle_props = properties::read(&project_path.join("gradle.properties")).unwrap_or_default();
+    let root_script = read_build_script(project_path).unwrap_or_default();
+    resolve_reference(&declared, &script, &gradle_props)
+      .or_else(|| resolve_reference(&declared, &root_script, &gradle_props))
+      .and_then(|v| parse_api_level(&v))
+  };
+  result.api_level = api_level;
+  result
+}
+
+/// `34`、`android-34`
+fn parse_api_level(s: &str) -> Option<u32> {
+  s.trim().trim_start_matches("android-").parse().ok()
+}
+
+/// 读取工程对构建环境的要求
+pub fn requirements(project_path: &Path) -> ProjectRequirements {
+  let catalog = VersionCatalog::load(project_path);
+  let app_modules: Vec<String> = discovery::discover_static(project_path, 0)
+    .map(|s| s.app_modules.into_iter().map(|m| m.module).collect())
+    .unwrap_or_default();
+  ProjectRequirements {
+    gradle_version: gradle_wrapper_version(project_path),
+    agp_version: agp_version(project_path),
+    compile_sdks: app_modules
+      .iter()
+      .map(|m| compile_sdk(project_path, m, catalog.as_ref()))
+      .collect(),
+  }
+}
+
+/// Gradle 实际使用的 JDK：工程指定 > `org.gradle.java.home` > `JAVA_HOME` > `PATH`
+pub fn gradle_jdk(project_path: &Path, pinned: Option<&Path>) -> Option<GradleJdk> {
+  if let Some(home) = pinned {
+    let major = env::java_major_version(&env::java_executable(home));
+    return Some(GradleJdk { path: home.to_path_buf(), source: "工程指定".into(), major });
+  }
+  let user_props = dirs::home_dir().and_then(|h| properties::read(&h.join(".gradle/gradle.properties")));
+  let project_props = properties::read(&project_path.join("gradle.properties"));
+  // 用户级 gradle.properties 优先于工程级
+  let configured = [user_props, project_props]
+    .into_iter()
+    .flatten()
+    .find_map(|p| p.get("org.gradle.java.home").cloned());
+  if let Some(home) = configured {
+    let home = PathBuf::from(home);
+    let major = env::java_major_version(&env::java_executable(&home));
+    return Some(GradleJdk { path: home, source: "org.gradle.java.home".into(), major });
+  }
+  if let Some(home) = env::java_home_from_env() {
+    let major = env::java_major_version(&env::java_executable(&home));
+    return Some(GradleJdk { path: home, source: "JAVA_HOME".into(), major });
+  }
+  let java = env::find_in_path(if cfg!(windows) { "java.exe" } else { "java" })?;
+  let major = env::java_major_version(&java);
+  Some(GradleJdk { path: java, source: "PATH".into(), major })
+}
+
+fn check(tool: &str, ok: bool, message: String, detail: Option<String>) -> EnvCheck {
+  EnvCheck { tool: tool.into(), ok, message, detail, applicable: true, items: vec![] }
+}
+
+/// 工程级检查项，`java_home` 为工程指定的构建 JDK
+pub fn checks(project_path: &Path, java_home: Option<&Path>) -> Vec<EnvCheck> {
+  let req = requirements(project_path);
+  let jdk = gradle_jdk(project_path, java_home);
+  let jdk_major = jdk.as_ref().and_then(|j| j.major);
+  let mut checks = Vec::new();
+
+  checks.push(match &jdk {
+    Some(j) => check(
+      "JDK（Gradle 使用）",
+      j.major.is_some(),
+      match j.major {
+        Some(m) => format!("JDK {}（来源 {}）", m, j.source),
+        None => format!("无法识别 JDK 版本（来源 {}）", j.source),
+      },
+      Some(j.path.display().to_string()),
+    ),
+    None => check("JDK（Gradle 使用）", false, "未找到 Gradle 可用的 JDK".into(), None),
+  });
+
+  // Gradle wrapper：AGP 要求的最低版本，以及可运行的最高 JDK
+  match &req.gradle_version {
+    Some(gradle) => {
+      let mut problems = Vec::new();
+      if let Some(min) = req.agp_version.as_deref().and_then(agp_min_gradle) {
+        if compare_versions(gradle, min) == Ordering::Less {
+          problems.push(format!(
+            "AGP {} 需要 Gradle {}+，当前 wrapper 为 {}",
+            req.agp_version.as_deref().unwrap_or_default(),
+            min,
+            gradle
+          ));
+        }
+      }
+      if let (Some(max), Some(found)) = (gradle_max_jdk(gradle), jdk_major) {
+        if found > max {
+          problems.push(format!("Gradle {} 最高支持 JDK {}，当前为 {}", gradle, max, found));
+        }
+      }
+      checks.push(check(
+        "Gradle Wrapper",
+        problems.is_empty(),
+        if problems.is_empty() { format!("Gradle {}", gradle) } else { problems.join("；") },
+        Some(format!("Gradle {}", gradle)),
+      ));
+    }
+    None => checks.push(check(
+      "Gradle Wrapper",
+      false,
+      "未找到 gradle/wrapper/gradle-wrapper.properties 或无法解析 distributionUrl".into(),
+      None,
+    )),
+  }
+
+  // AGP：所需的最低 JDK
+  match &req.agp_version {
+    Some(agp) => {
+      let min_jdk = agp_min_jdk(agp);
+      let (ok, message) = match jdk_major {
+        Some(found) if found < min_jdk => (
+          false,
+          format!("AGP {} 需要 JDK {}，当前为 {}", major_minor(agp), min_jdk, found),
+        ),
+        Some(_) => (true, format!("AGP {}（需要 JDK {}+）", agp, min_jdk)),
+        None => (false, format!("AGP {} 需要 JDK {}，未检测到 JDK 版本", major_minor(agp), min_jdk)),
+      };
+      checks.push(check("Android Gradle Plugin", ok, message, Some(format!("AGP {}", agp))));
+    }
+    None => checks.push(check(
+      "Android Gradle Plugin",
+      false,
+      "未能从版本目录或构建脚本中识别 AGP 版本".into(),
+      None,
+    )),
+  }
+
+  // compileSdk：对应的 SDK Platform 是否已安装
+  let sdk = sdk::detect(Some(project_path));
+  for m in &req.compile_sdks {
+    let tool = format!("compileSdk（{}）", m.module);
+    let item = match (m.api_level, &sdk) {
+      (None, _) => check(
+        &tool,
+        false,
+        if m.declared.is_some() { "无法识别 compileSdk 的取值".into() } else { "构建脚本中未找到 compileSdk".into() },
+        m.declared.as_ref().map(|d| format!("构建脚本中的写法: {}", d)),
+      ),
+      (Some(level), None) => check(&tool, false, format!("compileSdk {}，未找到 Android SDK", level), None),
+      (Some(level), Some(sdk)) if sdk.has_platform(level) => {
+        check(&tool, true, format!("compileSdk {}，已安装 android-{}", level, level), None)
+      }
+      (Some(level), Some(sdk)) => check(
+        &tool,
+        false,
+        format!("compileSdk {} 对应的 SDK Platform 未安装（已安装：{}）", level, sdk.platforms.join(", ")),
+        Some(format!("sdkmanager \"platforms;android-{}\"", level)),
+      ),
+    };
+    checks.push(item);
+  }
+  checks
+}
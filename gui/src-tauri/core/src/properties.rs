@@ -0,0 +1,90 @@
+//! Java `.properties` 文件读取（local.properties、gradle.properties、gradle-wrapper.properties）
+
+use std::{collections::HashMap, fs, path::Path};
+
+/// 解析 `.properties` 内容，支持 `=`/`:` 分隔、`#`/`!` 注释、行尾 `\` 续行与常见转义
+pub fn parse(content: &str) -> HashMap<String, String> {
+  let mut map = HashMap::new();
+  let mut logical = String::new();
+  for raw in content.lines() {
+    let line = raw.trim_start();
+    if logical.is_empty() && (line.is_empty() || line.starts_with('#') || line.starts_with('!')) {
+      continue;
+    }
+    // 奇数个结尾反斜杠表示续行
+    let trailing = line.chars().rev().take_while(|c| *c == '\\').count();
+    if trailing % 2 == 1 {
+      logical.push_str(&line[..line.len() - 1]);
+      continue;
+    }
+    logical.push_str(line);
+    if let Some((key, value)) = split_entry(&logical) {
+      map.insert(unescape(key.trim_end()), unescape(value.trim_start()));
+    }
+    logical.clear();
+  }
+  map
+}
+
+/// 读取 `.properties` 文件，文件不存在或无法读取时返回 None
+pub fn read(path: &Path) -> Option<HashMap<String, String>> {
+  fs::read_to_string(path).ok().map(|c| parse(&c))
+}
+
+/// 修改已有键的值并返回新内容（保留其他行与分隔符格式），键不存在时返回 None。
+/// 仅用于单行的简单值，如 `VERSION_CODE=12`
+pub fn set_value(content: &str, key: &str, value: &str) -> Option<String> {
+  let mut found = false;
+  let lines: Vec<String> = content
+    .split('\n')
+    .map(|raw| {
+      let line = raw.trim_start();
+      if found || line.starts_with('#') || line.starts_with('!') {
+        return raw.to_string();
+      }
+      match split_entry(line) {
+        Some((k, v)) if unescape(k.trim_end()) == key => {
+          found = true;
+          // 保留缩进、键与分隔符（含分隔符后的空白）以及行尾的 \r
+          let prefix_len = raw.len() - line.len() + (line.len() - v.len());
+          let separator_ws = v.len() - v.trim_start().len();
+          let cr = if raw.ends_with('\r') { "\r" } else { "" };
+          format!("{}{}{}", &raw[..prefix_len + separator_ws], value, cr)
+        }
+        _ => raw.to_string(),
+      }
+    })
+    .collect();
+  found.then(|| lines.join("\n"))
+}
+
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+  let mut escaped = false;
+  for (i, c) in line.char_indices() {
+    match c {
+      '\\' if !escaped => escaped = true,
+      '=' | ':' if !escaped => return Some((&line[..i], &line[i + 1..])),
+      _ => escaped = false,
+    }
+  }
+  (!line.trim().is_empty()).then_some((line, ""))
+}
+
+fn unescape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n') => out.push('\n'),
+      Some('t') => out.push('\t'),
+      Some('r') => out.push('\r'),
+      Some(other) => out.push(other),
+      None => {}
+    }
+  }
+  out
+}
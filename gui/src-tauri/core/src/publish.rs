@@ -0,0 +1,575 @@
+//! 发布到蒲公英（Pgyer）与 fir.im
+//!
+//! 凭据可以是密钥库引用（`secret:<名称>`），发布前解密。
+
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::{
+  config::{self, AppDirs},
+  history::{self, PublishedRecord},
+  inspect::{self, ArtifactInfo},
+  now_millis, release_notes, run_command, secrets, Error, Result,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PublishConfig {
+  pub platform: String, // "pgyer" 或 "fir"
+  pub api_key: Option<String>, // pgyer 使用
+  pub api_token: Option<String>, // fir 使用
+  pub password: Option<String>, // pgyer 可选密码
+  pub update_description: Option<String>, // 更新描述
+  /// 已保存的发布配置名称，未填写的 API Key/Token 与密码从该配置读取
+  #[serde(default)]
+  pub config_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PublishResult {
+  pub success: bool,
+  pub message: String,
+  pub download_url: Option<String>,
+  pub qr_code_url: Option<String>,
+  pub build_key: Option<String>,
+  pub build_shortcut_url: Option<String>,
+  /// 实际使用的更新描述（可能为自动生成）
+  pub update_description: Option<String>,
+  /// 发布的产物信息（包名、版本等），解析失败时为空
+  pub artifact: Option<ArtifactInfo>,
+}
+
+/// 上传到蒲公英（使用快速上传API，参考官方Node.js示例）
+/// 参考: https://github.com/PGYER/upload-app-api-example/tree/main/nodejs-demo
+async fn upload_to_pgyer(file_path: &Path, config: &PublishConfig) -> Result<PublishResult> {
+  if config.api_key.is_none() {
+    return Err(Error::InvalidInput("蒲公英 API Key 未配置".into()));
+  }
+
+  log::info!("开始上传到蒲公英（快速上传模式），文件: {:?}", file_path);
+  
+  let file_name = file_path
+    .file_name()
+    .and_then(|n| n.to_str())
+    .ok_or_else(|| Error::InvalidInput("无法获取文件名".into()))?;
+  
+  let file_metadata = tokio::fs::metadata(file_path)
+    .await
+    .map_err(|e| Error::io("获取文件信息失败", e))?;
+  let file_size = file_metadata.len();
+  log::info!("文件: {}, 大小: {} bytes ({:.2} MB)", file_name, file_size, file_size as f64 / 1024.0 / 1024.0);
+  
+  // 优化HTTP客户端配置以提高上传速度
+  let client = reqwest::Client::builder()
+    .timeout(std::time::Duration::from_secs(300)) // 5分钟超时，适合大文件上传
+    .connect_timeout(std::time::Duration::from_secs(30)) // 30秒连接超时
+    .tcp_keepalive(std::time::Duration::from_secs(60)) // TCP keepalive
+    .pool_max_idle_per_host(2) // 每个主机最多2个空闲连接
+    .build()
+    .map_err(|e| Error::http("创建HTTP客户端失败", e))?;
+  
+  // 步骤1: 获取上传凭证（getCOSToken）
+  log::info!("步骤1: 获取上传凭证...");
+  let mut token_form = Form::new()
+    .text("_api_key", config.api_key.as_ref().unwrap().clone())
+    .text("buildType", "android");
+  
+  if let Some(ref desc) = config.update_description {
+    if !desc.trim().is_empty() {
+      token_form = token_form.text("buildUpdateDescription", desc.trim().to_string());
+      log::info!("已设置更新描述: {}", desc.trim());
+    }
+  }
+  
+  if let Some(ref password) = config.password {
+    token_form = token_form
+      .text("buildInstallType", "2")  // 2 = 密码安装
+      .text("buildPassword", password.clone());
+    log::info!("已设置安装密码");
+  }
+  
+  log::info!("步骤1: 发送获取凭证请求到: https://api.pgyer.com/apiv2/app/getCOSToken");
+  let token_response = client
+    .post("https://api.pgyer.com/apiv2/app/getCOSToken")
+    .multipart(token_form)
+    .send()
+    .await
+    .map_err(|e| {
+      log::error!("步骤1: 请求失败: {}", e);
+      Error::http("获取上传凭证失败", e)
+    })?;
+  
+  let status = token_response.status();
+  log::info!("步骤1: 响应状态码: {}", status);
+  
+  let token_result: serde_json::Value = token_response
+    .json()
+    .await
+    .map_err(|e| {
+      log::error!("步骤1: 解析响应失败: {}", e);
+      Error::http("解析凭证响应失败", e)
+    })?;
+  
+  log::info!("步骤1: 响应内容: {}", serde_json::to_string_pretty(&token_result).unwrap_or_default());
+  
+  if token_result["code"].as_i64() != Some(0) {
+    let error_msg = format!(
+      "获取上传凭证失败: {}",
+      token_result["message"]
+        .as_str()
+        .unwrap_or("未知错误")
+    );
+    log::error!("步骤1: {}", error_msg);
+    return Err(Error::Publish(error_msg));
+  }
+  
+  let token_data = &token_result["data"];
+  let endpoint = token_data["endpoint"]
+    .as_str()
+    .ok_or_else(|| Error::Publish("无法获取上传端点".into()))?
+    .to_string();
+  let key = token_data["key"]
+    .as_str()
+    .ok_or_else(|| Error::Publish("无法获取上传密钥".into()))?
+    .to_string();
+  let params = token_data["params"]
+    .as_object()
+    .ok_or_else(|| Error::Publish("无法获取上传参数".into()))?;
+  
+  // 提前提取所有参数到 String，避免生命周期问题
+  let mut params_vec: Vec<(String, String)> = Vec::new();
+  for (param_key, param_value) in params {
+    if let Some(value) = param_value.as_str() {
+      params_vec.push((param_key.clone(), value.to_string()));
+    }
+  }
+  
+  let params_count = params_vec.len();
+  log::info!("步骤1: 成功获取上传凭证，端点: {}, 参数数量: {}", endpoint, params_count);
+  
+  // 步骤2: 上传文件到COS
+  log::info!("步骤2: 开始上传文件到COS...");
+  let start_time = std::time::Instant::now();
+  
+  // 读取文件内容
+  let file_bytes = tokio::fs::read(file_path)
+    .await
+    .map_err(|e| Error::io("读取文件失败", e))?;
+  
+  let file_part = Part::bytes(file_bytes)
+    .file_name(file_name.to_string())
+    .mime_str("application/vnd.android.package-archive")
+    .map_err(|e| Error::http("创建文件部分失败", e))?;
+  
+  // 构建上传表单，只包含文件（COS要求）
+  let upload_form = Form::new().part("file", file_part);
+  
+  // 构建HTTP请求，将签名参数作为URL查询参数或headers
+  log::info!("步骤2: 上传到COS端点: {}, 参数数量: {}", endpoint, params_count);
+  
+  // 解析endpoint URL，添加查询参数
+  let mut url = reqwest::Url::parse(&endpoint).map_err(|e| Error::Publish(format!("解析COS端点URL失败: {}", e)))?;
+  
+  // 分离参数：signature 和 key 作为查询参数，x-cos-security-token 作为 header
+  let mut security_token: Option<String> = None;
+  for (param_key, param_value) in params_vec {
+    if param_key == "x-cos-security-token" {
+      security_token = Some(param_value);
+      log::info!("步骤2: 将 {} 作为 header", param_key);
+    } else {
+      // signature 和 key 作为 URL 查询参数
+      url.query_pairs_mut().append_pair(&param_key, &param_value);
+      log::info!("步骤2: 添加查询参数: {} = {}...", param_key, &param_value[..param_value.len().min(50)]);
+    }
+  }
+  
+  let mut request_builder = client.post(url).multipart(upload_form);
+  
+  // 添加 x-cos-security-token 作为 header
+  if let Some(token) = security_token {
+    request_builder = request_builder.header("x-cos-security-token", token);
+  }
+  
+  let upload_response = request_builder
+    .send()
+    .await
+    .map_err(|e| {
+      let elapsed = start_time.elapsed();
+      log::error!("步骤2: 上传请求失败 (耗时: {:.2}秒): {}", elapsed.as_secs_f64(), e);
+      Error::http(format!("上传文件失败 (耗时: {:.2}秒)", elapsed.as_secs_f64()), e)
+    })?;
+  
+  let upload_elapsed = start_time.elapsed();
+  let upload_status = upload_response.status();
+  log::info!("步骤2: 文件上传完成，耗时: {:.2}秒，HTTP状态码: {}", upload_elapsed.as_secs_f64(), upload_status);
+  
+  // 检查上传响应状态
+  if !upload_status.is_success() {
+    // 尝试读取响应内容以便调试
+    let response_text = upload_response.text().await.unwrap_or_default();
+    log::error!("步骤2: 上传失败，HTTP状态码: {}，响应内容: {}", upload_status, response_text);
+    return Err(Error::Publish(format!("上传文件失败，HTTP状态码: {}", upload_status)));
+  }
+  
+  log::info!("步骤2: COS上传成功，准备检查上传状态");
+  
+  // 步骤3: 检查上传状态并获取应用信息（带重试机制）
+  log::info!("步骤3: 检查上传状态，buildKey: {}", key);
+  
+  let api_key = config.api_key.as_ref().unwrap().clone();
+  let max_retries = 60; // 最多重试60次（约3-5分钟）
+  let mut retry_count = 0;
+  
+  loop {
+    let info_form = Form::new()
+      .text("_api_key", api_key.clone())
+      .text("buildKey", key.clone());
+    
+    log::info!("步骤3: 发送请求到: https://api.pgyer.com/apiv2/app/buildInfo (尝试 {}/{})", retry_count + 1, max_retries);
+    let info_response = client
+      .post("https://api.pgyer.com/apiv2/app/buildInfo")
+      .multipart(info_form)
+      .send()
+      .await
+      .map_err(|e| {
+        log::error!("步骤3: 请求失败: {}", e);
+        Error::http("检查上传状态失败", e)
+      })?;
+    
+    let info_status = info_response.status();
+    log::info!("步骤3: 响应状态码: {}", info_status);
+    
+    let info_result: serde_json::Value = info_response
+      .json()
+      .await
+      .map_err(|e| {
+        log::error!("步骤3: 解析响应失败: {}", e);
+        Error::http("解析状态响应失败", e)
+      })?;
+    
+    log::info!("步骤3: 响应内容: {}", serde_json::to_string_pretty(&info_result).unwrap_or_default());
+    
+    let code = info_result["code"].as_i64();
+    
+    // 成功：code = 0
+    if code == Some(0) {
+      let data = &info_result["data"];
+      let download_url = data["buildShortcutUrl"]
+        .as_str()
+        .map(|s| format!("https://www.pgyer.com/{}", s));
+      
+      let total_elapsed = start_time.elapsed();
+      log::info!("步骤3: 上传成功，总耗时: {:.2}秒，重试次数: {}", total_elapsed.as_secs_f64(), retry_count);
+      log::info!("步骤3: 下载链接: {:?}", download_url);
+      log::info!("步骤3: 二维码: {:?}", data["buildQRCodeURL"].as_str());
+      
+      return Ok(PublishResult {
+        success: true,
+        message: "上传成功".to_string(),
+        download_url,
+        qr_code_url: data["buildQRCodeURL"].as_str().map(|s| s.to_string()),
+        build_key: data["buildKey"].as_str().map(|s| s.to_string()),
+        build_shortcut_url: data["buildShortcutUrl"].as_str().map(|s| s.to_string()),
+        update_description: config.update_description.clone(),
+        artifact: None,
+      });
+    }
+    
+    // 处理中：code = 1247，需要重试
+    if code == Some(1247) {
+      retry_count += 1;
+      
+      if retry_count >= max_retries {
+        let error_msg = format!(
+          "检查上传状态超时: 已重试 {} 次，应用仍在处理中",
+          max_retries
+        );
+        log::error!("步骤3: {}", error_msg);
+        return Err(Error::Publish(error_msg));
+      }
+      
+      // 随机等待 3-5 秒
+      let wait_seconds = 3 + (retry_count % 3); // 3, 4, 5 秒循环
+      log::info!("步骤3: 应用正在处理中 (code: 1247)，等待 {} 秒后重试...", wait_seconds);
+      tokio::time::sleep(std::time::Duration::from_secs(wait_seconds)).await;
+      continue;
+    }
+    
+    // 其他错误：直接返回
+    let error_msg = format!(
+      "检查上传状态失败: {}",
+      info_result["message"]
+        .as_str()
+        .unwrap_or("未知错误")
+    );
+    log::error!("步骤3: {}", error_msg);
+    log::error!("步骤3: 完整响应: {}", serde_json::to_string_pretty(&info_result).unwrap_or_default());
+    return Err(Error::Publish(error_msg));
+  }
+}
+
+/// 尝试使用 go-fir-cli 命令行工具上传（备选方案）
+async fn upload_to_fir_via_cli(file_path: &Path, config: &PublishConfig) -> Result<PublishResult> {
+  log::info!("尝试使用 go-fir-cli 命令行工具上传");
+  
+  // 查找 go-fir-cli 工具
+  let cli_names = ["go-fir-cli", "fir-cli"];
+  let mut cli_path: Option<String> = None;
+  
+  for name in &cli_names {
+    let (ok, output) = run_command("which", &[name]);
+    if ok && !output.is_empty() {
+      cli_path = Some(output.trim().to_string());
+      log::info!("找到 go-fir-cli: {}", cli_path.as_ref().unwrap());
+      break;
+    }
+  }
+  
+  let cli = cli_path.ok_or_else(|| {
+    let err = "未找到 go-fir-cli 工具，请先安装：https://github.com/PGYER/go-fir-cli/releases";
+    log::error!("{}", err);
+    Error::Publish(err.into())
+  })?;
+  
+  // 构建命令：go-fir-cli -t TOKEN upload -f FILE_PATH [-c CHANGELOG]
+  let file_path_str = file_path.to_string_lossy().to_string();
+  let token = config.api_token.as_ref().unwrap();
+  
+  log::info!("执行命令: {} -t {}... upload -f {}", cli, &token[..token.len().min(10)], file_path_str);
+  
+  let mut cmd = Command::new(&cli);
+  cmd.arg("-t").arg(token).arg("upload").arg("-f").arg(&file_path_str);
+  
+  // 如果有更新描述，添加 -c 参数
+  if let Some(ref desc) = config.update_description {
+    if !desc.trim().is_empty() {
+      log::info!("添加更新描述: {}", desc.trim());
+      cmd.arg("-c").arg(desc.trim());
+    }
+  }
+  
+  let output = cmd
+    .output()
+    .await
+    .map_err(|e| {
+      log::error!("执行 go-fir-cli 失败: {}", e);
+      Error::io("执行 go-fir-cli 失败", e)
+    })?;
+  
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  
+  log::info!("go-fir-cli 输出: {}", stdout);
+  if !stderr.is_empty() {
+    log::warn!("go-fir-cli 错误输出: {}", stderr);
+  }
+  
+  if output.status.success() {
+    // 解析输出以提取下载链接
+    let mut download_url: Option<String> = None;
+    let mut download_page_url: Option<String> = None;
+    
+    // 方法1: 从JSON响应中提取 download_url
+    // 查找包含 "download_url" 的JSON行
+    // for line in stdout.lines() {
+    //   let line = line.trim();
+    //   if line.contains("download_url") && line.starts_with('{') {
+    //     if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
+    //       if let Some(url) = json_value["download_url"].as_str() {
+    //         download_url = Some(url.to_string());
+    //         log::info!("从JSON中提取到下载链接: {}", url);
+    //         break;
+    //       }
+    //     }
+    //   }
+    // }
+    
+    // 方法2: 从应用信息JSON中提取 short 和 download_domain，构建下载页面URL
+    // for line in stdout.lines() {
+    //   let line = line.trim();
+    //   if line.contains("\"short\"") && line.contains("\"download_domain\"") && line.starts_with('{') {
+    //     if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
+    //       if let (Some(short), Some(domain)) = (
+    //         json_value["short"].as_str(),
+    //         json_value["download_domain"].as_str(),
+    //       ) {
+    //         // 构建下载页面URL: http://{domain}/{short}
+    //         let page_url = format!("http://{}/{}", domain, short);
+    //         download_page_url = Some(page_url.clone());
+    //         log::info!("从应用信息JSON中提取到下载页面: {}", page_url);
+            
+    //         // 如果没有找到直接下载链接，使用下载页面作为备用
+    //         if download_url.is_none() {
+    //           download_url = Some(page_url);
+    //         }
+    //         break;
+    //       }
+    //     }
+    //   }
+    // }
+    
+    // 方法3: 从文本中提取 "下载页面: " 后面的URL
+    if download_page_url.is_none() {
+      for line in stdout.lines() {
+        if line.contains("下载页面:") {
+          if let Some(start_idx) = line.find("下载页面:") {
+            let url_part = &line[start_idx + "下载页面:".len()..].trim();
+            if !url_part.is_empty() {
+              download_page_url = Some(url_part.to_string());
+              log::info!("从文本中提取到下载页面: {}", url_part);
+              
+              // 如果没有找到直接下载链接，使用下载页面作为备用
+              if download_url.is_none() {
+                download_url = Some(url_part.to_string());
+              }
+              break;
+
+            }
+          }
+        }
+      }
+    }
+    
+    Ok(PublishResult {
+      success: true,
+      message: "上传成功（通过 go-fir-cli）".to_string(),
+      download_url,
+      qr_code_url: None,
+      build_key: None,
+      build_shortcut_url: download_page_url,
+      update_description: config.update_description.clone(),
+      artifact: None,
+    })
+  } else {
+    Err(Error::Publish(format!("go-fir-cli 上传失败: {}\n{}", stdout, stderr)))
+  }
+}
+
+/// 上传到 fir.im（使用 go-fir-cli 命令行工具）
+async fn upload_to_fir(file_path: &Path, config: &PublishConfig) -> Result<PublishResult> {
+  if config.api_token.is_none() {
+    return Err(Error::InvalidInput("fir.im API Token 未配置".into()));
+  }
+
+  log::info!("开始上传到 fir.im（使用 go-fir-cli 工具），文件: {:?}", file_path);
+  log::info!("API Token: {}...", &config.api_token.as_ref().unwrap()[..config.api_token.as_ref().unwrap().len().min(10)]);
+
+  // 直接使用 go-fir-cli 命令行工具上传
+  upload_to_fir_via_cli(file_path, config).await
+}
+
+/// 上传 APK/AAB 到指定平台
+pub async fn publish(file_path: &str, config: &PublishConfig) -> Result<PublishResult> {
+  let path = Path::new(file_path);
+  if !path.exists() {
+    return Err(Error::InvalidInput(format!("文件不存在: {}", file_path)));
+  }
+
+  if !file_path.ends_with(".apk") && !file_path.ends_with(".aab") {
+    return Err(Error::InvalidInput("不支持的文件类型，仅支持 .apk 或 .aab 文件".into()));
+  }
+
+  match config.platform.as_str() {
+    "pgyer" => upload_to_pgyer(path, config).await,
+    "fir" => upload_to_fir(path, config).await,
+    _ => Err(Error::InvalidInput(format!("不支持的发布平台: {}", config.platform))),
+  }
+}
+
+/// 补全已保存配置中的凭据，并把密钥库引用解密为原值
+fn resolve_credentials(dirs: &AppDirs, config: &mut PublishConfig) -> Result<()> {
+  if let Some(name) = config.config_name.as_deref().filter(|n| !n.is_empty()) {
+    let saved = config::find_publish_platform(dirs, name)?;
+    let empty = |v: &Option<String>| v.as_deref().map_or(true, str::is_empty);
+    if empty(&config.api_key) {
+      config.api_key = saved.api_key;
+    }
+    if empty(&config.api_token) {
+      config.api_token = saved.api_token;
+    }
+    if empty(&config.password) {
+      config.password = saved.password;
+    }
+  }
+  config.api_key = secrets::reveal_opt(dirs, config.api_key.take())?;
+  config.api_token = secrets::reveal_opt(dirs, config.api_token.take())?;
+  config.password = secrets::reveal_opt(dirs, config.password.take())?;
+  Ok(())
+}
+
+/// 发布工程的产物：按需根据提交生成更新描述，发布成功后记录本次发布的提交。
+///
+/// `project` 未指定时从构建历史中查找产物所属的工程。`generate_notes` 为 true 时总是生成更新描述，
+/// 否则仅在未填写更新描述且工程开启了自动生成时生成。
+pub async fn publish_artifact(
+  dirs: &AppDirs,
+  file_path: &str,
+  mut config: PublishConfig,
+  project: Option<&str>,
+  generate_notes: bool,
+) -> Result<PublishResult> {
+  resolve_credentials(dirs, &mut config)?;
+  let history_dir = dirs.history_dir();
+  let build = history::find_by_artifact(&history_dir, Path::new(file_path))?;
+  let project = match project.map(str::to_string).or_else(|| build.as_ref().map(|b| b.project.clone())) {
+    Some(name) => Some(config::find_project(dirs, &name)?),
+    None => None,
+  };
+  // 指定的工程与产物所属的构建不一致时不使用该构建
+  let build = build.filter(|b| project.as_ref().map_or(true, |p| p.name == b.project));
+
+  let has_description = config.update_description.as_deref().is_some_and(|d| !d.trim().is_empty());
+  let auto = project.as_ref().and_then(|p| p.release_notes.as_ref()).is_some_and(|o| o.auto);
+  match &project {
+    Some(project) if generate_notes || (auto && !has_description) => {
+      match release_notes::generate(&history_dir, project, &config.platform, build.as_ref(), None) {
+        Ok(notes) => {
+          log::info!("已根据 {} 个提交生成更新描述", notes.commits.len());
+          if !notes.text.is_empty() {
+            config.update_description = Some(notes.text);
+          }
+        }
+        Err(e) if generate_notes => return Err(e),
+        Err(e) => log::warn!("生成更新描述失败，不填写更新描述: {}", e),
+      }
+    }
+    None if generate_notes => {
+      return Err(Error::InvalidInput("无法确定产物所属的工程，请指定工程后再生成更新描述".into()));
+    }
+    _ => {}
+  }
+
+  let artifact = match inspect::inspect(Path::new(file_path)) {
+    Ok(info) => {
+      log::info!("发布产物: {}", info.summary());
+      Some(info)
+    }
+    Err(e) => {
+      log::warn!("解析产物失败: {}", e);
+      None
+    }
+  };
+
+  let mut result = publish(file_path, &config).await?;
+  result.artifact = artifact;
+  if let (true, Some(project)) = (result.success, &project) {
+    // 只记录构建时的提交；找不到构建记录时工作区的 HEAD 不一定是产物的来源，提交留空
+    let commit = build.as_ref().and_then(|b| b.git_commit.clone());
+    if commit.is_none() {
+      log::warn!("未找到产物对应的构建记录，发布记录中不填写提交");
+    }
+    let record = PublishedRecord {
+      project: project.name.clone(),
+      platform: config.platform.clone(),
+      commit,
+      build_id: build.as_ref().map(|b| b.build_id.clone()),
+      published_at: now_millis(),
+      download_url: result.download_url.clone(),
+    };
+    if let Err(e) = history::record_published(&history_dir, record) {
+      log::warn!("保存发布记录失败: {}", e);
+    }
+  }
+  Ok(result)
+}
@@ -0,0 +1,250 @@
+//! 根据 git 提交生成发布的更新描述
+//!
+//! 收集上次发布到本次构建之间的提交，按 Conventional Commits 的类型分组后套用工程的模板。
+//! 每个工程在各发布平台上最后一次发布的提交见 [`history::last_published`]。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::OnceLock};
+
+use crate::{
+  config::{self, AppDirs, Project},
+  git,
+  history::{self, BuildRecord},
+  version, Error, Result,
+};
+
+/// 未配置模板时只输出提交列表
+pub const DEFAULT_TEMPLATE: &str = "{{notes}}";
+
+/// 分组顺序与标题，未列出的类型归入“其他”
+const GROUPS: &[(&str, &str)] = &[
+  ("feat", "新功能"),
+  ("fix", "问题修复"),
+  ("perf", "性能优化"),
+  ("refactor", "代码重构"),
+  ("revert", "回退"),
+];
+
+/// 对测试与用户没有意义的类型，不写入更新描述
+const HIDDEN_TYPES: &[&str] = &["chore", "ci", "build", "test", "style", "docs"];
+
+const FIELD_SEP: char = '\u{1f}';
+const RECORD_SEP: char = '\u{1e}';
+
+fn default_true() -> bool {
+  true
+}
+
+fn default_max_commits() -> usize {
+  50
+}
+
+/// 工程的更新描述配置
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotesOptions {
+  /// 发布时未填写更新描述则自动生成
+  #[serde(default)]
+  pub auto: bool,
+  /// 按 Conventional Commits 类型分组，关闭时按提交顺序列出全部提交
+  #[serde(default = "default_true")]
+  pub conventional: bool,
+  /// 模板，支持 `{{notes}}`、`{{version}}`、`{{branch}}`、`{{commit}}`、`{{count}}`
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub template: Option<String>,
+  /// 最多列出的提交数
+  #[serde(default = "default_max_commits")]
+  pub max_commits: usize,
+}
+
+impl Default for ReleaseNotesOptions {
+  fn default() -> Self {
+    Self { auto: false, conventional: true, template: None, max_commits: default_max_commits() }
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommitEntry {
+  pub commit: String,
+  pub short_commit: String,
+  pub author: String,
+  pub subject: String,
+  /// Conventional Commits 的类型与范围，不符合格式时为 None
+  pub kind: Option<String>,
+  pub scope: Option<String>,
+  /// 去掉类型前缀后的描述
+  pub description: String,
+  pub breaking: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ReleaseNotes {
+  pub text: String,
+  /// 起始提交（不含），None 表示没有发布记录，取最近的提交
+  pub since: Option<String>,
+  pub until: String,
+  pub commits: Vec<CommitEntry>,
+  /// 提交数超过 max_commits，列表已截断
+  pub truncated: bool,
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+  cell.get_or_init(|| Regex::new(pattern).expect("无效的正则表达式"))
+}
+
+fn parse_commit(record: &str) -> Option<CommitEntry> {
+  static HEADER: OnceLock<Regex> = OnceLock::new();
+  let mut fields = record.trim_start_matches('\n').split(FIELD_SEP);
+  let (commit, short_commit, author, subject) = (fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+  let body = fields.next().unwrap_or("");
+  let header = regex(&HEADER, r"^(?P<kind>[A-Za-z]+)(?:\((?P<scope>[^)]*)\))?(?P<bang>!)?:\s*(?P<desc>.+)$");
+  let (kind, scope, description, bang) = match header.captures(subject) {
+    Some(c) => (
+      Some(c["kind"].to_ascii_lowercase()),
+      c.name("scope").map(|s| s.as_str().trim().to_string()).filter(|s| !s.is_empty()),
+      c["desc"].trim().to_string(),
+      c.name("bang").is_some(),
+    ),
+    None => (None, None, subject.trim().to_string(), false),
+  };
+  Some(CommitEntry {
+    commit: commit.to_string(),
+    short_commit: short_commit.to_string(),
+    author: author.to_string(),
+    subject: subject.to_string(),
+    breaking: kind.is_some() && (bang || body.contains("BREAKING CHANGE")),
+    kind,
+    scope,
+    description,
+  })
+}
+
+/// `since`（不含）到 `until` 之间的提交，按时间倒序，不含合并提交。
+/// `since` 为 None 或不是 `until` 的祖先（如分支已变基）时取最近的 `limit` 个提交。
+pub fn commits(project_path: &Path, since: Option<&str>, until: &str, limit: usize) -> Result<(Vec<CommitEntry>, bool)> {
+  let since = since.filter(|s| {
+    let reachable = git::git(project_path, &["merge-base", "--is-ancestor", s, until]).is_some();
+    if !reachable {
+      log::warn!("上次发布的提交 {} 不在 {} 的历史中，改为取最近 {} 个提交", s, until, limit);
+    }
+    reachable
+  });
+  let range = match since {
+    Some(since) => format!("{}..{}", since, until),
+    None => until.to_string(),
+  };
+  let max_count = format!("--max-count={}", limit + 1);
+  let format = format!("--format={}%H{}%h{}%an{}%s{}%b{}", RECORD_SEP, FIELD_SEP, FIELD_SEP, FIELD_SEP, FIELD_SEP, FIELD_SEP);
+  let out = git::git(project_path, &["log", "--no-merges", &max_count, &format, &range, "--"])
+    .ok_or_else(|| Error::InvalidInput(format!("读取提交记录失败：{}", range)))?;
+  let mut entries: Vec<CommitEntry> = out.split(RECORD_SEP).filter_map(parse_commit).collect();
+  let truncated = entries.len() > limit;
+  entries.truncate(limit);
+  Ok((entries, truncated))
+}
+
+fn bullet(entry: &CommitEntry) -> String {
+  match &entry.scope {
+    Some(scope) => format!("- {}: {}", scope, entry.description),
+    None => format!("- {}", entry.description),
+  }
+}
+
+/// 提交列表：按类型分组，或按提交顺序列出
+pub fn render_notes(entries: &[CommitEntry], conventional: bool) -> String {
+  if !conventional {
+    return entries.iter().map(|e| format!("- {}", e.subject)).collect::<Vec<_>>().join("\n");
+  }
+  let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+  let mut push = |title: &'static str, line: String| match sections.iter_mut().find(|(t, _)| *t == title) {
+    Some((_, lines)) => lines.push(line),
+    None => sections.push((title, vec![line])),
+  };
+  for entry in entries.iter().filter(|e| e.breaking) {
+    push("不兼容的变更", bullet(entry));
+  }
+  for (kind, title) in GROUPS {
+    for entry in entries.iter().filter(|e| !e.breaking && e.kind.as_deref() == Some(*kind)) {
+      push(title, bullet(entry));
+    }
+  }
+  let known = |kind: &str| GROUPS.iter().any(|(k, _)| *k == kind) || HIDDEN_TYPES.contains(&kind);
+  for entry in entries.iter().filter(|e| !e.breaking && e.kind.as_deref().map_or(true, |k| !known(k))) {
+    push("其他", bullet(entry));
+  }
+  sections
+    .iter()
+    .map(|(title, lines)| format!("{}\n{}", title, lines.join("\n")))
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+fn render_template(template: &str, values: &[(&str, String)]) -> String {
+  let text = values
+    .iter()
+    .fold(template.to_string(), |acc, (key, value)| acc.replace(&format!("{{{{{}}}}}", key), value));
+  text.trim().to_string()
+}
+
+/// 生成工程发布到 `platform` 的更新描述。
+///
+/// `build` 为要发布的产物对应的构建，提供截止提交与版本号；为 None 时截止到当前 HEAD。
+/// `since` 覆盖上次发布的提交。
+pub fn generate(
+  history_dir: &Path,
+  project: &Project,
+  platform: &str,
+  build: Option<&BuildRecord>,
+  since: Option<&str>,
+) -> Result<ReleaseNotes> {
+  let project_path = Path::new(&project.path);
+  let head = git::read(project_path).ok_or_else(|| Error::InvalidInput(format!("工程 {} 不是 git 仓库或没有提交", project.name)))?;
+  let options = project.release_notes.clone().unwrap_or_default();
+  let until = build.and_then(|b| b.git_commit.clone()).unwrap_or_else(|| head.commit.clone());
+  let since = match since {
+    Some(s) => Some(s.to_string()),
+    None => history::last_published(history_dir, &project.name, platform)?.and_then(|r| r.commit),
+  };
+  let (entries, truncated) = commits(project_path, since.as_deref(), &until, options.max_commits.max(1))?;
+
+  let version = build
+    .and_then(|b| b.version.clone())
+    .unwrap_or_else(|| version::read(project_path, project.default_build_module().as_deref()).app_version());
+  let branch = build
+    .and_then(|b| b.git.as_ref())
+    .unwrap_or(&head)
+    .branch
+    .clone()
+    .unwrap_or_default();
+  let mut notes = render_notes(&entries, options.conventional);
+  if truncated {
+    notes.push_str("\n…");
+  }
+  let values = [
+    ("notes", notes),
+    ("version", version.to_string()),
+    ("branch", branch),
+    ("commit", until.chars().take(head.short_commit.len()).collect()),
+    ("count", entries.len().to_string()),
+  ];
+  let text = render_template(options.template.as_deref().unwrap_or(DEFAULT_TEMPLATE), &values);
+  Ok(ReleaseNotes { text, since, until, commits: entries, truncated })
+}
+
+/// 按工程名生成更新描述；`artifact` 为要发布的产物，用于从构建历史中确定截止提交与版本号
+pub fn generate_for_project(
+  dirs: &AppDirs,
+  name: &str,
+  platform: &str,
+  artifact: Option<&Path>,
+  since: Option<&str>,
+) -> Result<ReleaseNotes> {
+  let project = config::find_project(dirs, name)?;
+  let history_dir = dirs.history_dir();
+  let build = match artifact {
+    Some(path) => history::find_by_artifact(&history_dir, path)?.filter(|b| b.project == project.name),
+    None => None,
+  };
+  generate(&history_dir, &project, platform, build.as_ref(), since)
+}
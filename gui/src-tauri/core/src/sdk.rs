@@ -0,0 +1,287 @@
+//! Android SDK 检测：定位 SDK 并列出已安装的组件与已接受的许可
+//!
+//! 查找顺序：`ANDROID_HOME`、`ANDROID_SDK_ROOT`、工程 `local.properties` 的 `sdk.dir`、
+//! 各系统 Android Studio 的默认安装目录。
+
+use serde::Serialize;
+use std::{
+  cmp::Ordering,
+  env, fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{env::EnvCheck, properties};
+
+/// SDK 路径的来源
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum SdkSource {
+  AndroidHome,
+  AndroidSdkRoot,
+  LocalProperties,
+  DefaultLocation,
+}
+
+impl SdkSource {
+  fn label(self) -> &'static str {
+    match self {
+      SdkSource::AndroidHome => "ANDROID_HOME",
+      SdkSource::AndroidSdkRoot => "ANDROID_SDK_ROOT",
+      SdkSource::LocalProperties => "local.properties",
+      SdkSource::DefaultLocation => "默认安装目录",
+    }
+  }
+}
+
+/// 构建必需的许可文件
+const REQUIRED_LICENSE: &str = "android-sdk-license";
+
+/// sdkmanager 可能写入的其他许可文件（缺少时仅影响对应组件）
+const OPTIONAL_LICENSES: &[&str] = &[
+  "android-sdk-preview-license",
+  "android-sdk-arm-dbt-license",
+  "google-gdk-license",
+  "intel-android-extra-license",
+  "mips-android-sysimage-license",
+];
+
+/// 已安装的 SDK 组件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AndroidSdk {
+  pub root: PathBuf,
+  pub source: SdkSource,
+  /// build-tools 版本，从低到高
+  pub build_tools: Vec<String>,
+  /// 平台 API Level（如 `34`，预览版保留目录名）
+  pub platforms: Vec<String>,
+  pub ndk: Vec<String>,
+  pub cmdline_tools: Vec<String>,
+  /// `licenses/` 下存在的许可文件
+  pub licenses: Vec<String>,
+}
+
+impl AndroidSdk {
+  pub fn has_platform(&self, api_level: u32) -> bool {
+    self.platforms.iter().any(|p| p == &api_level.to_string())
+  }
+
+  pub fn required_license_accepted(&self) -> bool {
+    self.licenses.iter().any(|l| l == REQUIRED_LICENSE)
+  }
+}
+
+/// 定位 SDK 目录，`project_path` 用于读取 `local.properties`
+pub fn locate(project_path: Option<&Path>) -> Option<(PathBuf, SdkSource)> {
+  let from_env = |var: &str| env::var_os(var).map(PathBuf::from).filter(|p| !p.as_os_str().is_empty());
+  if let Some(p) = from_env("ANDROID_HOME") {
+    return Some((p, SdkSource::AndroidHome));
+  }
+  if let Some(p) = from_env("ANDROID_SDK_ROOT") {
+    return Some((p, SdkSource::AndroidSdkRoot));
+  }
+  if let Some(dir) = project_path
+    .and_then(|p| properties::read(&p.join("local.properties")))
+    .and_then(|props| props.get("sdk.dir").cloned())
+    .filter(|d| !d.is_empty())
+  {
+    return Some((PathBuf::from(dir), SdkSource::LocalProperties));
+  }
+  default_locations()
+    .into_iter()
+    .find(|p| p.is_dir())
+    .map(|p| (p, SdkSource::DefaultLocation))
+}
+
+fn default_locations() -> Vec<PathBuf> {
+  let mut candidates = Vec::new();
+  if cfg!(windows) {
+    if let Some(local) = env::var_os("LOCALAPPDATA") {
+      candidates.push(PathBuf::from(local).join("Android").join("Sdk"));
+    }
+  } else if let Some(home) = dirs::home_dir() {
+    if cfg!(target_os = "macos") {
+      candidates.push(home.join("Library/Android/sdk"));
+    } else {
+      candidates.push(home.join("Android/Sdk"));
+    }
+  }
+  candidates
+}
+
+/// 读取 SDK 目录下已安装的组件
+pub fn inspect(root: &Path, source: SdkSource) -> AndroidSdk {
+  let mut platforms: Vec<String> = subdirs(&root.join("platforms"))
+    .into_iter()
+    .map(|name| name.strip_prefix("android-").map(str::to_string).unwrap_or(name))
+    .collect();
+  platforms.sort_by(|a, b| compare_versions(a, b));
+
+  // 旧版 NDK 安装在 ndk-bundle，版本号记录在 source.properties
+  let mut ndk = subdirs(&root.join("ndk"));
+  if let Some(rev) = pkg_revision(&root.join("ndk-bundle")) {
+    if !ndk.contains(&rev) {
+      ndk.push(rev);
+    }
+  }
+  ndk.sort_by(|a, b| compare_versions(a, b));
+
+  // cmdline-tools/latest 需要读取真实版本
+  let mut cmdline_tools: Vec<String> = subdirs(&root.join("cmdline-tools"))
+    .into_iter()
+    .map(|name| match pkg_revision(&root.join("cmdline-tools").join(&name)) {
+      Some(rev) if name == "latest" => format!("latest ({})", rev),
+      _ => name,
+    })
+    .collect();
+  cmdline_tools.sort_by(|a, b| compare_versions(a, b));
+
+  let mut build_tools = subdirs(&root.join("build-tools"));
+  build_tools.sort_by(|a, b| compare_versions(a, b));
+
+  let mut licenses: Vec<String> = fs::read_dir(root.join("licenses"))
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter(|e| e.path().is_file() && e.metadata().map(|m| m.len() > 0).unwrap_or(false))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+    })
+    .unwrap_or_default();
+  licenses.sort();
+
+  AndroidSdk {
+    root: root.to_path_buf(),
+    source,
+    build_tools,
+    platforms,
+    ndk,
+    cmdline_tools,
+    licenses,
+  }
+}
+
+/// 定位并读取 SDK，目录不存在时返回 None
+pub fn detect(project_path: Option<&Path>) -> Option<AndroidSdk> {
+  let (root, source) = locate(project_path)?;
+  root.is_dir().then(|| inspect(&root, source))
+}
+
+fn subdirs(dir: &Path) -> Vec<String> {
+  fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.'))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn pkg_revision(dir: &Path) -> Option<String> {
+  properties::read(&dir.join("source.properties"))?.remove("Pkg.Revision")
+}
+
+/// 按数字段比较版本号（`34.0.0` > `9.0.0`，`r25` 等非数字部分按字符串比较）
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+  let parts = |s: &str| -> Vec<String> {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+      .filter(|p| !p.is_empty())
+      .map(str::to_string)
+      .collect()
+  };
+  let (pa, pb) = (parts(a), parts(b));
+  for (x, y) in pa.iter().zip(&pb) {
+    let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+      (Ok(x), Ok(y)) => x.cmp(&y),
+      (Ok(_), Err(_)) => Ordering::Greater,
+      (Err(_), Ok(_)) => Ordering::Less,
+      (Err(_), Err(_)) => x.cmp(y),
+    };
+    if ord != Ordering::Equal {
+      return ord;
+    }
+  }
+  pa.len().cmp(&pb.len())
+}
+
+/// SDK 相关的环境检查项
+pub fn checks(project_path: Option<&Path>) -> Vec<EnvCheck> {
+  let Some((root, source)) = locate(project_path) else {
+    return vec![EnvCheck {
+      tool: "Android SDK".into(),
+      ok: false,
+      message: "未找到 Android SDK，请设置 ANDROID_HOME 或在工程 local.properties 中配置 sdk.dir".into(),
+      detail: None,
+      applicable: true,
+      items: vec![],
+    }];
+  };
+  if !root.is_dir() {
+    return vec![EnvCheck {
+      tool: "Android SDK".into(),
+      ok: false,
+      message: format!("{} 指向的 SDK 目录不存在: {}", source.label(), root.display()),
+      detail: None,
+      applicable: true,
+      items: vec![],
+    }];
+  }
+
+  let sdk = inspect(&root, source);
+  let component = |tool: &str, items: &[String], missing: &str, required: bool| {
+    let ok = !items.is_empty() || !required;
+    EnvCheck {
+      tool: tool.into(),
+      ok,
+      message: match items.last() {
+        Some(latest) => format!("已安装 {} 个，最新 {}", items.len(), latest),
+        None => missing.into(),
+      },
+      detail: None,
+      applicable: true,
+      items: items.to_vec(),
+    }
+  };
+
+  let missing_optional: Vec<&str> = OPTIONAL_LICENSES
+    .iter()
+    .copied()
+    .filter(|l| !sdk.licenses.iter().any(|x| x == l))
+    .collect();
+  let licenses = EnvCheck {
+    tool: "SDK 许可".into(),
+    ok: sdk.required_license_accepted(),
+    message: if sdk.required_license_accepted() {
+      "已接受 Android SDK 许可".into()
+    } else {
+      format!("未接受 {}，请运行 sdkmanager --licenses", REQUIRED_LICENSE)
+    },
+    detail: (!missing_optional.is_empty()).then(|| format!("未接受的可选许可: {}", missing_optional.join(", "))),
+    applicable: true,
+    items: sdk.licenses.clone(),
+  };
+
+  vec![
+    EnvCheck {
+      tool: "Android SDK".into(),
+      ok: true,
+      message: format!("已检测到 Android SDK: {}（来源 {}）", sdk.root.display(), sdk.source.label()),
+      detail: Some(sdk.root.display().to_string()),
+      applicable: true,
+      items: vec![],
+    },
+    component("SDK Build-Tools", &sdk.build_tools, "未安装 build-tools", true),
+    component("SDK Platforms", &sdk.platforms, "未安装任何 SDK Platform", true),
+    component("NDK", &sdk.ndk, "未安装 NDK（仅包含原生代码的工程需要）", false),
+    component(
+      "SDK Command-line Tools",
+      &sdk.cmdline_tools,
+      "未安装 cmdline-tools，无法使用 sdkmanager 安装组件或接受许可",
+      false,
+    ),
+    licenses,
+  ]
+}
@@ -0,0 +1,481 @@
+//! 本地密钥库：发布平台的 API Key/Token、keystore 与签名密码用主密码加密后保存在数据目录的 secrets.json
+//!
+//! 配置文件中只保留 `secret:<名称>` 形式的引用，列表接口只返回打码后的预览。主密码经 Argon2id 派生为
+//! AES-256-GCM 密钥，每个密钥单独加密并以名称作为附加数据。GUI 输入主密码后在进程内保持解锁，
+//! 命令行读取环境变量 `ABT_MASTER_PASSWORD`。
+
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use aes_gcm::{
+  aead::{Aead, KeyInit, Payload},
+  Aes256Gcm, Nonce,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::AppDirs, now_millis, Error, Result};
+
+/// 配置中引用密钥的前缀
+pub const REFERENCE_PREFIX: &str = "secret:";
+/// 命令行读取主密码的环境变量
+pub const MASTER_PASSWORD_ENV: &str = "ABT_MASTER_PASSWORD";
+/// 新主密码的最短长度
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// Argon2id 参数，取 OWASP 推荐的最低配置：19 MiB 内存、2 次迭代
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+const KDF_PARALLELISM: u32 = 1;
+/// 用于校验主密码的固定明文
+const CHECK_PLAINTEXT: &[u8] = b"android-build-tool";
+const CHECK_AAD: &[u8] = b"check";
+/// 打码预览的前缀
+const MASK: &str = "********";
+/// 引用的密钥不存在时显示的预览
+const MISSING_PREVIEW: &str = "（密钥缺失）";
+
+/// 解锁后的密钥，与密钥库文件对应
+struct Unlocked {
+  path: PathBuf,
+  key: [u8; 32],
+}
+
+static UNLOCKED: Mutex<Option<Unlocked>> = Mutex::new(None);
+/// 串行化密钥库文件的读改写
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretFile {
+  version: u32,
+  kdf: KdfParams,
+  /// 固定明文的密文，用于判断主密码是否正确
+  check: Sealed,
+  secrets: BTreeMap<String, StoredSecret>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct KdfParams {
+  algorithm: String,
+  salt: String,
+  memory_kib: u32,
+  iterations: u32,
+  parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+  nonce: String,
+  ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSecret {
+  #[serde(flatten)]
+  sealed: Sealed,
+  /// 打码后的预览，未解锁时也可以显示
+  preview: String,
+  updated_at: u64,
+}
+
+/// 密钥库状态
+#[derive(Serialize)]
+pub struct SecretsStatus {
+  pub path: String,
+  /// 是否已设置主密码
+  pub initialized: bool,
+  pub unlocked: bool,
+  pub count: usize,
+}
+
+/// 列表中的密钥，只有打码后的预览
+#[derive(Serialize)]
+pub struct SecretInfo {
+  pub name: String,
+  pub preview: String,
+  pub updated_at: u64,
+}
+
+pub fn status(dirs: &AppDirs) -> Result<SecretsStatus> {
+  let path = dirs.secrets_file();
+  let file = load(&path)?;
+  let unlocked = file.as_ref().is_some_and(|f| key(&path, f).is_ok());
+  Ok(SecretsStatus {
+    path: path.display().to_string(),
+    initialized: file.is_some(),
+    unlocked,
+    count: file.map_or(0, |f| f.secrets.len()),
+  })
+}
+
+/// 输入主密码解锁；密钥库还不存在时以该密码创建
+pub fn unlock(dirs: &AppDirs, passphrase: &str) -> Result<SecretsStatus> {
+  let path = dirs.secrets_file();
+  {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let key = match load(&path)? {
+      Some(file) => derive_checked(&file, passphrase)?,
+      None => {
+        let (file, key) = initialize(passphrase)?;
+        save(&path, &file)?;
+        key
+      }
+    };
+    cache_key(&path, key);
+  }
+  status(dirs)
+}
+
+/// 清除进程内缓存的密钥
+pub fn lock() {
+  *UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 修改主密码，所有密钥用新密码重新加密
+pub fn change_passphrase(dirs: &AppDirs, old: &str, new: &str) -> Result<()> {
+  let path = dirs.secrets_file();
+  let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let Some(file) = load(&path)? else {
+    return Err(Error::Secrets("密钥库尚未创建".into()));
+  };
+  let old_key = derive_checked(&file, old)?;
+  let (mut next, key) = initialize(new)?;
+  for (name, secret) in &file.secrets {
+    let value = open(&old_key, name.as_bytes(), &secret.sealed)?;
+    next.secrets.insert(
+      name.clone(),
+      StoredSecret { sealed: seal(&key, name.as_bytes(), &value)?, preview: secret.preview.clone(), updated_at: secret.updated_at },
+    );
+  }
+  save(&path, &next)?;
+  cache_key(&path, key);
+  Ok(())
+}
+
+pub fn list(dirs: &AppDirs) -> Result<Vec<SecretInfo>> {
+  let file = load(&dirs.secrets_file())?;
+  Ok(
+    file
+      .map(|f| f.secrets)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(name, s)| SecretInfo { name, preview: s.preview, updated_at: s.updated_at })
+      .collect(),
+  )
+}
+
+/// 是否为 `secret:<名称>` 形式的引用
+pub fn is_reference(value: &str) -> bool {
+  value.starts_with(REFERENCE_PREFIX)
+}
+
+/// 引用解密为原值，其他值原样返回（兼容迁移前的明文配置）
+pub fn reveal(dirs: &AppDirs, value: &str) -> Result<String> {
+  let Some(name) = value.strip_prefix(REFERENCE_PREFIX) else {
+    return Ok(value.to_string());
+  };
+  let path = dirs.secrets_file();
+  let file = load(&path)?.ok_or_else(|| Error::SecretNotFound(name.to_string()))?;
+  let secret = file.secrets.get(name).ok_or_else(|| Error::SecretNotFound(name.to_string()))?;
+  let key = key(&path, &file)?;
+  let value = open(&key, name.as_bytes(), &secret.sealed)?;
+  String::from_utf8(value).map_err(|_| Error::Secrets(format!("密钥内容无效：{}", name)))
+}
+
+pub(crate) fn reveal_opt(dirs: &AppDirs, value: Option<String>) -> Result<Option<String>> {
+  value.map(|v| reveal(dirs, &v)).transpose()
+}
+
+/// 打码后的预览：引用取密钥库中保存的预览，明文直接打码
+pub(crate) fn mask(dirs: &AppDirs, value: Option<&str>) -> Option<String> {
+  let value = value.filter(|v| !v.is_empty())?;
+  if !value.starts_with(REFERENCE_PREFIX) {
+    return Some(preview(value));
+  }
+  Some(masked_with(load(&dirs.secrets_file()).ok().flatten().as_ref(), value))
+}
+
+fn masked_with(file: Option<&SecretFile>, value: &str) -> String {
+  let Some(name) = value.strip_prefix(REFERENCE_PREFIX) else {
+    return preview(value);
+  };
+  file
+    .and_then(|f| f.secrets.get(name))
+    .map_or_else(|| MISSING_PREVIEW.to_string(), |s| s.preview.clone())
+}
+
+/// 保存配置时处理密钥字段，返回写入配置的值：
+/// 空值清除原密钥，原有的引用与原值的预览保持不变，其他值加密保存并替换为引用。
+/// `hint` 为新密钥的名称，如 `publish/蒲公英/api_key`，与已有密钥重名时加上序号。
+pub(crate) fn store(dirs: &AppDirs, hint: &str, current: Option<&str>, incoming: Option<String>) -> Result<Option<String>> {
+  let current = current.filter(|v| !v.is_empty());
+  let path = dirs.secrets_file();
+  // 比较预览与写入都在锁内，避免并发保存时互相覆盖
+  let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let existing = load(&path)?;
+  let Some(incoming) = incoming.filter(|v| !v.is_empty()) else {
+    if let (Some(mut file), Some(name)) = (existing, current.and_then(|c| c.strip_prefix(REFERENCE_PREFIX))) {
+      if file.secrets.remove(name).is_some() {
+        save(&path, &file)?;
+      }
+    }
+    return Ok(None);
+  };
+  if let Some(current) = current {
+    if current == incoming || masked_with(existing.as_ref(), current) == incoming {
+      return Ok(Some(current.to_string()));
+    }
+  }
+  // 表单提交的是打码后的预览而没有原值可保留，说明原密钥已丢失，不能把预览当作密钥保存
+  if incoming.starts_with(MASK) || incoming == MISSING_PREVIEW {
+    return Err(Error::InvalidInput("请重新填写密钥，不能保存打码后的预览".into()));
+  }
+  // 只能保留字段原有的引用，不能改为引用其他密钥，否则可以把某个密钥发给另一个发布平台
+  if is_reference(&incoming) {
+    return Err(Error::InvalidInput("不能引用其他字段保存的密钥，请直接填写密钥".into()));
+  }
+
+  let (mut file, key) = match existing {
+    Some(file) => {
+      let key = key(&path, &file)?;
+      (file, key)
+    }
+    // 尚未创建时用环境变量中的主密码创建
+    None => {
+      let passphrase = env_passphrase().ok_or(Error::SecretsLocked)?;
+      let (file, key) = initialize(&passphrase)?;
+      cache_key(&path, key);
+      (file, key)
+    }
+  };
+  // 原值已经是引用时覆盖同一个密钥
+  let name = match current.and_then(|c| c.strip_prefix(REFERENCE_PREFIX)) {
+    Some(name) => name.to_string(),
+    None => unique_name(&file, hint),
+  };
+  let sealed = seal(&key, name.as_bytes(), incoming.as_bytes())?;
+  file.secrets.insert(name.clone(), StoredSecret { sealed, preview: preview(&incoming), updated_at: now_millis() });
+  save(&path, &file)?;
+  Ok(Some(format!("{}{}", REFERENCE_PREFIX, name)))
+}
+
+/// 删除引用的密钥，明文与空值忽略
+pub(crate) fn remove(dirs: &AppDirs, value: Option<&str>) -> Result<()> {
+  let Some(name) = value.and_then(|v| v.strip_prefix(REFERENCE_PREFIX)) else {
+    return Ok(());
+  };
+  let path = dirs.secrets_file();
+  let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(mut file) = load(&path)? {
+    if file.secrets.remove(name).is_some() {
+      save(&path, &file)?;
+    }
+  }
+  Ok(())
+}
+
+/// 较长的值保留末 4 位，其余全部打码
+fn preview(value: &str) -> String {
+  let chars: Vec<char> = value.chars().collect();
+  if chars.len() >= 12 {
+    format!("{}{}", MASK, chars[chars.len() - 4..].iter().collect::<String>())
+  } else {
+    MASK.to_string()
+  }
+}
+
+fn unique_name(file: &SecretFile, hint: &str) -> String {
+  let mut name = hint.to_string();
+  let mut n = 2;
+  while file.secrets.contains_key(&name) {
+    name = format!("{}-{}", hint, n);
+    n += 1;
+  }
+  name
+}
+
+fn env_passphrase() -> Option<String> {
+  std::env::var(MASTER_PASSWORD_ENV).ok().filter(|p| !p.is_empty())
+}
+
+fn cached_key(path: &Path) -> Option<[u8; 32]> {
+  let unlocked = UNLOCKED.lock().unwrap_or_else(|e| e.into_inner());
+  unlocked.as_ref().filter(|u| u.path == path).map(|u| u.key)
+}
+
+fn cache_key(path: &Path, key: [u8; 32]) {
+  *UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()) = Some(Unlocked { path: path.to_path_buf(), key });
+}
+
+/// 已解锁时使用缓存的密钥，否则尝试环境变量中的主密码
+fn key(path: &Path, file: &SecretFile) -> Result<[u8; 32]> {
+  if let Some(key) = cached_key(path) {
+    return Ok(key);
+  }
+  let passphrase = env_passphrase().ok_or(Error::SecretsLocked)?;
+  let key = derive_checked(file, &passphrase)?;
+  cache_key(path, key);
+  Ok(key)
+}
+
+fn initialize(passphrase: &str) -> Result<(SecretFile, [u8; 32])> {
+  if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+    return Err(Error::InvalidInput(format!("主密码至少需要 {} 个字符", MIN_PASSPHRASE_LEN)));
+  }
+  let mut salt = [0u8; 16];
+  OsRng.fill_bytes(&mut salt);
+  let kdf = KdfParams {
+    algorithm: "argon2id".into(),
+    salt: BASE64.encode(salt),
+    memory_kib: KDF_MEMORY_KIB,
+    iterations: KDF_ITERATIONS,
+    parallelism: KDF_PARALLELISM,
+  };
+  let key = derive(&kdf, passphrase)?;
+  let check = seal(&key, CHECK_AAD, CHECK_PLAINTEXT)?;
+  Ok((SecretFile { version: 1, kdf, check, secrets: BTreeMap::new() }, key))
+}
+
+fn derive(kdf: &KdfParams, passphrase: &str) -> Result<[u8; 32]> {
+  if kdf.algorithm != "argon2id" {
+    return Err(Error::Secrets(format!("不支持的密钥派生算法：{}", kdf.algorithm)));
+  }
+  let salt = decode(&kdf.salt)?;
+  let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+    .map_err(|e| Error::Secrets(format!("密钥派生参数无效：{}", e)))?;
+  let mut key = [0u8; 32];
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+    .map_err(|e| Error::Secrets(format!("密钥派生失败：{}", e)))?;
+  Ok(key)
+}
+
+/// 派生密钥并用校验密文确认主密码正确
+fn derive_checked(file: &SecretFile, passphrase: &str) -> Result<[u8; 32]> {
+  let key = derive(&file.kdf, passphrase)?;
+  match open(&key, CHECK_AAD, &file.check) {
+    Ok(value) if value == CHECK_PLAINTEXT => Ok(key),
+    _ => Err(Error::Secrets("主密码错误".into())),
+  }
+}
+
+fn seal(key: &[u8; 32], aad: &[u8], value: &[u8]) -> Result<Sealed> {
+  let cipher = Aes256Gcm::new(key.into());
+  let mut nonce = [0u8; 12];
+  OsRng.fill_bytes(&mut nonce);
+  let ciphertext = cipher
+    .encrypt(Nonce::from_slice(&nonce), Payload { msg: value, aad })
+    .map_err(|_| Error::Secrets("加密失败".into()))?;
+  Ok(Sealed { nonce: BASE64.encode(nonce), ciphertext: BASE64.encode(ciphertext) })
+}
+
+fn open(key: &[u8; 32], aad: &[u8], sealed: &Sealed) -> Result<Vec<u8>> {
+  let nonce = decode(&sealed.nonce)?;
+  if nonce.len() != 12 {
+    return Err(Error::Secrets("密钥库文件已损坏".into()));
+  }
+  let ciphertext = decode(&sealed.ciphertext)?;
+  Aes256Gcm::new(key.into())
+    .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad })
+    .map_err(|_| Error::Secrets("解密失败：主密码错误或密钥库文件已损坏".into()))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+  BASE64.decode(value).map_err(|_| Error::Secrets("密钥库文件已损坏".into()))
+}
+
+fn load(path: &Path) -> Result<Option<SecretFile>> {
+  if !path.exists() {
+    return Ok(None);
+  }
+  let content = fs::read_to_string(path).map_err(|e| Error::io(format!("读取密钥库失败 {}", path.display()), e))?;
+  serde_json::from_str(&content).map(Some).map_err(|e| Error::json(format!("密钥库格式错误 {}", path.display()), e))
+}
+
+/// 先写临时文件再替换，类 Unix 系统上只允许当前用户读写
+fn save(path: &Path, file: &SecretFile) -> Result<()> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).map_err(|e| Error::io(format!("创建目录失败 {}", parent.display()), e))?;
+  }
+  let content = serde_json::to_string_pretty(file).map_err(|e| Error::json("序列化密钥库失败", e))?;
+  let tmp = path.with_extension("json.tmp");
+  fs::write(&tmp, content).map_err(|e| Error::io(format!("写入密钥库失败 {}", tmp.display()), e))?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600));
+  }
+  fs::rename(&tmp, path).map_err(|e| Error::io(format!("写入密钥库失败 {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn seal_then_open() {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    let sealed = seal(&key, b"publish/api_key", b"token").unwrap();
+    assert_eq!(open(&key, b"publish/api_key", &sealed).unwrap(), b"token");
+    // 名称作为附加数据，换名称或换密钥都无法解密
+    assert!(open(&key, b"publish/other", &sealed).is_err());
+    assert!(open(&[0u8; 32], b"publish/api_key", &sealed).is_err());
+  }
+
+  #[test]
+  fn previews() {
+    assert_eq!(preview("short"), MASK);
+    assert_eq!(preview("abcdefgh1234"), "********1234");
+    assert_eq!(masked_with(None, "plain-value-5678"), "********5678");
+    assert_eq!(masked_with(None, "secret:missing"), MISSING_PREVIEW);
+  }
+
+  #[test]
+  fn store_then_reveal() {
+    let data_dir = std::env::temp_dir().join(format!("abt-test-secrets-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&data_dir);
+    let dirs = AppDirs { data_dir: Some(data_dir.clone()), ..AppDirs::default() };
+
+    assert!(unlock(&dirs, "short").is_err());
+    assert!(unlock(&dirs, "correct-horse").unwrap().unlocked);
+
+    let value = "abcdefghijkl1234".to_string();
+    let reference = store(&dirs, "publish/demo/api_key", None, Some(value.clone())).unwrap().unwrap();
+    assert_eq!(reference, "secret:publish/demo/api_key");
+    assert_eq!(reveal(&dirs, &reference).unwrap(), value);
+    assert!(!fs::read_to_string(dirs.secrets_file()).unwrap().contains(&value));
+
+    // 表单回传的预览保持原密钥不变
+    let masked = mask(&dirs, Some(&reference)).unwrap();
+    assert_eq!(masked, "********1234");
+    assert_eq!(store(&dirs, "publish/demo/api_key", Some(&reference), Some(masked.clone())).unwrap(), Some(reference.clone()));
+    assert!(matches!(store(&dirs, "publish/demo/api_key", None, Some(masked)), Err(Error::InvalidInput(_))));
+    // 不能引用其他字段的密钥
+    let other = store(&dirs, "signing/demo/store_password", None, Some("keystore-pass".into())).unwrap();
+    assert!(matches!(store(&dirs, "publish/demo/api_key", Some(&reference), other.clone()), Err(Error::InvalidInput(_))));
+    assert!(matches!(store(&dirs, "publish/other/api_key", None, other.clone()), Err(Error::InvalidInput(_))));
+    remove(&dirs, other.as_deref()).unwrap();
+
+    // 修改时覆盖同一个密钥，新增时名称不冲突
+    assert_eq!(store(&dirs, "publish/demo/api_key", Some(&reference), Some("changed".into())).unwrap(), Some(reference.clone()));
+    assert_eq!(reveal(&dirs, &reference).unwrap(), "changed");
+    let second = store(&dirs, "publish/demo/api_key", None, Some("other".into())).unwrap().unwrap();
+    assert_eq!(second, "secret:publish/demo/api_key-2");
+
+    assert_eq!(store(&dirs, "publish/demo/api_key", Some(&second), None).unwrap(), None);
+    let names: Vec<String> = list(&dirs).unwrap().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, ["publish/demo/api_key"]);
+
+    assert!(matches!(unlock(&dirs, "wrong-password"), Err(Error::Secrets(_))));
+    let _ = fs::remove_dir_all(&data_dir);
+  }
+}
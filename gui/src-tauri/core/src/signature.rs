@@ -0,0 +1,1097 @@
+//! APK 签名校验：JAR 签名（v1）、APK 签名方案 v2/v3/v3.1 与 v4（`.idsig`）
+//!
+//! 与 apksigner 一样校验签名与内容摘要，并给出签名证书的指纹、主题与有效期。
+
+use std::{
+  collections::HashMap,
+  io::{Cursor, Read},
+  path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use cms::{
+  cert::CertificateChoices,
+  content_info::ContentInfo,
+  signed_data::{SignedData, SignerIdentifier},
+};
+use rsa::{
+  pkcs8::{AssociatedOid, DecodePublicKey},
+  signature::{hazmat::PrehashVerifier, Verifier},
+  RsaPublicKey,
+};
+use serde::Serialize;
+use sha2::{digest::FixedOutputReset, Digest, Sha256, Sha384, Sha512};
+use x509_cert::{
+  der::{asn1::ObjectIdentifier, asn1::OctetString, Decode, Encode},
+  Certificate,
+};
+use zip::ZipArchive;
+
+use crate::{
+  format_date, now_millis,
+  signing_block::{Apk, Reader, V2_BLOCK_ID, V31_BLOCK_ID, V3_BLOCK_ID},
+  Error, Result,
+};
+
+/// v2/v3 内容摘要的分块大小
+const CHUNK_SIZE: usize = 1024 * 1024;
+/// verity 摘要（Merkle 树）的块大小
+const VERITY_BLOCK_SIZE: usize = 4096;
+/// 每种签名最多列出的错误数，文件被大量修改时避免刷屏
+const MAX_ERRORS: usize = 10;
+const DEBUG_CERT_CN: &str = "CN=Android Debug";
+/// 证书到期前多少天开始提示（与 jarsigner 相同，约 6 个月）
+pub const EXPIRY_WARNING_DAYS: u64 = 180;
+const DAY_MILLIS: u64 = 86_400_000;
+
+const OID_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const OID_EC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const OID_DSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10040.4.1");
+const OID_P256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const OID_P384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+const OID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum SignatureScheme {
+  #[serde(rename = "v1")]
+  V1,
+  #[serde(rename = "v2")]
+  V2,
+  #[serde(rename = "v3")]
+  V3,
+  #[serde(rename = "v3.1")]
+  V31,
+  #[serde(rename = "v4")]
+  V4,
+}
+
+impl SignatureScheme {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::V1 => "v1",
+      Self::V2 => "v2",
+      Self::V3 => "v3",
+      Self::V31 => "v3.1",
+      Self::V4 => "v4",
+    }
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CertificateInfo {
+  pub subject: String,
+  pub issuer: String,
+  /// 序列号（十六进制）
+  pub serial: String,
+  pub sha1: String,
+  pub sha256: String,
+  /// 有效期起止（毫秒时间戳）
+  pub not_before: u64,
+  pub not_after: u64,
+  /// 如 `RSA 2048`、`EC P-256`
+  pub key_algorithm: String,
+  /// Android 调试证书（`CN=Android Debug`）
+  pub debug: bool,
+}
+
+/// 一种签名方案的校验结果
+#[derive(Serialize, Clone, Debug)]
+pub struct SchemeResult {
+  pub scheme: SignatureScheme,
+  pub verified: bool,
+  pub errors: Vec<String>,
+  /// 各签名者的证书
+  pub signers: Vec<CertificateInfo>,
+  /// v3/v3.1 签名者适用的 SDK 范围（多个签名者时为合并后的范围）
+  pub min_sdk: Option<u32>,
+  pub max_sdk: Option<u32>,
+}
+
+impl SchemeResult {
+  fn new(scheme: SignatureScheme) -> Self {
+    Self { scheme, verified: false, errors: Vec::new(), signers: Vec::new(), min_sdk: None, max_sdk: None }
+  }
+
+  fn error(&mut self, message: impl Into<String>) {
+    if self.errors.len() < MAX_ERRORS {
+      self.errors.push(message.into());
+    } else if self.errors.len() == MAX_ERRORS {
+      self.errors.push("……更多错误已省略".into());
+    }
+  }
+
+  fn finish(mut self) -> Self {
+    self.verified = self.errors.is_empty() && !self.signers.is_empty();
+    self
+  }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SignatureReport {
+  pub path: String,
+  /// 存在签名且所有签名方案都校验通过
+  pub verified: bool,
+  /// APK 中存在的签名方案
+  pub schemes: Vec<SchemeResult>,
+  /// 所有签名方案中的证书（按 SHA-256 去重）
+  pub signers: Vec<CertificateInfo>,
+  /// 使用 Android 调试证书签名
+  pub debug_signed: bool,
+  pub warnings: Vec<String>,
+}
+
+impl SignatureReport {
+  /// 单行摘要，用于构建日志
+  pub fn summary(&self) -> String {
+    let schemes = self.schemes.iter().map(|s| s.scheme.label()).collect::<Vec<_>>().join("+");
+    if schemes.is_empty() {
+      return "未签名".into();
+    }
+    let status = if self.verified { "校验通过" } else { "校验失败" };
+    let signer = self
+      .signers
+      .first()
+      .map(|c| format!("，{}（SHA-256 {}…）", c.subject, &c.sha256[..16]))
+      .unwrap_or_default();
+    format!("签名 {} {}{}", schemes, status, signer)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Hash {
+  Sha1,
+  Sha256,
+  Sha384,
+  Sha512,
+}
+
+impl Hash {
+  pub fn digest(self, data: &[u8]) -> Vec<u8> {
+    self.digest_parts(&[data])
+  }
+
+  pub fn digest_parts(self, parts: &[&[u8]]) -> Vec<u8> {
+    fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+      let mut hasher = D::new();
+      for part in parts {
+        hasher.update(part);
+      }
+      hasher.finalize().to_vec()
+    }
+    match self {
+      Self::Sha1 => run::<sha1::Sha1>(parts),
+      Self::Sha256 => run::<Sha256>(parts),
+      Self::Sha384 => run::<Sha384>(parts),
+      Self::Sha512 => run::<Sha512>(parts),
+    }
+  }
+
+  pub fn oid(self) -> ObjectIdentifier {
+    ObjectIdentifier::new_unwrap(match self {
+      Self::Sha1 => "1.3.14.3.2.26",
+      Self::Sha256 => "2.16.840.1.101.3.4.2.1",
+      Self::Sha384 => "2.16.840.1.101.3.4.2.2",
+      Self::Sha512 => "2.16.840.1.101.3.4.2.3",
+    })
+  }
+
+  fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+    match oid.to_string().as_str() {
+      "1.3.14.3.2.26" => Some(Self::Sha1),
+      "2.16.840.1.101.3.4.2.1" => Some(Self::Sha256),
+      "2.16.840.1.101.3.4.2.2" => Some(Self::Sha384),
+      "2.16.840.1.101.3.4.2.3" => Some(Self::Sha512),
+      _ => None,
+    }
+  }
+
+  /// MANIFEST.MF / .SF 中摘要属性的算法名，如 `SHA-256-Digest` 中的 `SHA-256`
+  pub fn jar_name(self) -> &'static str {
+    match self {
+      Self::Sha1 => "SHA1",
+      Self::Sha256 => "SHA-256",
+      Self::Sha384 => "SHA-384",
+      Self::Sha512 => "SHA-512",
+    }
+  }
+
+  fn from_jar_name(name: &str) -> Option<Self> {
+    match name.to_ascii_uppercase().as_str() {
+      "SHA1" | "SHA-1" => Some(Self::Sha1),
+      "SHA-256" => Some(Self::Sha256),
+      "SHA-384" => Some(Self::Sha384),
+      "SHA-512" => Some(Self::Sha512),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum KeyKind {
+  RsaPkcs1,
+  RsaPss,
+  Ecdsa,
+  Dsa,
+}
+
+/// APK 内容摘要的计算方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ContentDigest {
+  ChunkedSha256,
+  ChunkedSha512,
+  VeritySha256,
+}
+
+/// v2/v3/v4 的签名算法 ID
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Algorithm {
+  pub id: u32,
+  pub key: KeyKind,
+  pub hash: Hash,
+  pub content: ContentDigest,
+}
+
+pub(crate) const ALGORITHMS: &[Algorithm] = &[
+  Algorithm { id: 0x0101, key: KeyKind::RsaPss, hash: Hash::Sha256, content: ContentDigest::ChunkedSha256 },
+  Algorithm { id: 0x0102, key: KeyKind::RsaPss, hash: Hash::Sha512, content: ContentDigest::ChunkedSha512 },
+  Algorithm { id: 0x0103, key: KeyKind::RsaPkcs1, hash: Hash::Sha256, content: ContentDigest::ChunkedSha256 },
+  Algorithm { id: 0x0104, key: KeyKind::RsaPkcs1, hash: Hash::Sha512, content: ContentDigest::ChunkedSha512 },
+  Algorithm { id: 0x0201, key: KeyKind::Ecdsa, hash: Hash::Sha256, content: ContentDigest::ChunkedSha256 },
+  Algorithm { id: 0x0202, key: KeyKind::Ecdsa, hash: Hash::Sha512, content: ContentDigest::ChunkedSha512 },
+  Algorithm { id: 0x0301, key: KeyKind::Dsa, hash: Hash::Sha256, content: ContentDigest::ChunkedSha256 },
+  Algorithm { id: 0x0421, key: KeyKind::RsaPkcs1, hash: Hash::Sha256, content: ContentDigest::VeritySha256 },
+  Algorithm { id: 0x0423, key: KeyKind::Ecdsa, hash: Hash::Sha256, content: ContentDigest::VeritySha256 },
+  Algorithm { id: 0x0425, key: KeyKind::Dsa, hash: Hash::Sha256, content: ContentDigest::VeritySha256 },
+];
+
+pub(crate) fn algorithm(id: u32) -> Option<Algorithm> {
+  ALGORITHMS.iter().find(|a| a.id == id).copied()
+}
+
+/// 用 `SubjectPublicKeyInfo` 格式的公钥校验签名
+pub(crate) fn verify_signature(
+  public_key: &[u8],
+  key: KeyKind,
+  hash: Hash,
+  message: &[u8],
+  signature: &[u8],
+) -> std::result::Result<(), String> {
+  let failed = |_| "签名校验失败".to_string();
+  match key {
+    KeyKind::RsaPkcs1 | KeyKind::RsaPss => {
+      let public_key = RsaPublicKey::from_public_key_der(public_key).map_err(|e| format!("无法解析 RSA 公钥：{}", e))?;
+      let pss = key == KeyKind::RsaPss;
+      match hash {
+        Hash::Sha1 => verify_rsa::<sha1::Sha1>(public_key, pss, message, signature),
+        Hash::Sha256 => verify_rsa::<Sha256>(public_key, pss, message, signature),
+        Hash::Sha384 => verify_rsa::<Sha384>(public_key, pss, message, signature),
+        Hash::Sha512 => verify_rsa::<Sha512>(public_key, pss, message, signature),
+      }
+      .map_err(failed)
+    }
+    KeyKind::Ecdsa => {
+      let prehash = hash.digest(message);
+      if let Ok(public_key) = p256::ecdsa::VerifyingKey::from_public_key_der(public_key) {
+        let signature = p256::ecdsa::Signature::from_der(signature).map_err(failed)?;
+        return public_key.verify_prehash(&prehash, &signature).map_err(failed);
+      }
+      if let Ok(public_key) = p384::ecdsa::VerifyingKey::from_public_key_der(public_key) {
+        let signature = p384::ecdsa::Signature::from_der(signature).map_err(failed)?;
+        return public_key.verify_prehash(&prehash, &signature).map_err(failed);
+      }
+      Err("不支持的 EC 公钥，仅支持 P-256 与 P-384".into())
+    }
+    KeyKind::Dsa => Err("不支持校验 DSA 签名".into()),
+  }
+}
+
+fn verify_rsa<D>(key: RsaPublicKey, pss: bool, message: &[u8], signature: &[u8]) -> rsa::signature::Result<()>
+where
+  D: Digest + AssociatedOid + FixedOutputReset,
+{
+  if pss {
+    let signature = rsa::pss::Signature::try_from(signature)?;
+    rsa::pss::VerifyingKey::<D>::new(key).verify(message, &signature)
+  } else {
+    let signature = rsa::pkcs1v15::Signature::try_from(signature)?;
+    rsa::pkcs1v15::VerifyingKey::<D>::new(key).verify(message, &signature)
+  }
+}
+
+/// 解析 DER 格式的证书
+pub(crate) fn certificate_info(der: &[u8]) -> std::result::Result<CertificateInfo, String> {
+  let cert = Certificate::from_der(der).map_err(|e| format!("无法解析证书：{}", e))?;
+  let tbs = &cert.tbs_certificate;
+  let subject = tbs.subject.to_string();
+  let spki = &tbs.subject_public_key_info;
+  let key_algorithm = match spki.algorithm.oid {
+    OID_RSA => {
+      let bits = spki
+        .to_der()
+        .ok()
+        .and_then(|der| RsaPublicKey::from_public_key_der(&der).ok())
+        .map(|k| rsa::traits::PublicKeyParts::size(&k) * 8);
+      bits.map_or_else(|| "RSA".to_string(), |bits| format!("RSA {}", bits))
+    }
+    OID_EC => match spki.algorithm.parameters.as_ref().and_then(|p| p.decode_as::<ObjectIdentifier>().ok()) {
+      Some(OID_P256) => "EC P-256".to_string(),
+      Some(OID_P384) => "EC P-384".to_string(),
+      Some(curve) => format!("EC {}", curve),
+      None => "EC".to_string(),
+    },
+    OID_DSA => "DSA".to_string(),
+    oid => oid.to_string(),
+  };
+  Ok(CertificateInfo {
+    debug: subject.split(',').any(|rdn| rdn.trim() == DEBUG_CERT_CN),
+    subject,
+    issuer: tbs.issuer.to_string(),
+    serial: hex::encode(tbs.serial_number.as_bytes()),
+    sha1: hex::encode(Hash::Sha1.digest(der)),
+    sha256: hex::encode(Hash::Sha256.digest(der)),
+    not_before: tbs.validity.not_before.to_unix_duration().as_millis() as u64,
+    not_after: tbs.validity.not_after.to_unix_duration().as_millis() as u64,
+    key_algorithm,
+  })
+}
+
+/// 证书已过期、尚未生效或将在 [`EXPIRY_WARNING_DAYS`] 天内过期时的提示
+pub(crate) fn validity_warning(cert: &CertificateInfo, now: u64) -> Option<String> {
+  if cert.not_after < now {
+    Some(format!("证书已过期（{}）：{}", format_date(cert.not_after), cert.subject))
+  } else if cert.not_before > now {
+    Some(format!("证书尚未生效（{}）：{}", format_date(cert.not_before), cert.subject))
+  } else if cert.not_after - now < EXPIRY_WARNING_DAYS * DAY_MILLIS {
+    let days = (cert.not_after - now) / DAY_MILLIS;
+    Some(format!("证书将在 {} 天后过期（{}）：{}", days, format_date(cert.not_after), cert.subject))
+  } else {
+    None
+  }
+}
+
+/// 证书中的 `SubjectPublicKeyInfo`（DER）
+fn certificate_public_key(der: &[u8]) -> Option<Vec<u8>> {
+  let cert = Certificate::from_der(der).ok()?;
+  cert.tbs_certificate.subject_public_key_info.to_der().ok()
+}
+
+/// 校验 APK 的签名。`release` 为 true 时，使用调试证书签名会给出警告
+pub fn verify_apk(path: &Path, release: bool) -> Result<SignatureReport> {
+  let data = std::fs::read(path).map_err(|e| Error::io(format!("读取 {} 失败", path.display()), e))?;
+  verify_data(path, &data, release)
+}
+
+/// 校验内存中的 APK，`path` 用于报告与查找同目录的 `.idsig`
+pub(crate) fn verify_data(path: &Path, data: &[u8], release: bool) -> Result<SignatureReport> {
+  let apk = Apk::parse(data)?;
+  let mut zip = ZipArchive::new(Cursor::new(data))
+    .map_err(|e| Error::Artifact(format!("{} 不是有效的 APK：{}", path.display(), e)))?;
+
+  let mut digests = ContentDigests::new(&apk);
+  let mut schemes = Vec::new();
+  let block_schemes = [(SignatureScheme::V2, V2_BLOCK_ID), (SignatureScheme::V3, V3_BLOCK_ID), (SignatureScheme::V31, V31_BLOCK_ID)];
+  let present = |id| apk.get(id).is_some();
+  if let Some(v1) = verify_v1(&mut zip, present(V2_BLOCK_ID), present(V3_BLOCK_ID)) {
+    schemes.push(v1);
+  }
+  // v4 签名的 APK 摘要取自 v2/v3 签名者的内容摘要
+  let mut apk_digests = Vec::new();
+  for (scheme, id) in block_schemes {
+    if let Some(value) = apk.get(id) {
+      schemes.push(verify_block(scheme, value, &mut digests, &mut apk_digests));
+    }
+  }
+  let idsig = idsig_path(path);
+  if idsig.exists() {
+    schemes.push(verify_v4(&idsig, data, &apk_digests));
+  }
+
+  let mut signers: Vec<CertificateInfo> = Vec::new();
+  for cert in schemes.iter().flat_map(|s| s.signers.iter()) {
+    if !signers.iter().any(|c| c.sha256 == cert.sha256) {
+      signers.push(cert.clone());
+    }
+  }
+  let debug_signed = signers.iter().any(|c| c.debug);
+  let mut warnings = Vec::new();
+  if schemes.is_empty() {
+    warnings.push("APK 未签名".to_string());
+  } else if schemes.iter().all(|s| s.scheme == SignatureScheme::V1) {
+    warnings.push("仅有 v1 签名，Android 11 及以上（targetSdk ≥ 30）要求 v2 或更高版本的签名".to_string());
+  }
+  let now = now_millis();
+  warnings.extend(signers.iter().filter_map(|cert| validity_warning(cert, now)));
+  let fingerprints = |scheme| {
+    schemes
+      .iter()
+      .find(|s| s.scheme == scheme && !s.signers.is_empty())
+      .map(|s| s.signers.iter().map(|c| c.sha256.clone()).collect::<Vec<_>>())
+  };
+  if let (Some(v1), Some(v2)) = (fingerprints(SignatureScheme::V1), fingerprints(SignatureScheme::V2)) {
+    if v1 != v2 {
+      warnings.push("v1 与 v2 签名的证书不一致".to_string());
+    }
+  }
+  if release && debug_signed {
+    warnings.push("release 构建使用了 Android 调试证书签名".to_string());
+  }
+
+  Ok(SignatureReport {
+    path: path.to_string_lossy().to_string(),
+    verified: !schemes.is_empty() && schemes.iter().all(|s| s.verified),
+    schemes,
+    signers,
+    debug_signed,
+    warnings,
+  })
+}
+
+/// v4 签名文件：`app.apk` 对应 `app.apk.idsig`
+fn idsig_path(path: &Path) -> PathBuf {
+  let mut name = path.as_os_str().to_owned();
+  name.push(".idsig");
+  PathBuf::from(name)
+}
+
+/// 按计算方式缓存的 APK 内容摘要
+struct ContentDigests<'a> {
+  apk: &'a Apk<'a>,
+  cache: HashMap<ContentDigest, std::result::Result<Vec<u8>, String>>,
+}
+
+impl<'a> ContentDigests<'a> {
+  fn new(apk: &'a Apk<'a>) -> Self {
+    Self { apk, cache: HashMap::new() }
+  }
+
+  fn get(&mut self, kind: ContentDigest) -> std::result::Result<Vec<u8>, String> {
+    let apk = self.apk;
+    self.cache.entry(kind).or_insert_with(|| content_digest(apk, kind)).clone()
+  }
+}
+
+/// 计算 v2/v3 签名覆盖的内容摘要：ZIP 条目、中央目录与 EOCD
+pub(crate) fn content_digest(apk: &Apk, kind: ContentDigest) -> std::result::Result<Vec<u8>, String> {
+  let eocd = apk.eocd_without_block();
+  let sections = [apk.entries(), apk.central_directory(), &eocd];
+  match kind {
+    ContentDigest::ChunkedSha256 => Ok(chunked_digest(Hash::Sha256, &sections)),
+    ContentDigest::ChunkedSha512 => Ok(chunked_digest(Hash::Sha512, &sections)),
+    ContentDigest::VeritySha256 => {
+      if sections[0].len() % VERITY_BLOCK_SIZE != 0 {
+        return Err("签名块未按 4096 字节对齐，无法计算 verity 摘要".into());
+      }
+      let data = sections.concat();
+      let mut digest = verity_root(&data, &[]).to_vec();
+      digest.extend_from_slice(&(data.len() as u64).to_le_bytes());
+      Ok(digest)
+    }
+  }
+}
+
+/// 按 1 MB 分块计算摘要，再对分块摘要计算摘要
+fn chunked_digest(hash: Hash, sections: &[&[u8]]) -> Vec<u8> {
+  let chunks: Vec<&[u8]> = sections.iter().flat_map(|s| s.chunks(CHUNK_SIZE)).collect();
+  let mut top = vec![0x5a];
+  top.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+  for chunk in chunks {
+    top.extend(hash.digest_parts(&[&[0xa5], &(chunk.len() as u32).to_le_bytes(), chunk]));
+  }
+  hash.digest(&top)
+}
+
+/// fs-verity 的 Merkle 树根哈希（SHA-256，4096 字节块，不足的部分补零）
+pub(crate) fn verity_root(data: &[u8], salt: &[u8]) -> [u8; 32] {
+  let zeros = [0u8; VERITY_BLOCK_SIZE];
+  let hash_blocks = |data: &[u8]| {
+    let mut level = Vec::with_capacity(data.len() / VERITY_BLOCK_SIZE * 32 + 32);
+    for block in data.chunks(VERITY_BLOCK_SIZE) {
+      level.extend(Hash::Sha256.digest_parts(&[salt, block, &zeros[block.len()..]]));
+    }
+    level
+  };
+  let mut level = hash_blocks(data);
+  while level.len() > VERITY_BLOCK_SIZE {
+    level = hash_blocks(&level);
+  }
+  let root = Hash::Sha256.digest_parts(&[salt, &level, &zeros[level.len()..]]);
+  root.try_into().unwrap_or_default()
+}
+
+/// 校验 v2/v3/v3.1 签名块
+fn verify_block(
+  scheme: SignatureScheme,
+  value: &[u8],
+  digests: &mut ContentDigests,
+  apk_digests: &mut Vec<Vec<u8>>,
+) -> SchemeResult {
+  let mut result = SchemeResult::new(scheme);
+  let signers = match Reader::new(value, "APK 签名块").items() {
+    Ok(signers) => signers,
+    Err(e) => {
+      result.error(e.to_string());
+      return result.finish();
+    }
+  };
+  if signers.is_empty() {
+    result.error("没有签名者");
+  }
+  for signer in signers {
+    if let Err(e) = verify_signer(scheme, signer, digests, apk_digests, &mut result) {
+      result.error(e.to_string());
+    }
+  }
+  result.finish()
+}
+
+fn verify_signer(
+  scheme: SignatureScheme,
+  signer: &[u8],
+  digests: &mut ContentDigests,
+  apk_digests: &mut Vec<Vec<u8>>,
+  result: &mut SchemeResult,
+) -> Result<()> {
+  let v3 = scheme != SignatureScheme::V2;
+  let mut reader = Reader::new(signer, "APK 签名块");
+  let signed_data = reader.bytes()?;
+  let sdk_range = if v3 { Some((reader.u32()?, reader.u32()?)) } else { None };
+  if let Some((min_sdk, max_sdk)) = sdk_range {
+    // 多个签名者时合并为覆盖所有签名者的 SDK 范围
+    result.min_sdk = Some(result.min_sdk.map_or(min_sdk, |m| m.min(min_sdk)));
+    result.max_sdk = Some(result.max_sdk.map_or(max_sdk, |m| m.max(max_sdk)));
+  }
+  let signatures = reader.items()?;
+  let public_key = reader.bytes()?;
+
+  let mut data = Reader::new(signed_data, "APK 签名块");
+  let digest_items = data.items()?;
+  let certificates = data.items()?;
+  // signed data 之外的 SDK 范围不受签名保护，必须与 signed data 中的一致
+  if let Some((min_sdk, max_sdk)) = sdk_range {
+    let (signed_min, signed_max) = (data.u32()?, data.u32()?);
+    if (signed_min, signed_max) != (min_sdk, max_sdk) {
+      result.error(format!(
+        "签名者的 SDK 范围 {}-{} 与签名数据中的 {}-{} 不一致",
+        min_sdk, max_sdk, signed_min, signed_max
+      ));
+    }
+  }
+
+  // 校验签名者对 signed data 的签名
+  let mut signed_ids = Vec::new();
+  for item in signatures {
+    let mut item = Reader::new(item, "APK 签名块");
+    let id = item.u32()?;
+    let signature = item.bytes()?;
+    let Some(alg) = algorithm(id).filter(|a| a.key != KeyKind::Dsa) else {
+      continue;
+    };
+    if let Err(e) = verify_signature(public_key, alg.key, alg.hash, signed_data, signature) {
+      result.error(format!("{}（算法 0x{:04x}）", e, id));
+    }
+    signed_ids.push(id);
+  }
+  if signed_ids.is_empty() {
+    result.error("没有支持的签名算法");
+    return Ok(());
+  }
+
+  // 证书公钥必须与签名者公钥一致
+  let Some(cert) = certificates.first() else {
+    result.error("签名者没有证书");
+    return Ok(());
+  };
+  match certificate_info(cert) {
+    Ok(info) => result.signers.push(info),
+    Err(e) => result.error(e),
+  }
+  if certificate_public_key(cert).as_deref() != Some(public_key) {
+    result.error("证书公钥与签名者公钥不一致");
+  }
+
+  // 校验内容摘要
+  let mut digest_ids = Vec::new();
+  for item in digest_items {
+    let mut item = Reader::new(item, "APK 签名块");
+    let id = item.u32()?;
+    let expected = item.bytes()?;
+    digest_ids.push(id);
+    let Some(alg) = algorithm(id) else {
+      continue;
+    };
+    apk_digests.push(expected.to_vec());
+    match digests.get(alg.content) {
+      Ok(actual) if actual == expected => {}
+      Ok(_) => result.error(format!("APK 内容摘要不匹配（算法 0x{:04x}），文件可能已被修改", id)),
+      Err(e) => result.error(e),
+    }
+  }
+  if signed_ids.iter().any(|id| !digest_ids.contains(id)) {
+    result.error("签名算法与摘要算法不一致");
+  }
+  Ok(())
+}
+
+/// 校验 v4 签名文件（`.idsig`）
+fn verify_v4(idsig: &Path, apk: &[u8], apk_digests: &[Vec<u8>]) -> SchemeResult {
+  let mut result = SchemeResult::new(SignatureScheme::V4);
+  match std::fs::read(idsig) {
+    Ok(data) => {
+      if let Err(e) = verify_idsig(&data, apk, apk_digests, &mut result) {
+        result.error(e.to_string());
+      }
+    }
+    Err(e) => result.error(format!("读取 {} 失败：{}", idsig.display(), e)),
+  }
+  result.finish()
+}
+
+fn verify_idsig(data: &[u8], apk: &[u8], apk_digests: &[Vec<u8>], result: &mut SchemeResult) -> Result<()> {
+  let what = ".idsig";
+  let mut reader = Reader::new(data, what);
+  let _version = reader.u32()?;
+  let mut hashing = reader.nested()?;
+  let mut signing = reader.nested()?;
+
+  let hash_algorithm = hashing.u32()?;
+  let log2_block_size = hashing.u8()?;
+  let salt = hashing.bytes()?;
+  let root_hash = hashing.bytes()?;
+
+  let apk_digest = signing.bytes()?;
+  let certificate = signing.bytes()?;
+  let additional_data = signing.bytes()?;
+  let public_key = signing.bytes()?;
+  let algorithm_id = signing.u32()?;
+  let signature = signing.bytes()?;
+
+  match certificate_info(certificate) {
+    Ok(info) => result.signers.push(info),
+    Err(e) => result.error(e),
+  }
+  if certificate_public_key(certificate).as_deref() != Some(public_key) {
+    result.error("证书公钥与签名者公钥不一致");
+  }
+
+  // 被签名的数据：各字段按 v4 格式重新序列化
+  let with_len = |b: &[u8]| [&(b.len() as u32).to_le_bytes()[..], b].concat();
+  let fields = [with_len(salt), with_len(root_hash), with_len(apk_digest), with_len(certificate), with_len(additional_data)];
+  let size = 4 + 8 + 4 + 1 + fields.iter().map(Vec::len).sum::<usize>();
+  let mut signed = Vec::with_capacity(size);
+  signed.extend_from_slice(&(size as u32).to_le_bytes());
+  signed.extend_from_slice(&(apk.len() as u64).to_le_bytes());
+  signed.extend_from_slice(&hash_algorithm.to_le_bytes());
+  signed.push(log2_block_size);
+  for field in &fields {
+    signed.extend_from_slice(field);
+  }
+  match algorithm(algorithm_id) {
+    Some(alg) => {
+      if let Err(e) = verify_signature(public_key, alg.key, alg.hash, &signed, signature) {
+        result.error(e);
+      }
+    }
+    None => result.error(format!("不支持的签名算法 0x{:04x}", algorithm_id)),
+  }
+
+  // 1 为 SHA-256，块大小 4096
+  if hash_algorithm != 1 || log2_block_size != 12 {
+    result.error("不支持的哈希树参数");
+  } else if verity_root(apk, salt)[..] != *root_hash {
+    result.error("APK 的哈希树根与 .idsig 不一致，文件可能已被修改");
+  }
+  if !apk_digests.iter().any(|d| d == apk_digest) {
+    result.error("v4 签名的 APK 摘要与 v2/v3 签名不一致");
+  }
+  Ok(())
+}
+
+/// MANIFEST.MF / .SF 中的一节：原始字节与属性
+struct Section<'a> {
+  raw: &'a [u8],
+  attributes: Vec<(String, String)>,
+}
+
+impl Section<'_> {
+  fn get(&self, name: &str) -> Option<&str> {
+    self.attributes.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+  }
+
+  /// 摘要属性，如 `SHA-256-Digest`（`suffix` 为 `-Digest`）
+  fn digests(&self, suffix: &str) -> Vec<(Hash, &str)> {
+    self
+      .attributes
+      .iter()
+      .filter_map(|(k, v)| {
+        let name = k.strip_suffix(suffix)?;
+        Some((Hash::from_jar_name(name)?, v.as_str()))
+      })
+      .collect()
+  }
+}
+
+/// 按空行分节；以空格开头的行是上一行的续行
+fn manifest_sections(data: &[u8]) -> Vec<Section<'_>> {
+  let mut sections = Vec::new();
+  let mut start = 0;
+  let mut attributes: Vec<(String, String)> = Vec::new();
+  let mut pos = 0;
+  while pos < data.len() {
+    let end = data[pos..].iter().position(|b| *b == b'\n').map_or(data.len(), |i| pos + i + 1);
+    let line = String::from_utf8_lossy(&data[pos..end]);
+    let line = line.trim_end_matches(['\r', '\n']);
+    pos = end;
+    if line.is_empty() {
+      if !attributes.is_empty() {
+        sections.push(Section { raw: &data[start..pos], attributes: std::mem::take(&mut attributes) });
+      }
+      start = pos;
+    } else if let Some(rest) = line.strip_prefix(' ') {
+      if let Some((_, value)) = attributes.last_mut() {
+        value.push_str(rest);
+      }
+    } else if let Some((key, value)) = line.split_once(':') {
+      attributes.push((key.trim().to_string(), value.trim_start().to_string()));
+    }
+  }
+  if !attributes.is_empty() {
+    sections.push(Section { raw: &data[start..], attributes });
+  }
+  sections
+}
+
+fn digest_matches(hash: Hash, data: &[u8], expected: &str) -> bool {
+  BASE64.decode(expected.trim()).is_ok_and(|expected| hash.digest(data) == expected)
+}
+
+fn read_zip_entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> std::result::Result<Vec<u8>, String> {
+  let mut file = zip.by_name(name).map_err(|e| format!("读取 {} 失败：{}", name, e))?;
+  let mut buf = Vec::with_capacity(file.size() as usize);
+  file.read_to_end(&mut buf).map_err(|e| format!("读取 {} 失败：{}", name, e))?;
+  Ok(buf)
+}
+
+/// 校验 JAR 签名（v1），没有 .SF 文件时返回 None
+fn verify_v1(zip: &mut ZipArchive<Cursor<&[u8]>>, has_v2: bool, has_v3: bool) -> Option<SchemeResult> {
+  let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+  // META-INF 下的顶层文件不参与 v1 签名，与 Android 系统的校验一致
+  let in_meta_inf = |name: &str| name.strip_prefix("META-INF/").is_some_and(|rest| !rest.contains('/'));
+  let sf_files: Vec<&String> = names.iter().filter(|n| in_meta_inf(n) && n.to_ascii_uppercase().ends_with(".SF")).collect();
+  if sf_files.is_empty() {
+    return None;
+  }
+
+  let mut result = SchemeResult::new(SignatureScheme::V1);
+  let manifest = match read_zip_entry(zip, "META-INF/MANIFEST.MF") {
+    Ok(manifest) => manifest,
+    Err(e) => {
+      result.error(e);
+      return Some(result.finish());
+    }
+  };
+  let sections = manifest_sections(&manifest);
+  let by_name: HashMap<&str, &Section> = sections.iter().filter_map(|s| Some((s.get("Name")?, s))).collect();
+
+  for sf_name in sf_files {
+    let base = &sf_name[..sf_name.len() - 3];
+    let block_name = names.iter().find(|n| {
+      n.len() > base.len()
+        && n.starts_with(base)
+        && matches!(n[base.len()..].to_ascii_uppercase().as_str(), ".RSA" | ".EC" | ".DSA")
+    });
+    let Some(block_name) = block_name else {
+      result.error(format!("{} 没有对应的签名块文件", sf_name));
+      continue;
+    };
+    let sf = match read_zip_entry(zip, sf_name) {
+      Ok(sf) => sf,
+      Err(e) => {
+        result.error(e);
+        continue;
+      }
+    };
+    match read_zip_entry(zip, block_name).and_then(|block| verify_pkcs7(&block, &sf)) {
+      Ok(cert) => match certificate_info(&cert) {
+        Ok(info) => result.signers.push(info),
+        Err(e) => result.error(e),
+      },
+      Err(e) => {
+        result.error(format!("{}：{}", block_name, e));
+        continue;
+      }
+    }
+
+    let sf_sections = manifest_sections(&sf);
+    let Some(main) = sf_sections.first() else {
+      result.error(format!("{} 为空", sf_name));
+      continue;
+    };
+    // 声明了更高版本签名但 APK 中没有，说明签名被剥离
+    if let Some(signed) = main.get("X-Android-APK-Signed") {
+      let ids: Vec<&str> = signed.split(',').map(str::trim).collect();
+      if ids.contains(&"2") && !has_v2 {
+        result.error(format!("{} 声明了 v2 签名，但 APK 中没有 v2 签名，可能已被剥离", sf_name));
+      }
+      if ids.contains(&"3") && !has_v3 {
+        result.error(format!("{} 声明了 v3 签名，但 APK 中没有 v3 签名，可能已被剥离", sf_name));
+      }
+    }
+    // 优先按整个 MANIFEST.MF 的摘要校验，不一致时逐节校验
+    let whole = main.digests("-Digest-Manifest");
+    if whole.is_empty() || !whole.iter().all(|(hash, expected)| digest_matches(*hash, &manifest, expected)) {
+      // MANIFEST.MF 主属性不在任何一节中，需要单独校验
+      let main_digests = main.digests("-Digest-Manifest-Main-Attributes");
+      let manifest_main = sections.first().filter(|s| s.get("Name").is_none());
+      if !main_digests.is_empty()
+        && !manifest_main.is_some_and(|m| main_digests.iter().all(|(hash, expected)| digest_matches(*hash, m.raw, expected)))
+      {
+        result.error(format!("{} 中 MANIFEST.MF 主属性的摘要不一致，文件可能已被修改", sf_name));
+      }
+      for section in &sf_sections[1..] {
+        let Some(name) = section.get("Name") else {
+          continue;
+        };
+        let matched = by_name.get(name).is_some_and(|m| {
+          let digests = section.digests("-Digest");
+          !digests.is_empty() && digests.iter().all(|(hash, expected)| digest_matches(*hash, m.raw, expected))
+        });
+        if !matched {
+          result.error(format!("{} 中 {} 的摘要与 MANIFEST.MF 不一致", sf_name, name));
+        }
+      }
+      // 逐节校验时 .SF 中必须有每个条目的节，否则新增条目与对应的 MANIFEST.MF 节不受签名保护
+      let signed: Vec<&str> = sf_sections[1..].iter().filter_map(|s| s.get("Name")).collect();
+      for name in names.iter().filter(|n| !n.ends_with('/') && !in_meta_inf(n) && !signed.contains(&n.as_str())) {
+        result.error(format!("{} 未受 {} 的签名保护", name, sf_name));
+      }
+    }
+  }
+
+  // 每个条目都必须在 MANIFEST.MF 中声明且摘要一致
+  for name in names.iter().filter(|n| !n.ends_with('/') && !in_meta_inf(n)) {
+    let Some(section) = by_name.get(name.as_str()) else {
+      result.error(format!("{} 未在 MANIFEST.MF 中声明", name));
+      continue;
+    };
+    let digests = section.digests("-Digest");
+    if digests.is_empty() {
+      result.error(format!("{} 没有支持的摘要算法", name));
+      continue;
+    }
+    match read_zip_entry(zip, name) {
+      Ok(data) => {
+        if !digests.iter().all(|(hash, expected)| digest_matches(*hash, &data, expected)) {
+          result.error(format!("{} 的摘要不匹配，文件可能已被修改", name));
+        }
+      }
+      Err(e) => result.error(e),
+    }
+  }
+  Some(result.finish())
+}
+
+/// 校验 PKCS#7 签名块对 .SF 的签名，返回签名者证书（DER）
+fn verify_pkcs7(block: &[u8], content: &[u8]) -> std::result::Result<Vec<u8>, String> {
+  let invalid = |e: x509_cert::der::Error| format!("无法解析签名块：{}", e);
+  let info = ContentInfo::from_der(block).map_err(invalid)?;
+  let signed: SignedData = info.content.decode_as().map_err(invalid)?;
+  let certificates: Vec<&Certificate> = signed
+    .certificates
+    .iter()
+    .flat_map(|set| set.0.iter())
+    .filter_map(|c| match c {
+      CertificateChoices::Certificate(cert) => Some(cert),
+      _ => None,
+    })
+    .collect();
+  let signer = signed.signer_infos.0.iter().next().ok_or("签名块中没有签名者")?;
+  let cert = match &signer.sid {
+    SignerIdentifier::IssuerAndSerialNumber(id) => certificates
+      .iter()
+      .find(|c| c.tbs_certificate.issuer == id.issuer && c.tbs_certificate.serial_number == id.serial_number),
+    SignerIdentifier::SubjectKeyIdentifier(_) => certificates.first(),
+  }
+  .ok_or("签名块中没有签名者的证书")?;
+
+  let hash = Hash::from_oid(&signer.digest_alg.oid).ok_or("不支持的摘要算法")?;
+  let (key, hash) = jar_signature_algorithm(&signer.signature_algorithm.oid, hash).ok_or("不支持的签名算法")?;
+  let message = match &signer.signed_attrs {
+    Some(attributes) => {
+      let digest = attributes
+        .iter()
+        .find(|a| a.oid == OID_MESSAGE_DIGEST)
+        .and_then(|a| a.values.iter().next())
+        .and_then(|v| v.decode_as::<OctetString>().ok())
+        .ok_or("签名块中没有 messageDigest 属性")?;
+      if digest.as_bytes() != hash.digest(content) {
+        return Err("签名块中的摘要与 .SF 不一致".into());
+      }
+      attributes.to_der().map_err(invalid)?
+    }
+    None => content.to_vec(),
+  };
+  let public_key = cert.tbs_certificate.subject_public_key_info.to_der().map_err(invalid)?;
+  verify_signature(&public_key, key, hash, &message, signer.signature.as_bytes())?;
+  cert.to_der().map_err(invalid)
+}
+
+/// JAR 签名块中的签名算法；只有密钥类型的 OID 时使用摘要算法
+fn jar_signature_algorithm(oid: &ObjectIdentifier, digest: Hash) -> Option<(KeyKind, Hash)> {
+  Some(match oid.to_string().as_str() {
+    "1.2.840.113549.1.1.1" => (KeyKind::RsaPkcs1, digest),
+    "1.2.840.113549.1.1.5" => (KeyKind::RsaPkcs1, Hash::Sha1),
+    "1.2.840.113549.1.1.11" => (KeyKind::RsaPkcs1, Hash::Sha256),
+    "1.2.840.113549.1.1.12" => (KeyKind::RsaPkcs1, Hash::Sha384),
+    "1.2.840.113549.1.1.13" => (KeyKind::RsaPkcs1, Hash::Sha512),
+    "1.2.840.10045.2.1" => (KeyKind::Ecdsa, digest),
+    "1.2.840.10045.4.1" => (KeyKind::Ecdsa, Hash::Sha1),
+    "1.2.840.10045.4.3.2" => (KeyKind::Ecdsa, Hash::Sha256),
+    "1.2.840.10045.4.3.3" => (KeyKind::Ecdsa, Hash::Sha384),
+    "1.2.840.10045.4.3.4" => (KeyKind::Ecdsa, Hash::Sha512),
+    "1.2.840.10040.4.1" | "2.16.840.1.101.3.4.3.2" => (KeyKind::Dsa, digest),
+    _ => return None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    keystores::{tests::signing_key, KeyAlgorithm},
+    signer::{sign, SigningSchemes},
+    signing_block::tests::sample_apk,
+    zipalign::{self, ZipEntry},
+  };
+
+  const V1_ONLY: SigningSchemes = SigningSchemes { v1: true, v2: false, v3: false };
+
+  fn verify(data: &[u8]) -> SignatureReport {
+    verify_data(Path::new("test.apk"), data, false).unwrap()
+  }
+
+  /// 修改 ZIP 条目后重新打包，不重新签名
+  fn rewrite(data: &[u8], edit: impl FnOnce(&mut Vec<ZipEntry>)) -> Vec<u8> {
+    let apk = Apk::parse(data).unwrap();
+    let mut entries = zipalign::read_entries(&apk).unwrap();
+    edit(&mut entries);
+    zipalign::write_aligned(&entries).unwrap()
+  }
+
+  /// 用 `edit` 修改 MANIFEST.MF
+  fn edit_manifest(entries: &mut [ZipEntry], edit: impl FnOnce(&str) -> String) {
+    let entry = entries.iter_mut().find(|e| e.name == "META-INF/MANIFEST.MF").unwrap();
+    let manifest = String::from_utf8(entry.contents().unwrap()).unwrap();
+    *entry = ZipEntry::deflated(entry.name.clone(), edit(&manifest).as_bytes());
+  }
+
+  fn scheme(report: &SignatureReport, scheme: SignatureScheme) -> &SchemeResult {
+    report.schemes.iter().find(|s| s.scheme == scheme).unwrap()
+  }
+
+  #[test]
+  fn unsigned() {
+    let report = verify(&sample_apk());
+    assert!(!report.verified);
+    assert!(report.schemes.is_empty());
+    assert_eq!(report.warnings, ["APK 未签名"]);
+    assert_eq!(report.summary(), "未签名");
+  }
+
+  #[test]
+  fn signed() {
+    let data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), SigningSchemes::default(), None).unwrap();
+    let report = verify(&data);
+    assert!(report.verified, "{:?}", report.schemes);
+    let labels: Vec<&str> = report.schemes.iter().map(|s| s.scheme.label()).collect();
+    assert_eq!(labels, ["v1", "v2", "v3"]);
+    assert_eq!(report.signers.len(), 1);
+    assert!(!report.debug_signed);
+    let v3 = scheme(&report, SignatureScheme::V3);
+    assert_eq!((v3.min_sdk, v3.max_sdk), (Some(28), Some(i32::MAX as u32)));
+  }
+
+  #[test]
+  fn modified_entry() {
+    let mut data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), SigningSchemes::default(), None).unwrap();
+    let pos = data.windows(4).position(|w| w == b"\x7fELF").unwrap();
+    data[pos + 1] ^= 1;
+    let report = verify(&data);
+    assert!(!report.verified);
+    for result in &report.schemes {
+      assert!(!result.verified, "{} 应校验失败", result.scheme.label());
+      assert!(!result.errors.is_empty());
+    }
+  }
+
+  #[test]
+  fn stripped_block() {
+    let data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), SigningSchemes::default(), None).unwrap();
+    let stripped = Apk::parse(&data).unwrap().with_pairs(&[]);
+    let report = verify(&stripped);
+    assert!(!report.verified);
+    let v1 = scheme(&report, SignatureScheme::V1);
+    assert!(v1.errors.iter().any(|e| e.contains("可能已被剥离")), "{:?}", v1.errors);
+  }
+
+  #[test]
+  fn unsigned_manifest_section() {
+    let data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), V1_ONLY, None).unwrap();
+    // 新增条目并在 MANIFEST.MF 中补上对应的节，.SF 不变
+    let added = rewrite(&data, |entries| {
+      entries.push(ZipEntry::deflated("assets/extra.txt", b"extra"));
+      let digest = BASE64.encode(Hash::Sha256.digest(b"extra"));
+      edit_manifest(entries, |m| format!("{}Name: assets/extra.txt\r\nSHA-256-Digest: {}\r\n\r\n", m, digest));
+    });
+    let report = verify(&added);
+    assert!(!report.verified);
+    let v1 = scheme(&report, SignatureScheme::V1);
+    assert!(v1.errors.iter().any(|e| e.starts_with("assets/extra.txt 未受")), "{:?}", v1.errors);
+  }
+
+  #[test]
+  fn manifest_main_attributes() {
+    let data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), V1_ONLY, None).unwrap();
+
+    let tampered = rewrite(&data, |entries| {
+      edit_manifest(entries, |m| m.replacen("Manifest-Version: 1.0", "Manifest-Version: 1.1", 1));
+    });
+    let report = verify(&tampered);
+    assert!(!report.verified);
+    let v1 = scheme(&report, SignatureScheme::V1);
+    assert!(v1.errors.iter().any(|e| e.contains("主属性")), "{:?}", v1.errors);
+
+    // 追加的节即使摘要正确也不受签名保护
+    let appended = rewrite(&data, |entries| {
+      entries.push(ZipEntry::stored("assets/extra.txt", b""));
+      let digest = BASE64.encode(Hash::Sha256.digest(b""));
+      edit_manifest(entries, |m| format!("{}Name: assets/extra.txt\r\nSHA-256-Digest: {}\r\n\r\n", m, digest));
+    });
+    assert!(!verify(&appended).verified);
+  }
+
+  #[test]
+  fn v3_sdk_range_outside_signed_data() {
+    let data = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), SigningSchemes::default(), None).unwrap();
+    let apk = Apk::parse(&data).unwrap();
+    // 签名者：signed data 之后是未签名的 minSdk 与 maxSdk
+    let mut v3 = apk.get(V3_BLOCK_ID).unwrap().to_vec();
+    let signed_data_len = u32::from_le_bytes(v3[8..12].try_into().unwrap()) as usize;
+    let min_sdk = 12 + signed_data_len;
+    v3[min_sdk..min_sdk + 4].copy_from_slice(&24u32.to_le_bytes());
+    let v2 = apk.get(V2_BLOCK_ID).unwrap();
+    let modified = apk.with_pairs(&[(V2_BLOCK_ID, v2), (V3_BLOCK_ID, &v3)]);
+
+    let report = verify(&modified);
+    assert!(!report.verified);
+    let v3 = scheme(&report, SignatureScheme::V3);
+    assert!(v3.errors.iter().any(|e| e.contains("SDK 范围 24-")), "{:?}", v3.errors);
+    assert!(scheme(&report, SignatureScheme::V2).verified);
+  }
+
+  #[test]
+  fn digest_parts_matches_digest() {
+    for hash in [Hash::Sha1, Hash::Sha256, Hash::Sha384, Hash::Sha512] {
+      assert_eq!(hash.digest_parts(&[b"ab", b"", b"c"]), hash.digest(b"abc"));
+    }
+  }
+}
@@ -0,0 +1,429 @@
+//! APK 签名：用签名配置中的 keystore 重新签名构建产物
+//!
+//! 与 apksigner 的顺序一致：去掉原有签名并按 zipalign 规则对齐，写入 JAR 签名（v1），
+//! 再对结果计算内容摘要，写入 v2/v3 签名块。签名后用 [`signature::verify_apk`] 校验输出。
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use cms::{
+  cert::{CertificateChoices, IssuerAndSerialNumber},
+  content_info::{CmsVersion, ContentInfo},
+  signed_data::{CertificateSet, EncapsulatedContentInfo, SignedData, SignerIdentifier, SignerInfo, SignerInfos},
+};
+use serde::{Deserialize, Serialize};
+use x509_cert::{
+  der::{
+    asn1::{ObjectIdentifier, OctetString, SetOfVec},
+    Any, Decode, Encode,
+  },
+  spki::AlgorithmIdentifierOwned,
+  Certificate,
+};
+
+use crate::{
+  config::{self, AppDirs, SigningProfile},
+  inspect,
+  keystore::{Keystore, PrivateKey, SigningKey},
+  keystores, secrets,
+  signature::{self, content_digest, Hash, SignatureReport},
+  signing_block::{length_prefixed, sequence, Apk, V2_BLOCK_ID, V3_BLOCK_ID},
+  zipalign::{self, ZipEntry},
+  Error, Result,
+};
+
+const OID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const OID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+const CREATED_BY: &str = "1.0 (Android Build Tool)";
+/// v2 签名中的防降级属性：同时有 v3 签名时，去掉 v3 会导致 v2 校验失败
+const STRIPPING_PROTECTION_ATTR: u32 = 0xbeef_f00d;
+/// v3 签名适用的最低 SDK（Android 9）
+const V3_MIN_SDK: u32 = 28;
+/// 低于 Android 4.3 的系统不支持 SHA-256 的 JAR 签名
+const SHA256_JAR_MIN_SDK: u32 = 18;
+
+/// 启用的签名方案
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct SigningSchemes {
+  pub v1: bool,
+  pub v2: bool,
+  pub v3: bool,
+}
+
+impl Default for SigningSchemes {
+  fn default() -> Self {
+    Self { v1: true, v2: true, v3: true }
+  }
+}
+
+impl SigningSchemes {
+  pub fn is_empty(&self) -> bool {
+    !self.v1 && !self.v2 && !self.v3
+  }
+
+  pub fn labels(&self) -> Vec<&'static str> {
+    [(self.v1, "v1"), (self.v2, "v2"), (self.v3, "v3")]
+      .into_iter()
+      .filter_map(|(enabled, label)| enabled.then_some(label))
+      .collect()
+  }
+}
+
+/// 签名结果
+#[derive(Serialize, Clone, Debug)]
+pub struct SignResult {
+  pub input: String,
+  pub output: String,
+  pub profile: String,
+  /// 签名所用密钥的别名
+  pub alias: String,
+  /// 对输出文件的签名校验结果
+  pub report: SignatureReport,
+}
+
+/// 用指定名称的签名配置签名 APK；`output` 与 `input` 可以相同
+pub fn sign_apk(dirs: &AppDirs, profile: &str, input: &Path, output: &Path) -> Result<SignResult> {
+  let profile = config::find_signing_profile(dirs, profile)?;
+  sign_with_profile(dirs, &profile, input, output)
+}
+
+pub fn sign_with_profile(dirs: &AppDirs, profile: &SigningProfile, input: &Path, output: &Path) -> Result<SignResult> {
+  if profile.schemes.is_empty() {
+    return Err(Error::InvalidInput("至少需要启用一种签名方案".into()));
+  }
+  if !input.is_file() {
+    return Err(Error::InvalidInput(format!("文件不存在：{}", input.display())));
+  }
+  let (keystore_path, store_password) = keystores::resolve(dirs, profile)?;
+  let keystore = Keystore::open(&keystore_path, &store_password)?;
+  let key_password = secrets::reveal_opt(dirs, profile.key_password.clone().filter(|p| !p.is_empty()))?;
+  let key_password = key_password.as_deref().unwrap_or(&store_password);
+  let key = keystore.signing_key(profile.key_alias.as_deref(), key_password)?;
+  let data = fs::read(input).map_err(|e| Error::io(format!("读取文件失败 {}", input.display()), e))?;
+  let min_sdk = inspect::inspect_apk(input).ok().and_then(|info| info.min_sdk);
+  let signed = sign(&data, &key, profile.schemes, min_sdk)?;
+
+  // 先写入临时文件并校验，通过后再替换输出文件
+  let temp = temp_path(output);
+  fs::write(&temp, signed).map_err(|e| Error::io(format!("写入文件失败 {}", temp.display()), e))?;
+  let report = match signature::verify_apk(&temp, false) {
+    Ok(report) if report.verified => report,
+    Ok(report) => {
+      let _ = fs::remove_file(&temp);
+      let errors: Vec<&str> = report.schemes.iter().flat_map(|s| s.errors.iter().map(String::as_str)).collect();
+      return Err(Error::Signing(format!("签名后校验失败：{}", errors.join("；"))));
+    }
+    Err(e) => {
+      let _ = fs::remove_file(&temp);
+      return Err(e);
+    }
+  };
+  fs::rename(&temp, output).map_err(|e| Error::io(format!("写入文件失败 {}", output.display()), e))?;
+  Ok(SignResult {
+    input: input.to_string_lossy().to_string(),
+    output: output.to_string_lossy().to_string(),
+    profile: profile.name.clone(),
+    alias: key.alias,
+    report: SignatureReport { path: output.to_string_lossy().to_string(), ..report },
+  })
+}
+
+/// 签名输出的默认路径：去掉文件名中的 -unsigned，没有时加上 -signed
+pub fn default_output(input: &Path) -> PathBuf {
+  let name = input.file_name().unwrap_or_default().to_string_lossy();
+  let stripped = name.replace("-unsigned", "");
+  if stripped != name {
+    return input.with_file_name(stripped);
+  }
+  let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+  input.with_file_name(format!("{}-signed.apk", stem))
+}
+
+fn temp_path(output: &Path) -> PathBuf {
+  let mut name = output.file_name().unwrap_or_default().to_os_string();
+  name.push(".signing");
+  output.with_file_name(name)
+}
+
+/// 去掉原有签名后重新签名，返回对齐后的 APK
+pub(crate) fn sign(data: &[u8], key: &SigningKey, schemes: SigningSchemes, min_sdk: Option<u32>) -> Result<Vec<u8>> {
+  let apk = Apk::parse(data)?;
+  let mut entries = zipalign::read_entries(&apk)?;
+  entries.retain(|e| !is_signature_file(&e.name));
+  if schemes.v1 {
+    let hash = match min_sdk {
+      Some(sdk) if sdk < SHA256_JAR_MIN_SDK => Hash::Sha1,
+      _ => Hash::Sha256,
+    };
+    let files = jar_signature(&entries, key, hash, schemes)?;
+    entries.splice(0..0, files);
+  }
+  let aligned = zipalign::write_aligned(&entries)?;
+  if !schemes.v2 && !schemes.v3 {
+    return Ok(aligned);
+  }
+
+  let apk = Apk::parse(&aligned)?;
+  let algorithm = signature::algorithm(key.key.algorithm_id()).ok_or_else(|| Error::Signing("不支持的签名算法".into()))?;
+  let digest = content_digest(&apk, algorithm.content).map_err(Error::Signing)?;
+  let signer = |v3: bool| -> Result<Vec<u8>> {
+    let mut digests = algorithm.id.to_le_bytes().to_vec();
+    digests.extend(length_prefixed(&digest));
+    let mut signed_data = sequence(&[digests]);
+    signed_data.extend(sequence(&key.certificates));
+    if v3 {
+      signed_data.extend(V3_MIN_SDK.to_le_bytes());
+      signed_data.extend(i32::MAX.to_le_bytes());
+    }
+    let mut attributes = Vec::new();
+    if !v3 && schemes.v3 {
+      let mut attribute = STRIPPING_PROTECTION_ATTR.to_le_bytes().to_vec();
+      attribute.extend(3u32.to_le_bytes());
+      attributes.push(attribute);
+    }
+    signed_data.extend(sequence(&attributes));
+
+    let signature = key.key.sign(algorithm.hash, &signed_data).map_err(Error::Signing)?;
+    let mut signature_entry = algorithm.id.to_le_bytes().to_vec();
+    signature_entry.extend(length_prefixed(&signature));
+    let mut signer = length_prefixed(&signed_data);
+    if v3 {
+      signer.extend(V3_MIN_SDK.to_le_bytes());
+      signer.extend(i32::MAX.to_le_bytes());
+    }
+    signer.extend(sequence(&[signature_entry]));
+    signer.extend(length_prefixed(&public_key(key)?));
+    Ok(sequence(&[signer]))
+  };
+  let mut blocks = Vec::new();
+  if schemes.v2 {
+    blocks.push((V2_BLOCK_ID, signer(false)?));
+  }
+  if schemes.v3 {
+    blocks.push((V3_BLOCK_ID, signer(true)?));
+  }
+  let pairs: Vec<(u32, &[u8])> = blocks.iter().map(|(id, value)| (*id, value.as_slice())).collect();
+  Ok(apk.with_pairs(&pairs))
+}
+
+/// 原有的 JAR 签名文件：MANIFEST.MF 与 META-INF 下的 .SF、签名块
+fn is_signature_file(name: &str) -> bool {
+  let Some(rest) = name.strip_prefix("META-INF/").filter(|rest| !rest.contains('/')) else {
+    return false;
+  };
+  let upper = rest.to_ascii_uppercase();
+  upper == "MANIFEST.MF"
+    || upper.starts_with("SIG-")
+    || [".SF", ".RSA", ".DSA", ".EC"].iter().any(|ext| upper.ends_with(ext))
+}
+
+fn public_key(key: &SigningKey) -> Result<Vec<u8>> {
+  let cert = Certificate::from_der(&key.certificates[0]).map_err(|e| Error::Keystore(format!("证书格式错误：{}", e)))?;
+  cert
+    .tbs_certificate
+    .subject_public_key_info
+    .to_der()
+    .map_err(|e| Error::Keystore(format!("证书格式错误：{}", e)))
+}
+
+/// 写入一个属性，每行最多 72 字节，超出部分以空格开头续行
+fn write_attribute(out: &mut Vec<u8>, name: &str, value: &str) {
+  let line = format!("{}: {}", name, value);
+  let mut rest = line.as_str();
+  let mut first = true;
+  while !rest.is_empty() {
+    let mut end = rest.len().min(if first { 72 } else { 71 });
+    while !rest.is_char_boundary(end) {
+      end -= 1;
+    }
+    if !first {
+      out.push(b' ');
+    }
+    out.extend_from_slice(&rest.as_bytes()[..end]);
+    out.extend_from_slice(b"\r\n");
+    rest = &rest[end..];
+    first = false;
+  }
+}
+
+/// 生成 MANIFEST.MF、.SF 与签名块，返回新增的条目
+fn jar_signature<'a>(entries: &[ZipEntry], key: &SigningKey, hash: Hash, schemes: SigningSchemes) -> Result<Vec<ZipEntry<'a>>> {
+  let digest_name = format!("{}-Digest", hash.jar_name());
+  let mut files: Vec<&ZipEntry> = entries.iter().filter(|e| !e.name.ends_with('/')).collect();
+  files.sort_by(|a, b| a.name.cmp(&b.name));
+
+  let mut manifest = Vec::new();
+  write_attribute(&mut manifest, "Manifest-Version", "1.0");
+  write_attribute(&mut manifest, "Created-By", CREATED_BY);
+  manifest.extend_from_slice(b"\r\n");
+  let main_attributes = manifest.len();
+  let mut sections = Vec::with_capacity(files.len());
+  for entry in files {
+    let start = manifest.len();
+    write_attribute(&mut manifest, "Name", &entry.name);
+    write_attribute(&mut manifest, &digest_name, &BASE64.encode(hash.digest(&entry.contents()?)));
+    manifest.extend_from_slice(b"\r\n");
+    sections.push((entry.name.as_str(), start..manifest.len()));
+  }
+
+  let mut sf = Vec::new();
+  write_attribute(&mut sf, "Signature-Version", "1.0");
+  write_attribute(&mut sf, "Created-By", CREATED_BY);
+  write_attribute(&mut sf, &format!("{}-Manifest", digest_name), &BASE64.encode(hash.digest(&manifest)));
+  // 与 jarsigner 相同，主属性单独记录摘要，逐节校验时也能发现主属性被修改
+  write_attribute(
+    &mut sf,
+    &format!("{}-Manifest-Main-Attributes", digest_name),
+    &BASE64.encode(hash.digest(&manifest[..main_attributes])),
+  );
+  // 声明同时有 v2/v3 签名，防止去掉 v2/v3 后降级为只校验 v1
+  let signed_with: Vec<&str> = [(schemes.v2, "2"), (schemes.v3, "3")]
+    .into_iter()
+    .filter_map(|(enabled, id)| enabled.then_some(id))
+    .collect();
+  if !signed_with.is_empty() {
+    write_attribute(&mut sf, "X-Android-APK-Signed", &signed_with.join(", "));
+  }
+  sf.extend_from_slice(b"\r\n");
+  for (name, range) in sections {
+    write_attribute(&mut sf, "Name", name);
+    write_attribute(&mut sf, &digest_name, &BASE64.encode(hash.digest(&manifest[range])));
+    sf.extend_from_slice(b"\r\n");
+  }
+
+  let block = pkcs7_signature(key, hash, &sf)?;
+  let base = signature_file_name(&key.alias);
+  Ok(vec![
+    ZipEntry::deflated(MANIFEST_NAME, &manifest),
+    ZipEntry::deflated(format!("META-INF/{}.SF", base), &sf),
+    ZipEntry::deflated(format!("META-INF/{}.{}", base, key.key.jar_extension()), &block),
+  ])
+}
+
+/// 签名文件名：别名转为大写，只保留字母、数字、`-` 与 `_`，最长 8 个字符
+fn signature_file_name(alias: &str) -> String {
+  let name: String = alias
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+    .take(8)
+    .collect::<String>()
+    .to_ascii_uppercase();
+  if name.is_empty() {
+    "CERT".into()
+  } else {
+    name
+  }
+}
+
+/// 对 .SF 的 PKCS#7 分离签名，不带签名属性
+fn pkcs7_signature(key: &SigningKey, hash: Hash, content: &[u8]) -> Result<Vec<u8>> {
+  let invalid = |e: x509_cert::der::Error| Error::Signing(format!("生成签名块失败：{}", e));
+  let certificates = key
+    .certificates
+    .iter()
+    .map(|der| Certificate::from_der(der))
+    .collect::<std::result::Result<Vec<_>, _>>()
+    .map_err(|e| Error::Keystore(format!("证书格式错误：{}", e)))?;
+  let cert = &certificates[0].tbs_certificate;
+  let digest_algorithm = AlgorithmIdentifierOwned { oid: hash.oid(), parameters: None };
+  let parameters = match key.key {
+    PrivateKey::Rsa(_) => Some(Any::null()),
+    _ => None,
+  };
+  let signer = SignerInfo {
+    version: CmsVersion::V1,
+    sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+      issuer: cert.issuer.clone(),
+      serial_number: cert.serial_number.clone(),
+    }),
+    digest_alg: digest_algorithm.clone(),
+    signed_attrs: None,
+    signature_algorithm: AlgorithmIdentifierOwned { oid: key.key.jar_signature_oid(hash), parameters },
+    signature: OctetString::new(key.key.sign(hash, content).map_err(Error::Signing)?).map_err(invalid)?,
+    unsigned_attrs: None,
+  };
+  let certificates = certificates.into_iter().map(CertificateChoices::Certificate).collect::<Vec<_>>();
+  let signed = SignedData {
+    version: CmsVersion::V1,
+    digest_algorithms: SetOfVec::try_from(vec![digest_algorithm]).map_err(invalid)?,
+    encap_content_info: EncapsulatedContentInfo { econtent_type: OID_DATA, econtent: None },
+    certificates: Some(CertificateSet(SetOfVec::try_from(certificates).map_err(invalid)?)),
+    crls: None,
+    signer_infos: SignerInfos(SetOfVec::try_from(vec![signer]).map_err(invalid)?),
+  };
+  let info = ContentInfo { content_type: OID_SIGNED_DATA, content: Any::encode_from(&signed).map_err(invalid)? };
+  info.to_der().map_err(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    keystores::{tests::signing_key, KeyAlgorithm},
+    signature::{verify_data, SignatureScheme},
+    signing_block::tests::sample_apk,
+  };
+
+  fn verify(data: &[u8]) -> SignatureReport {
+    verify_data(Path::new("test.apk"), data, false).unwrap()
+  }
+
+  fn scheme_labels(report: &SignatureReport) -> Vec<&'static str> {
+    report.schemes.iter().map(|s| s.scheme.label()).collect()
+  }
+
+  #[test]
+  fn sign_then_verify() {
+    for algorithm in [KeyAlgorithm::Rsa, KeyAlgorithm::Ec] {
+      let key = signing_key(algorithm);
+      let signed = sign(&sample_apk(), &key, SigningSchemes::default(), Some(21)).unwrap();
+      let report = verify(&signed);
+      assert!(report.verified, "{:?}", report.schemes);
+      assert_eq!(scheme_labels(&report), ["v1", "v2", "v3"]);
+      assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+      assert_eq!(report.signers.len(), 1);
+      assert_eq!(report.signers[0].subject, "CN=Test,O=ABT,C=CN");
+
+      let v3 = report.schemes.iter().find(|s| s.scheme == SignatureScheme::V3).unwrap();
+      assert_eq!(v3.min_sdk, Some(V3_MIN_SDK));
+      assert_eq!(v3.max_sdk, Some(i32::MAX as u32));
+    }
+  }
+
+  #[test]
+  fn sign_selected_schemes() {
+    let key = signing_key(KeyAlgorithm::Ec);
+    let v1 = sign(&sample_apk(), &key, SigningSchemes { v1: true, v2: false, v3: false }, None).unwrap();
+    let report = verify(&v1);
+    assert!(report.verified);
+    assert_eq!(scheme_labels(&report), ["v1"]);
+    assert!(Apk::parse(&v1).unwrap().pairs.is_empty());
+
+    let v2 = sign(&sample_apk(), &key, SigningSchemes { v1: false, v2: true, v3: false }, None).unwrap();
+    let report = verify(&v2);
+    assert!(report.verified);
+    assert_eq!(scheme_labels(&report), ["v2"]);
+    let apk = Apk::parse(&v2).unwrap();
+    assert!(zipalign::read_entries(&apk).unwrap().iter().all(|e| !e.name.starts_with("META-INF/")));
+  }
+
+  #[test]
+  fn resign_replaces_signature() {
+    let signed = sign(&sample_apk(), &signing_key(KeyAlgorithm::Ec), SigningSchemes::default(), None).unwrap();
+    let key = signing_key(KeyAlgorithm::Ec);
+    let resigned = sign(&signed, &key, SigningSchemes::default(), None).unwrap();
+    let report = verify(&resigned);
+    assert!(report.verified, "{:?}", report.schemes);
+    assert_eq!(report.signers.len(), 1);
+    assert_eq!(report.signers[0].sha256, signature::certificate_info(&key.certificates[0]).unwrap().sha256);
+
+    let apk = Apk::parse(&resigned).unwrap();
+    let names: Vec<String> = zipalign::read_entries(&apk).unwrap().into_iter().map(|e| e.name).collect();
+    assert_eq!(names.iter().filter(|n| n.as_str() == MANIFEST_NAME).count(), 1);
+  }
+}
@@ -0,0 +1,300 @@
+//! APK 的 ZIP 结构与 APK 签名块（APK Signing Block）
+//!
+//! 签名块位于 ZIP 条目与中央目录之间，由若干 ID-值对组成，v2/v3 签名及渠道信息都保存在其中。
+
+use crate::{axml::invalid, Result};
+
+pub(crate) const V2_BLOCK_ID: u32 = 0x7109_871a;
+pub(crate) const V3_BLOCK_ID: u32 = 0xf053_68c0;
+pub(crate) const V31_BLOCK_ID: u32 = 0x1b93_ad61;
+
+const MAGIC: &[u8; 16] = b"APK Sig Block 42";
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_MIN_SIZE: usize = 22;
+/// EOCD 中中央目录偏移字段的位置
+const EOCD_CD_OFFSET: usize = 16;
+
+/// 按小端读取长度前缀结构
+pub(crate) struct Reader<'a> {
+  data: &'a [u8],
+  pos: usize,
+  what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+  pub fn new(data: &'a [u8], what: &'static str) -> Self {
+    Self { data, pos: 0, what }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.pos >= self.data.len()
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+    let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+    let end = end.ok_or_else(|| invalid(self.what, format!("偏移 {} 处的数据超出范围", self.pos)))?;
+    let bytes = &self.data[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+
+  pub fn u8(&mut self) -> Result<u8> {
+    Ok(self.take(1)?[0])
+  }
+
+  pub fn u32(&mut self) -> Result<u32> {
+    let b = self.take(4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+  }
+
+  pub fn u64(&mut self) -> Result<u64> {
+    let b = self.take(8)?;
+    Ok(u64::from_le_bytes(b.try_into().unwrap_or_default()))
+  }
+
+  /// u32 长度前缀的字节串
+  pub fn bytes(&mut self) -> Result<&'a [u8]> {
+    let len = self.u32()? as usize;
+    self.take(len)
+  }
+
+  /// u32 长度前缀的字节串，作为新的读取器
+  pub fn nested(&mut self) -> Result<Reader<'a>> {
+    Ok(Reader::new(self.bytes()?, self.what))
+  }
+
+  /// 长度前缀序列中的全部元素
+  pub fn items(&mut self) -> Result<Vec<&'a [u8]>> {
+    let mut sequence = self.nested()?;
+    let mut items = Vec::new();
+    while !sequence.is_empty() {
+      items.push(sequence.bytes()?);
+    }
+    Ok(items)
+  }
+}
+
+/// APK 的 ZIP 结构：ZIP 条目、签名块、中央目录、EOCD 的位置
+pub(crate) struct Apk<'a> {
+  pub data: &'a [u8],
+  /// 签名块的起始位置；没有签名块时等于中央目录偏移
+  pub block_start: usize,
+  pub cd_offset: usize,
+  pub eocd_offset: usize,
+  /// 签名块中的 ID-值对，按出现顺序
+  pub pairs: Vec<(u32, &'a [u8])>,
+}
+
+impl<'a> Apk<'a> {
+  pub fn parse(data: &'a [u8]) -> Result<Self> {
+    let what = "APK";
+    let eocd_offset = find_eocd(data).ok_or_else(|| invalid(what, "未找到 ZIP 中央目录结尾记录"))?;
+    let cd_offset = read_u32(data, eocd_offset + EOCD_CD_OFFSET) as usize;
+    if cd_offset > eocd_offset {
+      return Err(invalid(what, "中央目录偏移超出范围"));
+    }
+    let mut apk = Self { data, block_start: cd_offset, cd_offset, eocd_offset, pairs: Vec::new() };
+    apk.read_signing_block()?;
+    Ok(apk)
+  }
+
+  fn read_signing_block(&mut self) -> Result<()> {
+    let what = "APK 签名块";
+    // 签名块结尾：u64 大小 + 16 字节魔数
+    if self.cd_offset < 32 || &self.data[self.cd_offset - 16..self.cd_offset] != MAGIC {
+      return Ok(());
+    }
+    let size = read_u64(self.data, self.cd_offset - 24) as usize;
+    // 大小至少包含结尾的 u64 大小与魔数
+    if size < 24 {
+      return Err(invalid(what, "签名块大小错误"));
+    }
+    let start = self
+      .cd_offset
+      .checked_sub(size)
+      .and_then(|s| s.checked_sub(8))
+      .ok_or_else(|| invalid(what, "签名块大小超出范围"))?;
+    if read_u64(self.data, start) as usize != size {
+      return Err(invalid(what, "签名块首尾记录的大小不一致"));
+    }
+    let mut reader = Reader::new(&self.data[start + 8..self.cd_offset - 24], what);
+    while !reader.is_empty() {
+      let len = reader.u64()? as usize;
+      if len < 4 {
+        return Err(invalid(what, "ID-值对长度错误"));
+      }
+      let id = reader.u32()?;
+      let value = reader.take(len - 4)?;
+      self.pairs.push((id, value));
+    }
+    self.block_start = start;
+    Ok(())
+  }
+
+  pub fn get(&self, id: u32) -> Option<&'a [u8]> {
+    self.pairs.iter().find(|(i, _)| *i == id).map(|(_, v)| *v)
+  }
+
+  /// ZIP 条目部分（签名块之前）
+  pub fn entries(&self) -> &'a [u8] {
+    &self.data[..self.block_start]
+  }
+
+  pub fn central_directory(&self) -> &'a [u8] {
+    &self.data[self.cd_offset..self.eocd_offset]
+  }
+
+  /// 中央目录偏移指向签名块起始位置的 EOCD，即没有签名块时的 EOCD，签名摘要按此计算
+  pub fn eocd_without_block(&self) -> Vec<u8> {
+    let mut eocd = self.data[self.eocd_offset..].to_vec();
+    eocd[EOCD_CD_OFFSET..EOCD_CD_OFFSET + 4].copy_from_slice(&(self.block_start as u32).to_le_bytes());
+    eocd
+  }
+
+  /// 用给定的 ID-值对替换签名块，返回完整的 APK；没有 ID-值对时去掉签名块
+  pub fn with_pairs(&self, pairs: &[(u32, &[u8])]) -> Vec<u8> {
+    let block = build_block(pairs);
+    let mut out = Vec::with_capacity(self.block_start + block.len() + self.data.len() - self.cd_offset);
+    out.extend_from_slice(self.entries());
+    out.extend_from_slice(&block);
+    out.extend_from_slice(self.central_directory());
+    let mut eocd = self.data[self.eocd_offset..].to_vec();
+    eocd[EOCD_CD_OFFSET..EOCD_CD_OFFSET + 4].copy_from_slice(&((self.block_start + block.len()) as u32).to_le_bytes());
+    out.extend_from_slice(&eocd);
+    out
+  }
+}
+
+/// 签名块：u64 大小 + ID-值对 + u64 大小 + 魔数，大小不含开头的 8 字节
+fn build_block(pairs: &[(u32, &[u8])]) -> Vec<u8> {
+  if pairs.is_empty() {
+    return Vec::new();
+  }
+  let size: usize = pairs.iter().map(|(_, v)| 12 + v.len()).sum::<usize>() + 24;
+  let mut block = Vec::with_capacity(size + 8);
+  block.extend_from_slice(&(size as u64).to_le_bytes());
+  for (id, value) in pairs {
+    block.extend_from_slice(&(value.len() as u64 + 4).to_le_bytes());
+    block.extend_from_slice(&id.to_le_bytes());
+    block.extend_from_slice(value);
+  }
+  block.extend_from_slice(&(size as u64).to_le_bytes());
+  block.extend_from_slice(MAGIC);
+  block
+}
+
+/// u32 长度前缀的字节串
+pub(crate) fn length_prefixed(data: &[u8]) -> Vec<u8> {
+  let mut out = Vec::with_capacity(4 + data.len());
+  out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+  out.extend_from_slice(data);
+  out
+}
+
+/// 长度前缀序列：每个元素带长度前缀，整体再带长度前缀
+pub(crate) fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+  let items: Vec<u8> = items.iter().flat_map(|item| length_prefixed(item)).collect();
+  length_prefixed(&items)
+}
+
+fn find_eocd(data: &[u8]) -> Option<usize> {
+  if data.len() < EOCD_MIN_SIZE {
+    return None;
+  }
+  // EOCD 之后最多有 65535 字节的注释
+  let last = data.len() - EOCD_MIN_SIZE;
+  let first = last.saturating_sub(u16::MAX as usize);
+  (first..=last).rev().find(|&pos| {
+    read_u32(data, pos) == EOCD_SIGNATURE && pos + EOCD_MIN_SIZE + read_u16(data, pos + 20) as usize == data.len()
+  })
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+  u16::from_le_bytes([data[pos], data[pos + 1]])
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+  u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn read_u64(data: &[u8], pos: usize) -> u64 {
+  u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap_or_default())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::zipalign::{self, ZipEntry};
+
+  /// 测试用的未签名 APK：压缩与不压缩的条目各有，含一个需要按页对齐的 .so
+  pub(crate) fn sample_apk() -> Vec<u8> {
+    zipalign::write_aligned(&[
+      ZipEntry::deflated("AndroidManifest.xml", b"<manifest package=\"com.example.demo\"/>"),
+      ZipEntry::deflated("classes.dex", &b"dex\n035\0".repeat(64)),
+      ZipEntry::stored("resources.arsc", b"arsc"),
+      ZipEntry::stored("lib/arm64-v8a/libdemo.so", b"\x7fELF"),
+      ZipEntry::deflated("assets/readme.txt", b"hello"),
+    ])
+    .unwrap()
+  }
+
+  #[test]
+  fn replace_pairs() {
+    let unsigned = sample_apk();
+    let apk = Apk::parse(&unsigned).unwrap();
+    assert!(apk.pairs.is_empty());
+    assert_eq!(apk.block_start, apk.cd_offset);
+
+    let with_block = apk.with_pairs(&[(V2_BLOCK_ID, b"v2"), (0x7177_7777, b"walle")]);
+    let parsed = Apk::parse(&with_block).unwrap();
+    assert_eq!(parsed.pairs, [(V2_BLOCK_ID, &b"v2"[..]), (0x7177_7777, &b"walle"[..])]);
+    assert_eq!(parsed.get(V2_BLOCK_ID), Some(&b"v2"[..]));
+    assert_eq!(parsed.get(V3_BLOCK_ID), None);
+    assert_eq!(parsed.block_start, apk.block_start);
+    assert_eq!(parsed.entries(), apk.entries());
+    assert_eq!(parsed.central_directory(), apk.central_directory());
+    // 签名摘要按去掉签名块后的 EOCD 计算
+    assert_eq!(parsed.eocd_without_block(), &unsigned[apk.eocd_offset..]);
+
+    assert_eq!(parsed.with_pairs(&[]), unsigned);
+  }
+
+  #[test]
+  fn rejects_inconsistent_block() {
+    let unsigned = sample_apk();
+    let mut data = Apk::parse(&unsigned).unwrap().with_pairs(&[(V2_BLOCK_ID, b"v2")]);
+    let start = Apk::parse(&data).unwrap().block_start;
+    data[start] ^= 1;
+    assert!(Apk::parse(&data).is_err());
+  }
+
+  #[test]
+  fn rejects_short_block() {
+    let unsigned = sample_apk();
+    let apk = Apk::parse(&unsigned).unwrap();
+    // 首尾记录的大小一致但小于结尾的长度
+    let mut data = apk.entries().to_vec();
+    data.extend(16u64.to_le_bytes());
+    data.extend([0; 8]);
+    data.extend(16u64.to_le_bytes());
+    data.extend(MAGIC);
+    let cd_offset = data.len() as u32;
+    data.extend(apk.central_directory());
+    let mut eocd = unsigned[apk.eocd_offset..].to_vec();
+    eocd[EOCD_CD_OFFSET..EOCD_CD_OFFSET + 4].copy_from_slice(&cd_offset.to_le_bytes());
+    data.extend(eocd);
+    assert!(Apk::parse(&data).is_err());
+  }
+
+  #[test]
+  fn length_prefixed_sequence() {
+    let mut reader = Reader::new(&[], "测试");
+    assert!(reader.is_empty());
+    assert!(reader.u32().is_err());
+
+    let data = sequence(&[b"ab".to_vec(), b"c".to_vec()]);
+    let mut reader = Reader::new(&data, "测试");
+    assert_eq!(reader.items().unwrap(), [&b"ab"[..], &b"c"[..]]);
+    assert!(reader.is_empty());
+  }
+}
@@ -0,0 +1,246 @@
+//! 构建变体模型：按 flavor dimension 顺序排列的 product flavor + build type
+//!
+//! 负责生成 Gradle 任务名与 AGP 的产物目录，并在有发现结果时校验变体是否存在。
+
+use crate::{
+  discovery::{capitalize, flavor_combination_name, ModuleVariants},
+  Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct BuildVariant {
+  /// 按 flavor dimension 顺序排列的 flavor 名称
+  pub flavors: Vec<String>,
+  pub build_type: String,
+}
+
+impl BuildVariant {
+  /// 完整变体名，如 `freeStagingRelease`
+  pub fn name(&self) -> String {
+    let mut parts = self.flavors.clone();
+    parts.push(self.build_type.clone());
+    flavor_combination_name(&parts)
+  }
+
+  /// flavor 组合名（不含 build type），如 `freeStaging`；没有 flavor 时为空
+  pub fn flavor_name(&self) -> String {
+    flavor_combination_name(&self.flavors)
+  }
+
+  /// Gradle 任务名，如 `:app:assembleFreeStagingRelease`
+  pub fn task(&self, prefix: &str, module: Option<&str>) -> String {
+    let task = format!("{}{}", prefix, capitalize(&self.name()));
+    match module {
+      Some(m) => format!(":{}:{}", m.trim_start_matches(':'), task),
+      None => task,
+    }
+  }
+
+  /// AGP 的 APK 输出目录：`build/outputs/apk/<flavorName>/<buildType>`
+  pub fn apk_output_dir(&self, module_path: &Path) -> PathBuf {
+    let mut dir = module_path.join("build/outputs/apk");
+    if !self.flavors.is_empty() {
+      dir = dir.join(self.flavor_name());
+    }
+    dir.join(&self.build_type)
+  }
+
+  /// AGP 的 AAB 输出目录：`build/outputs/bundle/<variantName>`
+  pub fn bundle_output_dir(&self, module_path: &Path) -> PathBuf {
+    module_path.join("build/outputs/bundle").join(self.name())
+  }
+
+  /// 本工具的输出子目录：`<flavorName>/<buildType>`
+  pub fn output_subdir(&self) -> PathBuf {
+    let mut dir = PathBuf::new();
+    if !self.flavors.is_empty() {
+      dir = dir.join(self.flavor_name());
+    }
+    dir.join(&self.build_type)
+  }
+
+  /// 根据传入参数解析变体。
+  ///
+  /// - `flavors` 优先；否则将 `variant`（flavor 组合名，如 `freeStaging`）拆分为各维度的 flavor
+  /// - 有模块发现结果时校验 flavor、build type 以及组合是否存在，名称按工程中的大小写归一
+  /// - 没有发现结果时不做校验，build type 首字母转为小写（`Release` -> `release`）
+  pub fn resolve(
+    variant: Option<&str>,
+    flavors: Option<Vec<String>>,
+    build_type: &str,
+    module: Option<&ModuleVariants>,
+  ) -> Result<BuildVariant> {
+    let build_type = build_type.trim();
+    if build_type.is_empty() {
+      return Err(Error::InvalidVariant("Build Type 不能为空".into()));
+    }
+    let variant = variant.map(str::trim).filter(|v| !v.is_empty());
+
+    let Some(module) = module else {
+      let flavors = flavors.unwrap_or_else(|| variant.map(|v| vec![v.to_string()]).unwrap_or_default());
+      return Ok(BuildVariant { flavors, build_type: decapitalize(build_type) });
+    };
+
+    let build_type = module
+      .build_types
+      .iter()
+      .find(|b| b.eq_ignore_ascii_case(build_type))
+      .cloned()
+      .ok_or_else(|| {
+        Error::InvalidVariant(format!(
+          "模块 {} 中不存在 Build Type：{}（可用：{}）",
+          module.module,
+          build_type,
+          module.build_types.join(", ")
+        ))
+      })?;
+
+    let flavors = match (flavors, variant) {
+      (Some(flavors), _) => order_flavors(module, &flavors)?,
+      (None, Some(v)) => split_flavor_name(module, v)?,
+      (None, None) => vec![],
+    };
+
+    let resolved = BuildVariant { flavors, build_type };
+    if !module.variants.is_empty() && !module.variants.iter().any(|v| v.flavors == resolved.flavors && v.build_type == resolved.build_type) {
+      let available: Vec<&str> = module.variants.iter().map(|v| v.name.as_str()).collect();
+      return Err(Error::InvalidVariant(format!(
+        "模块 {} 中不存在变体 {}（可用：{}）",
+        module.module,
+        resolved.name(),
+        available.join(", ")
+      )));
+    }
+    Ok(resolved)
+  }
+}
+
+/// 校验 flavor 是否存在，并按 dimension 顺序排列
+fn order_flavors(module: &ModuleVariants, flavors: &[String]) -> Result<Vec<String>> {
+  let mut slots: Vec<Option<String>> = vec![None; module.flavor_dimensions.len()];
+  let mut undimensioned = Vec::new();
+  for name in flavors {
+    let flavor = module
+      .product_flavors
+      .iter()
+      .find(|f| f.name.eq_ignore_ascii_case(name))
+      .ok_or_else(|| Error::InvalidVariant(format!("模块 {} 中不存在 flavor：{}", module.module, name)))?;
+    let index = flavor
+      .dimension
+      .as_ref()
+      .and_then(|d| module.flavor_dimensions.iter().position(|x| x == d));
+    match index {
+      Some(i) => {
+        if let Some(existing) = &slots[i] {
+          return Err(Error::InvalidVariant(format!(
+            "flavor {} 与 {} 属于同一维度 {}",
+            existing, flavor.name, module.flavor_dimensions[i]
+          )));
+        }
+        slots[i] = Some(flavor.name.clone());
+      }
+      None => undimensioned.push(flavor.name.clone()),
+    }
+  }
+
+  let missing: Vec<&str> = slots
+    .iter()
+    .zip(&module.flavor_dimensions)
+    .filter(|(slot, _)| slot.is_none())
+    .map(|(_, d)| d.as_str())
+    .collect();
+  if !missing.is_empty() {
+    return Err(Error::InvalidVariant(format!("缺少 flavor 维度：{}", missing.join(", "))));
+  }
+  Ok(slots.into_iter().flatten().chain(undimensioned).collect())
+}
+
+/// 将 flavor 组合名（如 `freeStaging`）匹配为工程中已有的 flavor 组合
+fn split_flavor_name(module: &ModuleVariants, name: &str) -> Result<Vec<String>> {
+  module
+    .variants
+    .iter()
+    .map(|v| &v.flavors)
+    .find(|flavors| flavor_combination_name(flavors).eq_ignore_ascii_case(name))
+    .cloned()
+    .or_else(|| {
+      // 没有变体列表时按单个 flavor 处理
+      module
+        .product_flavors
+        .iter()
+        .find(|f| f.name.eq_ignore_ascii_case(name))
+        .map(|f| vec![f.name.clone()])
+    })
+    .ok_or_else(|| Error::InvalidVariant(format!("模块 {} 中不存在 flavor 组合：{}", module.module, name)))
+}
+
+fn decapitalize(s: &str) -> String {
+  let mut chars = s.chars();
+  match chars.next() {
+    Some(c) => c.to_lowercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::discovery::{ProductFlavor, VariantInfo};
+
+  /// tier（free/paid）× env（staging/prod）× debug/release
+  fn module() -> ModuleVariants {
+    let flavor = |name: &str, dimension: &str| ProductFlavor { name: name.into(), dimension: Some(dimension.into()) };
+    let mut variants = Vec::new();
+    for tier in ["free", "paid"] {
+      for env in ["staging", "prod"] {
+        for build_type in ["debug", "release"] {
+          let variant = BuildVariant { flavors: vec![tier.into(), env.into()], build_type: build_type.into() };
+          variants.push(VariantInfo { name: variant.name(), flavors: variant.flavors, build_type: variant.build_type });
+        }
+      }
+    }
+    ModuleVariants {
+      module: "app".into(),
+      flavor_dimensions: vec!["tier".into(), "env".into()],
+      product_flavors: vec![flavor("free", "tier"), flavor("paid", "tier"), flavor("staging", "env"), flavor("prod", "env")],
+      build_types: vec!["debug".into(), "release".into()],
+      variants,
+    }
+  }
+
+  #[test]
+  fn resolve_without_discovery() {
+    let variant = BuildVariant::resolve(Some("free"), None, "Release", None).unwrap();
+    assert_eq!(variant.flavors, ["free"]);
+    assert_eq!(variant.build_type, "release");
+    assert_eq!(variant.task("assemble", Some(":app")), ":app:assembleFreeRelease");
+    assert!(BuildVariant::resolve(None, None, " ", None).is_err());
+  }
+
+  #[test]
+  fn resolve_with_discovery() {
+    let module = module();
+    let variant = BuildVariant::resolve(Some("FreeStaging"), None, "RELEASE", Some(&module)).unwrap();
+    assert_eq!(variant.flavors, ["free", "staging"]);
+    assert_eq!(variant.build_type, "release");
+    assert_eq!(variant.apk_output_dir(Path::new("app")), Path::new("app/build/outputs/apk/freeStaging/release"));
+
+    // 按维度顺序排列
+    let flavors = Some(vec!["prod".to_string(), "paid".to_string()]);
+    let variant = BuildVariant::resolve(None, flavors, "debug", Some(&module)).unwrap();
+    assert_eq!(variant.name(), "paidProdDebug");
+  }
+
+  #[test]
+  fn resolve_rejects_unknown_names() {
+    let module = module();
+    assert!(BuildVariant::resolve(Some("freeStaging"), None, "benchmark", Some(&module)).is_err());
+    assert!(BuildVariant::resolve(Some("vip"), None, "debug", Some(&module)).is_err());
+    let same_dimension = Some(vec!["free".to_string(), "paid".to_string()]);
+    assert!(BuildVariant::resolve(None, same_dimension, "debug", Some(&module)).is_err());
+    let missing_dimension = Some(vec!["free".to_string()]);
+    assert!(BuildVariant::resolve(None, missing_dimension, "debug", Some(&module)).is_err());
+  }
+}
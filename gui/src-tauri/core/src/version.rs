@@ -0,0 +1,577 @@
+//! 版本号管理：读取 versionCode/versionName，构建前按策略修改，构建失败时回滚
+//!
+//! 版本号可以定义在：
+//! - 模块构建脚本的字面量（`versionCode 12`、`versionName = "1.2.0"`），或根脚本 `ext` 中的变量
+//! - `gradle.properties`（模块目录优先，其次工程根目录），脚本中通过 `project.property("VERSION_CODE")` 等引用
+//! - 版本目录 `[versions]`，脚本中通过 `libs.versions.versionCode.get().toInt()` 引用
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+  fmt, fs,
+  ops::Range,
+  path::{Path, PathBuf},
+  str::FromStr,
+  sync::OnceLock,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+  catalog::{accessor_key, VersionCatalog},
+  discovery::{self, blank_comments},
+  civil_from_days, properties, run_command, Error, Result,
+};
+
+/// 构建脚本未声明时，在 gradle.properties 中查找的键
+const CODE_PROPERTY_KEYS: &[&str] = &["VERSION_CODE", "versionCode", "APP_VERSION_CODE", "version_code"];
+const NAME_PROPERTY_KEYS: &[&str] = &["VERSION_NAME", "versionName", "APP_VERSION_NAME", "version_name"];
+
+/// 版本号的定义位置
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum VersionLocation {
+  /// 构建脚本中的字面量，`key` 为 `versionCode` 或 ext 变量名
+  BuildScript { file: PathBuf, key: String },
+  GradleProperties { file: PathBuf, key: String },
+  VersionCatalog { file: PathBuf, key: String },
+}
+
+impl fmt::Display for VersionLocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (file, key) = match self {
+      VersionLocation::BuildScript { file, key }
+      | VersionLocation::GradleProperties { file, key }
+      | VersionLocation::VersionCatalog { file, key } => (file, key),
+    };
+    write!(f, "{} ({})", file.display(), key)
+  }
+}
+
+/// 读取到的版本号及其定义位置
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+  pub version_code: Option<u64>,
+  pub version_name: Option<String>,
+  pub code_location: Option<VersionLocation>,
+  pub name_location: Option<VersionLocation>,
+}
+
+impl VersionInfo {
+  pub fn app_version(&self) -> AppVersion {
+    AppVersion { version_code: self.version_code, version_name: self.version_name.clone() }
+  }
+}
+
+/// 构建使用的版本号，记录在构建结果与历史中
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AppVersion {
+  pub version_code: Option<u64>,
+  pub version_name: Option<String>,
+}
+
+impl fmt::Display for AppVersion {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = self.version_name.as_deref().unwrap_or("-");
+    match self.version_code {
+      Some(code) => write!(f, "{} ({})", name, code),
+      None => write!(f, "{}", name),
+    }
+  }
+}
+
+/// versionCode 的修改策略
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "strategy", rename_all = "camelCase")]
+pub enum CodeBump {
+  /// 当前值 + 1
+  Increment,
+  /// UTC 时间 `yyMMddHH`，不大于当前值时取当前值 + 1
+  Timestamp,
+  /// `git rev-list --count HEAD`
+  GitCommitCount,
+  Explicit { value: u64 },
+}
+
+/// versionName 的修改策略，递增时保留 `-beta` 等后缀
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "strategy", rename_all = "camelCase")]
+pub enum NameBump {
+  Major,
+  Minor,
+  Patch,
+  Explicit { value: String },
+}
+
+impl FromStr for CodeBump {
+  type Err = String;
+
+  /// `increment`、`timestamp`、`git-count` 或数字
+  fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    match s {
+      "increment" => Ok(CodeBump::Increment),
+      "timestamp" => Ok(CodeBump::Timestamp),
+      "git-count" | "gitCommitCount" => Ok(CodeBump::GitCommitCount),
+      _ => s
+        .parse()
+        .map(|value| CodeBump::Explicit { value })
+        .map_err(|_| format!("versionCode 策略应为 increment、timestamp、git-count 或数字：{}", s)),
+    }
+  }
+}
+
+impl FromStr for NameBump {
+  type Err = String;
+
+  /// `major`、`minor`、`patch` 或具体的版本名
+  fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    match s {
+      "major" => Ok(NameBump::Major),
+      "minor" => Ok(NameBump::Minor),
+      "patch" => Ok(NameBump::Patch),
+      "" => Err("versionName 不能为空".into()),
+      _ => Ok(NameBump::Explicit { value: s.to_string() }),
+    }
+  }
+}
+
+/// 构建前修改版本号的方式，`code` 与 `name` 都为空时不修改
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionBump {
+  #[serde(default)]
+  pub code: Option<CodeBump>,
+  #[serde(default)]
+  pub name: Option<NameBump>,
+  /// 构建失败或取消时恢复修改前的文件
+  #[serde(default = "default_rollback")]
+  pub rollback_on_failure: bool,
+}
+
+fn default_rollback() -> bool {
+  true
+}
+
+impl Default for VersionBump {
+  fn default() -> Self {
+    Self { code: None, name: None, rollback_on_failure: true }
+  }
+}
+
+impl VersionBump {
+  pub fn is_empty(&self) -> bool {
+    self.code.is_none() && self.name.is_none()
+  }
+}
+
+/// 已写入文件的版本修改，未调用 [`VersionChange::keep`] 时在释放时回滚
+pub struct VersionChange {
+  pub previous: AppVersion,
+  pub applied: AppVersion,
+  pub rollback_on_failure: bool,
+  backups: Vec<(PathBuf, String)>,
+  settled: bool,
+}
+
+impl VersionChange {
+  /// 保留修改
+  pub fn keep(&mut self) {
+    self.settled = true;
+  }
+
+  /// 恢复修改前的文件内容
+  pub fn rollback(&mut self) -> Result<()> {
+    self.settled = true;
+    for (file, content) in &self.backups {
+      fs::write(file, content).map_err(|e| Error::io(format!("回滚版本号失败: {}", file.display()), e))?;
+    }
+    Ok(())
+  }
+
+  /// 修改过的文件
+  pub fn files(&self) -> impl Iterator<Item = &Path> {
+    self.backups.iter().map(|(p, _)| p.as_path())
+  }
+}
+
+impl Drop for VersionChange {
+  fn drop(&mut self) {
+    // 构建未执行就提前返回（如启动 gradlew 失败）时恢复文件
+    if !self.settled {
+      if let Err(e) = self.rollback() {
+        log::warn!("{}", e);
+      }
+    }
+  }
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+  cell.get_or_init(|| Regex::new(pattern).expect("无效的正则表达式"))
+}
+
+fn script_path(dir: &Path) -> Option<PathBuf> {
+  ["build.gradle.kts", "build.gradle"]
+    .iter()
+    .map(|name| dir.join(name))
+    .find(|p| p.is_file())
+}
+
+/// 脚本中 `key` 的赋值：返回值表达式，字面量时额外返回其在原文中的范围（不含引号）
+struct Assignment {
+  expr: String,
+  literal: Option<Range<usize>>,
+}
+
+/// 在脚本中查找 `key 12`、`key = 12`、`key(12)`、`key: 12` 形式的赋值，跳过注释
+fn find_assignment(content: &str, key: &str) -> Option<Assignment> {
+  let masked = blank_comments(content);
+  let pattern = Regex::new(&format!(
+    r#"(?:^|[^\w.])(?:(?:ext|project\.ext|rootProject\.ext)\.)?{}[ \t]*(?:=|:|\()?[ \t]*(?P<v>"[^"\n]*"|'[^'\n]*'|[^\s;,]+)"#,
+    regex::escape(key)
+  ))
+  .ok()?;
+  let caps = pattern.captures(&masked)?;
+  let m = caps.name("v")?;
+  let mut value = m.as_str();
+  let mut range = m.range();
+  // `versionCode(12)` 去掉多余的右括号
+  while value.ends_with(')') && value.matches(')').count() > value.matches('(').count() {
+    value = &value[..value.len() - 1];
+    range.end -= 1;
+  }
+
+  let quoted = value.len() >= 2 && (value.starts_with('"') || value.starts_with('\'')) && value.ends_with(&value[..1]);
+  let literal = if quoted && !value.contains('$') {
+    Some(range.start + 1..range.end - 1)
+  } else if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+    Some(range)
+  } else {
+    None
+  };
+  Some(Assignment { expr: value.to_string(), literal })
+}
+
+/// 从表达式中取出引用的变量名：`project.property("VERSION_CODE")`、`VERSION_CODE.toInteger()`、
+/// `rootProject.ext.versionCode`、`"${versionName}"`
+fn referenced_name(expr: &str) -> Option<String> {
+  static QUOTED_ARG: OnceLock<Regex> = OnceLock::new();
+  static TEMPLATE: OnceLock<Regex> = OnceLock::new();
+  let quoted_arg = regex(&QUOTED_ARG, r#"\(\s*["']([\w.\-]+)["']\s*\)"#);
+  let template = regex(&TEMPLATE, r#"^["']\$\{?([\w.]+)\}?["']$"#);
+  if let Some(c) = quoted_arg.captures(expr).or_else(|| template.captures(expr)) {
+    return c.get(1).map(|m| m.as_str().rsplit('.').next().unwrap_or_default().to_string());
+  }
+  // `Integer.parseInt(VERSION_CODE)`
+  if let Some(inner) = expr.find('(').filter(|_| expr.ends_with(')')).map(|i| &expr[i + 1..expr.len() - 1]) {
+    if !inner.is_empty() {
+      return referenced_name(inner);
+    }
+  }
+  let name = expr
+    .trim_start_matches('$')
+    .trim_matches(|c| c == '{' || c == '}')
+    .trim_end_matches(".toInteger()")
+    .trim_end_matches(".toInt()")
+    .trim_end_matches(".get()")
+    .rsplit('.')
+    .next()?
+    .to_string();
+  (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') && !name.starts_with(|c: char| c.is_ascii_digit()))
+    .then_some(name)
+}
+
+/// 定位版本号的定义，返回原始值与位置
+fn locate(project_path: &Path, module_dir: &Path, key: &str, property_keys: &[&str]) -> Option<(String, VersionLocation)> {
+  static CATALOG_REF: OnceLock<Regex> = OnceLock::new();
+  let catalog_ref = regex(&CATALOG_REF, r#"libs\.versions\.([\w.]+?)\.get\(\)"#);
+
+  let property_files: Vec<PathBuf> = [module_dir, project_path]
+    .iter()
+    .map(|d| d.join("gradle.properties"))
+    .fold(Vec::new(), |mut acc, p| {
+      if !acc.contains(&p) {
+        acc.push(p);
+      }
+      acc
+    });
+  let from_properties = |names: &[&str]| {
+    property_files.iter().find_map(|file| {
+      let props = properties::read(file)?;
+      names.iter().find_map(|name| {
+        props
+          .get(*name)
+          .map(|v| (v.clone(), VersionLocation::GradleProperties { file: file.clone(), key: name.to_string() }))
+      })
+    })
+  };
+
+  let module_script = script_path(module_dir);
+  let assignment = module_script
+    .as_ref()
+    .and_then(|file| fs::read_to_string(file).ok())
+    .and_then(|content| find_assignment(&content, key));
+  let Some(assignment) = assignment else {
+    return from_properties(property_keys);
+  };
+  let module_script = module_script?;
+
+  if let Some(range) = &assignment.literal {
+    let content = fs::read_to_string(&module_script).ok()?;
+    return Some((content[range.clone()].to_string(), VersionLocation::BuildScript { file: module_script, key: key.into() }));
+  }
+  if let Some(c) = catalog_ref.captures(&assignment.expr) {
+    let catalog = VersionCatalog::load(project_path)?;
+    let value = catalog.version(&c[1])?;
+    let file = project_path.join(VersionCatalog::RELATIVE_PATH);
+    return Some((value, VersionLocation::VersionCatalog { file, key: c[1].to_string() }));
+  }
+
+  // 变量：gradle.properties，其次模块脚本或根脚本中的字面量赋值（如 ext 块）
+  let name = referenced_name(&assignment.expr)?;
+  if let Some(found) = from_properties(&[name.as_str()]) {
+    return Some(found);
+  }
+  let root_script = script_path(project_path);
+  [Some(module_script), root_script].into_iter().flatten().find_map(|file| {
+    let content = fs::read_to_string(&file).ok()?;
+    let range = find_assignment(&content, &name)?.literal?;
+    Some((content[range].to_string(), VersionLocation::BuildScript { file, key: name.clone() }))
+  })
+}
+
+fn version_dir(project_path: &Path, module: Option<&str>) -> PathBuf {
+  match module {
+    Some(m) => discovery::module_dir(project_path, m),
+    None => project_path.to_path_buf(),
+  }
+}
+
+/// 读取模块（未指定时为工程根目录）的 versionCode/versionName
+pub fn read(project_path: &Path, module: Option<&str>) -> VersionInfo {
+  let dir = version_dir(project_path, module);
+  let code = locate(project_path, &dir, "versionCode", CODE_PROPERTY_KEYS);
+  let name = locate(project_path, &dir, "versionName", NAME_PROPERTY_KEYS);
+  let (version_code, code_location) = match code {
+    Some((value, location)) => match value.trim().parse() {
+      Ok(code) => (Some(code), Some(location)),
+      Err(_) => {
+        log::warn!("versionCode 不是整数: {} ({})", value, location);
+        (None, None)
+      }
+    },
+    None => (None, None),
+  };
+  let (version_name, name_location) = match name {
+    Some((value, location)) => (Some(value), Some(location)),
+    None => (None, None),
+  };
+  VersionInfo { version_code, version_name, code_location, name_location }
+}
+
+/// 写入新值前返回修改后的文件内容
+fn updated_content(location: &VersionLocation, content: &str, value: &str) -> Result<String> {
+  match location {
+    VersionLocation::BuildScript { key, file } => {
+      let range = find_assignment(content, key)
+        .and_then(|a| a.literal)
+        .ok_or_else(|| Error::Version(format!("未在 {} 中找到 {} 的字面量", file.display(), key)))?;
+      Ok(format!("{}{}{}", &content[..range.start], value, &content[range.end..]))
+    }
+    VersionLocation::GradleProperties { key, file } => properties::set_value(content, key, value)
+      .ok_or_else(|| Error::Version(format!("未在 {} 中找到 {}", file.display(), key))),
+    VersionLocation::VersionCatalog { key, file } => {
+      let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| Error::Version(format!("解析 {} 失败: {}", file.display(), e)))?;
+      let item = doc
+        .get_mut("versions")
+        .and_then(|v| v.as_table_like_mut())
+        .and_then(|t| {
+          let actual = t.iter().map(|(k, _)| k.to_string()).find(|k| accessor_key(k) == accessor_key(key))?;
+          t.get_mut(&actual)
+        })
+        .ok_or_else(|| Error::Version(format!("版本目录中没有 {}", key)))?;
+      let decor = item.as_value().map(|v| v.decor().clone());
+      *item = toml_edit::value(value);
+      if let (Some(decor), Some(v)) = (decor, item.as_value_mut()) {
+        *v.decor_mut() = decor;
+      }
+      Ok(doc.to_string())
+    }
+  }
+}
+
+fn location_file(location: &VersionLocation) -> &Path {
+  match location {
+    VersionLocation::BuildScript { file, .. }
+    | VersionLocation::GradleProperties { file, .. }
+    | VersionLocation::VersionCatalog { file, .. } => file,
+  }
+}
+
+/// UTC 时间 `yyMMddHH`
+fn timestamp_code() -> u64 {
+  let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+  let (days, hour) = (secs / 86_400, secs % 86_400 / 3_600);
+  let (year, month, day) = civil_from_days(days as i64);
+  ((year % 100) as u64) * 1_000_000 + (month as u64) * 10_000 + (day as u64) * 100 + hour
+}
+
+fn next_code(project_path: &Path, current: Option<u64>, bump: &CodeBump) -> Result<u64> {
+  let require_current = || current.ok_or_else(|| Error::Version("未找到当前的 versionCode，无法递增".into()));
+  match bump {
+    CodeBump::Increment => Ok(require_current()? + 1),
+    CodeBump::Timestamp => Ok(timestamp_code().max(current.map_or(0, |c| c + 1))),
+    CodeBump::GitCommitCount => {
+      let (ok, out) = run_command("git", &["-C", &project_path.to_string_lossy(), "rev-list", "--count", "HEAD"]);
+      if !ok {
+        return Err(Error::Version(format!("获取 git 提交数失败: {}", out)));
+      }
+      out.trim().parse().map_err(|_| Error::Version(format!("无法解析 git 提交数: {}", out)))
+    }
+    CodeBump::Explicit { value } => Ok(*value),
+  }
+}
+
+/// `1.2.3-beta` 按策略递增数字部分，保留后缀
+fn next_name(current: Option<&str>, bump: &NameBump) -> Result<String> {
+  let index = match bump {
+    NameBump::Explicit { value } => return Ok(value.clone()),
+    NameBump::Major => 0,
+    NameBump::Minor => 1,
+    NameBump::Patch => 2,
+  };
+  let current = current.ok_or_else(|| Error::Version("未找到当前的 versionName，无法递增".into()))?;
+  let core_len = current.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(current.len());
+  let (core, suffix) = current.split_at(core_len);
+  let mut parts: Vec<u64> = core
+    .split('.')
+    .filter(|p| !p.is_empty())
+    .map(|p| p.parse().map_err(|_| Error::Version(format!("versionName 不是数字版本: {}", current))))
+    .collect::<Result<_>>()?;
+  if parts.is_empty() {
+    return Err(Error::Version(format!("versionName 不是数字版本: {}", current)));
+  }
+  while parts.len() <= index {
+    parts.push(0);
+  }
+  parts[index] += 1;
+  for p in parts.iter_mut().skip(index + 1) {
+    *p = 0;
+  }
+  let core: Vec<String> = parts.iter().map(u64::to_string).collect();
+  Ok(format!("{}{}", core.join("."), suffix))
+}
+
+/// 按策略修改版本号并写入文件，策略为空时返回 None
+pub fn bump(project_path: &Path, module: Option<&str>, bump: &VersionBump) -> Result<Option<VersionChange>> {
+  if bump.is_empty() {
+    return Ok(None);
+  }
+  let info = read(project_path, module);
+  let previous = info.app_version();
+  let mut applied = previous.clone();
+  let mut edits: Vec<(&VersionLocation, String)> = Vec::new();
+
+  if let Some(code_bump) = &bump.code {
+    let location = info
+      .code_location
+      .as_ref()
+      .ok_or_else(|| Error::Version("未找到 versionCode 的定义（构建脚本、gradle.properties 或版本目录）".into()))?;
+    let code = next_code(project_path, info.version_code, code_bump)?;
+    if info.version_code.is_some_and(|current| code <= current) {
+      log::warn!("新的 versionCode {} 不大于当前值 {}，应用商店可能拒绝安装包", code, info.version_code.unwrap_or_default());
+    }
+    applied.version_code = Some(code);
+    edits.push((location, code.to_string()));
+  }
+  if let Some(name_bump) = &bump.name {
+    let location = info
+      .name_location
+      .as_ref()
+      .ok_or_else(|| Error::Version("未找到 versionName 的定义（构建脚本、gradle.properties 或版本目录）".into()))?;
+    let name = next_name(info.version_name.as_deref(), name_bump)?;
+    applied.version_name = Some(name.clone());
+    edits.push((location, name));
+  }
+
+  // 先计算全部新内容，再统一写入，避免只写了一半
+  let mut backups: Vec<(PathBuf, String)> = Vec::new();
+  let mut updated: Vec<(PathBuf, String)> = Vec::new();
+  for (location, value) in edits {
+    let file = location_file(location).to_path_buf();
+    let current = match updated.iter().find(|(p, _)| *p == file) {
+      Some((_, content)) => content.clone(),
+      None => {
+        let content = fs::read_to_string(&file).map_err(|e| Error::io(format!("读取 {} 失败", file.display()), e))?;
+        backups.push((file.clone(), content.clone()));
+        content
+      }
+    };
+    let content = updated_content(location, &current, &value)?;
+    match updated.iter_mut().find(|(p, _)| *p == file) {
+      Some(entry) => entry.1 = content,
+      None => updated.push((file, content)),
+    }
+  }
+
+  let change = VersionChange { previous, applied, rollback_on_failure: bump.rollback_on_failure, backups, settled: false };
+  for (file, content) in &updated {
+    // 写入失败时 change 释放，已写入的文件随之恢复
+    fs::write(file, content).map_err(|e| Error::io(format!("写入 {} 失败", file.display()), e))?;
+  }
+  Ok(Some(change))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn next_name_keeps_suffix() {
+    assert_eq!(next_name(Some("1.2.3-beta"), &NameBump::Patch).unwrap(), "1.2.4-beta");
+    assert_eq!(next_name(Some("1.2.3"), &NameBump::Minor).unwrap(), "1.3.0");
+    assert_eq!(next_name(Some("1.9"), &NameBump::Major).unwrap(), "2.0");
+    assert_eq!(next_name(Some("2"), &NameBump::Patch).unwrap(), "2.0.1");
+    assert_eq!(next_name(None, &NameBump::Explicit { value: "3.0.0".into() }).unwrap(), "3.0.0");
+    assert!(next_name(Some("beta"), &NameBump::Patch).is_err());
+    assert!(next_name(None, &NameBump::Patch).is_err());
+  }
+
+  #[test]
+  fn next_code_strategies() {
+    let dir = Path::new(".");
+    assert_eq!(next_code(dir, Some(41), &CodeBump::Increment).unwrap(), 42);
+    assert!(next_code(dir, None, &CodeBump::Increment).is_err());
+    assert_eq!(next_code(dir, Some(41), &CodeBump::Explicit { value: 7 }).unwrap(), 7);
+    // 时间戳不大于当前值时取当前值 + 1
+    assert_eq!(next_code(dir, Some(9_999_999_999), &CodeBump::Timestamp).unwrap(), 10_000_000_000);
+    assert_eq!("git-count".parse::<CodeBump>().unwrap(), CodeBump::GitCommitCount);
+    assert!("soon".parse::<CodeBump>().is_err());
+  }
+
+  #[test]
+  fn bump_writes_and_rolls_back() {
+    let project = std::env::temp_dir().join(format!("abt-test-version-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&project);
+    fs::create_dir_all(project.join("app")).unwrap();
+    let script = "android {\n  defaultConfig {\n    versionCode 5\n    versionName \"1.2.3-beta\"\n  }\n}\n";
+    let script_file = project.join("app/build.gradle");
+    fs::write(&script_file, script).unwrap();
+
+    let info = read(&project, Some("app"));
+    assert_eq!(info.version_code, Some(5));
+    assert_eq!(info.version_name.as_deref(), Some("1.2.3-beta"));
+
+    let strategy = VersionBump { code: Some(CodeBump::Increment), name: Some(NameBump::Minor), rollback_on_failure: true };
+    let mut change = bump(&project, Some("app"), &strategy).unwrap().unwrap();
+    assert_eq!(change.applied.version_code, Some(6));
+    assert_eq!(change.applied.version_name.as_deref(), Some("1.3.0-beta"));
+    let info = read(&project, Some("app"));
+    assert_eq!((info.version_code, info.version_name.as_deref()), (Some(6), Some("1.3.0-beta")));
+
+    change.rollback().unwrap();
+    assert_eq!(fs::read_to_string(&script_file).unwrap(), script);
+    let _ = fs::remove_dir_all(&project);
+  }
+}
@@ -0,0 +1,315 @@
+//! 按 zipalign 规则重写 ZIP：未压缩条目的数据按 4 字节对齐，未压缩的 .so 按 16 KB 页对齐
+//!
+//! 原样复制压缩后的数据与文件名，不重新压缩；签名块、ZIP 注释与数据描述符（data descriptor）都不保留。
+//! 不支持 ZIP64：条目数或大小超出普通 ZIP 的上限时报错。
+
+use std::{borrow::Cow, io::Read};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::{axml::invalid, signing_block::Apk, Result};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+/// EOCD 中条目总数的偏移
+const EOCD_TOTAL_ENTRIES: usize = 10;
+const LOCAL_HEADER_SIZE: usize = 30;
+const CENTRAL_HEADER_SIZE: usize = 46;
+/// 数据描述符标志位，重写后大小与 CRC 都在头部中
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+/// ZIP64 中用于表示“见 ZIP64 扩展字段”的占位值
+const ZIP64_MARKER_U16: u16 = 0xffff;
+const ZIP64_MARKER_U32: u32 = 0xffff_ffff;
+/// Android 的对齐扩展字段：u16 对齐值 + 填充
+const ALIGNMENT_EXTRA_ID: u16 = 0xd935;
+const DEFAULT_ALIGNMENT: usize = 4;
+/// 未压缩的 .so 按页对齐，可以直接 mmap（兼容 16 KB 页大小的设备）
+const LIBRARY_ALIGNMENT: usize = 16384;
+/// 1980-01-01 00:00，新增条目的修改时间，保证输出可复现
+const DOS_EPOCH: (u16, u16) = (0, 0x21);
+
+/// ZIP 条目：中央目录中的元数据与压缩后的数据
+#[derive(Clone)]
+pub(crate) struct ZipEntry<'a> {
+  /// 文件名（非 UTF-8 的字节按替换字符显示，只用于查找与提示）
+  pub name: String,
+  /// 原始文件名，写出时原样保留
+  raw_name: Cow<'a, [u8]>,
+  version_made_by: u16,
+  flags: u16,
+  pub method: u16,
+  time: u16,
+  date: u16,
+  crc: u32,
+  size: u32,
+  external_attributes: u32,
+  /// 压缩后的数据
+  data: Cow<'a, [u8]>,
+}
+
+impl<'a> ZipEntry<'a> {
+  /// 新增的 deflate 压缩条目
+  pub fn deflated(name: impl Into<String>, data: &[u8]) -> Self {
+    use std::io::Write;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    // 写入内存不会失败
+    let _ = encoder.write_all(data);
+    let compressed = encoder.finish().unwrap_or_default();
+    Self::new(name.into(), METHOD_DEFLATED, crc32(data), data.len() as u32, compressed)
+  }
+
+  /// 新增的不压缩条目
+  pub fn stored(name: impl Into<String>, data: &[u8]) -> Self {
+    Self::new(name.into(), METHOD_STORED, crc32(data), data.len() as u32, data.to_vec())
+  }
+
+  fn new(name: String, method: u16, crc: u32, size: u32, data: Vec<u8>) -> Self {
+    Self {
+      raw_name: Cow::Owned(name.as_bytes().to_vec()),
+      name,
+      // 与 apksigner 新增的条目相同：ZIP 2.0，不带文件属性
+      version_made_by: 20,
+      flags: 0,
+      method,
+      time: DOS_EPOCH.0,
+      date: DOS_EPOCH.1,
+      crc,
+      size,
+      external_attributes: 0,
+      data: Cow::Owned(data),
+    }
+  }
+
+  /// 解压后的内容
+  pub fn contents(&self) -> Result<Vec<u8>> {
+    match self.method {
+      METHOD_STORED => Ok(self.data.to_vec()),
+      METHOD_DEFLATED => {
+        let mut out = Vec::with_capacity(self.size as usize);
+        DeflateDecoder::new(&self.data[..])
+          .read_to_end(&mut out)
+          .map_err(|e| invalid("APK", format!("无法解压 {}：{}", self.name, e)))?;
+        Ok(out)
+      }
+      method => Err(invalid("APK", format!("{} 使用了不支持的压缩方式 {}", self.name, method))),
+    }
+  }
+
+  fn alignment(&self) -> Option<usize> {
+    if self.method != METHOD_STORED {
+      return None;
+    }
+    Some(if self.name.ends_with(".so") { LIBRARY_ALIGNMENT } else { DEFAULT_ALIGNMENT })
+  }
+}
+
+/// 按中央目录的顺序读取全部条目
+pub(crate) fn read_entries<'a>(apk: &Apk<'a>) -> Result<Vec<ZipEntry<'a>>> {
+  let what = "APK";
+  let data = apk.data;
+  let cd = apk.central_directory();
+  let total = read_u16(data, apk.eocd_offset + EOCD_TOTAL_ENTRIES) as usize;
+  if total == ZIP64_MARKER_U16 as usize {
+    return Err(invalid(what, "条目数超出普通 ZIP 的上限（ZIP64），暂不支持"));
+  }
+  let mut entries = Vec::new();
+  let mut pos = 0;
+  while pos + CENTRAL_HEADER_SIZE <= cd.len() && read_u32(cd, pos) == CENTRAL_HEADER_SIGNATURE {
+    let name_len = read_u16(cd, pos + 28) as usize;
+    let extra_len = read_u16(cd, pos + 30) as usize;
+    let comment_len = read_u16(cd, pos + 32) as usize;
+    let name_end = pos + CENTRAL_HEADER_SIZE + name_len;
+    if name_end > cd.len() {
+      return Err(invalid(what, "中央目录已损坏"));
+    }
+    let raw_name = &cd[pos + CENTRAL_HEADER_SIZE..name_end];
+    let name = String::from_utf8_lossy(raw_name).into_owned();
+    let (compressed_size, size, offset) = (read_u32(cd, pos + 20), read_u32(cd, pos + 24), read_u32(cd, pos + 42));
+    if [compressed_size, size, offset].contains(&ZIP64_MARKER_U32) {
+      return Err(invalid(what, format!("{} 使用了 ZIP64 格式，暂不支持", name)));
+    }
+    let (compressed_size, offset) = (compressed_size as usize, offset as usize);
+    // 数据位置以本地文件头中的文件名与扩展字段长度为准
+    if offset + LOCAL_HEADER_SIZE > apk.block_start || read_u32(data, offset) != LOCAL_HEADER_SIGNATURE {
+      return Err(invalid(what, format!("{} 的本地文件头已损坏", name)));
+    }
+    let start = offset + LOCAL_HEADER_SIZE + read_u16(data, offset + 26) as usize + read_u16(data, offset + 28) as usize;
+    let end = start + compressed_size;
+    if end > apk.block_start {
+      return Err(invalid(what, format!("{} 的数据超出范围", name)));
+    }
+    entries.push(ZipEntry {
+      name,
+      raw_name: Cow::Borrowed(raw_name),
+      version_made_by: read_u16(cd, pos + 4),
+      flags: read_u16(cd, pos + 8) & !FLAG_DATA_DESCRIPTOR,
+      method: read_u16(cd, pos + 10),
+      time: read_u16(cd, pos + 12),
+      date: read_u16(cd, pos + 14),
+      crc: read_u32(cd, pos + 16),
+      size,
+      external_attributes: read_u32(cd, pos + 38),
+      data: Cow::Borrowed(&data[start..end]),
+    });
+    pos = name_end + extra_len + comment_len;
+  }
+  // 中央目录被截断或损坏时不能静默丢掉其后的条目
+  if entries.len() != total {
+    return Err(invalid(what, format!("中央目录已损坏：记录了 {} 个条目，实际读取到 {} 个", total, entries.len())));
+  }
+  Ok(entries)
+}
+
+/// 写出对齐后的 ZIP（不含签名块），超出普通 ZIP 的上限时报错
+pub(crate) fn write_aligned(entries: &[ZipEntry]) -> Result<Vec<u8>> {
+  let what = "APK";
+  if entries.len() >= ZIP64_MARKER_U16 as usize {
+    return Err(invalid(what, "条目数超出普通 ZIP 的上限（ZIP64），暂不支持"));
+  }
+  let mut out = Vec::new();
+  let mut central = Vec::new();
+  for entry in entries {
+    let offset = out.len();
+    if offset >= ZIP64_MARKER_U32 as usize || entry.data.len() >= ZIP64_MARKER_U32 as usize {
+      return Err(invalid(what, "APK 大小超出普通 ZIP 的上限（ZIP64），暂不支持"));
+    }
+    let name = &entry.raw_name[..];
+    if name.len() > u16::MAX as usize {
+      return Err(invalid(what, format!("文件名过长：{}", entry.name)));
+    }
+    let version_needed: u16 = if entry.method == METHOD_STORED { 10 } else { 20 };
+    let mut extra = Vec::new();
+    if let Some(alignment) = entry.alignment() {
+      // 扩展字段头 4 字节 + 对齐值 2 字节，其余用 0 填充
+      let data_start = offset + LOCAL_HEADER_SIZE + name.len() + 6;
+      let padding = (alignment - data_start % alignment) % alignment;
+      extra.extend_from_slice(&ALIGNMENT_EXTRA_ID.to_le_bytes());
+      extra.extend_from_slice(&(2 + padding as u16).to_le_bytes());
+      extra.extend_from_slice(&(alignment as u16).to_le_bytes());
+      extra.resize(6 + padding, 0);
+    }
+
+    out.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+    out.extend_from_slice(&version_needed.to_le_bytes());
+    write_common(&mut out, entry);
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(&extra);
+    out.extend_from_slice(&entry.data);
+
+    central.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+    central.extend_from_slice(&entry.version_made_by.to_le_bytes());
+    central.extend_from_slice(&version_needed.to_le_bytes());
+    write_common(&mut central, entry);
+    central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    // 扩展字段长度、注释长度、磁盘号、内部属性
+    central.extend_from_slice(&[0; 8]);
+    central.extend_from_slice(&entry.external_attributes.to_le_bytes());
+    central.extend_from_slice(&(offset as u32).to_le_bytes());
+    central.extend_from_slice(name);
+  }
+
+  if out.len() + central.len() >= ZIP64_MARKER_U32 as usize {
+    return Err(invalid(what, "APK 大小超出普通 ZIP 的上限（ZIP64），暂不支持"));
+  }
+  let cd_offset = out.len() as u32;
+  let count = entries.len() as u16;
+  out.extend_from_slice(&central);
+  out.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+  out.extend_from_slice(&[0; 4]);
+  out.extend_from_slice(&count.to_le_bytes());
+  out.extend_from_slice(&count.to_le_bytes());
+  out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+  out.extend_from_slice(&cd_offset.to_le_bytes());
+  out.extend_from_slice(&0u16.to_le_bytes());
+  Ok(out)
+}
+
+/// 本地文件头与中央目录共有的字段：标志位到未压缩大小
+fn write_common(out: &mut Vec<u8>, entry: &ZipEntry) {
+  out.extend_from_slice(&entry.flags.to_le_bytes());
+  out.extend_from_slice(&entry.method.to_le_bytes());
+  out.extend_from_slice(&entry.time.to_le_bytes());
+  out.extend_from_slice(&entry.date.to_le_bytes());
+  out.extend_from_slice(&entry.crc.to_le_bytes());
+  out.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+  out.extend_from_slice(&entry.size.to_le_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+  let mut crc = flate2::Crc::new();
+  crc.update(data);
+  crc.sum()
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+  u16::from_le_bytes([data[pos], data[pos + 1]])
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+  u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::signing_block::tests::sample_apk;
+
+  fn data_offset(apk: &[u8], entry: &ZipEntry) -> usize {
+    entry.data.as_ptr() as usize - apk.as_ptr() as usize
+  }
+
+  #[test]
+  fn write_then_read() {
+    let data = sample_apk();
+    let apk = Apk::parse(&data).unwrap();
+    assert!(apk.pairs.is_empty());
+    let entries = read_entries(&apk).unwrap();
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+      names,
+      ["AndroidManifest.xml", "classes.dex", "resources.arsc", "lib/arm64-v8a/libdemo.so", "assets/readme.txt"]
+    );
+    assert_eq!(entries[1].method, METHOD_DEFLATED);
+    assert_eq!(entries[1].contents().unwrap(), b"dex\n035\0".repeat(64));
+    assert_eq!(entries[3].contents().unwrap(), b"\x7fELF");
+
+    assert_eq!(data_offset(&data, &entries[2]) % DEFAULT_ALIGNMENT, 0);
+    assert_eq!(data_offset(&data, &entries[3]) % LIBRARY_ALIGNMENT, 0);
+    // 重新写出的结果不变
+    assert_eq!(write_aligned(&entries).unwrap(), data);
+  }
+
+  #[test]
+  fn keeps_raw_names() {
+    let entry = ZipEntry { raw_name: Cow::Owned(b"res/\xff.png".to_vec()), ..ZipEntry::stored("res/?.png", b"png") };
+    let data = write_aligned(&[entry]).unwrap();
+    let apk = Apk::parse(&data).unwrap();
+    let entries = read_entries(&apk).unwrap();
+    assert_eq!(&entries[0].raw_name[..], b"res/\xff.png");
+    assert_eq!(entries[0].name, "res/\u{fffd}.png");
+  }
+
+  #[test]
+  fn rejects_truncated_central_directory() {
+    let mut data = sample_apk();
+    let apk = Apk::parse(&data).unwrap();
+    let second = apk.cd_offset + CENTRAL_HEADER_SIZE + read_u16(&data, apk.cd_offset + 28) as usize;
+    data[second] ^= 0xff;
+    let apk = Apk::parse(&data).unwrap();
+    assert!(read_entries(&apk).is_err());
+  }
+
+  #[test]
+  fn rejects_zip64() {
+    let mut data = sample_apk();
+    let cd_offset = Apk::parse(&data).unwrap().cd_offset;
+    data[cd_offset + 20..cd_offset + 24].copy_from_slice(&ZIP64_MARKER_U32.to_le_bytes());
+    let apk = Apk::parse(&data).unwrap();
+    assert!(read_entries(&apk).is_err());
+  }
+}
@@ -1,954 +1,368 @@
-use serde::{Deserialize, Serialize};
-use tauri::Manager;
-use std::{
-  fs,
-  path::{Path, PathBuf},
-  process::Command as StdCommand,
+use abt_core::{
+  build::{self, ArtifactKind, BuildLogLine, BuildRegistry, BuildRequest, BuildResult},
+  channel::{self, ChannelOutput, ChannelRequest, ChannelScheme, FoundChannel},
+  config::{
+    self, AppDirs, Project, ProjectsConfig, PublishPlatformConfig, PublishPlatformsConfig, SigningProfile,
+    SigningProfilesConfig,
+  },
+  discovery::{DiscoveryMode, ProjectStructure},
+  env::{self, EnvCheck, Jdk},
+  gradle_options::GradleOptions,
+  history::{self, BuildRecord, HistoryFilter},
+  inspect::{self, ArtifactInfo},
+  keystores::{self, CreateKeystoreRequest, ImportKeystoreRequest, KeystoreInfo},
+  project_env,
+  publish::{self, PublishConfig, PublishResult},
+  release_notes::{self, ReleaseNotes},
+  secrets::{self, SecretInfo, SecretsStatus},
+  signature::{self, SignatureReport},
+  signer::{self, SignResult},
+  version::{self, VersionBump, VersionInfo},
+  Error,
 };
-use tokio::process::Command;
-use reqwest::multipart::{Form, Part};
-
-#[derive(Serialize)]
-struct EnvCheck {
-  tool: String,
-  ok: bool,
-  message: String,
-  detail: Option<String>,
-}
-
-fn run_command(cmd: &str, args: &[&str]) -> (bool, String) {
-  match StdCommand::new(cmd).args(args).output() {
-    Ok(out) => {
-      let mut data = Vec::new();
-      data.extend(out.stdout);
-      data.extend(out.stderr);
-      (out.status.success(), String::from_utf8_lossy(&data).trim().to_string())
-    }
-    Err(err) => (false, err.to_string()),
-  }
-}
-
-fn check_java() -> EnvCheck {
-  let (ok, out) = run_command("java", &["-version"]);
-  EnvCheck {
-    tool: "Java (JDK)".into(),
-    ok,
-    message: if ok { "已检测到 JDK" } else { "未检测到 JDK，请安装 JDK 17+" }.into(),
-    detail: Some(out),
-  }
-}
+use std::path::Path;
+use tauri::{Emitter, Manager};
 
-fn check_java_home() -> EnvCheck {
-  let (ok, out) = run_command("/usr/libexec/java_home", &[]);
-  EnvCheck {
-    tool: "JAVA_HOME".into(),
-    ok,
-    message: if ok { format!("JAVA_HOME: {out}") } else { "未检测到 JAVA_HOME".into() },
-    detail: Some(out),
-  }
-}
-
-fn check_xcode() -> EnvCheck {
-  let (ok, out) = run_command("xcode-select", &["-p"]);
-  EnvCheck {
-    tool: "Xcode CLT".into(),
-    ok,
-    message: if ok {
-      format!("已检测到 Xcode Command Line Tools: {out}")
-    } else {
-      "未检测到 Xcode CLT".into()
-    },
-    detail: Some(out),
+/// 使用 Tauri 提供的应用目录，与命令行默认查找的目录一致
+fn app_dirs(app_handle: &tauri::AppHandle) -> AppDirs {
+  AppDirs {
+    config_override: None,
+    config_dir: app_handle.path().app_config_dir().ok(),
+    data_dir: app_handle.path().app_data_dir().ok(),
   }
 }
 
+/// 检测构建环境，指定工程时从其 local.properties 读取 SDK 路径
 #[tauri::command]
-fn check_env() -> Vec<EnvCheck> {
-  vec![
-    check_java(),
-    check_java_home(),
-    check_xcode(),
-  ]
+fn check_env(app_handle: tauri::AppHandle, project: Option<String>) -> Result<Vec<EnvCheck>, Error> {
+  let project_path = match project {
+    Some(name) => Some(config::find_project(&app_dirs(&app_handle), &name)?.path),
+    None => None,
+  };
+  Ok(env::check_env(project_path.as_deref().map(Path::new)))
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct Project {
-  name: String,
-  path: String,
-  #[serde(rename = "defaultModule")]
-  default_module: Option<String>,
-  #[serde(rename = "modules")]
-  modules: Option<Vec<String>>,
-  #[serde(rename = "defaultVariant")]
-  default_variant: Option<String>,
-  #[serde(rename = "variants")]
-  variants: Option<Vec<String>>,
-  #[serde(rename = "buildType")]
-  build_type: Option<String>,
+#[tauri::command]
+fn check_project_env(app_handle: tauri::AppHandle, name: String) -> Result<Vec<EnvCheck>, Error> {
+  let project = config::find_project(&app_dirs(&app_handle), &name)?;
+  Ok(project_env::checks(Path::new(&project.path), project.java_home.as_deref().map(Path::new)))
 }
 
-#[derive(Serialize, Deserialize)]
-struct ProjectsConfig {
-  projects: Vec<Project>,
+#[tauri::command]
+fn list_jdks() -> Vec<Jdk> {
+  env::list_jdks()
 }
 
-fn config_path(app_handle: &tauri::AppHandle) -> PathBuf {
-  let cwd = std::env::current_dir().unwrap_or_default();
-  let candidates = [
-    cwd.join("config/projects.json"),
-    cwd.join("../config/projects.json"),
-  ];
-  for c in candidates {
-    if c.exists() {
-      return c;
-    }
-  }
-
-  if let Ok(dir) = app_handle.path().app_config_dir() {
-    return dir.join("projects.json");
-  }
-
-  std::env::temp_dir().join("projects.json")
+#[tauri::command]
+fn list_projects(app_handle: tauri::AppHandle) -> Result<ProjectsConfig, Error> {
+  config::list_projects(&app_dirs(&app_handle))
 }
 
-fn ensure_config(app_handle: &tauri::AppHandle) -> PathBuf {
-  let path = config_path(app_handle);
-  if let Some(parent) = path.parent() {
-    let _ = fs::create_dir_all(parent);
-  }
-  if !path.exists() {
-    let default = ProjectsConfig { projects: vec![] };
-    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
-  }
-  path
+#[tauri::command]
+fn add_project(app_handle: tauri::AppHandle, project: Project) -> Result<(), Error> {
+  config::add_project(&app_dirs(&app_handle), project)
 }
 
 #[tauri::command]
-fn list_projects(app_handle: tauri::AppHandle) -> Result<ProjectsConfig, String> {
-  let path = ensure_config(&app_handle);
-  let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-  let cfg: ProjectsConfig = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-  Ok(cfg)
+fn update_project(app_handle: tauri::AppHandle, name: String, project: Project) -> Result<(), Error> {
+  config::update_project(&app_dirs(&app_handle), &name, project)
 }
 
 #[tauri::command]
-fn add_project(app_handle: tauri::AppHandle, project: Project) -> Result<(), String> {
-  let path = ensure_config(&app_handle);
-  let mut cfg = list_projects(app_handle.clone())?;
-  if cfg.projects.iter().any(|p| p.name == project.name) {
-    return Err(format!("工程名已存在：{}", project.name));
-  }
-  let gradle_name = if cfg!(windows) { "gradlew.bat" } else { "gradlew" };
-  let gradle_path = Path::new(&project.path).join(gradle_name);
-  if !gradle_path.exists() {
-    return Err("未找到 gradlew，请确认工程路径正确".into());
-  }
-  cfg.projects.push(project);
-  fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
+fn delete_project(app_handle: tauri::AppHandle, name: String) -> Result<(), Error> {
+  config::delete_project(&app_dirs(&app_handle), &name)
 }
 
+/// 从工程的 settings.gradle 与构建脚本中发现模块和变体，并写回 projects.json
 #[tauri::command]
-fn update_project(app_handle: tauri::AppHandle, name: String, project: Project) -> Result<(), String> {
-  let path = ensure_config(&app_handle);
-  let mut cfg = list_projects(app_handle.clone())?;
-  let index = cfg.projects.iter().position(|p| p.name == name);
-  match index {
-    Some(idx) => {
-      let existing_project = &cfg.projects[idx];
-      
-      // 如果更新了路径，需要验证 gradlew 是否存在
-      if project.path != existing_project.path {
-        let gradle_name = if cfg!(windows) { "gradlew.bat" } else { "gradlew" };
-        let gradle_path = Path::new(&project.path).join(gradle_name);
-        if !gradle_path.exists() {
-          return Err("未找到 gradlew，请确认工程路径正确".into());
-        }
-      }
-      
-      // 更新工程信息（保留原有字段，用新值覆盖）
-      cfg.projects[idx] = project;
-      fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
-    }
-    None => Err(format!("工程不存在：{}", name)),
-  }
+async fn discover_project(
+  app_handle: tauri::AppHandle,
+  name: String,
+  mode: Option<DiscoveryMode>,
+) -> Result<ProjectStructure, Error> {
+  config::discover_project(&app_dirs(&app_handle), &name, mode.unwrap_or_default()).await
 }
 
+/// 取消正在进行的构建：先正常终止进程树，宽限期后仍未退出则强制结束
 #[tauri::command]
-fn delete_project(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
-  let path = ensure_config(&app_handle);
-  let mut cfg = list_projects(app_handle.clone())?;
-  let index = cfg.projects.iter().position(|p| p.name == name);
-  match index {
-    Some(idx) => {
-      cfg.projects.remove(idx);
-      fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
-    }
-    None => Err(format!("工程不存在：{}", name)),
-  }
+fn cancel_build(app_handle: tauri::AppHandle, build_id: String) -> Result<(), Error> {
+  let pid = app_handle.state::<BuildRegistry>().cancel(&build_id)?;
+  tauri::async_runtime::spawn(async move {
+    app_handle
+      .state::<BuildRegistry>()
+      .kill_after_grace_period(&build_id, pid)
+      .await;
+  });
+  Ok(())
 }
 
-#[derive(Serialize)]
-struct BuildResult {
-  code: i32,
-  output: String,
-}
+/// 构建日志事件名，前端通过 `listen("build-log")` 订阅
+const BUILD_LOG_EVENT: &str = "build-log";
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn build_project(
   app_handle: tauri::AppHandle,
   name: String,
   module: Option<String>,
   variant: Option<String>,
+  flavors: Option<Vec<String>>,
   build_type: Option<String>,
+  artifact_kind: Option<ArtifactKind>,
   output_dir: Option<String>,
-) -> Result<BuildResult, String> {
-  let cfg = list_projects(app_handle.clone())?;
-  let project = cfg.projects.iter().find(|p| p.name == name).ok_or("未找到工程")?;
-  
-  // 获取构建参数，优先使用传入的值，否则使用工程默认值
-  let variant = variant.or_else(|| {
-    // 如果有 variants 数组，使用第一个
-    project.variants.as_ref()
-      .and_then(|v| v.first())
-      .map(|s| s.clone())
-      .or_else(|| project.default_variant.clone())
-  });
-  let build_type = build_type.or_else(|| project.build_type.clone()).unwrap_or_else(|| "Debug".into());
-  let module = module.or_else(|| {
-    // 如果有 modules 数组，使用第一个
-    project.modules.as_ref()
-      .and_then(|m| m.first())
-      .map(|s| s.clone())
-      .or_else(|| project.default_module.clone())
-  });
-  
-  // variant规则：variant + buildType（不需要大小写转换，以填写为准）
-  let full_variant = match &variant {
-    Some(v) => format!("{}{}", v, build_type),
-    None => build_type.clone(),
+  build_id: Option<String>,
+  gradle_options: Option<GradleOptions>,
+  version_bump: Option<VersionBump>,
+  signing_profile: Option<String>,
+) -> Result<BuildResult, Error> {
+  let request = BuildRequest {
+    name,
+    module,
+    variant,
+    flavors,
+    build_type,
+    artifact_kind,
+    output_dir,
+    build_id,
+    gradle_options,
+    version_bump,
+    signing_profile,
   };
-  
-  // 输出目录：优先使用传入的 output_dir，否则使用 path+module+variant/buildType
-  let output_dir = if let Some(ref custom_dir) = output_dir {
-    Path::new(custom_dir).to_path_buf()
-  } else {
-    let mut dir = Path::new(&project.path).to_path_buf();
-    if let Some(m) = &module {
-      dir = dir.join(m);
+  let emitter = app_handle.clone();
+  let sink = Box::new(move |line: &BuildLogLine| {
+    if let Err(e) = emitter.emit(BUILD_LOG_EVENT, line) {
+      log::warn!("推送构建日志失败: {}", e);
     }
-    // 使用 variant/buildType 格式，而不是 variant+buildType
-    match &variant {
-      Some(v) => {
-        dir = dir.join(v);
-        dir = dir.join(&build_type);
-      }
-      None => {
-        dir = dir.join(&build_type);
-      }
-    }
-    dir
-  };
-  
-  // 构建gradle任务名称
-  let task = match &module {
-    Some(m) => format!(":{}:assemble{}", m, full_variant),
-    None => format!("assemble{}", full_variant),
-  };
-  
-  let gradle_name = if cfg!(windows) { "gradlew.bat" } else { "gradlew" };
-  let gradle_path = Path::new(&project.path).join(gradle_name);
-  if !gradle_path.exists() {
-    return Err("未找到 gradlew，请确认工程路径正确".into());
-  }
-  
-  // 使用异步 Command 执行构建命令，避免阻塞主线程
-  let output = Command::new(&gradle_path)
-    .arg(&task)
-    .current_dir(&project.path)
-    .output()
-    .await
-    .map_err(|e| format!("执行构建命令失败: {}", e))?;
-
-  let mut combined = Vec::new();
-  combined.extend(output.stdout);
-  combined.extend(output.stderr);
-  let code = output.status.code().unwrap_or(-1);
-  
-  let mut output_text = String::from_utf8_lossy(&combined).to_string();
-  
-  // 如果构建成功，复制构建产物到输出目录
-  if code == 0 {
-    let output_dir_str = output_dir.to_string_lossy().to_string();
-    output_text.push_str(&format!("\n\n输出目录: {}\n", output_dir_str));
-    
-    // 查找并复制构建产物
-    let module_path = match &module {
-      Some(m) => Path::new(&project.path).join(m),
-      None => Path::new(&project.path).to_path_buf(),
-    };
-    
-    output_text.push_str(&format!("查找路径: {}\n", module_path.to_string_lossy()));
-    let artifacts = find_build_artifacts(&module_path);
-    output_text.push_str(&format!("找到 {} 个构建产物\n", artifacts.len()));
-    
-    if !artifacts.is_empty() {
-      for artifact in &artifacts {
-        output_text.push_str(&format!("  - {}\n", artifact.to_string_lossy()));
-      }
-      
-      // 如果输出目录已存在，先清理目录（确保只保留最新的构建产物）
-      if output_dir.exists() {
-        output_text.push_str(&format!("清理输出目录: {}\n", output_dir.to_string_lossy()));
-        clean_directory(&output_dir, &mut output_text);
-      }
-      
-      // 确保输出目录存在
-      if let Err(e) = fs::create_dir_all(&output_dir) {
-        output_text.push_str(&format!("创建输出目录失败: {}\n", e));
-      } else {
-        for artifact in &artifacts {
-          if let Some(file_name) = artifact.file_name() {
-            let dest = output_dir.join(file_name);
-            if !artifact.exists() {
-              output_text.push_str(&format!("❌ 源文件不存在: {}\n", artifact.to_string_lossy()));
-              continue;
-            }
-            if let Err(e) = fs::copy(artifact, &dest) {
-              output_text.push_str(&format!("❌ 复制失败 {}: {}\n", file_name.to_string_lossy(), e));
-            } else {
-              output_text.push_str(&format!("✅ 已复制: {} -> {}\n", file_name.to_string_lossy(), dest.to_string_lossy()));
-            }
-          }
-        }
-      }
-    } else {
-      output_text.push_str("未找到构建产物，请检查构建是否成功\n");
-    }
-  } else {
-    // 构建失败时也显示输出目录信息
-    let output_dir_str = output_dir.to_string_lossy().to_string();
-    output_text = format!("输出目录: {}\n\n{}", output_dir_str, output_text);
-  }
+  });
+  let registry = app_handle.state::<BuildRegistry>();
+  build::run_build(&app_dirs(&app_handle), &registry, request, sink).await
+}
 
-  Ok(BuildResult {
-    code,
-    output: output_text,
-  })
+/// 读取工程模块当前的 versionCode/versionName
+#[tauri::command]
+fn read_project_version(app_handle: tauri::AppHandle, name: String, module: Option<String>) -> Result<VersionInfo, Error> {
+  let project = config::find_project(&app_dirs(&app_handle), &name)?;
+  let module = module.or_else(|| project.default_build_module());
+  Ok(version::read(Path::new(&project.path), module.as_deref()))
 }
 
-/// 清理目录中的所有文件和子目录
-fn clean_directory(dir: &Path, output_text: &mut String) {
-  if !dir.exists() {
-    return;
-  }
-  
-  if let Ok(entries) = fs::read_dir(dir) {
-    for entry in entries.flatten() {
-      let path = entry.path();
-      let result = if path.is_dir() {
-        fs::remove_dir_all(&path)
-      } else {
-        fs::remove_file(&path)
-      };
-      
-      if let Err(e) = result {
-        output_text.push_str(&format!("⚠️ 清理文件/目录失败: {}: {}\n", path.to_string_lossy(), e));
-      }
-    }
-  }
+/// 查询构建历史，按开始时间倒序
+#[tauri::command]
+fn list_build_history(app_handle: tauri::AppHandle, filter: Option<HistoryFilter>) -> Result<Vec<BuildRecord>, Error> {
+  history::list(&app_dirs(&app_handle).history_dir(), &filter.unwrap_or_default())
 }
 
-/// 查找构建产物（APK/AAB）
-fn find_build_artifacts(module_path: &Path) -> Vec<PathBuf> {
-  let mut artifacts = Vec::new();
-  
-  // APK 文件路径
-  let apk_path = module_path.join("build/outputs/apk");
-  // AAB 文件路径
-  let bundle_path = module_path.join("build/outputs/bundle");
-  
-  // 查找所有 APK 文件
-  if apk_path.exists() {
-    find_artifacts_recursive(&apk_path, "apk", &mut artifacts);
-  }
-  
-  // 查找所有 AAB 文件
-  if bundle_path.exists() {
-    find_artifacts_recursive(&bundle_path, "aab", &mut artifacts);
-  }
-  
-  artifacts
-}
-
-/// 递归查找构建产物
-fn find_artifacts_recursive(dir: &Path, extension: &str, artifacts: &mut Vec<PathBuf>) {
-  if let Ok(entries) = fs::read_dir(dir) {
-    for entry in entries.flatten() {
-      let path = entry.path();
-      if path.is_dir() {
-        find_artifacts_recursive(&path, extension, artifacts);
-      } else if path.extension().and_then(|s| s.to_str()) == Some(extension) {
-        artifacts.push(path);
-      }
-    }
-  }
+/// 删除构建历史及对应日志，返回删除的条数
+#[tauri::command]
+fn delete_build_history(app_handle: tauri::AppHandle, build_ids: Vec<String>) -> Result<usize, Error> {
+  history::delete(&app_dirs(&app_handle).history_dir(), &build_ids)
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct PublishConfig {
-  platform: String, // "pgyer" 或 "fir"
-  api_key: Option<String>, // pgyer 使用
-  api_token: Option<String>, // fir 使用
-  password: Option<String>, // pgyer 可选密码
-  update_description: Option<String>, // 更新描述
+/// 读取历史构建的完整日志
+#[tauri::command]
+fn read_build_log(app_handle: tauri::AppHandle, build_id: String) -> Result<String, Error> {
+  history::read_log(&app_dirs(&app_handle).history_dir(), &build_id)
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct PublishPlatformConfig {
-  name: String, // 配置名称
-  platform: String, // "pgyer" 或 "fir"
-  api_key: Option<String>, // pgyer 使用
-  api_token: Option<String>, // fir 使用
-  password: Option<String>, // pgyer 可选密码
-  default_description: Option<String>, // 默认更新描述
+#[tauri::command]
+fn list_publish_platforms(app_handle: tauri::AppHandle) -> Result<PublishPlatformsConfig, Error> {
+  config::list_publish_platforms(&app_dirs(&app_handle))
 }
 
-#[derive(Serialize, Deserialize)]
-struct PublishPlatformsConfig {
-  platforms: Vec<PublishPlatformConfig>,
+#[tauri::command]
+fn add_publish_platform(app_handle: tauri::AppHandle, platform: PublishPlatformConfig) -> Result<(), Error> {
+  config::add_publish_platform(&app_dirs(&app_handle), platform)
 }
 
-fn publish_config_path(app_handle: &tauri::AppHandle) -> PathBuf {
-  let cwd = std::env::current_dir().unwrap_or_default();
-  let candidates = [
-    cwd.join("config/publish_platforms.json"),
-    cwd.join("../config/publish_platforms.json"),
-  ];
-  for c in candidates {
-    if c.exists() {
-      return c;
-    }
-  }
+#[tauri::command]
+fn update_publish_platform(app_handle: tauri::AppHandle, name: String, platform: PublishPlatformConfig) -> Result<(), Error> {
+  config::update_publish_platform(&app_dirs(&app_handle), &name, platform)
+}
 
-  if let Ok(dir) = app_handle.path().app_config_dir() {
-    return dir.join("publish_platforms.json");
-  }
+#[tauri::command]
+fn delete_publish_platform(app_handle: tauri::AppHandle, name: String) -> Result<(), Error> {
+  config::delete_publish_platform(&app_dirs(&app_handle), &name)
+}
 
-  std::env::temp_dir().join("publish_platforms.json")
+#[tauri::command]
+fn list_signing_profiles(app_handle: tauri::AppHandle) -> Result<SigningProfilesConfig, Error> {
+  config::list_signing_profiles(&app_dirs(&app_handle))
 }
 
-fn ensure_publish_config(app_handle: &tauri::AppHandle) -> PathBuf {
-  let path = publish_config_path(app_handle);
-  if let Some(parent) = path.parent() {
-    let _ = fs::create_dir_all(parent);
-  }
-  if !path.exists() {
-    let default = PublishPlatformsConfig { platforms: vec![] };
-    let _ = fs::write(&path, serde_json::to_string_pretty(&default).unwrap_or_else(|_| "{}".into()));
-  }
-  path
+#[tauri::command]
+fn add_signing_profile(app_handle: tauri::AppHandle, profile: SigningProfile) -> Result<(), Error> {
+  config::add_signing_profile(&app_dirs(&app_handle), profile)
 }
 
 #[tauri::command]
-fn list_publish_platforms(app_handle: tauri::AppHandle) -> Result<PublishPlatformsConfig, String> {
-  let path = ensure_publish_config(&app_handle);
-  let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-  let cfg: PublishPlatformsConfig = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-  Ok(cfg)
+fn update_signing_profile(app_handle: tauri::AppHandle, name: String, profile: SigningProfile) -> Result<(), Error> {
+  config::update_signing_profile(&app_dirs(&app_handle), &name, profile)
 }
 
 #[tauri::command]
-fn add_publish_platform(app_handle: tauri::AppHandle, platform: PublishPlatformConfig) -> Result<(), String> {
-  let path = ensure_publish_config(&app_handle);
-  let mut cfg = list_publish_platforms(app_handle.clone())?;
-  if cfg.platforms.iter().any(|p| p.name == platform.name) {
-    return Err(format!("配置名称已存在：{}", platform.name));
-  }
-  cfg.platforms.push(platform);
-  fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
+fn delete_signing_profile(app_handle: tauri::AppHandle, name: String) -> Result<(), Error> {
+  config::delete_signing_profile(&app_dirs(&app_handle), &name)
 }
 
 #[tauri::command]
-fn update_publish_platform(app_handle: tauri::AppHandle, name: String, platform: PublishPlatformConfig) -> Result<(), String> {
-  let path = ensure_publish_config(&app_handle);
-  let mut cfg = list_publish_platforms(app_handle.clone())?;
-  let index = cfg.platforms.iter().position(|p| p.name == name);
-  match index {
-    Some(idx) => {
-      cfg.platforms[idx] = platform;
-      fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
-    }
-    None => Err(format!("配置不存在：{}", name)),
-  }
+fn list_keystores(app_handle: tauri::AppHandle) -> Result<Vec<KeystoreInfo>, Error> {
+  keystores::list(&app_dirs(&app_handle))
 }
 
 #[tauri::command]
-fn delete_publish_platform(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
-  let path = ensure_publish_config(&app_handle);
-  let mut cfg = list_publish_platforms(app_handle.clone())?;
-  let index = cfg.platforms.iter().position(|p| p.name == name);
-  match index {
-    Some(idx) => {
-      cfg.platforms.remove(idx);
-      fs::write(path, serde_json::to_string_pretty(&cfg).unwrap()).map_err(|e| e.to_string())
-    }
-    None => Err(format!("配置不存在：{}", name)),
-  }
+fn import_keystore(app_handle: tauri::AppHandle, request: ImportKeystoreRequest) -> Result<KeystoreInfo, Error> {
+  keystores::import(&app_dirs(&app_handle), request)
 }
 
-#[derive(Serialize)]
-struct PublishResult {
-  success: bool,
-  message: String,
-  download_url: Option<String>,
-  qr_code_url: Option<String>,
-  build_key: Option<String>,
-  build_shortcut_url: Option<String>,
+/// 生成 RSA 4096 位密钥需要数秒，放到阻塞线程中执行
+#[tauri::command]
+async fn create_keystore(app_handle: tauri::AppHandle, request: CreateKeystoreRequest) -> Result<KeystoreInfo, Error> {
+  let dirs = app_dirs(&app_handle);
+  tauri::async_runtime::spawn_blocking(move || keystores::create(&dirs, request))
+    .await
+    .map_err(|e| Error::Keystore(format!("创建 keystore 的任务异常退出：{}", e)))?
 }
 
-/// 上传到蒲公英（使用快速上传API，参考官方Node.js示例）
-/// 参考: https://github.com/PGYER/upload-app-api-example/tree/main/nodejs-demo
-async fn upload_to_pgyer(file_path: &Path, config: &PublishConfig) -> Result<PublishResult, String> {
-  if config.api_key.is_none() {
-    return Err("蒲公英 API Key 未配置".to_string());
-  }
+#[tauri::command]
+fn delete_keystore(app_handle: tauri::AppHandle, id: String) -> Result<(), Error> {
+  keystores::delete(&app_dirs(&app_handle), &id)
+}
 
-  log::info!("开始上传到蒲公英（快速上传模式），文件: {:?}", file_path);
-  
-  let file_name = file_path
-    .file_name()
-    .and_then(|n| n.to_str())
-    .ok_or("无法获取文件名")?;
-  
-  let file_metadata = tokio::fs::metadata(file_path)
-    .await
-    .map_err(|e| format!("获取文件信息失败: {}", e))?;
-  let file_size = file_metadata.len();
-  log::info!("文件: {}, 大小: {} bytes ({:.2} MB)", file_name, file_size, file_size as f64 / 1024.0 / 1024.0);
-  
-  // 优化HTTP客户端配置以提高上传速度
-  let client = reqwest::Client::builder()
-    .timeout(std::time::Duration::from_secs(300)) // 5分钟超时，适合大文件上传
-    .connect_timeout(std::time::Duration::from_secs(30)) // 30秒连接超时
-    .tcp_keepalive(std::time::Duration::from_secs(60)) // TCP keepalive
-    .pool_max_idle_per_host(2) // 每个主机最多2个空闲连接
-    .build()
-    .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-  
-  // 步骤1: 获取上传凭证（getCOSToken）
-  log::info!("步骤1: 获取上传凭证...");
-  let mut token_form = Form::new()
-    .text("_api_key", config.api_key.as_ref().unwrap().clone())
-    .text("buildType", "android");
-  
-  if let Some(ref desc) = config.update_description {
-    if !desc.trim().is_empty() {
-      token_form = token_form.text("buildUpdateDescription", desc.trim().to_string());
-      log::info!("已设置更新描述: {}", desc.trim());
-    }
-  }
-  
-  if let Some(ref password) = config.password {
-    token_form = token_form
-      .text("buildInstallType", "2")  // 2 = 密码安装
-      .text("buildPassword", password.clone());
-    log::info!("已设置安装密码");
-  }
-  
-  log::info!("步骤1: 发送获取凭证请求到: https://api.pgyer.com/apiv2/app/getCOSToken");
-  let token_response = client
-    .post("https://api.pgyer.com/apiv2/app/getCOSToken")
-    .multipart(token_form)
-    .send()
-    .await
-    .map_err(|e| {
-      log::error!("步骤1: 请求失败: {}", e);
-      format!("获取上传凭证失败: {}", e)
-    })?;
-  
-  let status = token_response.status();
-  log::info!("步骤1: 响应状态码: {}", status);
-  
-  let token_result: serde_json::Value = token_response
-    .json()
-    .await
-    .map_err(|e| {
-      log::error!("步骤1: 解析响应失败: {}", e);
-      format!("解析凭证响应失败: {}", e)
-    })?;
-  
-  log::info!("步骤1: 响应内容: {}", serde_json::to_string_pretty(&token_result).unwrap_or_default());
-  
-  if token_result["code"].as_i64() != Some(0) {
-    let error_msg = format!(
-      "获取上传凭证失败: {}",
-      token_result["message"]
-        .as_str()
-        .unwrap_or("未知错误")
-    );
-    log::error!("步骤1: {}", error_msg);
-    return Err(error_msg);
-  }
-  
-  let token_data = &token_result["data"];
-  let endpoint = token_data["endpoint"]
-    .as_str()
-    .ok_or("无法获取上传端点")?
-    .to_string();
-  let key = token_data["key"]
-    .as_str()
-    .ok_or("无法获取上传密钥")?
-    .to_string();
-  let params = token_data["params"]
-    .as_object()
-    .ok_or("无法获取上传参数")?;
-  
-  // 提前提取所有参数到 String，避免生命周期问题
-  let mut params_vec: Vec<(String, String)> = Vec::new();
-  for (param_key, param_value) in params {
-    if let Some(value) = param_value.as_str() {
-      params_vec.push((param_key.clone(), value.to_string()));
-    }
-  }
-  
-  let params_count = params_vec.len();
-  log::info!("步骤1: 成功获取上传凭证，端点: {}, 参数数量: {}", endpoint, params_count);
-  
-  // 步骤2: 上传文件到COS
-  log::info!("步骤2: 开始上传文件到COS...");
-  let start_time = std::time::Instant::now();
-  
-  // 读取文件内容
-  let file_bytes = tokio::fs::read(file_path)
-    .await
-    .map_err(|e| format!("读取文件失败: {}", e))?;
-  
-  let file_part = Part::bytes(file_bytes)
-    .file_name(file_name.to_string())
-    .mime_str("application/vnd.android.package-archive")
-    .map_err(|e| format!("创建文件部分失败: {}", e))?;
-  
-  // 构建上传表单，只包含文件（COS要求）
-  let upload_form = Form::new().part("file", file_part);
-  
-  // 构建HTTP请求，将签名参数作为URL查询参数或headers
-  log::info!("步骤2: 上传到COS端点: {}, 参数数量: {}", endpoint, params_count);
-  
-  // 解析endpoint URL，添加查询参数
-  let mut url = reqwest::Url::parse(&endpoint).map_err(|e| format!("解析COS端点URL失败: {}", e))?;
-  
-  // 分离参数：signature 和 key 作为查询参数，x-cos-security-token 作为 header
-  let mut security_token: Option<String> = None;
-  for (param_key, param_value) in params_vec {
-    if param_key == "x-cos-security-token" {
-      security_token = Some(param_value);
-      log::info!("步骤2: 将 {} 作为 header", param_key);
-    } else {
-      // signature 和 key 作为 URL 查询参数
-      url.query_pairs_mut().append_pair(&param_key, &param_value);
-      log::info!("步骤2: 添加查询参数: {} = {}...", param_key, &param_value[..param_value.len().min(50)]);
-    }
-  }
-  
-  let mut request_builder = client.post(url).multipart(upload_form);
-  
-  // 添加 x-cos-security-token 作为 header
-  if let Some(token) = security_token {
-    request_builder = request_builder.header("x-cos-security-token", token);
-  }
-  
-  let upload_response = request_builder
-    .send()
+#[tauri::command]
+fn secrets_status(app_handle: tauri::AppHandle) -> Result<SecretsStatus, Error> {
+  secrets::status(&app_dirs(&app_handle))
+}
+
+/// 主密码经 Argon2id 派生，耗时较长，放到阻塞线程中执行；密钥库不存在时以该密码创建
+#[tauri::command]
+async fn unlock_secrets(app_handle: tauri::AppHandle, passphrase: String) -> Result<SecretsStatus, Error> {
+  let dirs = app_dirs(&app_handle);
+  tauri::async_runtime::spawn_blocking(move || secrets::unlock(&dirs, &passphrase))
     .await
-    .map_err(|e| {
-      let elapsed = start_time.elapsed();
-      log::error!("步骤2: 上传请求失败 (耗时: {:.2}秒): {}", elapsed.as_secs_f64(), e);
-      format!("上传文件失败 (耗时: {:.2}秒): {}", elapsed.as_secs_f64(), e)
-    })?;
-  
-  let upload_elapsed = start_time.elapsed();
-  let upload_status = upload_response.status();
-  log::info!("步骤2: 文件上传完成，耗时: {:.2}秒，HTTP状态码: {}", upload_elapsed.as_secs_f64(), upload_status);
-  
-  // 检查上传响应状态
-  if !upload_status.is_success() {
-    // 尝试读取响应内容以便调试
-    let response_text = upload_response.text().await.unwrap_or_default();
-    log::error!("步骤2: 上传失败，HTTP状态码: {}，响应内容: {}", upload_status, response_text);
-    return Err(format!("上传文件失败，HTTP状态码: {}", upload_status));
-  }
-  
-  log::info!("步骤2: COS上传成功，准备检查上传状态");
-  
-  // 步骤3: 检查上传状态并获取应用信息（带重试机制）
-  log::info!("步骤3: 检查上传状态，buildKey: {}", key);
-  
-  let api_key = config.api_key.as_ref().unwrap().clone();
-  let max_retries = 60; // 最多重试60次（约3-5分钟）
-  let mut retry_count = 0;
-  
-  loop {
-    let info_form = Form::new()
-      .text("_api_key", api_key.clone())
-      .text("buildKey", key.clone());
-    
-    log::info!("步骤3: 发送请求到: https://api.pgyer.com/apiv2/app/buildInfo (尝试 {}/{})", retry_count + 1, max_retries);
-    let info_response = client
-      .post("https://api.pgyer.com/apiv2/app/buildInfo")
-      .multipart(info_form)
-      .send()
-      .await
-      .map_err(|e| {
-        log::error!("步骤3: 请求失败: {}", e);
-        format!("检查上传状态失败: {}", e)
-      })?;
-    
-    let info_status = info_response.status();
-    log::info!("步骤3: 响应状态码: {}", info_status);
-    
-    let info_result: serde_json::Value = info_response
-      .json()
-      .await
-      .map_err(|e| {
-        log::error!("步骤3: 解析响应失败: {}", e);
-        format!("解析状态响应失败: {}", e)
-      })?;
-    
-    log::info!("步骤3: 响应内容: {}", serde_json::to_string_pretty(&info_result).unwrap_or_default());
-    
-    let code = info_result["code"].as_i64();
-    
-    // 成功：code = 0
-    if code == Some(0) {
-      let data = &info_result["data"];
-      let download_url = data["buildShortcutUrl"]
-        .as_str()
-        .map(|s| format!("https://www.pgyer.com/{}", s));
-      
-      let total_elapsed = start_time.elapsed();
-      log::info!("步骤3: 上传成功，总耗时: {:.2}秒，重试次数: {}", total_elapsed.as_secs_f64(), retry_count);
-      log::info!("步骤3: 下载链接: {:?}", download_url);
-      log::info!("步骤3: 二维码: {:?}", data["buildQRCodeURL"].as_str());
-      
-      return Ok(PublishResult {
-        success: true,
-        message: "上传成功".to_string(),
-        download_url,
-        qr_code_url: data["buildQRCodeURL"].as_str().map(|s| s.to_string()),
-        build_key: data["buildKey"].as_str().map(|s| s.to_string()),
-        build_shortcut_url: data["buildShortcutUrl"].as_str().map(|s| s.to_string()),
-      });
-    }
-    
-    // 处理中：code = 1247，需要重试
-    if code == Some(1247) {
-      retry_count += 1;
-      
-      if retry_count >= max_retries {
-        let error_msg = format!(
-          "检查上传状态超时: 已重试 {} 次，应用仍在处理中",
-          max_retries
-        );
-        log::error!("步骤3: {}", error_msg);
-        return Err(error_msg);
-      }
-      
-      // 随机等待 3-5 秒
-      let wait_seconds = 3 + (retry_count % 3); // 3, 4, 5 秒循环
-      log::info!("步骤3: 应用正在处理中 (code: 1247)，等待 {} 秒后重试...", wait_seconds);
-      tokio::time::sleep(std::time::Duration::from_secs(wait_seconds)).await;
-      continue;
-    }
-    
-    // 其他错误：直接返回
-    let error_msg = format!(
-      "检查上传状态失败: {}",
-      info_result["message"]
-        .as_str()
-        .unwrap_or("未知错误")
-    );
-    log::error!("步骤3: {}", error_msg);
-    log::error!("步骤3: 完整响应: {}", serde_json::to_string_pretty(&info_result).unwrap_or_default());
-    return Err(error_msg);
-  }
+    .map_err(|e| Error::Secrets(format!("解锁密钥库的任务异常退出：{}", e)))?
 }
 
-/// 尝试使用 go-fir-cli 命令行工具上传（备选方案）
-async fn upload_to_fir_via_cli(file_path: &Path, config: &PublishConfig) -> Result<PublishResult, String> {
-  log::info!("尝试使用 go-fir-cli 命令行工具上传");
-  
-  // 查找 go-fir-cli 工具
-  let cli_names = ["go-fir-cli", "fir-cli"];
-  let mut cli_path: Option<String> = None;
-  
-  for name in &cli_names {
-    let (ok, output) = run_command("which", &[name]);
-    if ok && !output.is_empty() {
-      cli_path = Some(output.trim().to_string());
-      log::info!("找到 go-fir-cli: {}", cli_path.as_ref().unwrap());
-      break;
-    }
-  }
-  
-  let cli = cli_path.ok_or_else(|| {
-    let err = "未找到 go-fir-cli 工具，请先安装：https://github.com/PGYER/go-fir-cli/releases".to_string();
-    log::error!("{}", err);
-    err
-  })?;
-  
-  // 构建命令：go-fir-cli -t TOKEN upload -f FILE_PATH [-c CHANGELOG]
-  let file_path_str = file_path.to_string_lossy().to_string();
-  let token = config.api_token.as_ref().unwrap();
-  
-  log::info!("执行命令: {} -t {}... upload -f {}", cli, &token[..token.len().min(10)], file_path_str);
-  
-  let mut cmd = Command::new(&cli);
-  cmd.arg("-t").arg(token).arg("upload").arg("-f").arg(&file_path_str);
-  
-  // 如果有更新描述，添加 -c 参数
-  if let Some(ref desc) = config.update_description {
-    if !desc.trim().is_empty() {
-      log::info!("添加更新描述: {}", desc.trim());
-      cmd.arg("-c").arg(desc.trim());
-    }
-  }
-  
-  let output = cmd
-    .output()
+#[tauri::command]
+fn lock_secrets() {
+  secrets::lock();
+}
+
+#[tauri::command]
+async fn change_master_password(app_handle: tauri::AppHandle, old_passphrase: String, new_passphrase: String) -> Result<(), Error> {
+  let dirs = app_dirs(&app_handle);
+  tauri::async_runtime::spawn_blocking(move || secrets::change_passphrase(&dirs, &old_passphrase, &new_passphrase))
     .await
-    .map_err(|e| {
-      let err = format!("执行 go-fir-cli 失败: {}", e);
-      log::error!("{}", err);
-      err
-    })?;
-  
-  let stdout = String::from_utf8_lossy(&output.stdout);
-  let stderr = String::from_utf8_lossy(&output.stderr);
-  
-  log::info!("go-fir-cli 输出: {}", stdout);
-  if !stderr.is_empty() {
-    log::warn!("go-fir-cli 错误输出: {}", stderr);
-  }
-  
-  if output.status.success() {
-    // 解析输出以提取下载链接
-    let mut download_url: Option<String> = None;
-    let mut download_page_url: Option<String> = None;
-    
-    // 方法1: 从JSON响应中提取 download_url
-    // 查找包含 "download_url" 的JSON行
-    // for line in stdout.lines() {
-    //   let line = line.trim();
-    //   if line.contains("download_url") && line.starts_with('{') {
-    //     if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-    //       if let Some(url) = json_value["download_url"].as_str() {
-    //         download_url = Some(url.to_string());
-    //         log::info!("从JSON中提取到下载链接: {}", url);
-    //         break;
-    //       }
-    //     }
-    //   }
-    // }
-    
-    // 方法2: 从应用信息JSON中提取 short 和 download_domain，构建下载页面URL
-    // for line in stdout.lines() {
-    //   let line = line.trim();
-    //   if line.contains("\"short\"") && line.contains("\"download_domain\"") && line.starts_with('{') {
-    //     if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(line) {
-    //       if let (Some(short), Some(domain)) = (
-    //         json_value["short"].as_str(),
-    //         json_value["download_domain"].as_str(),
-    //       ) {
-    //         // 构建下载页面URL: http://{domain}/{short}
-    //         let page_url = format!("http://{}/{}", domain, short);
-    //         download_page_url = Some(page_url.clone());
-    //         log::info!("从应用信息JSON中提取到下载页面: {}", page_url);
-            
-    //         // 如果没有找到直接下载链接，使用下载页面作为备用
-    //         if download_url.is_none() {
-    //           download_url = Some(page_url);
-    //         }
-    //         break;
-    //       }
-    //     }
-    //   }
-    // }
-    
-    // 方法3: 从文本中提取 "下载页面: " 后面的URL
-    if download_page_url.is_none() {
-      for line in stdout.lines() {
-        if line.contains("下载页面:") {
-          if let Some(start_idx) = line.find("下载页面:") {
-            let url_part = &line[start_idx + "下载页面:".len()..].trim();
-            if !url_part.is_empty() {
-              download_page_url = Some(url_part.to_string());
-              log::info!("从文本中提取到下载页面: {}", url_part);
-              
-              // 如果没有找到直接下载链接，使用下载页面作为备用
-              if download_url.is_none() {
-                download_url = Some(url_part.to_string());
-              }
-              break;
-
-            }
-          }
-        }
-      }
-    }
-    
-    Ok(PublishResult {
-      success: true,
-      message: format!("上传成功（通过 go-fir-cli）"),
-      download_url,
-      qr_code_url: None,
-      build_key: None,
-      build_shortcut_url: download_page_url,
-    })
-  } else {
-    Err(format!("go-fir-cli 上传失败: {}\n{}", stdout, stderr))
-  }
+    .map_err(|e| Error::Secrets(format!("修改主密码的任务异常退出：{}", e)))?
 }
 
-/// 上传到 fir.im（使用 go-fir-cli 命令行工具）
-async fn upload_to_fir(file_path: &Path, config: &PublishConfig) -> Result<PublishResult, String> {
-  if config.api_token.is_none() {
-    return Err("fir.im API Token 未配置".to_string());
-  }
+#[tauri::command]
+fn list_secrets(app_handle: tauri::AppHandle) -> Result<Vec<SecretInfo>, Error> {
+  secrets::list(&app_dirs(&app_handle))
+}
 
-  log::info!("开始上传到 fir.im（使用 go-fir-cli 工具），文件: {:?}", file_path);
-  log::info!("API Token: {}...", &config.api_token.as_ref().unwrap()[..config.api_token.as_ref().unwrap().len().min(10)]);
+/// 把配置文件中的明文密钥迁移到密钥库，返回迁移的字段
+#[tauri::command]
+fn migrate_secrets(app_handle: tauri::AppHandle) -> Result<Vec<String>, Error> {
+  config::migrate_secrets(&app_dirs(&app_handle))
+}
 
-  // 直接使用 go-fir-cli 命令行工具上传
-  upload_to_fir_via_cli(file_path, config).await
+/// 用签名配置重新签名 APK；`output_path` 未指定时去掉文件名中的 -unsigned，没有时加上 -signed
+#[tauri::command]
+async fn sign_apk(
+  app_handle: tauri::AppHandle,
+  profile: String,
+  file_path: String,
+  output_path: Option<String>,
+) -> Result<SignResult, Error> {
+  let dirs = app_dirs(&app_handle);
+  let input = Path::new(&file_path).to_path_buf();
+  let output = output_path.map(Into::into).unwrap_or_else(|| signer::default_output(&input));
+  tauri::async_runtime::spawn_blocking(move || signer::sign_apk(&dirs, &profile, &input, &output))
+    .await
+    .map_err(|e| Error::Signing(format!("签名任务异常退出：{}", e)))?
 }
 
+/// 发布产物；`project` 未指定时从构建历史中查找产物所属的工程，`generate_notes` 为 true 时根据提交生成更新描述
 #[tauri::command]
 async fn publish_apk(
+  app_handle: tauri::AppHandle,
   file_path: String,
   config: PublishConfig,
-) -> Result<PublishResult, String> {
-  let path = Path::new(&file_path);
-  if !path.exists() {
-    return Err(format!("文件不存在: {}", file_path));
-  }
+  project: Option<String>,
+  generate_notes: Option<bool>,
+) -> Result<PublishResult, Error> {
+  publish::publish_artifact(&app_dirs(&app_handle), &file_path, config, project.as_deref(), generate_notes.unwrap_or(false)).await
+}
 
-  if !file_path.ends_with(".apk") && !file_path.ends_with(".aab") {
-    return Err("不支持的文件类型，仅支持 .apk 或 .aab 文件".to_string());
-  }
+/// 解析 APK/AAB 的包名、版本、SDK 版本、权限、ABI 与 AAB 模块
+#[tauri::command]
+fn inspect_apk(file_path: String) -> Result<ArtifactInfo, Error> {
+  inspect::inspect(Path::new(&file_path))
+}
 
-  match config.platform.as_str() {
-    "pgyer" => upload_to_pgyer(path, &config).await,
-    "fir" => upload_to_fir(path, &config).await,
-    _ => Err(format!("不支持的发布平台: {}", config.platform)),
-  }
+/// 校验 APK 签名（v1/v2/v3/v3.1，及同目录的 v4 `.idsig`），`release` 为 true 时提示调试证书签名
+#[tauri::command]
+fn verify_apk_signature(file_path: String, release: Option<bool>) -> Result<SignatureReport, Error> {
+  signature::verify_apk(Path::new(&file_path), release.unwrap_or(false))
 }
 
+/// 为渠道文件中的每个渠道生成渠道包（不重新签名）；每个渠道包需要写出完整的 APK 并校验签名，放到阻塞线程中执行。
+/// 未指定的渠道文件、写入方式与文件名模板取工程的渠道设置，`project` 未指定时从构建历史中查找 APK 所属的工程
+#[tauri::command]
+async fn write_channel_apks(
+  app_handle: tauri::AppHandle,
+  file_path: String,
+  channel_file: Option<String>,
+  project: Option<String>,
+  scheme: Option<ChannelScheme>,
+  output_dir: Option<String>,
+  name_template: Option<String>,
+) -> Result<ChannelOutput, Error> {
+  let dirs = app_dirs(&app_handle);
+  tauri::async_runtime::spawn_blocking(move || {
+    let input = Path::new(&file_path);
+    let project = match project.filter(|p| !p.is_empty()) {
+      Some(name) => Some(config::find_project(&dirs, &name)?),
+      None => history::find_by_artifact(&dirs.history_dir(), input)?.and_then(|b| config::find_project(&dirs, &b.project).ok()),
+    };
+    let channel_file = channel_file.filter(|f| !f.is_empty()).map(Into::into);
+    let request = ChannelRequest::resolve(project.as_ref(), channel_file, scheme, name_template)?;
+    let channels = channel::read_channel_file(&request.channel_file)?;
+    channel::write_channels(
+      input,
+      request.scheme,
+      &channels,
+      output_dir.as_deref().map(Path::new),
+      request.name_template.as_deref(),
+    )
+  })
+  .await
+  .map_err(|e| Error::Artifact(format!("生成渠道包的任务异常退出：{}", e)))?
+}
+
+/// 读取 APK 中各种方式写入的渠道
+#[tauri::command]
+fn read_apk_channel(file_path: String) -> Result<Vec<FoundChannel>, Error> {
+  channel::read_channel(Path::new(&file_path))
+}
+
+/// 预览工程发布到该平台的更新描述（上次发布到本次构建之间的提交）
+#[tauri::command]
+fn generate_release_notes(
+  app_handle: tauri::AppHandle,
+  name: String,
+  platform: String,
+  file_path: Option<String>,
+) -> Result<ReleaseNotes, Error> {
+  release_notes::generate_for_project(&app_dirs(&app_handle), &name, &platform, file_path.as_deref().map(Path::new), None)
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_shell::init())
+    .manage(BuildRegistry::default())
     .setup(|app| {
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -960,22 +374,20 @@ pub fn run() {
       
       // 设置窗口大小为屏幕的 2/3
       if let Some(window) = app.get_webview_window("main") {
-        if let Ok(monitor) = window.current_monitor() {
-          if let Some(monitor) = monitor {
-            let screen_size = monitor.size();
-            let width = (screen_size.width as f64 * 0.667) as u32;
-            let height = (screen_size.height as f64 * 0.667) as u32;
-            
-            if let Err(e) = window.set_size(tauri::LogicalSize::new(width, height)) {
-              eprintln!("设置窗口大小失败: {:?}", e);
-            }
-            
-            // 居中窗口
-            let x = (screen_size.width as f64 * 0.167) as i32;
-            let y = (screen_size.height as f64 * 0.167) as i32;
-            if let Err(e) = window.set_position(tauri::LogicalPosition::new(x, y)) {
-              eprintln!("设置窗口位置失败: {:?}", e);
-            }
+        if let Ok(Some(monitor)) = window.current_monitor() {
+          let screen_size = monitor.size();
+          let width = (screen_size.width as f64 * 0.667) as u32;
+          let height = (screen_size.height as f64 * 0.667) as u32;
+          
+          if let Err(e) = window.set_size(tauri::LogicalSize::new(width, height)) {
+            eprintln!("设置窗口大小失败: {:?}", e);
+          }
+          
+          // 居中窗口
+          let x = (screen_size.width as f64 * 0.167) as i32;
+          let y = (screen_size.height as f64 * 0.167) as i32;
+          if let Err(e) = window.set_position(tauri::LogicalPosition::new(x, y)) {
+            eprintln!("设置窗口位置失败: {:?}", e);
           }
         }
       }
@@ -984,16 +396,44 @@ pub fn run() {
     })
     .invoke_handler(tauri::generate_handler![
       check_env,
+      check_project_env,
+      list_jdks,
       list_projects,
       add_project,
       update_project,
       delete_project,
+      discover_project,
       build_project,
+      cancel_build,
+      read_project_version,
+      list_build_history,
+      delete_build_history,
+      read_build_log,
       publish_apk,
+      generate_release_notes,
+      inspect_apk,
+      verify_apk_signature,
+      write_channel_apks,
+      read_apk_channel,
       list_publish_platforms,
       add_publish_platform,
       update_publish_platform,
-      delete_publish_platform
+      delete_publish_platform,
+      list_signing_profiles,
+      add_signing_profile,
+      update_signing_profile,
+      delete_signing_profile,
+      sign_apk,
+      list_keystores,
+      import_keystore,
+      create_keystore,
+      delete_keystore,
+      secrets_status,
+      unlock_secrets,
+      lock_secrets,
+      change_master_password,
+      list_secrets,
+      migrate_secrets
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -1,5 +1,6 @@
 import { useEffect, useMemo, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 import { open as openUrl } from "@tauri-apps/plugin-shell";
 import {
@@ -13,6 +14,7 @@ import {
   ConfigProvider,
   Form,
   Input,
+  InputNumber,
   Layout,
   List,
   Modal,
@@ -21,7 +23,9 @@ import {
   Row,
   Select,
   Space,
+  Switch,
   Tag,
+  Tooltip,
   Typography,
 } from "antd";
 import type { ThemeConfig } from "antd";
@@ -38,17 +42,125 @@ import {
   DeleteOutlined,
   MinusCircleOutlined,
   CloudUploadOutlined,
-  EditOutlined
+  EditOutlined,
+  SafetyCertificateOutlined,
+  LockOutlined,
+  UnlockOutlined
 } from "@ant-design/icons";
 import "./App.css";
 
-type EnvCheck = { tool: string; ok: boolean; message: string; detail?: string };
-type Project = { name: string; path: string; defaultModule?: string; modules?: string[]; defaultVariant?: string; variants?: string[]; buildType?: "Debug" | "Release" };
+type EnvCheck = { tool: string; ok: boolean; message: string; detail?: string; applicable: boolean; items?: string[] };
+type Project = { name: string; path: string; defaultModule?: string; modules?: string[]; defaultVariant?: string; variants?: string[]; buildType?: "Debug" | "Release"; artifactKind?: ArtifactKind; javaHome?: string; gradleOptions?: GradleOptions; gitPolicy?: GitPolicy; signingProfile?: string; channels?: ChannelOptions; discovered?: ProjectStructure };
+type GitPolicy = { injectProperties?: boolean; requireCleanForRelease?: boolean; releaseBranches?: string[] };
+type GradleOptions = { args?: string[]; properties?: Record<string, string>; systemProperties?: Record<string, string>; env?: Record<string, string>; secrets?: string[] };
+// 工程表单中 Gradle 参数以多行 KEY=VALUE 文本编辑，提交时转换为 gradleOptions
+type GradleOptionFields = { gradleArgs?: string[]; gradleProperties?: string; gradleSystemProperties?: string; gradleEnv?: string; gradleSecrets?: string[] };
+type ProjectFormValues = Project & GradleOptionFields;
+type Jdk = { home: string; vendor: string; version: string; major: number; minor: number; patch: number; bits?: number };
+type ProjectStructure = { modules: string[]; appModules: { module: string; variants: { name: string }[] }[]; mode: "static" | "gradle"; discoveredAt: number; warnings: string[] };
+type ArtifactKind = "apk" | "aab" | "both";
 type ProjectsConfig = { projects: Project[] };
-type BuildResult = { code: number; output: string };
-type PublishResult = { success: boolean; message: string; download_url?: string; qr_code_url?: string; build_key?: string; build_shortcut_url?: string };
+type BuildResult = { build_id: string; status: "success" | "failed" | "cancelled"; variant?: { flavors: string[]; build_type: string }; code: number; output: string; artifacts?: string[]; version?: AppVersion | null; git?: GitInfo | null; artifact_info?: ArtifactInfo[]; signatures?: SignatureReport[] };
+type ArtifactInfo = { path: string; size: number; package: string; version_code?: number | null; version_name?: string | null; min_sdk?: number | null; target_sdk?: number | null; compile_sdk?: number | null; label?: string | null; launchable_activity?: string | null; permissions: string[]; abis: string[]; modules: ModuleInfo[] };
+type CertificateInfo = { subject: string; issuer: string; serial: string; sha1: string; sha256: string; not_before: number; not_after: number; key_algorithm: string; debug: boolean };
+type SchemeResult = { scheme: "v1" | "v2" | "v3" | "v3.1" | "v4"; verified: boolean; errors: string[]; signers: CertificateInfo[]; min_sdk?: number | null; max_sdk?: number | null };
+type SignatureReport = { path: string; verified: boolean; schemes: SchemeResult[]; signers: CertificateInfo[]; debug_signed: boolean; warnings: string[] };
+type ModuleInfo = { name: string; kind: "base" | "feature" | "asset_pack"; size: number; uncompressed_size: number };
+type GitInfo = { branch?: string | null; commit: string; short_commit: string; dirty: boolean; tag?: string | null };
+type AppVersion = { version_code?: number | null; version_name?: string | null };
+type VersionBumpFields = { bumpCode?: "increment" | "timestamp" | "gitCommitCount"; bumpName?: "patch" | "minor" | "major" };
+type BuildLogLine = { build_id: string; stream: "stdout" | "stderr" | "system"; line: string; timestamp: number };
+type PublishResult = { success: boolean; message: string; download_url?: string; qr_code_url?: string; build_key?: string; build_shortcut_url?: string; update_description?: string | null; artifact?: ArtifactInfo | null };
+type ReleaseNotes = { text: string; since?: string | null; until: string; commits: { commit: string; subject: string }[]; truncated: boolean };
 type PublishPlatformConfig = { name: string; platform: string; api_key?: string; api_token?: string; password?: string; default_description?: string };
 type PublishPlatformsConfig = { platforms: PublishPlatformConfig[] };
+type SigningScheme = "v1" | "v2" | "v3";
+type SigningProfile = { name: string; keystore: string; storePassword?: string; keyAlias?: string; keyPassword?: string; schemes?: Record<SigningScheme, boolean> };
+type SigningProfilesConfig = { profiles: SigningProfile[] };
+// 签名配置表单中签名方案以多选编辑，提交时转换为 schemes
+type SigningProfileFormValues = Omit<SigningProfile, "schemes"> & { schemeList: SigningScheme[] };
+type KeystoreType = "jks" | "pkcs12";
+type KeystoreInfo = { id: string; path: string; store_type: KeystoreType; source?: string | null; created_at: number; entries: { alias: string; key_entry: boolean; certificates: CertificateInfo[] }[]; warnings: string[]; error?: string | null };
+// 导入与新建共用一个表单，mode 决定提交的字段
+type ChannelScheme = "walle" | "vasdolly" | "meta-inf";
+type ChannelOptions = { scheme?: ChannelScheme; channelFile?: string; nameTemplate?: string };
+type FoundChannel = { scheme: ChannelScheme; channel: string; extras: Record<string, string> };
+type ChannelOutput = { input: string; scheme: ChannelScheme; output_dir: string; apks: { channel: string; path: string }[]; warnings: string[] };
+type ChannelFormValues = { filePath: string; project?: string; channelFile?: string; scheme?: ChannelScheme; outputDir?: string; nameTemplate?: string };
+type SecretsStatus = { path: string; initialized: boolean; unlocked: boolean; count: number };
+type SecretInfo = { name: string; preview: string; updated_at: number };
+type KeystoreFormValues = { id: string; path?: string; storeType?: KeystoreType; storePassword: string; alias?: string; keyPassword?: string; keyAlgorithm?: "rsa" | "ec"; keySize?: number; subject?: string; validityYears?: number };
+type SignResult = { input: string; output: string; profile: string; alias: string; report: SignatureReport };
+// 后端命令的错误：code 为稳定的机器码（如 project_not_found），message 为提示，detail 为底层原因
+type CommandError = { code: string; category: string; message: string; detail?: string | null };
+
+const errorText = (e: unknown): string => {
+  if (typeof e === "string") return e;
+  const err = e as Partial<CommandError>;
+  if (err && typeof err.message === "string") {
+    return err.detail ? `${err.message}（${err.detail}）` : err.message;
+  }
+  return String(e);
+};
+
+const parsePairs = (text?: string): Record<string, string> => {
+  const map: Record<string, string> = {};
+  (text ?? "").split("\n").forEach((line) => {
+    const index = line.indexOf("=");
+    if (index > 0) map[line.slice(0, index).trim()] = line.slice(index + 1).trim();
+  });
+  return map;
+};
+
+const formatPairs = (map?: Record<string, string>) =>
+  Object.entries(map ?? {}).map(([k, v]) => `${k}=${v}`).join("\n");
+
+const toGradleOptions = (values: GradleOptionFields): GradleOptions => ({
+  args: values.gradleArgs ?? [],
+  properties: parsePairs(values.gradleProperties),
+  systemProperties: parsePairs(values.gradleSystemProperties),
+  env: parsePairs(values.gradleEnv),
+  secrets: values.gradleSecrets ?? [],
+});
+
+// 未选择任何策略时传 null，使用工程配置的默认策略
+const toVersionBump = ({ bumpCode, bumpName }: VersionBumpFields) =>
+  bumpCode || bumpName
+    ? { code: bumpCode ? { strategy: bumpCode } : null, name: bumpName ? { strategy: bumpName } : null, rollbackOnFailure: true }
+    : null;
+
+const formatVersion = (v?: AppVersion | null) =>
+  v ? `${v.version_name ?? "-"}${v.version_code != null ? ` (${v.version_code})` : ""}` : "";
+
+const gradleArgOptions = ["--stacktrace", "--info", "--offline", "--no-daemon", "--parallel", "--build-cache", "--refresh-dependencies"]
+  .map((v) => ({ label: v, value: v }));
+
+// 表单值转为工程配置：去掉 Gradle 参数的编辑字段
+const toProject = ({ gradleArgs, gradleProperties, gradleSystemProperties, gradleEnv, gradleSecrets, ...project }: ProjectFormValues): Project => ({
+  ...project,
+  gradleOptions: toGradleOptions({ gradleArgs, gradleProperties, gradleSystemProperties, gradleEnv, gradleSecrets }),
+});
+
+const signingSchemes: SigningScheme[] = ["v1", "v2", "v3"];
+const channelSchemeOptions: { label: string; value: ChannelScheme }[] = [
+  { label: "Walle", value: "walle" },
+  { label: "VasDolly", value: "vasdolly" },
+  { label: "META-INF（v1）", value: "meta-inf" },
+];
+
+const enabledSchemes = (profile: SigningProfile): SigningScheme[] =>
+  signingSchemes.filter((s) => profile.schemes?.[s] ?? true);
+
+const toSigningProfile = ({ schemeList, ...profile }: SigningProfileFormValues): SigningProfile => ({
+  ...profile,
+  schemes: { v1: schemeList.includes("v1"), v2: schemeList.includes("v2"), v3: schemeList.includes("v3") },
+});
+
+const artifactKindOptions: { value: ArtifactKind; label: string }[] = [
+  { value: "apk", label: "APK（assemble）" },
+  { value: "aab", label: "AAB（bundle）" },
+  { value: "both", label: "APK + AAB" },
+];
 
 const statusTag = (ok: boolean) => (
   <Tag
@@ -91,6 +203,8 @@ function App() {
   const [selectedMenu, setSelectedMenu] = useState<"env" | "projects" | "build" | "publish">("env");
   const [envLoading, setEnvLoading] = useState(false);
   const [envChecks, setEnvChecks] = useState<EnvCheck[]>([]);
+  const [envProject, setEnvProject] = useState<string | undefined>();
+  const [jdks, setJdks] = useState<Jdk[]>([]);
   const [projects, setProjects] = useState<Project[]>([]);
   const [projectsLoading, setProjectsLoading] = useState(false);
   const [publishPlatforms, setPublishPlatforms] = useState<PublishPlatformConfig[]>([]);
@@ -102,6 +216,7 @@ function App() {
     module?: string; 
     variant?: string; 
     buildType?: "Debug" | "Release";
+    artifactKind?: ArtifactKind;
     publish?: boolean;
     publishPlatformConfig?: string;
     publishPlatform?: "pgyer" | "fir";
@@ -109,24 +224,55 @@ function App() {
     publishApiToken?: string;
     publishPassword?: string;
     publishDescription?: string;
-  }>();
+  } & GradleOptionFields & VersionBumpFields>();
   const [buildResult, setBuildResult] = useState<BuildResult | null>(null);
   const [publishResult, setPublishResult] = useState<PublishResult | null>(null);
   const [building, setBuilding] = useState(false);
+  const [liveLog, setLiveLog] = useState("");
+  const [currentBuildId, setCurrentBuildId] = useState<string | null>(null);
+  const [cancelling, setCancelling] = useState(false);
   const [publishing, setPublishing] = useState(false);
   const [buildLogCollapsed, setBuildLogCollapsed] = useState(false);
   const [addModalOpen, setAddModalOpen] = useState(false);
   const [adding, setAdding] = useState(false);
   const [editingProject, setEditingProject] = useState<Project | null>(null);
+  const [discovering, setDiscovering] = useState<string | null>(null);
   const [publishPlatformModalOpen, setPublishPlatformModalOpen] = useState(false);
   const [editingPlatform, setEditingPlatform] = useState<PublishPlatformConfig | null>(null);
   const [addingPlatform, setAddingPlatform] = useState(false);
+  const [signingProfiles, setSigningProfiles] = useState<SigningProfile[]>([]);
+  const [signingProfilesLoading, setSigningProfilesLoading] = useState(false);
+  const [signingProfileForm] = Form.useForm<SigningProfileFormValues>();
+  const [signingModalOpen, setSigningModalOpen] = useState(false);
+  const [editingSigning, setEditingSigning] = useState<SigningProfile | null>(null);
+  const [savingSigning, setSavingSigning] = useState(false);
+  const [signingApk, setSigningApk] = useState<string | null>(null);
+  const [keystores, setKeystores] = useState<KeystoreInfo[]>([]);
+  const [keystoresLoading, setKeystoresLoading] = useState(false);
+  const [keystoreForm] = Form.useForm<KeystoreFormValues>();
+  const [keystoreMode, setKeystoreMode] = useState<"import" | "create" | null>(null);
+  const [savingKeystore, setSavingKeystore] = useState(false);
+  const [channelForm] = Form.useForm<ChannelFormValues>();
+  const [writingChannels, setWritingChannels] = useState(false);
+  const [channelOutput, setChannelOutput] = useState<ChannelOutput | null>(null);
+  const [secretsStatus, setSecretsStatus] = useState<SecretsStatus | null>(null);
+  const [secretList, setSecretList] = useState<SecretInfo[]>([]);
+  const [masterPassword, setMasterPassword] = useState("");
+  const [unlockingSecrets, setUnlockingSecrets] = useState(false);
+  const [passwordModalOpen, setPasswordModalOpen] = useState(false);
+  const [masterPasswordForm] = Form.useForm<{ oldPassphrase: string; newPassphrase: string; confirm: string }>();
 
-  const loadEnv = async () => {
+  const loadEnv = async (project = envProject) => {
     setEnvLoading(true);
     try {
-      const res = await invoke<EnvCheck[]>("check_env");
-      setEnvChecks(res);
+      const res = await invoke<EnvCheck[]>("check_env", { project: project ?? null });
+      // 选择工程后追加工程级检查（AGP/Gradle/JDK 兼容性与 compileSdk）
+      const projectChecks = project
+        ? await invoke<EnvCheck[]>("check_project_env", { name: project })
+        : [];
+      setEnvChecks([...res, ...projectChecks]);
+    } catch (e) {
+      messageApi.error(errorText(e));
     } finally {
       setEnvLoading(false);
     }
@@ -152,10 +298,52 @@ function App() {
     }
   };
 
+  const loadSigningProfiles = async () => {
+    setSigningProfilesLoading(true);
+    try {
+      const res = await invoke<SigningProfilesConfig>("list_signing_profiles");
+      setSigningProfiles(res.profiles ?? []);
+    } finally {
+      setSigningProfilesLoading(false);
+    }
+  };
+
+  const loadKeystores = async () => {
+    setKeystoresLoading(true);
+    try {
+      setKeystores(await invoke<KeystoreInfo[]>("list_keystores"));
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setKeystoresLoading(false);
+    }
+  };
+
+  const loadSecrets = async () => {
+    try {
+      setSecretsStatus(await invoke<SecretsStatus>("secrets_status"));
+      setSecretList(await invoke<SecretInfo[]>("list_secrets"));
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
+  const loadJdks = async () => {
+    try {
+      setJdks(await invoke<Jdk[]>("list_jdks"));
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
   useEffect(() => {
     loadEnv();
     loadProjects();
     loadPublishPlatforms();
+    loadSigningProfiles();
+    loadKeystores();
+    loadSecrets();
+    loadJdks();
   }, []);
 
   const projectOptions = useMemo(
@@ -203,28 +391,28 @@ function App() {
     return options;
   };
 
-  const handleAddProject = async (values: Project) => {
+  const handleAddProject = async (values: ProjectFormValues) => {
     setAdding(true);
     try {
-      await invoke("add_project", { project: values });
+      await invoke("add_project", { project: toProject(values) });
       messageApi.success("工程已添加");
       addForm.resetFields();
       setAddModalOpen(false);
       setEditingProject(null);
       loadProjects();
     } catch (e) {
-      messageApi.error((e as Error).message);
+      messageApi.error(errorText(e));
     } finally {
       setAdding(false);
     }
   };
 
-  const handleUpdateProject = async (values: Project) => {
+  const handleUpdateProject = async (values: ProjectFormValues) => {
     if (!editingProject) return;
     setAdding(true);
     try {
-      // 确保工程名不会被修改，使用原有的工程名
-      const updateData = { ...values, name: editingProject.name };
+      // 确保工程名不会被修改，使用原有的工程名；表单中没有的字段保留原值
+      const updateData = { ...editingProject, ...toProject(values), name: editingProject.name };
       await invoke("update_project", { name: editingProject.name, project: updateData });
       messageApi.success("工程已更新");
       addForm.resetFields();
@@ -232,7 +420,7 @@ function App() {
       setEditingProject(null);
       loadProjects();
     } catch (e) {
-      messageApi.error((e as Error).message);
+      messageApi.error(errorText(e));
     } finally {
       setAdding(false);
     }
@@ -247,10 +435,34 @@ function App() {
       modules: project.modules || [],
       variants: project.variants || [],
       buildType: project.buildType || "Debug",
+      artifactKind: project.artifactKind || "apk",
+      javaHome: project.javaHome,
+      gradleArgs: project.gradleOptions?.args ?? [],
+      gradleProperties: formatPairs(project.gradleOptions?.properties),
+      gradleSystemProperties: formatPairs(project.gradleOptions?.systemProperties),
+      gradleEnv: formatPairs(project.gradleOptions?.env),
+      gradleSecrets: project.gradleOptions?.secrets ?? [],
+      gitPolicy: project.gitPolicy ?? {},
+      signingProfile: project.signingProfile,
+      channels: project.channels ?? {},
     });
     setAddModalOpen(true);
   };
 
+  const handleDiscoverProject = async (name: string, mode: "static" | "gradle") => {
+    setDiscovering(name);
+    try {
+      const res = await invoke<ProjectStructure>("discover_project", { name, mode });
+      res.warnings.forEach((w) => messageApi.warning(w));
+      messageApi.success(`已同步：${res.appModules.length} 个应用模块，${res.appModules.reduce((n, m) => n + m.variants.length, 0)} 个变体`);
+      loadProjects();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setDiscovering(null);
+    }
+  };
+
   const handleDeleteProject = async (name: string) => {
     Modal.confirm({
       title: "确认删除",
@@ -264,7 +476,7 @@ function App() {
           messageApi.success("工程已删除");
           loadProjects();
         } catch (e) {
-          messageApi.error((e as Error).message);
+          messageApi.error(errorText(e));
         }
       },
     });
@@ -309,11 +521,32 @@ function App() {
     }
   };
 
+  // 根据上次发布以来的提交生成更新描述，填入表单供修改
+  const handlePreviewNotes = async () => {
+    const { project, publishPlatform } = buildForm.getFieldsValue(["project", "publishPlatform"]);
+    if (!project || !publishPlatform) {
+      messageApi.warning("请先选择工程与发布平台");
+      return;
+    }
+    try {
+      const notes = await invoke<ReleaseNotes>("generate_release_notes", { name: project, platform: publishPlatform });
+      if (!notes.text) {
+        messageApi.info("上次发布以来没有新的提交");
+        return;
+      }
+      buildForm.setFieldsValue({ publishDescription: notes.text, publishNotes: false });
+      messageApi.success(`已根据 ${notes.commits.length} 个提交生成更新描述`);
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
   const handleBuild = async (values: {
     project: string;
     module?: string;
     variant?: string;
     buildType?: "Debug" | "Release";
+    artifactKind?: ArtifactKind;
     publish?: boolean;
     publishPlatformConfig?: string;
     publishPlatform?: "pgyer" | "fir";
@@ -321,37 +554,51 @@ function App() {
     publishApiToken?: string;
     publishPassword?: string;
     publishDescription?: string;
-  }) => {
+    publishNotes?: boolean;
+  } & GradleOptionFields & VersionBumpFields) => {
     setBuilding(true);
     setBuildResult(null);
     setPublishResult(null);
+    setLiveLog("");
+    // 先订阅构建日志，再发起构建，避免丢失最开始的输出
+    const buildId = crypto.randomUUID();
+    setCurrentBuildId(buildId);
+    const unlisten = await listen<BuildLogLine>("build-log", (event) => {
+      if (event.payload.build_id === buildId) {
+        setLiveLog((prev) => prev + event.payload.line + "\n");
+      }
+    });
     try {
       const res = await invoke<BuildResult>("build_project", {
         name: values.project,
         module: values.module || null,
         variant: values.variant || null,
         buildType: values.buildType || null,
+        artifactKind: values.artifactKind || null,
         outputDir: null,
+        buildId,
+        // 本次构建追加/覆盖工程配置的 Gradle 参数
+        gradleOptions: toGradleOptions(values),
+        versionBump: toVersionBump(values),
       });
       setBuildResult(res);
       
       // 如果构建成功且配置了发布，则自动发布
-      if (res.code === 0 && values.publish) {
+      // 签名或渠道包失败时 Gradle 退出码仍为 0，以构建状态为准
+      if (res.status === "success" && values.publish) {
         // 如果选择了已保存的配置，从配置中获取信息
-        let publishConfig: { platform: string; api_key?: string; api_token?: string; password?: string; update_description?: string } | null = null;
+        let publishConfig: { platform: string; api_key?: string; api_token?: string; password?: string; update_description?: string; config_name?: string } | null = null;
         
         // 获取更新描述：优先使用用户输入的，否则使用配置的默认值
         const updateDescription = values.publishDescription?.trim() || undefined;
         
         if (values.publishPlatformConfig) {
-          // 从已保存的配置中获取
+          // 凭据保存在本地密钥库中，列表只有打码后的预览，由后端按配置名称读取
           const selected = publishPlatforms.find(p => p.name === values.publishPlatformConfig);
           if (selected) {
             publishConfig = {
               platform: selected.platform,
-              api_key: selected.api_key,
-              api_token: selected.api_token,
-              password: selected.password,
+              config_name: selected.name,
               // 优先使用用户输入的更新描述，如果没有则使用配置的默认描述
               update_description: updateDescription || selected.default_description,
             };
@@ -372,21 +619,8 @@ function App() {
           return;
         }
         
-        // 从构建输出中提取 APK 文件路径
-        // 输出格式类似：✅ 已复制: app-release.apk -> /path/to/output/app-release.apk
-        const outputLines = res.output.split('\n');
-        let apkPath: string | null = null;
-        
-        for (const line of outputLines) {
-          if (line.includes('✅ 已复制:') && (line.includes('.apk') || line.includes('.aab'))) {
-            // 提取文件路径（在 -> 之后）
-            const match = line.match(/->\s*(.+)$/);
-            if (match && match[1]) {
-              apkPath = match[1].trim();
-              break;
-            }
-          }
-        }
+        // 使用构建结果中的产物路径（重新签名后已去掉 -unsigned）
+        const apkPath = res.artifacts?.find((p) => /\.(apk|aab)$/i.test(p)) ?? null;
         
         if (apkPath) {
           setPublishing(true);
@@ -406,7 +640,10 @@ function App() {
                 api_token: publishConfig.api_token || null,
                 password: publishConfig.password || null,
                 update_description: finalUpdateDesc,
+                config_name: publishConfig.config_name || null,
               },
+              project: values.project,
+              generateNotes: values.publishNotes ?? false,
             });
             setPublishResult(publishRes);
             if (publishRes.success) {
@@ -417,9 +654,9 @@ function App() {
           } catch (e) {
             setPublishResult({
               success: false,
-              message: (e as Error).message,
+              message: errorText(e),
             });
-            messageApi.error(`发布出错: ${(e as Error).message}`);
+            messageApi.error(`发布出错: ${errorText(e)}`);
           } finally {
             setPublishing(false);
           }
@@ -428,9 +665,23 @@ function App() {
         }
       }
     } catch (e) {
-      setBuildResult({ code: -1, output: (e as Error).message });
+      setBuildResult({ build_id: buildId, status: "failed", code: -1, output: errorText(e) });
     } finally {
+      unlisten();
       setBuilding(false);
+      setCurrentBuildId(null);
+      setCancelling(false);
+    }
+  };
+
+  const handleCancelBuild = async () => {
+    if (!currentBuildId) return;
+    setCancelling(true);
+    try {
+      await invoke("cancel_build", { buildId: currentBuildId });
+    } catch (e) {
+      messageApi.error(`取消构建失败: ${errorText(e)}`);
+      setCancelling(false);
     }
   };
 
@@ -445,9 +696,23 @@ function App() {
         </span>
       }
       extra={
-        <Button icon={<ReloadOutlined />} onClick={loadEnv} loading={envLoading} size="small">
-          刷新环境
-        </Button>
+        <Space>
+          <Select
+            allowClear
+            size="small"
+            placeholder="检查工程（可选）"
+            style={{ width: 220 }}
+            options={projects.map((p) => ({ label: p.name, value: p.name }))}
+            value={envProject}
+            onChange={(value?: string) => {
+              setEnvProject(value);
+              loadEnv(value);
+            }}
+          />
+          <Button icon={<ReloadOutlined />} onClick={() => loadEnv()} loading={envLoading} size="small">
+            刷新环境
+          </Button>
+        </Space>
       }
       loading={envLoading}
     >
@@ -463,12 +728,17 @@ function App() {
               <Space direction="vertical" style={{ width: '100%' }}>
                 <div style={{ display: "flex", alignItems: "center", justifyContent: "space-between", gap: 12 }}>
                   <Typography.Text strong style={{ fontSize: '15px' }}>{item.tool}</Typography.Text>
-                  {statusTag(item.ok)}
+                  {item.applicable ? statusTag(item.ok) : <Tag>不适用</Tag>}
                 </div>
                 <Typography.Text type={item.ok ? "secondary" : "danger"} style={{ fontSize: '13px' }}>
                   {item.message}
                   {item.detail ? ` | ${item.detail}` : ""}
                 </Typography.Text>
+                {item.items && item.items.length > 0 && (
+                  <Space size={[4, 4]} wrap>
+                    {item.items.map((v) => <Tag key={v}>{v}</Tag>)}
+                  </Space>
+                )}
               </Space>
             </List.Item>
           )}
@@ -506,6 +776,17 @@ function App() {
           renderItem={(item) => (
             <List.Item
               actions={[
+                <Button
+                  key="discover"
+                  type="text"
+                  icon={<ReloadOutlined />}
+                  loading={discovering === item.name}
+                  onClick={() => handleDiscoverProject(item.name, "gradle")}
+                  title="运行 Gradle 读取模块与构建变体（失败时使用静态解析）"
+                  size="small"
+                >
+                  同步变体
+                </Button>,
                 <Button
                   key="edit"
                   type="text"
@@ -652,6 +933,115 @@ function App() {
               )}
             </Form.List>
           </Form.Item>
+          <Form.Item
+            name="artifactKind"
+            label="默认产物类型"
+            tooltip="构建时默认生成的产物，发布到 Google Play 的工程可选择 AAB"
+            initialValue="apk"
+          >
+            <Select options={artifactKindOptions} />
+          </Form.Item>
+          <Form.Item
+            name="javaHome"
+            label="构建 JDK"
+            tooltip="构建时导出为 JAVA_HOME，不选择时使用系统环境中的 JDK"
+          >
+            <Select
+              allowClear
+              placeholder="使用系统 JDK"
+              options={jdks.map((j) => ({
+                label: `${j.version} ${j.vendor}${j.bits ? ` ${j.bits} 位` : ""}（${j.home}）`,
+                value: j.home,
+              }))}
+            />
+          </Form.Item>
+          <Form.Item
+            name="signingProfile"
+            label="签名配置"
+            tooltip="构建完成后用该配置重新签名 APK，签名密钥不需要写进 build.gradle"
+          >
+            <Select
+              allowClear
+              placeholder="不重新签名"
+              options={signingProfiles.map((p) => ({ label: p.name, value: p.name }))}
+            />
+          </Form.Item>
+          <Form.Item
+            name={["channels", "channelFile"]}
+            label="渠道文件"
+            tooltip="相对工程目录；填写后构建成功的 APK 会自动生成渠道包，输出到 channels/"
+          >
+            <Input allowClear placeholder="不生成渠道包" />
+          </Form.Item>
+          <Form.Item name={["channels", "scheme"]} label="渠道写入方式">
+            <Select placeholder="Walle" options={channelSchemeOptions} />
+          </Form.Item>
+          <Form.Item name={["channels", "nameTemplate"]} label="渠道包文件名模板" tooltip="支持 {{name}}（原文件名）、{{channel}}、{{version}}、{{code}}、{{package}}">
+            <Input allowClear placeholder="{{name}}-{{channel}}.apk" />
+          </Form.Item>
+          <Collapse
+            size="small"
+            style={{ marginBottom: 24 }}
+            items={[
+              {
+                key: "gradle",
+                label: "Gradle 参数",
+                forceRender: true,
+                children: (
+                  <>
+                    <Form.Item name="gradleArgs" label="命令行参数">
+                      <Select mode="tags" placeholder="如 --stacktrace、--offline" options={gradleArgOptions} />
+                    </Form.Item>
+                    <Form.Item name="gradleProperties" label="-P 工程属性" tooltip="每行一个 KEY=VALUE">
+                      <Input.TextArea rows={3} placeholder={"apiBaseUrl=https://api.example.com\nstorePassword=..."} />
+                    </Form.Item>
+                    <Form.Item name="gradleSystemProperties" label="-D 系统属性" tooltip="每行一个 KEY=VALUE">
+                      <Input.TextArea rows={2} placeholder="org.gradle.jvmargs=-Xmx4g" />
+                    </Form.Item>
+                    <Form.Item name="gradleEnv" label="环境变量" tooltip="每行一个 KEY=VALUE">
+                      <Input.TextArea rows={2} />
+                    </Form.Item>
+                    <Form.Item
+                      name="gradleSecrets"
+                      label="敏感键名"
+                      tooltip="这些键的值会在构建日志中隐藏；名称包含 password、token、secret 等的键会自动隐藏"
+                      style={{ marginBottom: 0 }}
+                    >
+                      <Select mode="tags" placeholder="如 MAPS_KEY" />
+                    </Form.Item>
+                  </>
+                ),
+              },
+              {
+                key: "git",
+                label: "Git",
+                forceRender: true,
+                children: (
+                  <>
+                    <Form.Item
+                      name={["gitPolicy", "injectProperties"]}
+                      label="注入 git 信息"
+                      tooltip="以 -PgitBranch、-PgitCommit、-PgitCommitShort、-PgitDirty、-PgitTag 传给 Gradle"
+                      valuePropName="checked"
+                    >
+                      <Switch />
+                    </Form.Item>
+                    <Form.Item name={["gitPolicy", "requireCleanForRelease"]} label="Release 要求工作区干净" valuePropName="checked">
+                      <Switch />
+                    </Form.Item>
+                    <Form.Item
+                      name={["gitPolicy", "releaseBranches"]}
+                      label="允许 Release 的分支"
+                      tooltip="支持 * 通配，如 release/*；为空时不限制"
+                      style={{ marginBottom: 0 }}
+                    >
+                      <Select mode="tags" placeholder="如 main、release/*" />
+                    </Form.Item>
+                  </>
+                ),
+              },
+            ]}
+          />
           <Form.Item>
             <Button
               type="primary"
@@ -684,7 +1074,7 @@ function App() {
       setEditingPlatform(null);
       loadPublishPlatforms();
     } catch (e) {
-      messageApi.error((e as Error).message);
+      messageApi.error(errorText(e));
     } finally {
       setAddingPlatform(false);
     }
@@ -709,12 +1099,220 @@ function App() {
           messageApi.success("配置已删除");
           loadPublishPlatforms();
         } catch (e) {
-          messageApi.error((e as Error).message);
+          messageApi.error(errorText(e));
+        }
+      },
+    });
+  };
+
+  const handleSaveSigningProfile = async (values: SigningProfileFormValues) => {
+    setSavingSigning(true);
+    try {
+      const profile = toSigningProfile(values);
+      if (editingSigning) {
+        await invoke("update_signing_profile", { name: editingSigning.name, profile });
+        messageApi.success("签名配置已更新");
+      } else {
+        await invoke("add_signing_profile", { profile });
+        messageApi.success("签名配置已添加");
+      }
+      signingProfileForm.resetFields();
+      setSigningModalOpen(false);
+      setEditingSigning(null);
+      loadSigningProfiles();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setSavingSigning(false);
+    }
+  };
+
+  const handleEditSigningProfile = (profile: SigningProfile) => {
+    setEditingSigning(profile);
+    signingProfileForm.setFieldsValue({ ...profile, schemeList: enabledSchemes(profile) });
+    setSigningModalOpen(true);
+  };
+
+  const handleDeleteSigningProfile = async (name: string) => {
+    Modal.confirm({
+      title: "确认删除",
+      content: `确定要删除签名配置 "${name}" 吗？keystore 文件不会被删除。`,
+      okText: "删除",
+      okType: "danger",
+      cancelText: "取消",
+      onOk: async () => {
+        try {
+          await invoke("delete_signing_profile", { name });
+          messageApi.success("签名配置已删除");
+          loadSigningProfiles();
+        } catch (e) {
+          messageApi.error(errorText(e));
         }
       },
     });
   };
 
+  const handleSelectKeystoreFile = async () => {
+    const result = await open({ multiple: false, title: "选择 keystore 文件", filters: [{ name: "Keystore", extensions: ["jks", "keystore", "p12", "pfx"] }] });
+    if (typeof result === "string") {
+      keystoreForm.setFieldsValue({ path: result });
+    }
+  };
+
+  const handleSaveKeystore = async (values: KeystoreFormValues) => {
+    setSavingKeystore(true);
+    try {
+      const info = keystoreMode === "import"
+        ? await invoke<KeystoreInfo>("import_keystore", { request: { id: values.id, path: values.path, storePassword: values.storePassword } })
+        : await invoke<KeystoreInfo>("create_keystore", { request: values });
+      messageApi.success(keystoreMode === "import" ? "keystore 已导入" : "keystore 已创建");
+      info.warnings.forEach((w) => messageApi.warning(w));
+      keystoreForm.resetFields();
+      setKeystoreMode(null);
+      loadKeystores();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setSavingKeystore(false);
+    }
+  };
+
+  const handleDeleteKeystore = async (id: string) => {
+    Modal.confirm({
+      title: "确认删除",
+      content: `确定要删除 keystore "${id}" 吗？配置目录中的 keystore 文件会一并删除，此操作不可恢复。`,
+      okText: "删除",
+      okType: "danger",
+      cancelText: "取消",
+      onOk: async () => {
+        try {
+          await invoke("delete_keystore", { id });
+          messageApi.success("keystore 已删除");
+          loadKeystores();
+        } catch (e) {
+          messageApi.error(errorText(e));
+        }
+      },
+    });
+  };
+
+  // 首次输入的主密码用于创建密钥库，解锁后 keystore 列表才能读取证书
+  const handleUnlockSecrets = async () => {
+    setUnlockingSecrets(true);
+    try {
+      const status = await invoke<SecretsStatus>("unlock_secrets", { passphrase: masterPassword });
+      messageApi.success(secretsStatus?.initialized ? "密钥库已解锁" : "密钥库已创建");
+      setSecretsStatus(status);
+      setMasterPassword("");
+      loadSecrets();
+      loadKeystores();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setUnlockingSecrets(false);
+    }
+  };
+
+  const handleLockSecrets = async () => {
+    await invoke("lock_secrets");
+    loadSecrets();
+    loadKeystores();
+  };
+
+  const handleMigrateSecrets = async () => {
+    try {
+      const migrated = await invoke<string[]>("migrate_secrets");
+      if (migrated.length === 0) {
+        messageApi.info("配置文件中没有明文密钥");
+      } else {
+        messageApi.success(`已迁移 ${migrated.length} 项到密钥库`);
+      }
+      loadSecrets();
+      loadPublishPlatforms();
+      loadSigningProfiles();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
+  const handleChangeMasterPassword = async (values: { oldPassphrase: string; newPassphrase: string }) => {
+    try {
+      await invoke("change_master_password", { oldPassphrase: values.oldPassphrase, newPassphrase: values.newPassphrase });
+      messageApi.success("主密码已修改");
+      setPasswordModalOpen(false);
+      masterPasswordForm.resetFields();
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
+  // 选择 APK 后用签名配置重新签名，输出到同目录
+  const handleSignApk = async (profile: string) => {
+    const result = await open({ multiple: false, title: "选择要签名的 APK", filters: [{ name: "APK", extensions: ["apk"] }] });
+    if (typeof result !== "string") return;
+    setSigningApk(profile);
+    try {
+      const res = await invoke<SignResult>("sign_apk", { profile, filePath: result, outputPath: null });
+      const schemes = res.report.schemes.filter((s) => s.verified).map((s) => s.scheme).join(" + ");
+      messageApi.success(`已签名（${schemes}）：${res.output}`);
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setSigningApk(null);
+    }
+  };
+
+  const handleSelectChannelFile = async (field: "filePath" | "channelFile") => {
+    const result = field === "filePath"
+      ? await open({ multiple: false, title: "选择已签名的 APK", filters: [{ name: "APK", extensions: ["apk"] }] })
+      : await open({ multiple: false, title: "选择渠道文件", filters: [{ name: "渠道文件", extensions: ["txt"] }] });
+    if (typeof result === "string") {
+      channelForm.setFieldsValue({ [field]: result });
+    }
+  };
+
+  const handleWriteChannels = async (values: ChannelFormValues) => {
+    setWritingChannels(true);
+    setChannelOutput(null);
+    try {
+      const res = await invoke<ChannelOutput>("write_channel_apks", {
+        filePath: values.filePath,
+        // 未填写的渠道文件、写入方式与文件名模板取工程的渠道设置
+        channelFile: values.channelFile || null,
+        project: values.project || null,
+        scheme: values.scheme || null,
+        outputDir: values.outputDir || null,
+        nameTemplate: values.nameTemplate || null,
+      });
+      setChannelOutput(res);
+      res.warnings.forEach((w) => messageApi.warning(w));
+      messageApi.success(`已生成 ${res.apks.length} 个渠道包，签名校验通过`);
+    } catch (e) {
+      messageApi.error(errorText(e));
+    } finally {
+      setWritingChannels(false);
+    }
+  };
+
+  // 读取任意 APK 中的渠道，用于核对生成的渠道包
+  const handleReadChannel = async () => {
+    const result = await open({ multiple: false, title: "选择 APK", filters: [{ name: "APK", extensions: ["apk"] }] });
+    if (typeof result !== "string") return;
+    try {
+      const found = await invoke<FoundChannel[]>("read_apk_channel", { filePath: result });
+      if (found.length === 0) {
+        messageApi.info("该 APK 未写入渠道");
+      }
+      found.forEach((info) => {
+        const extras = Object.entries(info.extras).map(([k, v]) => `${k}=${v}`).join("，");
+        const label = channelSchemeOptions.find((o) => o.value === info.scheme)?.label ?? info.scheme;
+        messageApi.success(`渠道（${label}）：${info.channel}${extras ? `（${extras}）` : ""}`);
+      });
+    } catch (e) {
+      messageApi.error(errorText(e));
+    }
+  };
+
   const publishSection = (
     <>
       <Card
@@ -784,8 +1382,8 @@ function App() {
                 </Space>
                 <Typography.Text type="secondary" style={{ fontSize: '13px' }}>
                   {item.platform === "pgyer" 
-                    ? `API Key: ${item.api_key ? "已配置" : "未配置"}`
-                    : `API Token: ${item.api_token ? "已配置" : "未配置"}`
+                    ? `API Key: ${item.api_key ?? "未配置"}`
+                    : `API Token: ${item.api_token ?? "未配置"}`
                   }
                 </Typography.Text>
                 {item.default_description && (
@@ -833,6 +1431,7 @@ function App() {
                       name="api_key"
                       label="蒲公英 API Key"
                       rules={[{ required: true, message: "请输入 API Key" }]}
+                      extra="加密保存在本地密钥库中；编辑时保留打码的预览表示不修改"
                     >
                       <Input.Password placeholder="在蒲公英平台获取 API Key" />
                     </Form.Item>
@@ -847,6 +1446,7 @@ function App() {
                     name="api_token"
                     label="fir.im API Token"
                     rules={[{ required: true, message: "请输入 API Token" }]}
+                    extra="加密保存在本地密钥库中；编辑时保留打码的预览表示不修改"
                   >
                     <Input.Password placeholder="在 fir.im 平台获取 API Token" />
                   </Form.Item>
@@ -872,6 +1472,493 @@ function App() {
           </Form.Item>
         </Form>
       </Modal>
+      <Card
+        style={{ marginTop: 16 }}
+        title={
+          <span className="ds-cardTitle">
+            <span className="ds-iconBadge">
+              <LockOutlined />
+            </span>
+            <span>本地密钥库</span>
+          </span>
+        }
+        extra={
+          <Space>
+            <Button icon={<ReloadOutlined />} onClick={loadSecrets} size="small">
+              刷新
+            </Button>
+            <Button onClick={handleMigrateSecrets} disabled={!secretsStatus?.unlocked} size="small">
+              迁移明文密钥
+            </Button>
+            <Button onClick={() => setPasswordModalOpen(true)} disabled={!secretsStatus?.initialized} size="small">
+              修改主密码
+            </Button>
+          </Space>
+        }
+      >
+        <Space direction="vertical" style={{ width: '100%' }}>
+          <Typography.Text type="secondary" style={{ fontSize: '13px' }}>
+            API Key/Token 与 keystore 密码用主密码加密保存在 {secretsStatus?.path ?? "应用数据目录"}，配置文件中只保存引用
+          </Typography.Text>
+          {secretsStatus?.unlocked ? (
+            <Space>
+              <Tag color="success" icon={<UnlockOutlined />}>已解锁</Tag>
+              <Button icon={<LockOutlined />} onClick={handleLockSecrets} size="small">
+                锁定
+              </Button>
+            </Space>
+          ) : (
+            <Space.Compact style={{ width: '100%', maxWidth: 420 }}>
+              <Input.Password
+                value={masterPassword}
+                onChange={(e) => setMasterPassword(e.target.value)}
+                onPressEnter={handleUnlockSecrets}
+                placeholder={secretsStatus?.initialized ? "输入主密码解锁" : "设置主密码（至少 8 个字符）"}
+              />
+              <Button type="primary" icon={<UnlockOutlined />} onClick={handleUnlockSecrets} loading={unlockingSecrets}>
+                {secretsStatus?.initialized ? "解锁" : "创建"}
+              </Button>
+            </Space.Compact>
+          )}
+          <List
+            size="small"
+            dataSource={secretList}
+            locale={{ emptyText: "暂无保存的密钥" }}
+            renderItem={(item) => (
+              <List.Item>
+                <Typography.Text style={{ fontSize: '13px' }}>{item.name}</Typography.Text>
+                <Typography.Text type="secondary" code style={{ fontSize: '12px' }}>{item.preview}</Typography.Text>
+              </List.Item>
+            )}
+          />
+        </Space>
+      </Card>
+      <Modal
+        title="修改主密码"
+        open={passwordModalOpen}
+        onCancel={() => {
+          setPasswordModalOpen(false);
+          masterPasswordForm.resetFields();
+        }}
+        footer={null}
+        destroyOnClose
+        centered
+      >
+        <Form layout="vertical" form={masterPasswordForm} onFinish={handleChangeMasterPassword}>
+          <Form.Item name="oldPassphrase" label="当前主密码" rules={[{ required: true, message: "请输入当前主密码" }]}>
+            <Input.Password />
+          </Form.Item>
+          <Form.Item name="newPassphrase" label="新主密码" rules={[{ required: true, min: 8, message: "主密码至少需要 8 个字符" }]}>
+            <Input.Password />
+          </Form.Item>
+          <Form.Item
+            name="confirm"
+            label="确认新主密码"
+            dependencies={["newPassphrase"]}
+            rules={[
+              { required: true, message: "请再次输入新主密码" },
+              ({ getFieldValue }) => ({
+                validator: (_, value) =>
+                  !value || getFieldValue("newPassphrase") === value ? Promise.resolve() : Promise.reject(new Error("两次输入的主密码不一致")),
+              }),
+            ]}
+          >
+            <Input.Password />
+          </Form.Item>
+          <Form.Item>
+            <Button type="primary" htmlType="submit" block>
+              修改
+            </Button>
+          </Form.Item>
+        </Form>
+      </Modal>
+      <Card
+        style={{ marginTop: 16 }}
+        title={
+          <span className="ds-cardTitle">
+            <span className="ds-iconBadge">
+              <SafetyCertificateOutlined />
+            </span>
+            <span>Keystore</span>
+          </span>
+        }
+        extra={
+          <Space>
+            <Button icon={<ReloadOutlined />} onClick={loadKeystores} loading={keystoresLoading} size="small">
+              刷新
+            </Button>
+            <Button
+              icon={<FolderOutlined />}
+              onClick={() => {
+                keystoreForm.resetFields();
+                setKeystoreMode("import");
+              }}
+              size="small"
+            >
+              导入
+            </Button>
+            <Button
+              type="primary"
+              icon={<PlusOutlined />}
+              onClick={() => {
+                keystoreForm.resetFields();
+                setKeystoreMode("create");
+              }}
+              size="small"
+            >
+              新建
+            </Button>
+          </Space>
+        }
+        loading={keystoresLoading}
+      >
+        <List
+          dataSource={keystores}
+          locale={{ emptyText: "暂无 keystore，请导入已有的 JKS/PKCS12 文件或新建" }}
+          renderItem={(item) => (
+            <List.Item
+              actions={[
+                <Button
+                  key="delete"
+                  type="text"
+                  danger
+                  icon={<DeleteOutlined />}
+                  onClick={() => handleDeleteKeystore(item.id)}
+                  size="small"
+                >
+                  删除
+                </Button>,
+              ]}
+            >
+              <Space direction="vertical" style={{ width: '100%' }}>
+                <Space>
+                  <SafetyCertificateOutlined style={{ color: "var(--ds-primary)", fontSize: '16px' }} />
+                  <Typography.Text strong style={{ fontSize: '15px' }}>{item.id}</Typography.Text>
+                  <Tag className="ds-tag--primary">{item.store_type.toUpperCase()}</Tag>
+                </Space>
+                {item.error && <Typography.Text type="danger" style={{ fontSize: '13px' }}>{item.error}</Typography.Text>}
+                {item.entries.map((entry) => {
+                  const cert = entry.certificates[0];
+                  return (
+                    <Space key={entry.alias} direction="vertical" size={0}>
+                      <Typography.Text style={{ fontSize: '13px' }}>
+                        {entry.alias}{entry.key_entry ? "" : "（受信任证书）"}
+                        {cert && `：${cert.subject}，${cert.key_algorithm}，有效期至 ${new Date(cert.not_after).toLocaleDateString()}`}
+                      </Typography.Text>
+                      {cert && (
+                        <Typography.Text type="secondary" copyable={{ text: cert.sha256 }} style={{ fontSize: '12px' }}>
+                          SHA-256 {cert.sha256.match(/.{2}/g)?.join(":").toUpperCase()}
+                        </Typography.Text>
+                      )}
+                    </Space>
+                  );
+                })}
+                {item.warnings.map((w) => (
+                  <Tag key={w} color="warning">{w}</Tag>
+                ))}
+              </Space>
+            </List.Item>
+          )}
+        />
+      </Card>
+      <Modal
+        title={keystoreMode === "import" ? "导入 keystore" : "新建 keystore"}
+        open={keystoreMode !== null}
+        onCancel={() => {
+          setKeystoreMode(null);
+          keystoreForm.resetFields();
+        }}
+        footer={null}
+        destroyOnClose
+        centered
+      >
+        <Form
+          layout="vertical"
+          form={keystoreForm}
+          onFinish={handleSaveKeystore}
+          initialValues={{ storeType: "pkcs12", keyAlgorithm: "rsa", validityYears: 25 }}
+        >
+          <Form.Item name="id" label="ID" tooltip="签名配置通过 ID 引用 keystore" rules={[{ required: true, message: "请输入 ID" }]}>
+            <Input placeholder="如：release" />
+          </Form.Item>
+          {keystoreMode === "import" ? (
+            <Form.Item label="Keystore 文件" required tooltip="文件会复制到配置目录">
+              <Space.Compact style={{ width: "100%" }}>
+                <Form.Item name="path" noStyle rules={[{ required: true, message: "请选择 keystore 文件" }]}>
+                  <Input placeholder="JKS 或 PKCS12（.jks / .keystore / .p12）" />
+                </Form.Item>
+                <Button icon={<FolderOutlined />} onClick={handleSelectKeystoreFile}>
+                  选择
+                </Button>
+              </Space.Compact>
+            </Form.Item>
+          ) : (
+            <>
+              <Form.Item name="storeType" label="格式">
+                <Select options={[{ label: "PKCS12（推荐）", value: "pkcs12" }, { label: "JKS", value: "jks" }]} />
+              </Form.Item>
+              <Form.Item name="alias" label="密钥别名" rules={[{ required: true, message: "请输入密钥别名" }]}>
+                <Input placeholder="如：upload" />
+              </Form.Item>
+              <Form.Item name="keyAlgorithm" label="密钥算法">
+                <Select
+                  onChange={() => keystoreForm.setFieldsValue({ keySize: undefined })}
+                  options={[{ label: "RSA", value: "rsa" }, { label: "EC", value: "ec" }]}
+                />
+              </Form.Item>
+              <Form.Item noStyle shouldUpdate={(prev, cur) => prev?.keyAlgorithm !== cur?.keyAlgorithm}>
+                {({ getFieldValue }) => (
+                  <Form.Item name="keySize" label="密钥长度">
+                    <Select
+                      placeholder={getFieldValue("keyAlgorithm") === "ec" ? "P-256" : "2048 位"}
+                      options={getFieldValue("keyAlgorithm") === "ec"
+                        ? [{ label: "P-256", value: 256 }, { label: "P-384", value: 384 }]
+                        : [2048, 3072, 4096].map((n) => ({ label: `${n} 位`, value: n }))}
+                    />
+                  </Form.Item>
+                )}
+              </Form.Item>
+              <Form.Item name="subject" label="证书主题" rules={[{ required: true, message: "请输入证书主题" }]}>
+                <Input placeholder="CN=名称, O=组织, C=CN" />
+              </Form.Item>
+              <Form.Item name="validityYears" label="有效期（年）" tooltip="Google Play 要求上传密钥的有效期到 2033 年以后">
+                <InputNumber min={1} max={100} style={{ width: "100%" }} />
+              </Form.Item>
+            </>
+          )}
+          <Form.Item name="storePassword" label="Keystore 密码" rules={[{ required: true, message: "请输入 keystore 密码" }]}>
+            <Input.Password />
+          </Form.Item>
+          {keystoreMode === "create" && (
+            <Form.Item noStyle shouldUpdate={(prev, cur) => prev?.storeType !== cur?.storeType}>
+              {({ getFieldValue }) =>
+                getFieldValue("storeType") === "jks" && (
+                  <Form.Item name="keyPassword" label="密钥密码（可选）" tooltip="不填时与 keystore 密码相同">
+                    <Input.Password />
+                  </Form.Item>
+                )
+              }
+            </Form.Item>
+          )}
+          <Form.Item>
+            <Button
+              type="primary"
+              htmlType="submit"
+              icon={keystoreMode === "import" ? <FolderOutlined /> : <PlusOutlined />}
+              size="large"
+              block
+              loading={savingKeystore}
+            >
+              {keystoreMode === "import" ? "导入" : "创建"}
+            </Button>
+          </Form.Item>
+        </Form>
+      </Modal>
+      <Card
+        style={{ marginTop: 16 }}
+        title={
+          <span className="ds-cardTitle">
+            <span className="ds-iconBadge">
+              <SafetyCertificateOutlined />
+            </span>
+            <span>签名配置</span>
+          </span>
+        }
+        extra={
+          <Space>
+            <Button icon={<ReloadOutlined />} onClick={loadSigningProfiles} loading={signingProfilesLoading} size="small">
+              刷新
+            </Button>
+            <Button
+              type="primary"
+              icon={<PlusOutlined />}
+              onClick={() => {
+                setEditingSigning(null);
+                signingProfileForm.resetFields();
+                setSigningModalOpen(true);
+              }}
+              size="small"
+            >
+              添加配置
+            </Button>
+          </Space>
+        }
+        loading={signingProfilesLoading}
+      >
+        <List
+          dataSource={signingProfiles}
+          locale={{ emptyText: "暂无签名配置，构建产物将保持 Gradle 的签名" }}
+          renderItem={(item) => (
+            <List.Item
+              actions={[
+                <Button
+                  key="sign"
+                  type="text"
+                  icon={<SafetyCertificateOutlined />}
+                  onClick={() => handleSignApk(item.name)}
+                  loading={signingApk === item.name}
+                  size="small"
+                >
+                  签名 APK
+                </Button>,
+                <Button
+                  key="edit"
+                  type="text"
+                  icon={<EditOutlined />}
+                  onClick={() => handleEditSigningProfile(item)}
+                  size="small"
+                >
+                  编辑
+                </Button>,
+                <Button
+                  key="delete"
+                  type="text"
+                  danger
+                  icon={<DeleteOutlined />}
+                  onClick={() => handleDeleteSigningProfile(item.name)}
+                  size="small"
+                >
+                  删除
+                </Button>,
+              ]}
+            >
+              <Space direction="vertical" style={{ width: '100%' }}>
+                <Space>
+                  <SafetyCertificateOutlined style={{ color: "var(--ds-primary)", fontSize: '16px' }} />
+                  <Typography.Text strong style={{ fontSize: '15px' }}>{item.name}</Typography.Text>
+                  {enabledSchemes(item).map((s) => (
+                    <Tag key={s} className="ds-tag--primary">{s}</Tag>
+                  ))}
+                </Space>
+                <Typography.Text type="secondary" style={{ fontSize: '13px' }}>
+                  {item.keystore}{item.keyAlias ? `（别名 ${item.keyAlias}）` : ""}
+                </Typography.Text>
+              </Space>
+            </List.Item>
+          )}
+        />
+      </Card>
+      <Modal
+        title={editingSigning ? "编辑签名配置" : "添加签名配置"}
+        open={signingModalOpen}
+        onCancel={() => {
+          setSigningModalOpen(false);
+          setEditingSigning(null);
+          signingProfileForm.resetFields();
+        }}
+        footer={null}
+        destroyOnClose
+        centered
+      >
+        <Form layout="vertical" form={signingProfileForm} onFinish={handleSaveSigningProfile} initialValues={{ schemeList: signingSchemes }}>
+          <Form.Item name="name" label="配置名称" rules={[{ required: true, message: "请输入配置名称" }]}>
+            <Input placeholder="如：release" />
+          </Form.Item>
+          <Form.Item name="keystore" label="Keystore" rules={[{ required: true, message: "请选择 keystore" }]}>
+            <Select
+              placeholder="先在上方导入或新建 keystore"
+              options={keystores.map((k) => ({ label: `${k.id}（${k.store_type.toUpperCase()}）`, value: k.id }))}
+            />
+          </Form.Item>
+          <Form.Item name="storePassword" label="Keystore 密码（可选）" tooltip="不填时使用 keystore 登记时的密码；密码加密保存在本地密钥库中，编辑时保留打码的预览表示不修改">
+            <Input.Password />
+          </Form.Item>
+          <Form.Item name="keyAlias" label="密钥别名（可选）" tooltip="keystore 中只有一个私钥时可以不填">
+            <Input />
+          </Form.Item>
+          <Form.Item name="keyPassword" label="密钥密码（可选）" tooltip="不填时与 keystore 密码相同">
+            <Input.Password />
+          </Form.Item>
+          <Form.Item name="schemeList" label="签名方案" rules={[{ required: true, message: "至少选择一种签名方案" }]}>
+            <Checkbox.Group options={signingSchemes.map((s) => ({ label: `${s} 签名`, value: s }))} />
+          </Form.Item>
+          <Form.Item>
+            <Button
+              type="primary"
+              htmlType="submit"
+              icon={editingSigning ? <EditOutlined /> : <PlusOutlined />}
+              size="large"
+              block
+              loading={savingSigning}
+            >
+              {savingSigning ? (editingSigning ? "更新中..." : "添加中...") : (editingSigning ? "更新配置" : "添加配置")}
+            </Button>
+          </Form.Item>
+        </Form>
+      </Modal>
+      <Card
+        style={{ marginTop: 16 }}
+        title={
+          <span className="ds-cardTitle">
+            <span className="ds-iconBadge">
+              <CloudUploadOutlined />
+            </span>
+            <span>渠道包</span>
+          </span>
+        }
+        extra={
+          <Button onClick={handleReadChannel} size="small">
+            读取渠道
+          </Button>
+        }
+      >
+        <Form layout="vertical" form={channelForm} onFinish={handleWriteChannels} >
+          <Form.Item label="APK" required tooltip="已签名的 APK，写入渠道后不需要重新签名，生成前会读回渠道并校验签名">
+            <Space.Compact style={{ width: "100%" }}>
+              <Form.Item name="filePath" noStyle rules={[{ required: true, message: "请选择 APK" }]}>
+                <Input placeholder="已签名的 APK" />
+              </Form.Item>
+              <Button icon={<FolderOutlined />} onClick={() => handleSelectChannelFile("filePath")}>
+                选择
+              </Button>
+            </Space.Compact>
+          </Form.Item>
+          <Form.Item name="project" label="工程（可选）" tooltip="使用工程保存的渠道设置；不选择时按构建历史查找 APK 所属的工程">
+            <Select allowClear placeholder="按构建历史自动查找" options={projectOptions} />
+          </Form.Item>
+          <Form.Item label="渠道文件" tooltip="每行一个渠道，渠道名后可跟 key=value 形式的附加信息，# 开头为注释；不填写时使用工程配置的渠道文件">
+            <Space.Compact style={{ width: "100%" }}>
+              <Form.Item name="channelFile" noStyle>
+                <Input placeholder="使用工程配置的渠道文件" />
+              </Form.Item>
+              <Button icon={<FolderOutlined />} onClick={() => handleSelectChannelFile("channelFile")}>
+                选择
+              </Button>
+            </Space.Compact>
+          </Form.Item>
+          <Form.Item
+            name="scheme"
+            label="写入方式"
+            tooltip="Walle、VasDolly 写入签名块，需要 v2/v3 签名；META-INF 新增空文件 META-INF/channel_渠道名，只用于 v1 签名的 APK。只有 Walle 支持附加信息"
+          >
+            <Select allowClear placeholder="工程设置（默认 Walle）" options={channelSchemeOptions} />
+          </Form.Item>
+          <Form.Item name="outputDir" label="输出目录（可选）" tooltip="默认为 APK 所在目录下的 channels/">
+            <Input />
+          </Form.Item>
+          <Form.Item name="nameTemplate" label="文件名模板" tooltip="支持 {{name}}（原文件名）、{{channel}}、{{version}}、{{code}}、{{package}}；不填写时使用工程设置">
+            <Input placeholder="{{name}}-{{channel}}.apk" />
+          </Form.Item>
+          <Form.Item>
+            <Button type="primary" htmlType="submit" loading={writingChannels} block>
+              生成渠道包
+            </Button>
+          </Form.Item>
+        </Form>
+        {channelOutput && (
+          <List
+            size="small"
+            header={<Typography.Text type="secondary">输出目录：{channelOutput.output_dir}</Typography.Text>}
+            dataSource={channelOutput.apks}
+            renderItem={(item) => (
+              <List.Item>
+                <Tag className="ds-tag--primary">{item.channel}</Tag>
+                <Typography.Text style={{ fontSize: '13px' }} copyable>{item.path}</Typography.Text>
+              </List.Item>
+            )}
+          />
+        )}
+      </Card>
     </>
   );
 
@@ -926,6 +2013,9 @@ function App() {
                     
                     // 默认选中 Debug
                     buildForm.setFieldsValue({ buildType: "Debug" });
+
+                    // 产物类型使用工程配置的默认值
+                    buildForm.setFieldsValue({ artifactKind: selectedProject.artifactKind || "apk" });
                   }
                 }}
               />
@@ -1000,6 +2090,56 @@ function App() {
               </Select>
             </Form.Item>
           </Col>
+          <Col span={12}>
+            <Form.Item
+              name="artifactKind"
+              label="产物类型"
+              tooltip="APK 执行 assemble 任务，AAB 执行 bundle 任务。留空则使用工程配置的默认值"
+              initialValue="apk"
+            >
+              <Select placeholder="选择产物类型" size="large" options={artifactKindOptions} />
+            </Form.Item>
+          </Col>
+        </Row>
+        <Row gutter={16}>
+          <Col span={12}>
+            <Form.Item name="gradleArgs" label="额外 Gradle 参数" tooltip="追加到工程配置的参数之后">
+              <Select mode="tags" placeholder="如 --stacktrace" options={gradleArgOptions} />
+            </Form.Item>
+          </Col>
+          <Col span={12}>
+            <Form.Item name="gradleProperties" label="覆盖 -P 属性" tooltip="每行一个 KEY=VALUE，同名属性覆盖工程配置">
+              <Input.TextArea rows={1} autoSize={{ minRows: 1, maxRows: 4 }} placeholder="versionNameSuffix=-rc1" />
+            </Form.Item>
+          </Col>
+        </Row>
+        <Row gutter={16}>
+          <Col span={12}>
+            <Form.Item name="bumpCode" label="versionCode" tooltip="构建前修改版本号，构建失败时自动恢复。不选择则使用工程配置">
+              <Select
+                allowClear
+                placeholder="不修改"
+                options={[
+                  { value: "increment", label: "递增 +1" },
+                  { value: "timestamp", label: "时间戳（yyMMddHH）" },
+                  { value: "gitCommitCount", label: "git 提交数" },
+                ]}
+              />
+            </Form.Item>
+          </Col>
+          <Col span={12}>
+            <Form.Item name="bumpName" label="versionName">
+              <Select
+                allowClear
+                placeholder="不修改"
+                options={[
+                  { value: "patch", label: "修订号 x.y.Z" },
+                  { value: "minor", label: "次版本号 x.Y.0" },
+                  { value: "major", label: "主版本号 X.0.0" },
+                ]}
+              />
+            </Form.Item>
+          </Col>
         </Row>
         <Form.Item name="publish" valuePropName="checked">
           <Checkbox>构建成功后自动发布到平台</Checkbox>
@@ -1024,7 +2164,7 @@ function App() {
                     <Form.Item
                       name="publishPlatformConfig"
                       label="选择已保存的配置（可选）"
-                      tooltip="如果选择已保存的配置，将自动填充 API Key/Token 等信息"
+                      tooltip="选择已保存的配置时，发布使用该配置保存在本地密钥库中的 API Key/Token"
                     >
                       <Select 
                         placeholder="选择已保存的配置或手动输入" 
@@ -1036,9 +2176,6 @@ function App() {
                             if (selected) {
                               buildForm.setFieldsValue({
                                 publishPlatform: selected.platform as "pgyer" | "fir",
-                                publishApiKey: selected.api_key,
-                                publishApiToken: selected.api_token,
-                                publishPassword: selected.password,
                                 publishDescription: selected.default_description,
                               });
                             }
@@ -1160,6 +2297,14 @@ function App() {
                         size="large"
                       />
                     </Form.Item>
+                    <Space style={{ marginTop: -12, marginBottom: 16 }}>
+                      <Form.Item name="publishNotes" valuePropName="checked" noStyle>
+                        <Checkbox>发布时根据提交生成更新描述</Checkbox>
+                      </Form.Item>
+                      <Button size="small" onClick={handlePreviewNotes}>
+                        预览
+                      </Button>
+                    </Space>
                   </Col>
                 </Row>
               </>
@@ -1177,8 +2322,25 @@ function App() {
           >
             {building ? "构建中..." : "开始构建"}
           </Button>
+          {building && currentBuildId && (
+            <Button
+              danger
+              onClick={handleCancelBuild}
+              loading={cancelling}
+              size="large"
+              block
+              style={{ marginTop: 8 }}
+            >
+              {cancelling ? "正在取消..." : "取消构建"}
+            </Button>
+          )}
         </Form.Item>
       </Form>
+      {building && !buildResult && (
+        <pre className="ds-logOutput" style={{ marginTop: 20, padding: '12px', backgroundColor: 'var(--ds-bg-layout)', borderRadius: '4px' }}>
+          {liveLog || "等待构建输出..."}
+        </pre>
+      )}
       {buildResult && (
         <Collapse
           style={{ marginTop: 20 }}
@@ -1189,7 +2351,22 @@ function App() {
               key: 'build-log',
               label: (
                 <span style={{ fontSize: '15px', fontWeight: 800 }}>
-                  {buildResult.code === 0 ? "✅ 构建成功" : `❌ 构建失败（退出码 ${buildResult.code}）`}
+                  {buildResult.status === "cancelled"
+                    ? "⏹ 构建已取消"
+                    : buildResult.status === "success"
+                      ? "✅ 构建成功"
+                      : buildResult.code === 0 ? "❌ 构建失败（签名或渠道包未完成）" : `❌ 构建失败（退出码 ${buildResult.code}）`}
+                  {buildResult.version && (
+                    <Tag style={{ marginLeft: 8 }}>版本 {formatVersion(buildResult.version)}</Tag>
+                  )}
+                  {buildResult.git && (
+                    <Tooltip title={buildResult.git.commit}>
+                      <Tag color={buildResult.git.dirty ? "orange" : undefined} style={{ marginLeft: 8 }}>
+                        {buildResult.git.branch ?? "detached"}@{buildResult.git.short_commit}
+                        {buildResult.git.dirty && " *"}
+                      </Tag>
+                    </Tooltip>
+                  )}
                 </span>
               ),
               children: (
@@ -1201,6 +2378,68 @@ function App() {
           ]}
         />
       )}
+      {buildResult?.artifact_info && buildResult.artifact_info.length > 0 && (
+        <List
+          style={{ marginTop: 12 }}
+          size="small"
+          bordered
+          dataSource={buildResult.artifact_info}
+          renderItem={(info) => (
+            <List.Item>
+              <List.Item.Meta
+                title={
+                  <Space wrap>
+                    <span>{info.label ?? info.package}</span>
+                    <Tag>{info.package}</Tag>
+                    <Tag>{info.version_name ?? "-"} ({info.version_code ?? "-"})</Tag>
+                    <Tag>minSdk {info.min_sdk ?? "-"} / targetSdk {info.target_sdk ?? "-"}</Tag>
+                    {info.abis.map((abi) => <Tag key={abi}>{abi}</Tag>)}
+                    {buildResult.signatures?.filter((s) => s.path === info.path).map((s) => (
+                      <Tooltip
+                        key={s.path}
+                        title={[
+                          ...s.schemes.flatMap((r) => [`${r.scheme}：${r.verified ? "通过" : "失败"}`, ...r.errors.map((e) => `  ${e}`)]),
+                          ...s.signers.map((c) => `${c.subject}\nSHA-256 ${c.sha256}\n有效期至 ${new Date(c.not_after).toLocaleDateString()}`),
+                        ].join("\n")}
+                        overlayStyle={{ whiteSpace: "pre-line", maxWidth: 520 }}
+                      >
+                        <Tag color={s.verified ? "green" : "red"}>
+                          {s.schemes.length > 0 ? `签名 ${s.schemes.map((r) => r.scheme).join("+")}` : "未签名"}
+                        </Tag>
+                        {s.debug_signed && <Tag color="orange">调试证书</Tag>}
+                      </Tooltip>
+                    ))}
+                  </Space>
+                }
+                description={
+                  <>
+                    <div>{info.path}（{(info.size / 1024 / 1024).toFixed(2)} MB）</div>
+                    {info.launchable_activity && <div>启动 Activity：{info.launchable_activity}</div>}
+                    {buildResult.signatures?.filter((s) => s.path === info.path).flatMap((s) => s.warnings).map((w) => (
+                      <div key={w} style={{ color: "#d46b08" }}>⚠️ {w}</div>
+                    ))}
+                    {info.permissions.length > 0 && (
+                      <Tooltip title={info.permissions.join("\n")} overlayStyle={{ whiteSpace: "pre-line" }}>
+                        <span>权限 {info.permissions.length} 项</span>
+                      </Tooltip>
+                    )}
+                    {info.modules.length > 0 && (
+                      <div>
+                        模块：
+                        {info.modules.map((m) => (
+                          <Tag key={m.name}>
+                            {m.name}（{{ base: "base", feature: "动态功能", asset_pack: "资源包" }[m.kind]}，{(m.size / 1024 / 1024).toFixed(2)} MB）
+                          </Tag>
+                        ))}
+                      </div>
+                    )}
+                  </>
+                }
+              />
+            </List.Item>
+          )}
+        />
+      )}
       {publishing && (
         <Alert
           style={{ marginTop: 20 }}
@@ -1261,6 +2500,18 @@ function App() {
                   </Typography.Link>
                 </div>
               )}
+              {publishResult.artifact && (
+                <div style={{ marginTop: 8 }}>
+                  <Typography.Text strong>产物：</Typography.Text>
+                  {publishResult.artifact.package} {publishResult.artifact.version_name ?? "-"} ({publishResult.artifact.version_code ?? "-"})
+                </div>
+              )}
+              {publishResult.update_description && (
+                <div style={{ marginTop: 8 }}>
+                  <Typography.Text strong>更新描述：</Typography.Text>
+                  <pre style={{ margin: '4px 0 0', whiteSpace: 'pre-wrap' }}>{publishResult.update_description}</pre>
+                </div>
+              )}
               {publishResult.qr_code_url && (
                 <div style={{ marginTop: 8 }}>
                   <Typography.Text strong>二维码：</Typography.Text>